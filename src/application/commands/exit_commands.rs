@@ -1,4 +1,7 @@
+use crate::domain::builtins::CommandInfo;
 use crate::domain::entities::Command;
+use crate::domain::errors::{ArgumentError, TermirustError};
+use crate::domain::options::{OptionParser, OptionSpec};
 
 /// Caso de uso para el comando exit
 pub struct ExitCommandsUseCase;
@@ -9,11 +12,37 @@ impl ExitCommandsUseCase {
         Self
     }
 
-    /// Ejecuta el comando exit - prepara la aplicación para cerrarse
-    pub fn execute_exit(&self, command: &Command) -> Result<ExitResult, String> {
+    /// Ejecuta el comando exit - prepara la aplicación para cerrarse.
+    /// Si quedan trabajos activos solo se cierra con `--force`.
+    pub fn execute_exit(&self, command: &Command, active_jobs: usize) -> Result<ExitResult, TermirustError> {
         // Validar que es un comando exit válido
         if command.name != "exit" && command.name != "quit" {
-            return Err("Comando no válido para exit".to_string());
+            return Err(TermirustError::invalid_argument("exit", ArgumentError::WrongCommand(command.name.clone())));
+        }
+
+        let parser = Self::options();
+        let parsed = parser.parse(&command.args)?;
+        if parsed.help {
+            return Ok(ExitResult {
+                should_exit: false,
+                message: parser.help(),
+                exit_code: 0,
+            });
+        }
+        if let Some(operand) = parsed.operands.first() {
+            return Err(parser.error(ArgumentError::UnexpectedOperand(operand.clone())));
+        }
+
+        let force = parsed.flag("force");
+        if active_jobs > 0 && !force {
+            return Ok(ExitResult {
+                should_exit: false,
+                message: format!(
+                    "⚠️  Hay {} trabajo(s) activo(s). Usa 'jobs' para verlos o 'exit --force' para cerrar igualmente.",
+                    active_jobs
+                ),
+                exit_code: 1,
+            });
         }
 
         // Crear el resultado del comando exit
@@ -26,32 +55,39 @@ impl ExitCommandsUseCase {
         Ok(result)
     }
 
+    /// Opciones que acepta `exit`; también generan su `--help`
+    pub fn options() -> OptionParser {
+        OptionParser::new("exit", "exit [--force]")
+            .with_description("Cierra la aplicación Termirust.")
+            .with_option(
+                OptionSpec::new("Cerrar aunque haya trabajos activos (reciben SIGHUP)")
+                    .with_short('f')
+                    .with_long("force"),
+            )
+    }
+
+    /// Indica si el resultado de un `exit` ya ejecutado debe cerrar la aplicación:
+    /// no lo hace `exit --help` ni un `exit` que falló
+    pub fn should_close(&self, result: &Command) -> bool {
+        self.is_exit_command(&result.name)
+            && result.exit_code == 0
+            && Self::options().parse(&result.args).is_ok_and(|parsed| !parsed.help)
+    }
+
     /// Valida si el comando es un comando exit
     pub fn is_exit_command(&self, command_name: &str) -> bool {
         command_name == "exit" || command_name == "quit"
     }
 
     /// Obtiene información sobre el comando exit
-    pub fn get_exit_info(&self) -> ExitCommandInfo {
-        ExitCommandInfo {
-            name: "exit".to_string(),
+    pub fn get_exit_info(&self) -> CommandInfo {
+        CommandInfo {
             aliases: vec!["quit".to_string()],
             description: "Cierra la aplicación Termirust".to_string(),
-            usage: "exit [--help]".to_string(),
-            help_text: r#"
-Comando: exit
-Alias: quit
-Descripción: Cierra la aplicación Termirust
-
-Uso:
-  exit          - Cierra la aplicación inmediatamente
-  exit --help   - Muestra esta ayuda
-  quit          - Alias para exit
-
-Ejemplos:
-  exit          # Cierra la aplicación
-  quit          # Cierra la aplicación (alias)
-"#.to_string(),
+            help_text: format!(
+                "{}\n\nEjemplos:\n  exit          # Cierra la aplicación\n  quit          # Cierra la aplicación (alias)\n  exit --force  # Cierra aunque haya trabajos activos",
+                Self::options().help()
+            ),
         }
     }
 }
@@ -63,48 +99,3 @@ pub struct ExitResult {
     pub message: String,
     pub exit_code: i32,
 }
-
-/// Información del comando exit
-#[derive(Debug, Clone)]
-pub struct ExitCommandInfo {
-    pub name: String,
-    pub aliases: Vec<String>,
-    pub description: String,
-    pub usage: String,
-    pub help_text: String,
-}
-
-impl ExitResult {
-    /// Crea un nuevo resultado de exit
-    pub fn new(should_exit: bool, message: String) -> Self {
-        Self {
-            should_exit,
-            message,
-            exit_code: if should_exit { 0 } else { 1 },
-        }
-    }
-
-    /// Crea un resultado de exit con código de salida personalizado
-    pub fn with_exit_code(mut self, exit_code: i32) -> Self {
-        self.exit_code = exit_code;
-        self
-    }
-}
-
-impl ExitCommandInfo {
-    /// Crea nueva información del comando exit
-    pub fn new() -> Self {
-        Self {
-            name: "exit".to_string(),
-            aliases: vec!["quit".to_string()],
-            description: "Cierra la aplicación Termirust".to_string(),
-            usage: "exit [--help]".to_string(),
-            help_text: "Comando para cerrar la aplicación".to_string(),
-        }
-    }
-
-    /// Obtiene el texto de ayuda formateado
-    pub fn get_formatted_help(&self) -> String {
-        self.help_text.clone()
-    }
-}
@@ -1,3 +1,9 @@
+use std::sync::{Arc, Mutex};
+
+use crate::domain::builtins::{BuiltinCommand, BuiltinContext};
+use crate::domain::entities::Command;
+use crate::domain::errors::{ArgumentError, TermirustError};
+use crate::domain::options::{OptionParser, OptionSpec};
 use crate::presentation::texts::{CommandHistory, CommandHistoryText};
 
 /// Casos de uso para los comandos del historial
@@ -97,28 +103,58 @@ pub struct HistoryCommandParser;
 impl HistoryCommandParser {
     /// Parsea un comando y determina qué acción ejecutar
     pub fn parse_command(command: &str) -> HistoryCommand {
-        let trimmed = command.trim();
-        
-        match trimmed {
-            "history" | "hist" => HistoryCommand::ShowFull,
-            "history -n 10" | "hist -n 10" => HistoryCommand::ShowRecent(10),
-            "history -c" | "hist -c" => HistoryCommand::Clear,
-            "history -s" | "hist -s" => HistoryCommand::ShowStats,
-            cmd if cmd.starts_with("history -g ") || cmd.starts_with("hist -g ") => {
-                let pattern = cmd.split_whitespace().nth(2).unwrap_or("").to_string();
-                HistoryCommand::Search(pattern)
-            }
-            cmd if cmd.starts_with("history -n ") || cmd.starts_with("hist -n ") => {
-                if let Some(count_str) = cmd.split_whitespace().nth(2) {
-                    if let Ok(count) = count_str.parse::<usize>() {
-                        return HistoryCommand::ShowRecent(count);
-                    }
-                }
-                HistoryCommand::Invalid("Formato inválido para history -n".to_string())
-            }
+        let mut parts = command.split_whitespace();
+        match parts.next() {
+            Some("history" | "hist") => Self::parse_args(&parts.collect::<Vec<_>>()),
             _ => HistoryCommand::NotHistoryCommand,
         }
     }
+
+    /// Opciones que acepta `history`; también generan su `--help`
+    pub fn options() -> OptionParser {
+        OptionParser::new("history", "history [-n N | -g patrón | -s | -c]")
+            .with_description("Muestra, busca o limpia el historial de comandos.")
+            .with_option(OptionSpec::new("Mostrar los últimos N comandos").with_short('n').with_long("recent").with_value("N"))
+            .with_option(OptionSpec::new("Buscar los comandos que contienen el patrón").with_short('g').with_long("grep").with_value("PATRÓN"))
+            .with_option(OptionSpec::new("Mostrar estadísticas del historial").with_short('s').with_long("stats"))
+            .with_option(OptionSpec::new("Limpiar el historial").with_short('c').with_long("clear"))
+    }
+
+    /// Parsea los argumentos ya separados de `history` (sin el nombre). Si se dan
+    /// varias acciones gana la última.
+    pub fn parse_args<S: AsRef<str>>(args: &[S]) -> HistoryCommand {
+        let parser = Self::options();
+        let parsed = match parser.parse(args) {
+            Ok(parsed) => parsed,
+            Err(e) => return HistoryCommand::Invalid(e),
+        };
+        if parsed.help {
+            return HistoryCommand::Help(parser.help());
+        }
+        if let Some(operand) = parsed.operands.first() {
+            return HistoryCommand::Invalid(parser.error(ArgumentError::UnexpectedOperand(operand.clone())));
+        }
+
+        let mut command = HistoryCommand::ShowFull;
+        for (name, value) in parsed.iter() {
+            command = match (name, value) {
+                ("recent", Some(count)) => match count.parse::<usize>() {
+                    Ok(count) => HistoryCommand::ShowRecent(count),
+                    Err(_) => {
+                        return HistoryCommand::Invalid(parser.error(ArgumentError::InvalidNumber {
+                            option: "-n".to_string(),
+                            value: count.to_string(),
+                        }))
+                    }
+                },
+                ("grep", Some(pattern)) => HistoryCommand::Search(pattern.to_string()),
+                ("stats", _) => HistoryCommand::ShowStats,
+                ("clear", _) => HistoryCommand::Clear,
+                _ => command,
+            };
+        }
+        command
+    }
     
     /// Verifica si un comando es un comando del historial
     #[allow(dead_code)]
@@ -128,6 +164,7 @@ impl HistoryCommandParser {
 }
 
 /// Enum que representa los diferentes comandos del historial
+#[allow(clippy::enum_variant_names)]
 #[derive(Debug, Clone)]
 pub enum HistoryCommand {
     ShowFull,
@@ -135,12 +172,15 @@ pub enum HistoryCommand {
     Clear,
     ShowStats,
     Search(String),
-    Invalid(String),
+    /// `history --help`, con el texto de ayuda
+    Help(String),
+    Invalid(TermirustError),
     NotHistoryCommand,
 }
 
 impl HistoryCommand {
-    /// Ejecuta el comando usando el caso de uso proporcionado
+    /// Ejecuta el comando usando el caso de uso proporcionado. Un comando inválido no
+    /// produce salida: su error lo devuelve `HistoryBuiltin`.
     pub fn execute(self, use_case: &mut HistoryCommandsUseCase) -> Vec<String> {
         match self {
             HistoryCommand::ShowFull => use_case.show_full_history(),
@@ -148,9 +188,59 @@ impl HistoryCommand {
             HistoryCommand::Clear => use_case.clear_history(),
             HistoryCommand::ShowStats => use_case.show_statistics(),
             HistoryCommand::Search(pattern) => use_case.search_commands(&pattern),
-            HistoryCommand::Invalid(message) => vec![format!("Error: {}", message)],
-            HistoryCommand::NotHistoryCommand => vec![],
+            HistoryCommand::Help(help) => help.lines().map(str::to_string).collect(),
+            HistoryCommand::Invalid(_) | HistoryCommand::NotHistoryCommand => vec![],
+        }
+    }
+}
+
+/// Comando interno `history` (o `hist`) sobre el historial compartido de la sesión
+pub struct HistoryBuiltin {
+    history_commands: Arc<Mutex<HistoryCommandsUseCase>>,
+}
+
+impl HistoryBuiltin {
+    pub fn new(history_commands: Arc<Mutex<HistoryCommandsUseCase>>) -> Self {
+        Self { history_commands }
+    }
+}
+
+impl BuiltinCommand for HistoryBuiltin {
+    fn name(&self) -> &str {
+        "history"
+    }
+
+    fn aliases(&self) -> &[&str] {
+        &["hist"]
+    }
+
+    fn description(&self) -> &str {
+        "Mostrar, buscar o limpiar el historial (-n N, -g patrón, -s, -c)"
+    }
+
+    fn usage(&self) -> &str {
+        "history [-n N | -g patrón | -s | -c]"
+    }
+
+    fn help(&self) -> String {
+        HistoryCommandParser::options().help()
+    }
+
+    fn options(&self) -> Vec<OptionSpec> {
+        HistoryCommandParser::options().options().to_vec()
+    }
+
+    fn execute(&self, command: &Command, _context: &mut BuiltinContext) -> Command {
+        let history_command = HistoryCommandParser::parse_args(&command.args);
+        if let HistoryCommand::Invalid(error) = history_command {
+            let exit_code = error.exit_code();
+            return command.clone().with_error(error).with_exit_code(exit_code);
         }
+        let output = match self.history_commands.lock() {
+            Ok(mut history_commands) => history_command.execute(&mut history_commands).join("\n"),
+            Err(_) => String::new(),
+        };
+        command.clone().with_output(output).with_exit_code(0)
     }
 }
 
@@ -1,6 +1,8 @@
 use std::fs;
 use std::path::{Path, PathBuf};
 use crate::domain::entities::{FileInfo, LsResult};
+use crate::domain::errors::{ArgumentError, TermirustError};
+use crate::domain::options::{OptionParser, OptionSpec};
 
 /// Caso de uso para el comando ls
 pub struct LsCommandsUseCase;
@@ -12,30 +14,29 @@ impl LsCommandsUseCase {
     }
 
     /// Ejecuta el comando ls básico - lista archivos y directorios
-    pub fn list_directory(&self, path: Option<&str>) -> Result<LsResult, String> {
+    pub fn list_directory(&self, path: Option<&str>) -> Result<LsResult, TermirustError> {
         let target_path = match path {
             Some(p) => PathBuf::from(p),
-            None => std::env::current_dir().map_err(|e| format!("Error obteniendo directorio actual: {}", e))?,
+            None => std::env::current_dir().map_err(|e| TermirustError::from_io("ls", None, &e))?,
         };
+        let display = target_path.to_string_lossy().to_string();
 
         if !target_path.exists() {
-            return Err(format!("El directorio '{}' no existe", target_path.display()));
+            return Err(TermirustError::NotFound { command: "ls".to_string(), path: Some(display) });
         }
 
         if !target_path.is_dir() {
-            return Err(format!("'{}' no es un directorio", target_path.display()));
+            return Err(TermirustError::invalid_argument("ls", ArgumentError::NotADirectory(display)));
         }
 
-        let mut result = LsResult::new(target_path.to_string_lossy().to_string());
+        let mut result = LsResult::new(display.clone());
 
         let entries = fs::read_dir(&target_path)
-            .map_err(|e| format!("Error leyendo directorio: {}", e))?;
+            .map_err(|e| TermirustError::from_io("ls", Some(&display), &e))?;
 
-        for entry in entries {
-            if let Ok(entry) = entry {
-                let file_info = self.create_file_info(&entry.path())?;
-                result.add_file(file_info);
-            }
+        for entry in entries.flatten() {
+            let file_info = self.create_file_info(&entry.path())?;
+            result.add_file(file_info);
         }
 
         result.sort_by_name();
@@ -43,15 +44,11 @@ impl LsCommandsUseCase {
     }
 
     /// Ejecuta el comando ls con opciones específicas
-    pub fn list_directory_with_options(&self, path: Option<&str>, options: &LsOptions) -> Result<LsResult, String> {
+    pub fn list_directory_with_options(&self, path: Option<&str>, options: &LsOptions) -> Result<LsResult, TermirustError> {
         let mut result = self.list_directory(path)?;
 
         // Aplicar ordenamiento según las opciones
-        match options.sort_by {
-            SortBy::Name => result.sort_by_name(),
-            SortBy::Size => result.sort_by_size(),
-            SortBy::Modified => result.sort_by_modified(),
-        }
+        Self::sort(&mut result, options);
 
         // Aplicar filtros
         if options.show_hidden {
@@ -71,10 +68,59 @@ impl LsCommandsUseCase {
         Ok(result)
     }
 
+    /// Lista varias rutas, como `ls *.rs src`: primero los errores de las que no
+    /// existen, luego los archivos indicados juntos y después cada directorio
+    pub fn list_paths(&self, paths: &[String], options: &LsOptions) -> Vec<Result<LsResult, TermirustError>> {
+        if paths.is_empty() {
+            return vec![self.list_directory_with_options(None, options)];
+        }
+
+        let mut errors = Vec::new();
+        let mut files = Vec::new();
+        let mut directories = Vec::new();
+        for path in paths {
+            let target = Path::new(path);
+            if target.is_dir() {
+                directories.push(path);
+            } else if target.exists() {
+                files.push(path);
+            } else {
+                errors.push(Err(TermirustError::NotFound { command: "ls".to_string(), path: Some(path.clone()) }));
+            }
+        }
+
+        let mut results = errors;
+        if !files.is_empty() {
+            let current_directory = std::env::current_dir().unwrap_or_default();
+            let mut result = LsResult::new(current_directory.to_string_lossy().to_string());
+            for path in files {
+                match self.create_file_info(Path::new(path)) {
+                    // Los archivos se muestran con la ruta que se escribió
+                    Ok(file_info) => result.add_file(FileInfo { name: path.clone(), ..file_info }),
+                    Err(e) => results.push(Err(e)),
+                }
+            }
+            Self::sort(&mut result, options);
+            results.push(Ok(result));
+        }
+        for path in directories {
+            results.push(self.list_directory_with_options(Some(path), options));
+        }
+        results
+    }
+
+    fn sort(result: &mut LsResult, options: &LsOptions) {
+        match options.sort_by {
+            SortBy::Name => result.sort_by_name(),
+            SortBy::Size => result.sort_by_size(),
+            SortBy::Modified => result.sort_by_modified(),
+        }
+    }
+
     /// Crea información detallada de un archivo
-    fn create_file_info(&self, path: &Path) -> Result<FileInfo, String> {
-        let metadata = fs::metadata(path)
-            .map_err(|e| format!("Error obteniendo metadatos de '{}': {}", path.display(), e))?;
+    fn create_file_info(&self, path: &Path) -> Result<FileInfo, TermirustError> {
+        let io_error = |e: std::io::Error| TermirustError::from_io("ls", Some(&path.to_string_lossy()), &e);
+        let metadata = fs::metadata(path).map_err(io_error)?;
 
         let name = path.file_name()
             .and_then(|n| n.to_str())
@@ -83,8 +129,7 @@ impl LsCommandsUseCase {
 
         let is_directory = metadata.is_dir();
         let size = if is_directory { 0 } else { metadata.len() };
-        let modified = metadata.modified()
-            .map_err(|e| format!("Error obteniendo fecha de modificación: {}", e))?;
+        let modified = metadata.modified().map_err(io_error)?;
 
         let permissions = self.format_permissions(&metadata);
         let (owner, group) = self.get_owner_and_group(path)?;
@@ -138,14 +183,14 @@ impl LsCommandsUseCase {
     }
 
     /// Obtiene el propietario y grupo del archivo
-    fn get_owner_and_group(&self, _path: &Path) -> Result<(String, String), String> {
+    fn get_owner_and_group(&self, _path: &Path) -> Result<(String, String), TermirustError> {
         // En Windows, esto puede no funcionar completamente
         // Para simplificar, retornamos valores por defecto
         Ok(("usuario".to_string(), "grupo".to_string()))
     }
 
     /// Obtiene estadísticas del directorio
-    pub fn get_directory_stats(&self, path: Option<&str>) -> Result<DirectoryStats, String> {
+    pub fn get_directory_stats(&self, path: Option<&str>) -> Result<DirectoryStats, TermirustError> {
         let ls_result = self.list_directory(path)?;
         
         Ok(DirectoryStats {
@@ -217,35 +262,50 @@ impl LsCommandParser {
             return LsCommand::NotLsCommand;
         }
 
-        let mut options = LsOptions::default();
-        let mut target_path = None;
-
-        for part in &parts[1..] {
-            if part.starts_with('-') {
-                // Es una opción
-                for flag in part[1..].chars() {
-                    match flag {
-                        'a' => options.show_hidden = true,
-                        'd' => options.show_only_directories = true,
-                        'f' => options.show_only_files = true,
-                        'l' => options.long_format = true,
-                        'h' => options.human_readable = true,
-                        'S' => options.sort_by = SortBy::Size,
-                        't' => options.sort_by = SortBy::Modified,
-                        _ => return LsCommand::Invalid(format!("Opción desconocida: -{}", flag)),
-                    }
-                }
-            } else {
-                // Es un path
-                if target_path.is_none() {
-                    target_path = Some(part.to_string());
-                } else {
-                    return LsCommand::Invalid("Demasiados argumentos".to_string());
-                }
-            }
+        Self::parse_args(&parts[1..])
+    }
+
+    /// Opciones que acepta `ls`; también generan su `--help`
+    pub fn options() -> OptionParser {
+        OptionParser::new("ls", "ls [opciones] [ruta...]")
+            .with_description("Lista archivos y directorios.")
+            .with_option(OptionSpec::new("Mostrar archivos ocultos").with_short('a').with_long("all"))
+            .with_option(OptionSpec::new("Mostrar solo directorios").with_short('d').with_long("directories"))
+            .with_option(OptionSpec::new("Mostrar solo archivos").with_short('f').with_long("files"))
+            .with_option(OptionSpec::new("Formato largo (detallado)").with_short('l').with_long("long"))
+            .with_option(OptionSpec::new("Tamaños en formato legible").with_short('h').with_long("human-readable"))
+            .with_option(OptionSpec::new("Ordenar por tamaño").with_short('S').with_long("sort-size"))
+            .with_option(OptionSpec::new("Ordenar por fecha de modificación").with_short('t').with_long("sort-time"))
+    }
+
+    /// Parsea los argumentos ya separados de un comando ls (sin el nombre)
+    pub fn parse_args<S: AsRef<str>>(args: &[S]) -> LsCommand {
+        let parser = Self::options();
+        let parsed = match parser.parse(args) {
+            Ok(parsed) => parsed,
+            Err(e) => return LsCommand::Invalid(e),
+        };
+        if parsed.help {
+            return LsCommand::Help(parser.help());
         }
 
-        LsCommand::List(target_path, options)
+        let mut options = LsOptions {
+            show_hidden: parsed.flag("all"),
+            show_only_directories: parsed.flag("directories"),
+            show_only_files: parsed.flag("files"),
+            long_format: parsed.flag("long"),
+            human_readable: parsed.flag("human-readable"),
+            ..LsOptions::default()
+        };
+        // Entre -S y -t gana el último
+        for (name, _) in parsed.iter() {
+            match name {
+                "sort-size" => options.sort_by = SortBy::Size,
+                "sort-time" => options.sort_by = SortBy::Modified,
+                _ => {}
+            }
+        }
+        LsCommand::List(parsed.operands, options)
     }
 
     /// Verifica si un comando es un comando ls
@@ -256,28 +316,40 @@ impl LsCommandParser {
 }
 
 /// Enum que representa los diferentes comandos ls
+#[allow(clippy::enum_variant_names)]
 #[derive(Debug, Clone)]
 pub enum LsCommand {
-    List(Option<String>, LsOptions),
+    List(Vec<String>, LsOptions),
     #[allow(dead_code)]
     Stats(Option<String>),
-    Invalid(String),
+    /// `ls --help`, con el texto de ayuda
+    Help(String),
+    Invalid(TermirustError),
     NotLsCommand,
 }
 
 impl LsCommand {
-    /// Ejecuta el comando usando el caso de uso proporcionado
-    pub fn execute(self, use_case: &LsCommandsUseCase) -> Result<LsResult, String> {
+    /// Opciones con las que se debe mostrar el resultado
+    pub fn options(&self) -> LsOptions {
         match self {
-            LsCommand::List(path, options) => {
-                use_case.list_directory_with_options(path.as_deref(), &options)
-            }
-            LsCommand::Stats(path) => {
-                use_case.get_directory_stats(path.as_deref())
-                    .and_then(|_| use_case.list_directory(path.as_deref()))
+            LsCommand::List(_, options) => options.clone(),
+            _ => LsOptions::default(),
+        }
+    }
+
+    /// Ejecuta el comando usando el caso de uso proporcionado. Devuelve un resultado
+    /// por cada grupo listado.
+    pub fn execute(self, use_case: &LsCommandsUseCase) -> Vec<Result<LsResult, TermirustError>> {
+        match self {
+            LsCommand::List(paths, options) => use_case.list_paths(&paths, &options),
+            LsCommand::Stats(path) => vec![use_case
+                .get_directory_stats(path.as_deref())
+                .and_then(|_| use_case.list_directory(path.as_deref()))],
+            LsCommand::Help(_) => Vec::new(),
+            LsCommand::Invalid(error) => vec![Err(error)],
+            LsCommand::NotLsCommand => {
+                vec![Err(TermirustError::invalid_argument("ls", ArgumentError::WrongCommand(String::new())))]
             }
-            LsCommand::Invalid(message) => Err(format!("Error: {}", message)),
-            LsCommand::NotLsCommand => Err("No es un comando ls".to_string()),
         }
     }
 }
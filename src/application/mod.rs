@@ -1,4 +1,5 @@
 pub mod use_cases;
 pub mod history_commands;
+pub mod session_builtins;
 pub mod ls_commands;
 pub mod commands;
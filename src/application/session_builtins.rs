@@ -0,0 +1,471 @@
+use std::path::PathBuf;
+
+use crate::domain::aliases::Aliases;
+use crate::domain::builtins::{BuiltinCommand, BuiltinContext, Session};
+use crate::domain::entities::{Command, ShellOptions};
+use crate::domain::environment::Environment;
+use crate::domain::errors::{AliasError, ArgumentError, Construct, TermirustError};
+use crate::domain::options::OptionSpec;
+
+/// Comandos internos que consultan o cambian el estado de la sesión: variables,
+/// opciones, alias y funciones
+pub fn session_builtins() -> Vec<Box<dyn BuiltinCommand>> {
+    vec![
+        Box::new(SetBuiltin),
+        Box::new(ExportBuiltin),
+        Box::new(UnsetBuiltin),
+        Box::new(EnvBuiltin),
+        Box::new(AliasBuiltin),
+        Box::new(UnaliasBuiltin),
+        Box::new(TypeBuiltin),
+        Box::new(SourceBuiltin),
+    ]
+}
+
+/// Busca un programa en los directorios de `path`, o el archivo mismo si el
+/// nombre ya es una ruta
+pub fn find_in_path(name: &str, path: Option<&str>) -> Option<PathBuf> {
+    if name.contains('/') {
+        return Some(PathBuf::from(name)).filter(|path| path.is_file());
+    }
+    std::env::split_paths(path?).map(|dir| dir.join(name)).find(|candidate| candidate.is_file())
+}
+
+/// Resultado con la salida y los errores de cada argumento; falla si hubo alguno
+fn session_result(command: &Command, output: Vec<String>, errors: Vec<TermirustError>) -> Command {
+    let exit_code = if errors.is_empty() { 0 } else { 1 };
+    errors
+        .into_iter()
+        .fold(command.clone().with_output(output.join("\n")), Command::with_error)
+        .with_exit_code(exit_code)
+}
+
+/// Error de un nombre de variable no válido
+fn invalid_identifier(command: &Command, name: &str) -> TermirustError {
+    TermirustError::invalid_argument(&command.name, ArgumentError::InvalidIdentifier(name.to_string()))
+}
+
+/// Error de un alias que no existe
+fn alias_not_found(command: &Command, name: &str) -> TermirustError {
+    TermirustError::Alias { command: command.name.clone(), reason: AliasError::NotFound(name.to_string()) }
+}
+
+/// Guarda los alias para las próximas sesiones
+fn save_aliases(session: &Session) -> Result<(), TermirustError> {
+    match session.alias_repository {
+        Some(repository) => repository.save(session.aliases),
+        None => Ok(()),
+    }
+}
+
+/// `set`: lista las variables o cambia las opciones de la sesión
+pub struct SetBuiltin;
+
+impl BuiltinCommand for SetBuiltin {
+    fn name(&self) -> &str {
+        "set"
+    }
+
+    fn description(&self) -> &str {
+        "Mostrar todas las variables o cambiar las opciones de la sesión (set -o pty)"
+    }
+
+    fn usage(&self) -> &str {
+        "set [-o|+o opción] [-e|+e]"
+    }
+
+    fn help(&self) -> String {
+        r#"Uso: set [-o|+o opción] [-e|+e]
+
+Sin argumentos lista las variables de la sesión. -o activa y +o desactiva una
+opción; con -o solo, lista las opciones y su estado. Las letras se pueden juntar:
+set -eo pipefail equivale a set -e -o pipefail.
+  set -o errexit  - Detener un script en el primer comando que falle (set -e)
+  set -o pipefail - El código de una tubería es el de la última etapa que falló
+  set -o pty      - Ejecutar programas interactivos (vim, top, less) en una pseudoterminal"#
+            .to_string()
+    }
+
+    fn execute(&self, command: &Command, context: &mut BuiltinContext) -> Command {
+        let state = &mut *context.session.state;
+        let result = if command.args.is_empty() {
+            Ok(state
+                .environment
+                .variables()
+                .map(|(name, variable)| format!("{}={}", name, Environment::quote(&variable.value)))
+                .collect::<Vec<_>>()
+                .join("\n"))
+        } else {
+            self.apply(&command.args, &mut state.options)
+        };
+        match result {
+            Ok(output) => command.clone().with_output(output).with_exit_code(0),
+            Err(e) => {
+                let error = e.in_command(&command.name);
+                let exit_code = error.exit_code();
+                command.clone().with_error(error).with_exit_code(exit_code)
+            }
+        }
+    }
+}
+
+impl SetBuiltin {
+    /// Aplica las opciones en orden. Cada `o` de un grupo de letras toma el siguiente
+    /// argumento como nombre de opción; sin él, devuelve el listado de opciones.
+    fn apply(&self, args: &[String], options: &mut ShellOptions) -> Result<String, TermirustError> {
+        let mut output = String::new();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let enabled = match arg.chars().next() {
+                Some('-') => true,
+                Some('+') => false,
+                _ => return Err(TermirustError::usage("", self.usage())),
+            };
+            if arg.len() == 1 {
+                return Err(TermirustError::usage("", self.usage()));
+            }
+            for letter in arg[1..].chars() {
+                match letter {
+                    'e' => options.set("errexit", enabled)?,
+                    'o' => match args.next() {
+                        Some(name) => options.set(name, enabled)?,
+                        None => output = options.describe().join("\n"),
+                    },
+                    _ => {
+                        let option = format!("{}{}", &arg[..1], letter);
+                        return Err(TermirustError::invalid_argument("", ArgumentError::UnknownOption(option)));
+                    }
+                }
+            }
+        }
+        Ok(output)
+    }
+}
+
+/// `export`: pasa variables al entorno de los procesos
+pub struct ExportBuiltin;
+
+impl BuiltinCommand for ExportBuiltin {
+    fn name(&self) -> &str {
+        "export"
+    }
+
+    fn description(&self) -> &str {
+        "Definir variables y exportarlas a los procesos ($NOMBRE, ${NOMBRE:-valor})"
+    }
+
+    fn usage(&self) -> &str {
+        "export NOMBRE[=valor]..."
+    }
+
+    fn help(&self) -> String {
+        r#"Uso: export NOMBRE[=valor]...
+
+Define variables y las exporta a los procesos que se lancen. Sin argumentos (o
+con -p) lista las exportadas.
+  export EDITOR=vim  - Define y exporta EDITOR
+  export NOMBRE      - Exporta una variable ya definida"#
+            .to_string()
+    }
+
+    fn options(&self) -> Vec<OptionSpec> {
+        vec![OptionSpec::new("Listar las variables exportadas").with_short('p')]
+    }
+
+    fn execute(&self, command: &Command, context: &mut BuiltinContext) -> Command {
+        let environment = &mut context.session.state.environment;
+        if command.args.is_empty() || command.args == ["-p"] {
+            let lines: Vec<String> = environment
+                .variables()
+                .filter(|(_, variable)| variable.exported)
+                .map(|(name, variable)| format!("export {}={}", name, Environment::quote(&variable.value)))
+                .collect();
+            return command.clone().with_output(lines.join("\n")).with_exit_code(0);
+        }
+
+        let mut errors = Vec::new();
+        for arg in &command.args {
+            let (name, value) = match arg.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_string())),
+                None => (arg.as_str(), None),
+            };
+            if Environment::is_valid_name(name) {
+                environment.export(name, value);
+            } else {
+                errors.push(invalid_identifier(command, arg));
+            }
+        }
+        session_result(command, Vec::new(), errors)
+    }
+}
+
+/// `unset`: elimina variables de la sesión, o funciones con `-f`
+pub struct UnsetBuiltin;
+
+impl BuiltinCommand for UnsetBuiltin {
+    fn name(&self) -> &str {
+        "unset"
+    }
+
+    fn description(&self) -> &str {
+        "Eliminar variables (con -f, funciones)"
+    }
+
+    fn usage(&self) -> &str {
+        "unset [-f] NOMBRE..."
+    }
+
+    fn help(&self) -> String {
+        "Uso: unset [-f] NOMBRE...\n\nElimina variables; con -f elimina funciones.".to_string()
+    }
+
+    fn options(&self) -> Vec<OptionSpec> {
+        vec![
+            OptionSpec::new("Eliminar funciones").with_short('f'),
+            OptionSpec::new("Eliminar variables").with_short('v'),
+        ]
+    }
+
+    fn execute(&self, command: &Command, context: &mut BuiltinContext) -> Command {
+        let state = &mut *context.session.state;
+        let functions = command.args.first().is_some_and(|arg| arg == "-f");
+        let mut errors = Vec::new();
+        for name in command.args.iter().filter(|arg| !matches!(arg.as_str(), "-v" | "-f")) {
+            if functions {
+                state.functions.remove(name);
+            } else if Environment::is_valid_name(name) {
+                state.environment.unset(name);
+            } else {
+                errors.push(invalid_identifier(command, name));
+            }
+        }
+        session_result(command, Vec::new(), errors)
+    }
+}
+
+/// `env` sin argumentos: lista el entorno que recibirán los procesos. Con argumentos
+/// (`env NOMBRE=valor programa`) se usa el programa del sistema.
+pub struct EnvBuiltin;
+
+impl BuiltinCommand for EnvBuiltin {
+    fn name(&self) -> &str {
+        "env"
+    }
+
+    fn description(&self) -> &str {
+        "Mostrar el entorno de los procesos"
+    }
+
+    fn usage(&self) -> &str {
+        "env [NOMBRE=valor... programa]"
+    }
+
+    fn help(&self) -> String {
+        r#"Uso: env [NOMBRE=valor... programa]
+
+Sin argumentos muestra el entorno que reciben los procesos: las variables
+exportadas de la sesión. Con argumentos se ejecuta el env del sistema."#
+            .to_string()
+    }
+
+    fn handles(&self, args: &[String]) -> bool {
+        args.is_empty()
+    }
+
+    fn execute(&self, command: &Command, context: &mut BuiltinContext) -> Command {
+        let lines: Vec<String> = context
+            .session
+            .state
+            .environment
+            .exported()
+            .into_iter()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect();
+        command.clone().with_output(lines.join("\n")).with_exit_code(0)
+    }
+}
+
+/// `alias`: lista, muestra o define alias, que se guardan entre sesiones
+pub struct AliasBuiltin;
+
+impl BuiltinCommand for AliasBuiltin {
+    fn name(&self) -> &str {
+        "alias"
+    }
+
+    fn description(&self) -> &str {
+        "Definir o listar alias (se guardan entre sesiones)"
+    }
+
+    fn usage(&self) -> &str {
+        "alias [nombre[=valor]...]"
+    }
+
+    fn help(&self) -> String {
+        r#"Uso: alias [nombre[=valor]...]
+
+Sin argumentos lista los alias; con nombre muestra su definición y con
+nombre=valor define uno. Los alias se guardan entre sesiones.
+  alias ll='ls -l'  - Define ll
+  alias ll          - Muestra la definición de ll"#
+            .to_string()
+    }
+
+    fn options(&self) -> Vec<OptionSpec> {
+        vec![OptionSpec::new("Listar los alias").with_short('p')]
+    }
+
+    fn execute(&self, command: &Command, context: &mut BuiltinContext) -> Command {
+        let session = &mut context.session;
+        let mut output = Vec::new();
+        let mut errors = Vec::new();
+        let mut changed = false;
+        let args: Vec<&String> = command.args.iter().filter(|arg| arg.as_str() != "-p").collect();
+        if args.is_empty() {
+            output.extend(session.aliases.iter().map(|(name, value)| Aliases::definition(name, value)));
+        }
+        for arg in args {
+            match arg.split_once('=') {
+                Some((name, value)) => match session.aliases.set(name, value.to_string()) {
+                    Ok(()) => changed = true,
+                    Err(e) => errors.push(e),
+                },
+                None => match session.aliases.get(arg) {
+                    Some(value) => output.push(Aliases::definition(arg, value)),
+                    None => errors.push(alias_not_found(command, arg)),
+                },
+            }
+        }
+        if changed {
+            errors.extend(save_aliases(session).err());
+        }
+        session_result(command, output, errors)
+    }
+}
+
+/// `unalias`: elimina alias
+pub struct UnaliasBuiltin;
+
+impl BuiltinCommand for UnaliasBuiltin {
+    fn name(&self) -> &str {
+        "unalias"
+    }
+
+    fn description(&self) -> &str {
+        "Eliminar alias (-a, todos)"
+    }
+
+    fn usage(&self) -> &str {
+        "unalias [-a] nombre..."
+    }
+
+    fn help(&self) -> String {
+        "Uso: unalias [-a] nombre...\n\nElimina alias; -a los elimina todos.".to_string()
+    }
+
+    fn options(&self) -> Vec<OptionSpec> {
+        vec![OptionSpec::new("Eliminar todos los alias").with_short('a')]
+    }
+
+    fn execute(&self, command: &Command, context: &mut BuiltinContext) -> Command {
+        if command.args.is_empty() {
+            return session_result(command, Vec::new(), vec![TermirustError::usage(&command.name, self.usage())]);
+        }
+        let session = &mut context.session;
+        let mut errors = Vec::new();
+        for arg in &command.args {
+            if arg == "-a" {
+                session.aliases.clear();
+            } else if !session.aliases.remove(arg) {
+                errors.push(alias_not_found(command, arg));
+            }
+        }
+        errors.extend(save_aliases(session).err());
+        session_result(command, Vec::new(), errors)
+    }
+}
+
+/// `type`: indica qué se ejecuta con cada nombre
+pub struct TypeBuiltin;
+
+impl BuiltinCommand for TypeBuiltin {
+    fn name(&self) -> &str {
+        "type"
+    }
+
+    fn description(&self) -> &str {
+        "Indicar si un nombre es un alias, una función, un comando interno o un programa"
+    }
+
+    fn usage(&self) -> &str {
+        "type nombre..."
+    }
+
+    fn help(&self) -> String {
+        "Uso: type nombre...\n\nIndica si cada nombre es un alias, una función, un comando interno o un programa del PATH."
+            .to_string()
+    }
+
+    fn execute(&self, command: &Command, context: &mut BuiltinContext) -> Command {
+        if command.args.is_empty() {
+            return session_result(command, Vec::new(), vec![TermirustError::usage(&command.name, self.usage())]);
+        }
+        let session = &context.session;
+        let mut output = Vec::new();
+        let mut errors = Vec::new();
+        for name in &command.args {
+            if let Some(value) = session.aliases.get(name) {
+                output.push(format!("{} es un alias de '{}'", name, value));
+            } else if let Some(function) = session.state.functions.get(name) {
+                output.push(format!("{} es una función", name));
+                output.push(function.text.clone());
+            } else if let Some(builtin) = context.registry.find(name) {
+                match builtin.location() {
+                    Some(path) => output.push(format!("{} es un plugin ({})", name, path.display())),
+                    None => output.push(format!("{} es un comando interno", name)),
+                }
+            } else if let Some(path) = find_in_path(name, session.state.environment.get("PATH")) {
+                output.push(format!("{} es {}", name, path.display()));
+            } else {
+                errors.push(TermirustError::NotFound { command: command.name.clone(), path: Some(name.clone()) });
+            }
+        }
+        session_result(command, output, errors)
+    }
+}
+
+/// `source`: ejecuta un script en la sesión. Sus líneas son pasos de la sesión, así
+/// que lo ejecuta el caso de uso; aquí solo llega dentro de una tubería.
+pub struct SourceBuiltin;
+
+impl BuiltinCommand for SourceBuiltin {
+    fn name(&self) -> &str {
+        "source"
+    }
+
+    fn aliases(&self) -> &[&str] {
+        &["."]
+    }
+
+    fn description(&self) -> &str {
+        "Ejecutar un script de Termirust (set -e lo detiene en el primer error)"
+    }
+
+    fn usage(&self) -> &str {
+        "source archivo.trs [argumentos...]"
+    }
+
+    fn help(&self) -> String {
+        r#"Uso: source archivo.trs [argumentos...]
+
+Ejecuta un script de Termirust en la sesión actual, con los argumentos como
+$1..$n. También se escribe '. archivo'. Con set -e el script se detiene en el
+primer comando que falle."#
+            .to_string()
+    }
+
+    fn execute(&self, command: &Command, _context: &mut BuiltinContext) -> Command {
+        let error = TermirustError::Unsupported { command: command.name.clone(), construct: Construct::SourceInPipeline };
+        command.clone().with_error(error).with_exit_code(1)
+    }
+}
@@ -1,75 +1,1008 @@
-use crate::domain::entities::{Command, TerminalMode};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use crate::domain::aliases::Aliases;
+use crate::domain::builtins::{asks_for_help, CommandCatalog, Session};
+use crate::domain::completion::{
+    self, option_candidates, Candidate, Completion, CompletionMenu, CompletionTarget, PathFilter,
+};
+use crate::domain::completion_spec::{ArgumentKind, CompletionSpec};
+use crate::domain::ast::{
+    AndOrList, CommandLine, CompoundCommand, ForClause, IfClause, Pipeline, Statement, WhileClause,
+};
+use crate::domain::document::{Document, DocumentView};
+use crate::domain::errors::{Construct, Nesting, SourceLocation, TermirustError};
+use crate::domain::expansion::Expander;
+use crate::domain::options::OptionSpec;
+use crate::domain::screen::Screen;
+use crate::domain::style::StyledLine;
+use crate::domain::suggestions::{closest, correct_line, CommandSuggestion, ExecutableCache};
+use crate::domain::entities::{
+    Command, CommandStep, EventSender, ExecutionEvent, InterruptFlag, PipelineOptions, PipelineStage,
+    TerminalMode, INTERRUPTED_EXIT_CODE,
+};
 use crate::domain::services::{CommandService, TerminalService};
-use crate::domain::repositories::CommandRepository;
+use crate::domain::repositories::{
+    AliasRepository, CommandRepository, CompletionSpecRepository, DirectoryRepository, UserRepository,
+};
 use crate::application::commands::exit_commands::ExitCommandsUseCase;
+use crate::application::history_commands::{HistoryBuiltin, HistoryCommandsUseCase};
+use crate::application::session_builtins::{find_in_path, session_builtins};
+
+/// Máximo de scripts anidados con `source` y de llamadas anidadas a funciones, para
+/// cortar un script que se carga a sí mismo o una función que se llama sin fin
+const MAX_NESTING: usize = 64;
 
-/// Caso de uso para ejecutar comandos
+/// Caso de uso para ejecutar comandos. Se ejecuta en un hilo de trabajo y comunica
+/// su salida a la interfaz mediante eventos.
 pub struct ExecuteCommandUseCase<R>
 where
     R: CommandRepository,
 {
     command_service: CommandService<R>,
     terminal_service: TerminalService,
+    history_commands: Arc<Mutex<HistoryCommandsUseCase>>,
+    /// Comandos registrados al crear la sesión, con lo que declaran para completarse
+    catalog: Arc<CommandCatalog>,
+    /// Alias de la sesión
+    aliases: Arc<Mutex<Aliases>>,
+    /// Dónde se guardan los alias al cambiarlos
+    alias_repository: Option<Box<dyn AliasRepository>>,
+    /// Directorios donde se buscan los nombres de archivo de los patrones (`*.rs`)
+    directories: Option<Arc<dyn DirectoryRepository>>,
+    /// Cuentas del sistema, para expandir `~usuario`
+    users: Option<Arc<dyn UserRepository>>,
+    /// Scripts en ejecución con `source`; mientras tanto no se repite el prompt
+    script_depth: usize,
+    /// Archivo y línea del script que se está ejecutando, para ubicar los errores
+    script_line: Option<SourceLocation>,
+    /// Cuerpos de estructuras de control en ejecución
+    body_depth: usize,
+    /// Llamadas a funciones en ejecución
+    call_depth: usize,
+    /// Condiciones de `if` y `while` y comandos antes del último de una lista `&&` o
+    /// `||` en ejecución: con `set -e`, sus fallos no detienen el script
+    errexit_ignored: usize,
+    /// Un comando falló con `set -e`: se abandonan los scripts en ejecución
+    errexit_stopped: bool,
+    /// Salida de la sustitución de comandos en curso; mientras hay una, la salida
+    /// de los comandos se guarda aquí en lugar de mostrarse
+    captured: Option<String>,
+    /// Programas del `PATH`, para sugerir y completar nombres de comandos
+    executables: ExecutableCache,
+    /// Especificaciones para completar los argumentos de programas externos, por nombre
+    completion_specs: BTreeMap<String, CompletionSpec>,
+    /// De dónde salen las especificaciones; también ejecuta sus comandos
+    spec_repository: Option<Box<dyn CompletionSpecRepository>>,
+    /// Salida de los comandos de las especificaciones por directorio y comando. Se
+    /// descarta al ejecutar una línea, que puede cambiarla (una rama nueva).
+    command_outputs: BTreeMap<(PathBuf, String), Vec<String>>,
+    /// Se levanta con Ctrl+C; corta los bucles y el resto de la línea
+    interrupt: InterruptFlag,
+    events: EventSender,
 }
 
 impl<R> ExecuteCommandUseCase<R>
 where
     R: CommandRepository,
 {
-    pub fn new(command_service: CommandService<R>, terminal_service: TerminalService) -> Self {
+    pub fn new(mut command_service: CommandService<R>, mut terminal_service: TerminalService, events: EventSender) -> Self {
+        // El estado de la sesión consulta la misma tabla de trabajos que el repositorio
+        terminal_service.get_state_mut().jobs = command_service.job_table();
+        let history_commands = Arc::new(Mutex::new(HistoryCommandsUseCase::new(100)));
+        command_service.register_builtin(Box::new(HistoryBuiltin::new(Arc::clone(&history_commands))));
+        for builtin in session_builtins() {
+            command_service.register_builtin(builtin);
+        }
+        let catalog = Arc::new(command_service.command_catalog());
         Self {
             command_service,
             terminal_service,
+            history_commands,
+            catalog,
+            aliases: Arc::new(Mutex::new(Aliases::default())),
+            alias_repository: None,
+            directories: None,
+            users: None,
+            script_depth: 0,
+            script_line: None,
+            body_depth: 0,
+            call_depth: 0,
+            errexit_ignored: 0,
+            errexit_stopped: false,
+            captured: None,
+            executables: ExecutableCache::default(),
+            completion_specs: BTreeMap::new(),
+            spec_repository: None,
+            command_outputs: BTreeMap::new(),
+            interrupt: InterruptFlag::default(),
+            events,
         }
     }
 
-    pub fn execute(&mut self, input: &str) -> Result<Command, String> {
-        // Parsear el comando
-        let command = self.command_service.parse_command(input);
-        
+    /// Carga los alias guardados y guarda allí los que se definan en la sesión
+    pub fn with_alias_repository(mut self, repository: Box<dyn AliasRepository>) -> Self {
+        match repository.load() {
+            Ok(aliases) => *self.lock_aliases() = aliases,
+            Err(e) => self.emit_error(e),
+        }
+        self.alias_repository = Some(repository);
+        self
+    }
+
+    /// Lee los directorios con `repository` para expandir los nombres de archivo
+    pub fn with_directory_repository(mut self, repository: Box<dyn DirectoryRepository>) -> Self {
+        self.directories = Some(Arc::from(repository));
+        self
+    }
+
+    /// Consulta las cuentas con `repository` para expandir `~usuario`
+    pub fn with_user_repository(mut self, repository: Box<dyn UserRepository>) -> Self {
+        self.users = Some(Arc::from(repository));
+        self
+    }
+
+    /// Carga las especificaciones para completar programas externos (`git`, `cargo`)
+    pub fn with_completion_spec_repository(mut self, repository: Box<dyn CompletionSpecRepository>) -> Self {
+        for spec in repository.load() {
+            match spec {
+                Ok(spec) => {
+                    self.completion_specs.insert(spec.name.clone(), spec);
+                }
+                Err(e) => self.emit_error(e),
+            }
+        }
+        self.spec_repository = Some(repository);
+        self
+    }
+
+    /// Ejecuta una línea completa. Cada tubería de la lista es un paso con su propio
+    /// eco del prompt; `&&` y `||` deciden según el código de salida del paso anterior.
+    pub fn execute(&mut self, input: &str) -> Result<Vec<CommandStep>, TermirustError> {
         // Agregar al historial
         self.terminal_service.get_state_mut().add_to_history(input.to_string());
+        self.interrupt.clear();
+        self.command_outputs.clear();
+        let mut steps = Vec::new();
+        self.execute_line(input, &mut steps).map(|_| steps)
+    }
+
+    /// Ejecuta una línea sin agregarla al historial, como las de un script, y devuelve
+    /// el código de salida de su última lista. Una línea vacía no lo cambia.
+    fn execute_line(&mut self, input: &str, steps: &mut Vec<CommandStep>) -> Result<i32, TermirustError> {
+        // Parsear el comando; un error de sintaxis no ejecuta nada
+        let command_line = match self.command_service.parse_command(input) {
+            Ok(command_line) => command_line,
+            Err(e) => {
+                self.echo_prompt(input);
+                let error = TermirustError::from(e);
+                self.terminal_service.get_state_mut().last_exit_code = error.exit_code();
+                return Err(error);
+            }
+        };
+        if command_line.items.is_empty() {
+            self.echo_prompt(input);
+            return Ok(self.terminal_service.get_state().last_exit_code);
+        }
         
-        // Agregar el comando ejecutado al output
+        Ok(self.execute_lists(&command_line, steps))
+    }
+
+    /// Ejecuta las listas de una línea o del cuerpo de una estructura de control y
+    /// devuelve el código de salida de la última. Ctrl+C cancela también el resto,
+    /// como en un shell POSIX, aunque solo se estuvieran ejecutando comandos internos.
+    fn execute_lists(&mut self, command_line: &CommandLine, steps: &mut Vec<CommandStep>) -> i32 {
+        let mut last_exit_code = 0;
+        for list in &command_line.items {
+            if list.background && (!list.rest.is_empty() || !matches!(list.first, Statement::Pipeline(_))) {
+                self.reject_background_list(list, steps);
+                last_exit_code = 1;
+                self.check_errexit(list.text(), last_exit_code);
+                continue;
+            }
+            // Solo el último comando de la lista puede detener el script con `set -e`
+            let last = list.rest.len();
+            last_exit_code = self.execute_tested(last > 0, |this| this.execute_statement(&list.first, list.background, steps));
+            let mut last_ran = last == 0;
+            for (index, (connector, statement)) in list.rest.iter().enumerate() {
+                if self.should_stop(last_exit_code) {
+                    break;
+                }
+                if connector.should_run(last_exit_code) {
+                    last_ran = index + 1 == last;
+                    last_exit_code = self.execute_tested(!last_ran, |this| this.execute_statement(statement, false, steps));
+                }
+            }
+            if last_ran {
+                self.check_errexit(list.text(), last_exit_code);
+            }
+            if self.should_stop(last_exit_code) {
+                break;
+            }
+        }
+        if self.interrupt.is_raised() {
+            return INTERRUPTED_EXIT_CODE;
+        }
+        last_exit_code
+    }
+
+    /// Se pidió cerrar la terminal, el último paso se interrumpió con Ctrl+C o un fallo
+    /// detuvo el script con `set -e`
+    fn should_stop(&self, exit_code: i32) -> bool {
+        self.should_exit() || exit_code == INTERRUPTED_EXIT_CODE || self.interrupt.is_raised() || self.errexit_stopped
+    }
+
+    /// Ejecuta una sentencia cuyo fallo se comprueba, como una condición: con `tested`,
+    /// `set -e` no detiene el script aunque falle ella o un comando de su cuerpo
+    fn execute_tested(&mut self, tested: bool, execute: impl FnOnce(&mut Self) -> i32) -> i32 {
+        if !tested {
+            return execute(self);
+        }
+        self.errexit_ignored += 1;
+        let exit_code = execute(self);
+        self.errexit_ignored -= 1;
+        exit_code
+    }
+
+    /// Con `set -e`, una lista que falla fuera de una condición detiene los scripts en
+    /// ejecución. Las sustituciones de comandos no heredan la opción.
+    fn check_errexit(&mut self, command: String, exit_code: i32) {
+        let applies = self.script_depth > 0
+            && self.errexit_ignored == 0
+            && self.captured.is_none()
+            && self.terminal_service.get_state().options.errexit;
+        if !applies || exit_code == 0 || exit_code == INTERRUPTED_EXIT_CODE || self.errexit_stopped {
+            return;
+        }
+        self.errexit_stopped = true;
+        self.emit_error(TermirustError::ScriptStopped { command, exit_code });
+    }
+
+    /// Ejecuta una tubería o una estructura de control. Las condiciones usan el
+    /// código de salida de sus comandos: 0 es verdadero.
+    fn execute_statement(&mut self, statement: &Statement, background: bool, steps: &mut Vec<CommandStep>) -> i32 {
+        let (command, text) = match statement {
+            Statement::Pipeline(pipeline) => return self.execute_step(pipeline, background, steps),
+            Statement::Compound { command, text } => (command, text),
+        };
+        self.echo_prompt(text);
+        self.body_depth += 1;
+        let exit_code = match command {
+            CompoundCommand::If(clause) => self.execute_if(clause, steps),
+            CompoundCommand::For(clause) => self.execute_for(clause, steps),
+            CompoundCommand::While(clause) => self.execute_while(clause, steps),
+            CompoundCommand::Function(function) => {
+                let functions = &mut self.terminal_service.get_state_mut().functions;
+                functions.insert(function.name.clone(), function.clone());
+                0
+            }
+        };
+        self.body_depth -= 1;
+        // La estructura es un paso más, después de los de su cuerpo, con su propio código
+        self.terminal_service.get_state_mut().last_exit_code = exit_code;
+        let result = Command::new(String::new(), Vec::new()).with_exit_code(exit_code);
+        steps.push(CommandStep::new(text.clone(), result));
+        exit_code
+    }
+
+    /// Ejecuta el cuerpo de la primera rama cuya condición termina con éxito
+    fn execute_if(&mut self, clause: &IfClause, steps: &mut Vec<CommandStep>) -> i32 {
+        for (condition, body) in &clause.branches {
+            let condition_code = self.execute_tested(true, |this| this.execute_lists(condition, steps));
+            if self.should_stop(condition_code) {
+                return condition_code;
+            }
+            if condition_code == 0 {
+                return self.execute_lists(body, steps);
+            }
+        }
+        match &clause.else_body {
+            Some(body) => self.execute_lists(body, steps),
+            None => 0,
+        }
+    }
+
+    /// Ejecuta el cuerpo una vez por palabra, con la variable del bucle asignada
+    fn execute_for(&mut self, clause: &ForClause, steps: &mut Vec<CommandStep>) -> i32 {
+        let values = match &clause.words {
+            Some(words) => match self.with_expander(|expander| {
+                let mut values = Vec::new();
+                for word in words {
+                    values.extend(expander.expand_word(word)?);
+                }
+                Ok(values)
+            }) {
+                Ok(values) => values,
+                Err(e) => {
+                    self.emit_error(e);
+                    return 1;
+                }
+            },
+            None => self.terminal_service.get_state().positional.clone(),
+        };
+
+        let mut exit_code = 0;
+        for value in values {
+            self.terminal_service.get_state_mut().environment.set(&clause.variable, value);
+            exit_code = self.execute_lists(&clause.body, steps);
+            if self.should_stop(exit_code) {
+                break;
+            }
+        }
+        exit_code
+    }
+
+    /// Repite el cuerpo mientras la condición termine con éxito
+    fn execute_while(&mut self, clause: &WhileClause, steps: &mut Vec<CommandStep>) -> i32 {
+        let mut exit_code = 0;
+        loop {
+            let condition_code = self.execute_tested(true, |this| this.execute_lists(&clause.condition, steps));
+            if self.should_stop(condition_code) {
+                return condition_code;
+            }
+            if condition_code != 0 {
+                return exit_code;
+            }
+            exit_code = self.execute_lists(&clause.body, steps);
+            if self.should_stop(exit_code) {
+                return exit_code;
+            }
+        }
+    }
+
+    /// Envía una línea de salida a la interfaz
+    fn emit_line(&self, line: String) {
+        let _ = self.events.send(ExecutionEvent::Output(line));
+    }
+
+    /// Envía un error a la interfaz, con el archivo y la línea del script si hay uno
+    fn emit_error(&self, error: TermirustError) {
+        let _ = self.events.send(ExecutionEvent::Error(self.locate(error)));
+    }
+
+    /// Muestra un error de la ejecución y lo convierte en el resultado fallido del
+    /// paso, con el código de salida que le corresponde
+    fn fail(&self, error: TermirustError) -> Command {
+        let exit_code = error.exit_code();
+        let error = self.locate(error);
+        let _ = self.events.send(ExecutionEvent::Error(error.clone()));
+        Command::new(String::new(), Vec::new()).with_error(error).with_exit_code(exit_code)
+    }
+
+    /// Agrega al output la línea del prompt seguida del texto ejecutado
+    fn echo_prompt(&self, text: &str) {
+        // Solo se repiten las líneas escritas en la terminal
+        if self.script_depth > 0 || self.body_depth > 0 || self.call_depth > 0 || self.captured.is_some() {
+            return;
+        }
         let current_dir = std::env::current_dir()
             .unwrap_or_default()
             .to_string_lossy()
             .to_string();
         let prompt = format!("{}:~$ ", current_dir);
-        self.terminal_service.add_output_line(format!("{}{}", prompt, input));
+        self.emit_line(format!("{}{}", prompt, text));
+    }
+
+    /// `&` solo puede lanzar una tubería: una lista con `&&` o `||` necesitaría un
+    /// subshell que la ejecute completa en segundo plano
+    fn reject_background_list(&mut self, list: &AndOrList, steps: &mut Vec<CommandStep>) {
+        let text = format!("{} &", list.text());
+        self.echo_prompt(&text);
+        let result = self.fail(TermirustError::Unsupported { command: String::new(), construct: Construct::BackgroundList });
+        steps.push(CommandStep::new(text, result));
+    }
+
+    /// Ejecuta una tubería como un paso de la línea y devuelve su código de salida
+    fn execute_step(&mut self, pipeline: &Pipeline, background: bool, steps: &mut Vec<CommandStep>) -> i32 {
+        let text = if background { format!("{} &", pipeline.text) } else { pipeline.text.clone() };
+        self.echo_prompt(&text);
         
-        // Ejecutar el comando
-        let result = self.command_service.execute_command(&command)?;
+        // La salida de la tubería llega a la interfaz mientras se ejecuta
+        let previous_dir = std::env::current_dir().ok();
+        let result = self
+            .expand_pipeline(pipeline)
+            .and_then(|commands| self.run_commands(commands, &pipeline.text, background))
+            .unwrap_or_else(|e| self.fail(e));
         
-        // Manejar comandos especiales
+        // Manejar comandos especiales; dentro de una sustitución no afectan a la sesión
         match result.name.as_str() {
+            _ if self.captured.is_some() => {}
+            "cd" if result.exit_code == 0 => self.update_working_directory(previous_dir),
             "clear" => {
-                self.terminal_service.clear_output();
+                let _ = self.events.send(ExecutionEvent::Clear);
             }
             "exit" | "quit" => {
                 // Usar el caso de uso específico del comando exit
                 let exit_use_case = ExitCommandsUseCase::new();
-                if exit_use_case.is_exit_command(&result.name) {
+                if exit_use_case.should_close(&result) {
                     // Marcar que se debe cerrar la aplicación
                     self.terminal_service.get_state_mut().should_exit = true;
                 }
             }
+            _ => {}
+        }
+        
+        let exit_code = result.exit_code;
+        self.terminal_service.get_state_mut().last_exit_code = exit_code;
+        steps.push(CommandStep::new(text, result));
+        exit_code
+    }
+
+    /// Ejecuta los comandos ya expandidos de una tubería. `source` y las funciones no
+    /// son etapas: sus líneas se ejecutan como pasos de la sesión.
+    fn run_commands(&mut self, commands: Vec<Command>, text: &str, background: bool) -> Result<Command, TermirustError> {
+        if let Some(function) = commands.iter().find(|command| self.is_function(&command.name)) {
+            if commands.len() > 1 || background || !function.redirects.is_empty() {
+                return Err(TermirustError::Unsupported {
+                    command: function.name.clone(),
+                    construct: Construct::FunctionInPipeline,
+                });
+            }
+        }
+        match commands.as_slice() {
+            [command] if matches!(command.name.as_str(), "source" | ".") && !asks_for_help(&command.args) => {
+                Ok(self.execute_source(command))
+            }
+            [command] if self.is_function(&command.name) => Ok(self.call_function(command)),
             _ => {
-                // Agregar salida al terminal
-                if !result.output.is_empty() {
-                    for line in result.output.lines() {
-                        self.terminal_service.add_output_line(line.to_string());
+                let capturing = self.captured.is_some();
+                let result = match self.execute_pipeline(commands, text, background, !capturing) {
+                    Ok(result) => result,
+                    Err(e) => return Ok(self.fail(e)),
+                };
+                if capturing {
+                    for line in result.error_output.lines() {
+                        let location = self.script_line.clone();
+                        let _ = self.events.send(ExecutionEvent::ErrorOutput { line: line.to_string(), location });
+                    }
+                    if let Some(output) = &mut self.captured {
+                        output.push_str(&result.output);
                     }
                 }
+                Ok(result)
             }
         }
-        
+    }
+
+    fn is_function(&self, name: &str) -> bool {
+        self.terminal_service.get_state().functions.contains_key(name)
+    }
+
+    /// Ejecuta el cuerpo de una función con sus argumentos como `$1..$n`
+    fn call_function(&mut self, command: &Command) -> Command {
+        let function = self.terminal_service.get_state().functions.get(&command.name).cloned();
+        let Some(function) = function else {
+            return command.clone().with_exit_code(127);
+        };
+        if self.call_depth >= MAX_NESTING {
+            return self.fail(TermirustError::TooDeep { command: command.name.clone(), nesting: Nesting::Calls });
+        }
+
+        let saved = std::mem::replace(&mut self.terminal_service.get_state_mut().positional, command.args.clone());
+        self.call_depth += 1;
+        let exit_code = self.execute_lists(&function.body, &mut Vec::new());
+        self.call_depth -= 1;
+        self.terminal_service.get_state_mut().positional = saved;
+        command.clone().with_exit_code(exit_code)
+    }
+
+    /// Ejecuta `source archivo [argumentos...]`: las líneas del archivo en la sesión
+    /// actual, con los argumentos como `$1..$n`
+    fn execute_source(&mut self, command: &Command) -> Command {
+        let result = match command.args.split_first() {
+            Some((path, [])) => self.run_script(Path::new(path)),
+            Some((path, args)) => {
+                let saved = std::mem::replace(&mut self.terminal_service.get_state_mut().positional, args.to_vec());
+                let result = self.run_script(Path::new(path));
+                self.terminal_service.get_state_mut().positional = saved;
+                result
+            }
+            None => Err(TermirustError::usage(&command.name, "source archivo.trs [argumentos...]")),
+        };
+        match result {
+            Ok(exit_code) => command.clone().with_exit_code(exit_code),
+            Err(e) => self.fail(e.in_command(&command.name)),
+        }
+    }
+
+    /// Ejecuta un script de Termirust línea por línea, como si se escribieran en la
+    /// terminal, y devuelve el código de salida de la última. Una estructura de control
+    /// puede ocupar varias líneas. Los errores indican el archivo y la línea; con
+    /// `set -e` el script se detiene en el primer fallo.
+    pub fn run_script(&mut self, path: &Path) -> Result<i32, TermirustError> {
+        let display = path.display().to_string();
+        if self.script_depth >= MAX_NESTING {
+            return Err(TermirustError::TooDeep { command: display, nesting: Nesting::Scripts });
+        }
+        let content = std::fs::read_to_string(path).map_err(|e| TermirustError::from_io("", Some(&display), &e))?;
+        let lines: Vec<&str> = content.lines().collect();
+
+        self.script_depth += 1;
+        let outer_line = self.script_line.take();
+        let mut exit_code = 0;
+        let mut index = 0;
+        while index < lines.len() {
+            self.script_line = Some(SourceLocation::new(&display, index + 1));
+            let mut line = lines[index].to_string();
+            index += 1;
+            // Se agregan líneas mientras falte cerrar algo, como el `fi` de un `if`
+            while index < lines.len()
+                && matches!(self.command_service.parse_command(&line), Err(e) if e.is_incomplete())
+            {
+                line.push('\n');
+                line.push_str(lines[index]);
+                index += 1;
+            }
+            exit_code = match self.execute_line(&line, &mut Vec::new()) {
+                Ok(exit_code) => exit_code,
+                Err(e) => {
+                    let exit_code = e.exit_code();
+                    self.emit_error(e);
+                    let command = line.lines().next().unwrap_or_default().trim().to_string();
+                    self.check_errexit(command, exit_code);
+                    exit_code
+                }
+            };
+            if self.should_stop(exit_code) {
+                break;
+            }
+        }
+        self.script_line = outer_line;
+        self.script_depth -= 1;
+        // `set -e` abandona también los scripts que cargaron a este con `source`
+        if self.script_depth == 0 {
+            self.errexit_stopped = false;
+        }
+        Ok(exit_code)
+    }
+
+    /// Ubica el error en el archivo y la línea del script en ejecución, si hay uno
+    fn locate(&self, error: TermirustError) -> TermirustError {
+        match &self.script_line {
+            Some(location) => error.at(location.clone()),
+            None => error,
+        }
+    }
+
+    /// Ejecuta el script de inicio del usuario si existe
+    pub fn run_startup_script(&mut self, path: &Path) {
+        if !path.is_file() {
+            return;
+        }
+        if let Err(e) = self.run_script(path) {
+            self.emit_error(e);
+        }
+    }
+
+    /// Expande cada etapa con el entorno actual de la sesión, después de reemplazar
+    /// los alias
+    fn expand_pipeline(&mut self, pipeline: &Pipeline) -> Result<Vec<Command>, TermirustError> {
+        let pipeline = self.lock_aliases().expand(pipeline)?;
+        self.with_expander(|expander| pipeline.commands.iter().map(|command| expander.expand_command(command)).collect())
+    }
+
+    /// Prepara un expansor con las variables y los parámetros de la sesión. Las
+    /// sustituciones de comandos se ejecutan aquí mismo y su salida pasa a formar
+    /// parte de la línea.
+    fn with_expander<T>(
+        &mut self,
+        expand: impl FnOnce(&Expander) -> Result<T, TermirustError>,
+    ) -> Result<T, TermirustError> {
+        let state = self.terminal_service.get_state();
+        let environment = state.environment.clone();
+        let positional = state.positional.clone();
+        let last_exit_code = state.last_exit_code;
+        let directories = self.directories.clone();
+        let users = self.users.clone();
+        let mut runner = |command: &str| self.capture_output(command);
+        let mut expander = Expander::new(&environment, last_exit_code).with_positional(&positional);
+        if let Some(directories) = &directories {
+            expander = expander.with_directories(directories.as_ref());
+        }
+        if let Some(users) = &users {
+            expander = expander.with_users(users.as_ref());
+        }
+        expand(&expander.with_command_substitution(&mut runner))
+    }
+
+    /// Ejecuta la línea de una sustitución `$(...)` y devuelve su salida estándar.
+    /// Funciona como un subshell: las variables, funciones, opciones y el directorio
+    /// que cambie no afectan a la sesión. Sus errores se muestran en la terminal.
+    fn capture_output(&mut self, line: &str) -> Result<String, TermirustError> {
+        let command_line = self.command_service.parse_command(line)?;
+        let state = self.terminal_service.get_state();
+        let saved = (
+            state.environment.clone(),
+            state.functions.clone(),
+            state.options.clone(),
+            state.last_exit_code,
+        );
+        let working_dir = std::env::current_dir().ok();
+        // La salida se captura, así que no hace falta una pseudoterminal
+        self.terminal_service.get_state_mut().options.pty = false;
+
+        let outer_capture = self.captured.replace(String::new());
+        self.execute_lists(&command_line, &mut Vec::new());
+        let output = std::mem::replace(&mut self.captured, outer_capture).unwrap_or_default();
+
+        let state = self.terminal_service.get_state_mut();
+        (state.environment, state.functions, state.options, state.last_exit_code) = saved;
+        if let Some(working_dir) = working_dir {
+            let _ = std::env::set_current_dir(working_dir);
+        }
+        Ok(output)
+    }
+
+    /// Mantiene `PWD` y `OLDPWD` al día después de un `cd`
+    fn update_working_directory(&mut self, previous_dir: Option<PathBuf>) {
+        let environment = &mut self.terminal_service.get_state_mut().environment;
+        if let Some(previous_dir) = previous_dir {
+            environment.set("OLDPWD", previous_dir.to_string_lossy().to_string());
+        }
+        if let Ok(current_dir) = std::env::current_dir() {
+            environment.set("PWD", current_dir.to_string_lossy().to_string());
+        }
+    }
+
+    /// Resuelve cada etapa de la tubería y la ejecuta con las opciones de la sesión.
+    /// Con `show_output` la salida llega a la interfaz; si no, solo se captura.
+    fn execute_pipeline(
+        &mut self,
+        commands: Vec<Command>,
+        text: &str,
+        background: bool,
+        show_output: bool,
+    ) -> Result<Command, TermirustError> {
+        let mut stages = Vec::with_capacity(commands.len());
+        for command in commands {
+            stages.push(self.resolve_stage(command)?);
+        }
+        let session = &self.terminal_service.get_state().options;
+        let options = PipelineOptions {
+            text: text.to_string(),
+            pipefail: session.pipefail,
+            background,
+            pty: session.pty,
+            environment: Some(self.terminal_service.get_state().environment.exported()),
+            location: self.script_line.clone(),
+        };
+        let programs: Vec<String> = stages
+            .iter()
+            .filter_map(|stage| match stage {
+                PipelineStage::External(command) => Some(command.name.clone()),
+                PipelineStage::Builtin(_) => None,
+            })
+            .collect();
+        let events = show_output.then_some(&self.events);
+        let mut result = self.command_service.execute_pipeline(stages, &options, events)?;
+        // Los errores de las etapas se muestran aunque la salida se capture
+        result.errors = result.errors.into_iter().map(|error| self.locate(error)).collect();
+        for error in &result.errors {
+            let _ = self.events.send(ExecutionEvent::Error(error.clone()));
+        }
+        if show_output && result.exit_code != 0 {
+            self.suggest_commands(&programs, text);
+        }
         Ok(result)
     }
 
+    /// Sugiere nombres parecidos para los programas de la tubería que no existen,
+    /// buscando entre el `PATH`, los comandos internos, los alias y las funciones
+    fn suggest_commands(&mut self, programs: &[String], text: &str) {
+        let path = self.terminal_service.get_state().environment.get("PATH").map(str::to_string);
+        let missing: Vec<&String> = programs
+            .iter()
+            .filter(|name| !name.contains('/') && find_in_path(name, path.as_deref()).is_none())
+            .collect();
+        if missing.is_empty() {
+            return;
+        }
+        let mut known: Vec<String> = self.catalog.names().map(str::to_string).collect();
+        known.extend(self.lock_aliases().iter().map(|(name, _)| name.to_string()));
+        known.extend(self.terminal_service.get_state().functions.keys().cloned());
+        known.extend(self.path_executables());
+
+        let suggestions: Vec<CommandSuggestion> = missing
+            .iter()
+            .map(|name| CommandSuggestion {
+                command: name.to_string(),
+                candidates: closest(name, known.iter().map(String::as_str)),
+            })
+            .filter(|suggestion| !suggestion.candidates.is_empty())
+            .collect();
+        if suggestions.is_empty() {
+            return;
+        }
+        // Solo se ofrece ejecutar la corrección si todos los comandos tienen sugerencia
+        let corrected = (suggestions.len() == missing.len())
+            .then(|| correct_line(text, &suggestions))
+            .flatten();
+        let _ = self.events.send(ExecutionEvent::Suggestions { suggestions, corrected });
+    }
+
+    /// Candidatos para completar con Tab la palabra del final de la línea: comandos,
+    /// opciones de los comandos internos, archivos o variables, según su posición. No
+    /// ejecuta procesos: si una especificación necesita la salida de un comando que no
+    /// está guardada, lo indica en `pending`.
+    pub fn complete(&mut self, line: &str) -> Completion {
+        self.complete_line(line, false)
+    }
+
+    /// Como `complete`, pero ejecuta el comando que falte y guarda su salida hasta la
+    /// próxima línea que se ejecute. Se llama desde el hilo de trabajo.
+    pub fn complete_with_commands(&mut self, line: &str) -> Completion {
+        self.complete_line(line, true)
+    }
+
+    fn complete_line(&mut self, line: &str, run_commands: bool) -> Completion {
+        let request = completion::analyze(line);
+        let home = self.terminal_service.get_state().environment.get("HOME").map(str::to_string);
+        let home = home.as_deref();
+        let mut pending = None;
+        let candidates = match &request.target {
+            CompletionTarget::Command if request.prefix.contains('/') => {
+                self.path_candidates(&request.prefix, home, PathFilter::Executables)
+            }
+            CompletionTarget::Command => self.command_candidates(),
+            CompletionTarget::Argument { command, args } => match self.spec_words(command, args) {
+                Some((program, words)) => {
+                    let (candidates, missing) =
+                        self.spec_candidates(&program, &words, &request.prefix, home, run_commands);
+                    pending = missing;
+                    candidates
+                }
+                None => self.argument_candidates(command, &request.prefix, home),
+            },
+            CompletionTarget::Redirect => self.path_candidates(&request.prefix, home, PathFilter::All),
+            CompletionTarget::Variable { .. } => self.variable_candidates(),
+        };
+        let completion = Completion::new(request, candidates);
+        match pending {
+            Some(command) => completion.with_pending(command),
+            None => completion,
+        }
+    }
+
+    /// Comandos internos y registrados, alias, funciones y programas del `PATH`
+    fn command_candidates(&mut self) -> Vec<Candidate> {
+        let mut candidates: Vec<Candidate> = self
+            .catalog
+            .names()
+            .map(|name| {
+                let description = self.catalog.get(name).map(|entry| entry.description.as_str()).unwrap_or_default();
+                Candidate::new(name).with_description(description)
+            })
+            .collect();
+        candidates.extend(
+            self.lock_aliases()
+                .iter()
+                .map(|(name, value)| Candidate::new(name).with_description(&format!("alias de '{}'", value))),
+        );
+        candidates.extend(
+            self.terminal_service
+                .get_state()
+                .functions
+                .keys()
+                .map(|name| Candidate::new(name).with_description("función")),
+        );
+        candidates.extend(self.path_executables().iter().map(|name| Candidate::new(name)));
+        candidates
+    }
+
+    /// Archivos y directorios que completan `prefix`; sin repositorio de directorios,
+    /// ninguno
+    fn path_candidates(&self, prefix: &str, home: Option<&str>, filter: PathFilter) -> Vec<Candidate> {
+        match &self.directories {
+            Some(directories) => completion::path_candidates(prefix, home, filter, directories.as_ref()),
+            None => Vec::new(),
+        }
+    }
+
+    /// Programas que se encuentran con el `PATH` de la sesión
+    fn path_executables(&mut self) -> Vec<String> {
+        let state = self.terminal_service.get_state();
+        let path = state.environment.get("PATH").unwrap_or_default().to_string();
+        match &self.directories {
+            Some(directories) => self.executables.names(&path, directories.as_ref()).iter().cloned().collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Argumentos de un comando: las opciones de un comando interno después de `-`, lo
+    /// que espera según el comando (directorios para `cd`, variables para `unset`), lo
+    /// que describe la especificación de un programa externo o archivos
+    fn argument_candidates(&mut self, command: &str, prefix: &str, home: Option<&str>) -> Vec<Candidate> {
+        let mut words = self.alias_words(command);
+        let command = words.remove(0);
+        let entry = self.catalog.get(&command).cloned();
+        if prefix.starts_with('-') {
+            if let Some(entry) = &entry {
+                let help = OptionSpec::new("Mostrar la ayuda").with_long("help");
+                return option_candidates(&[entry.options.as_slice(), &[help]].concat(), prefix);
+            }
+        }
+        match command.as_str() {
+            "cd" => self.path_candidates(prefix, home, PathFilter::Directories),
+            "help" | "type" => self.command_candidates(),
+            "unalias" => self.lock_aliases().iter().map(|(name, _)| Candidate::new(name)).collect(),
+            "unset" | "export" => self.variable_candidates(),
+            _ => {
+                let mut candidates: Vec<Candidate> = entry
+                    .map(|entry| entry.completions.iter().map(|value| Candidate::new(value)).collect())
+                    .unwrap_or_default();
+                candidates.extend(self.path_candidates(prefix, home, PathFilter::All));
+                candidates
+            }
+        }
+    }
+
+    /// Un alias se completa como lo que reemplaza (`gco` como `git checkout`); el
+    /// primer elemento es el comando
+    fn alias_words(&self, command: &str) -> Vec<String> {
+        let mut words: Vec<String> = match self.lock_aliases().get(command) {
+            Some(value) => value.split_whitespace().map(str::to_string).collect(),
+            None => Vec::new(),
+        };
+        if words.is_empty() {
+            words.push(command.to_string());
+        }
+        words
+    }
+
+    /// Programa externo con especificación y sus argumentos, con los alias expandidos
+    fn spec_words(&self, command: &str, args: &[String]) -> Option<(String, Vec<String>)> {
+        let mut words = self.alias_words(command);
+        let command = words.remove(0);
+        if self.catalog.get(&command).is_some() {
+            return None;
+        }
+        // `/usr/bin/git` se completa como `git`
+        let program = Path::new(&command).file_name()?.to_string_lossy().to_string();
+        if !self.completion_specs.contains_key(&program) {
+            return None;
+        }
+        words.extend(args.iter().cloned());
+        Some((program, words))
+    }
+
+    /// Lo que ofrece la especificación de un programa externo: subcomandos, opciones o
+    /// valores. Los archivos se buscan aquí; si falta la salida de un comando, se
+    /// devuelve el comando junto con lo que ya se tiene.
+    fn spec_candidates(
+        &mut self,
+        program: &str,
+        words: &[String],
+        prefix: &str,
+        home: Option<&str>,
+        run_commands: bool,
+    ) -> (Vec<Candidate>, Option<String>) {
+        let Some(spec) = self.completion_specs.get(program) else {
+            return (Vec::new(), None);
+        };
+        let matched = spec.resolve(words, prefix);
+        let mut candidates = matched.candidates;
+        match matched.arguments {
+            Some(ArgumentKind::File) => candidates.extend(self.path_candidates(prefix, home, PathFilter::All)),
+            Some(ArgumentKind::Directory) => candidates.extend(self.path_candidates(prefix, home, PathFilter::Directories)),
+            Some(ArgumentKind::Command(command)) => match self.command_output(&command, run_commands) {
+                Some(lines) => candidates.extend(lines.iter().map(|line| Candidate::new(line))),
+                None => return (candidates, Some(command)),
+            },
+            _ => {}
+        }
+        (candidates, None)
+    }
+
+    /// Salida de un comando de completado en el directorio actual: la guardada o, con
+    /// `run`, la que da al ejecutarlo. Sin `run` y sin guardar, `None`.
+    fn command_output(&mut self, command: &str, run: bool) -> Option<Vec<String>> {
+        let Some(repository) = &self.spec_repository else {
+            return Some(Vec::new());
+        };
+        let key = (std::env::current_dir().unwrap_or_default(), command.to_string());
+        if let Some(lines) = self.command_outputs.get(&key) {
+            return Some(lines.clone());
+        }
+        if !run {
+            return None;
+        }
+        let environment = self.terminal_service.get_state().environment.exported();
+        let lines = repository.command_output(command, &environment);
+        self.command_outputs.insert(key, lines.clone());
+        Some(lines)
+    }
+
+    /// Variables de la sesión
+    fn variable_candidates(&self) -> Vec<Candidate> {
+        self.terminal_service
+            .get_state()
+            .environment
+            .variables()
+            .map(|(name, _)| Candidate::new(name))
+            .collect()
+    }
+
+    /// Decide si una etapa es un comando interno (que se ejecuta aquí) o un proceso externo
+    fn resolve_stage(&mut self, command: Command) -> Result<PipelineStage, TermirustError> {
+        // Una etapa con solo redirecciones (`> archivo`) no ejecuta nada; con solo
+        // asignaciones (`NOMBRE=valor`) define variables de la sesión
+        if command.name.is_empty() {
+            let environment = &mut self.terminal_service.get_state_mut().environment;
+            for (name, value) in &command.assignments {
+                environment.set(name, value.clone());
+            }
+            return Ok(PipelineStage::Builtin(command));
+        }
+        // `comando --help` de un comando interno muestra lo mismo que `help comando`
+        if asks_for_help(&command.args) && self.is_internal(&command.name) {
+            let help = Command::new("help".to_string(), vec![command.name.clone()]);
+            let result = self.execute_builtin(&help, &[])?;
+            return Ok(PipelineStage::Builtin(Command {
+                output: result.output,
+                error_output: result.error_output,
+                errors: result.errors,
+                exit_code: result.exit_code,
+                ..command
+            }));
+        }
+        // `cd` sin argumentos va a la carpeta personal de la sesión
+        let command = match self.terminal_service.get_state().environment.get("HOME") {
+            Some(home) if command.name == "cd" && command.args.is_empty() => {
+                let home = home.to_string();
+                Command { args: vec![home], ..command }
+            }
+            _ => command,
+        };
+        if self.command_service.is_builtin(&command) {
+            // Las asignaciones delante del comando (`NOMBRE=valor plugin`) se suman al entorno
+            let mut environment = self.terminal_service.get_state().environment.exported();
+            environment.extend(command.assignments.iter().cloned());
+            let result = self.execute_builtin(&command, &environment)?;
+            return Ok(PipelineStage::Builtin(result));
+        }
+        Ok(PipelineStage::External(command))
+    }
+
+    /// Ejecuta un comando interno con el estado de la sesión, que puede cambiar
+    fn execute_builtin(&mut self, command: &Command, environment: &[(String, String)]) -> Result<Command, TermirustError> {
+        let mut aliases = self.aliases.lock().unwrap_or_else(PoisonError::into_inner);
+        let session = Session {
+            state: self.terminal_service.get_state_mut(),
+            aliases: &mut aliases,
+            alias_repository: self.alias_repository.as_deref(),
+        };
+        self.command_service.execute_command(command, environment, session)
+    }
+
+    /// Indica si el nombre es un comando interno de Termirust (los plugins no lo son:
+    /// reciben su `--help`)
+    fn is_internal(&self, name: &str) -> bool {
+        self.catalog.get(name).is_some_and(|entry| entry.location.is_none())
+    }
+
+    fn lock_aliases(&self) -> MutexGuard<'_, Aliases> {
+        self.aliases.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
     pub fn should_exit(&self) -> bool {
         self.terminal_service.should_exit()
     }
+
+    /// Obtiene un manejador compartido del historial de comandos ejecutados
+    pub fn history_handle(&self) -> Arc<Mutex<HistoryCommandsUseCase>> {
+        Arc::clone(&self.history_commands)
+    }
+
+    /// Aviso de Ctrl+C que la interfaz levanta mientras se ejecuta una línea
+    pub fn interrupt_handle(&self) -> InterruptFlag {
+        self.interrupt.clone()
+    }
+
+    /// Obtiene una copia del canal de eventos hacia la interfaz
+    pub fn event_sender(&self) -> EventSender {
+        self.events.clone()
+    }
 }
 
 /// Caso de uso para manejar la entrada de texto
@@ -86,10 +1019,111 @@ impl HandleInputUseCase {
         self.terminal_service.add_output_line(line);
     }
 
+    /// Agrega una línea con sus estilos ya resueltos, como la de un error
+    pub fn add_styled_line(&mut self, line: StyledLine) {
+        self.terminal_service.add_styled_line(line);
+    }
+
+    /// Agrega texto a la salida sin terminar la línea
+    pub fn add_partial_output(&mut self, text: String) {
+        self.terminal_service.add_partial_output(text);
+    }
+
+    /// Agrega texto con estilos sin terminar la línea, como el de un error
+    pub fn add_partial_styled_line(&mut self, line: StyledLine) {
+        self.terminal_service.add_partial_styled_line(line);
+    }
+
+    pub fn clear_output(&mut self) {
+        self.terminal_service.clear_output();
+    }
+
     pub fn clear_buffer(&mut self) {
         self.terminal_service.get_state_mut().clear_buffer();
     }
 
+    /// Marca la línea que se está ejecutando, o `None` cuando termina
+    pub fn set_running_command(&mut self, command: Option<String>) {
+        self.terminal_service.get_state_mut().running_command = command;
+    }
+
+    /// Obtiene la línea que se está ejecutando, si la hay
+    pub fn get_running_command(&self) -> Option<&str> {
+        self.terminal_service.get_state().running_command.as_deref()
+    }
+
+    /// Empieza a emular la pantalla de un programa lanzado en una pseudoterminal
+    pub fn open_screen(&mut self, cols: usize, rows: usize) {
+        self.terminal_service.get_state_mut().screen = Some(Screen::new(cols, rows));
+    }
+
+    /// Pasa a la pantalla emulada lo que escribió el programa. Devuelve las respuestas
+    /// que el programa pidió (posición del cursor, atributos), a escribir en la pseudoterminal.
+    pub fn feed_screen(&mut self, bytes: &[u8]) -> Vec<u8> {
+        match self.terminal_service.get_state_mut().screen.as_mut() {
+            Some(screen) => {
+                screen.feed(bytes);
+                screen.take_responses()
+            }
+            None => Vec::new(),
+        }
+    }
+
+    /// Ajusta la pantalla emulada al nuevo tamaño de la ventana
+    pub fn resize_screen(&mut self, cols: usize, rows: usize) {
+        if let Some(screen) = self.terminal_service.get_state_mut().screen.as_mut() {
+            screen.resize(cols, rows);
+        }
+    }
+
+    /// Cierra la pantalla emulada; su texto queda en la salida, como en una terminal real
+    pub fn close_screen(&mut self) {
+        if let Some(screen) = self.terminal_service.get_state_mut().screen.take() {
+            for line in screen.text_lines() {
+                self.terminal_service.add_styled_line(line);
+            }
+        }
+    }
+
+    /// Pantalla del programa que se ejecuta en una pseudoterminal, si lo hay
+    pub fn get_screen(&self) -> Option<&Screen> {
+        self.terminal_service.get_state().screen.as_ref()
+    }
+
+    /// Abre un documento en el visor, encima de la salida
+    pub fn open_document(&mut self, document: Document) {
+        self.terminal_service.get_state_mut().document = Some(DocumentView::new(document));
+    }
+
+    pub fn close_document(&mut self) {
+        self.terminal_service.get_state_mut().document = None;
+    }
+
+    /// Documento abierto en el visor, si lo hay
+    pub fn get_document(&self) -> Option<&DocumentView> {
+        self.terminal_service.get_state().document.as_ref()
+    }
+
+    pub fn get_document_mut(&mut self) -> Option<&mut DocumentView> {
+        self.terminal_service.get_state_mut().document.as_mut()
+    }
+
+    pub fn open_completion_menu(&mut self, completion: Completion) {
+        self.terminal_service.get_state_mut().completion_menu = Some(CompletionMenu::new(completion));
+    }
+
+    pub fn close_completion_menu(&mut self) {
+        self.terminal_service.get_state_mut().completion_menu = None;
+    }
+
+    pub fn get_completion_menu(&self) -> Option<&CompletionMenu> {
+        self.terminal_service.get_state().completion_menu.as_ref()
+    }
+
+    pub fn get_completion_menu_mut(&mut self) -> Option<&mut CompletionMenu> {
+        self.terminal_service.get_state_mut().completion_menu.as_mut()
+    }
+
 
 
     pub fn handle_key_press(&mut self, ch: char) {
@@ -106,18 +1140,23 @@ impl HandleInputUseCase {
 
     pub fn handle_arrow_left(&mut self) {
         let state = self.terminal_service.get_state_mut();
-        if state.cursor_position > 0 {
-            state.cursor_position -= 1;
+        if let Some(ch) = state.command_buffer[..state.cursor_position].chars().next_back() {
+            state.cursor_position -= ch.len_utf8();
         }
     }
 
     pub fn handle_arrow_right(&mut self) {
         let state = self.terminal_service.get_state_mut();
-        if state.cursor_position < state.command_buffer.len() {
-            state.cursor_position += 1;
+        if let Some(ch) = state.command_buffer[state.cursor_position..].chars().next() {
+            state.cursor_position += ch.len_utf8();
         }
     }
 
+    /// Reemplaza el final de la línea con lo que eligió el completado
+    pub fn replace_input_from(&mut self, start: usize, text: &str) {
+        self.terminal_service.get_state_mut().replace_from(start, text);
+    }
+
     pub fn handle_home(&mut self) {
         let state = self.terminal_service.get_state_mut();
         state.cursor_position = 0;
@@ -132,8 +1171,8 @@ impl HandleInputUseCase {
         self.terminal_service.get_state().command_buffer.clone()
     }
 
-    pub fn get_output_lines(&self) -> Vec<String> {
-        self.terminal_service.get_state().output_lines.clone()
+    pub fn get_output_lines(&self) -> &[StyledLine] {
+        &self.terminal_service.get_state().output_lines
     }
 
     pub fn get_cursor_position(&self) -> usize {
@@ -155,3 +1194,497 @@ impl HandleInputUseCase {
         self.terminal_service.get_state_mut().mode = TerminalMode::Matrix;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc::{self, Receiver};
+
+    use super::*;
+    use crate::domain::builtins::tests::Greeting;
+    use crate::infrastructure::repositories::{SystemCommandRepository, SystemDirectoryRepository, SystemUserRepository};
+    use crate::presentation::texts::ErrorMessages;
+
+    type Executor = ExecuteCommandUseCase<SystemCommandRepository>;
+
+    fn executor() -> (Executor, Receiver<ExecutionEvent>) {
+        let (events, receiver) = mpsc::channel();
+        let repository = SystemCommandRepository::new().with_error_formatter(ErrorMessages::format);
+        let service = CommandService::new(repository);
+        let executor = ExecuteCommandUseCase::new(service, TerminalService::new(), events)
+            .with_directory_repository(Box::new(SystemDirectoryRepository))
+            .with_user_repository(Box::new(SystemUserRepository));
+        (executor, receiver)
+    }
+
+    /// Script en la carpeta temporal que se borra al terminar la prueba
+    struct Script(PathBuf);
+
+    impl Script {
+        fn new(name: &str, content: &str) -> Self {
+            let path = std::env::temp_dir().join(format!("termirust-{}-{}.trs", name, std::process::id()));
+            std::fs::write(&path, content).expect("se puede escribir el script");
+            Self(path)
+        }
+    }
+
+    impl Drop for Script {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    /// Resultado de un script: su código de salida, las líneas de salida y los errores
+    struct ScriptRun {
+        exit_code: i32,
+        output: Vec<String>,
+        /// Líneas que los programas escribieron en su salida de errores
+        error_output: Vec<String>,
+        errors: Vec<TermirustError>,
+    }
+
+    fn run_script(name: &str, content: &str) -> ScriptRun {
+        let (mut executor, events) = executor();
+        let script = Script::new(name, content);
+        let exit_code = executor.run_script(&script.0).expect("el script existe");
+        drop(executor);
+        let mut run = ScriptRun { exit_code, output: Vec::new(), error_output: Vec::new(), errors: Vec::new() };
+        for event in events {
+            match event {
+                ExecutionEvent::Output(line) => run.output.push(line),
+                ExecutionEvent::ErrorOutput { line, .. } => run.error_output.push(line),
+                ExecutionEvent::Error(TermirustError::Located { error, .. }) => run.errors.push(*error),
+                ExecutionEvent::Error(error) => run.errors.push(error),
+                _ => {}
+            }
+        }
+        run
+    }
+
+    fn stopped(command: &str, exit_code: i32) -> TermirustError {
+        TermirustError::ScriptStopped { command: command.to_string(), exit_code }
+    }
+
+    #[test]
+    fn script_status_is_the_status_of_its_last_list() {
+        assert_eq!(run_script("status-ok", "false; true\n").exit_code, 0);
+        assert_eq!(run_script("status-fail", "true; false\n# comentario\n\n").exit_code, 1);
+        // La condición falla, pero el `if` sin rama termina bien
+        assert_eq!(run_script("status-if", "if false; then true; fi\n").exit_code, 0);
+    }
+
+    #[test]
+    fn errexit_ignores_failing_conditions() {
+        let run = run_script(
+            "errexit-conditions",
+            "set -e\nif false; then echo no; fi\nwhile false; do echo no; done\ni=0\nwhile test $i = 0; do i=1; done\necho fin\n",
+        );
+        assert_eq!(run.output, ["fin"]);
+        assert!(run.errors.is_empty());
+        assert_eq!(run.exit_code, 0);
+    }
+
+    #[test]
+    fn errexit_ignores_all_but_the_last_command_of_an_and_or_list() {
+        let run = run_script("errexit-and-or", "set -e\nfalse && echo no\nfalse || true\necho fin\n");
+        assert_eq!(run.output, ["fin"]);
+        assert_eq!(run.exit_code, 0);
+
+        let run = run_script("errexit-and-last", "set -e\ntrue && false\necho no\n");
+        assert!(run.output.is_empty());
+        assert_eq!(run.errors, [stopped("true && false", 1)]);
+    }
+
+    #[test]
+    fn errexit_stops_after_each_list_of_a_line() {
+        let run = run_script("errexit-lists", "set -e\necho a; false; echo no\necho no\n");
+        assert_eq!(run.output, ["a"]);
+        assert_eq!(run.errors, [stopped("false", 1)]);
+        assert_eq!(run.exit_code, 1);
+    }
+
+    #[test]
+    fn errexit_stops_inside_compound_bodies() {
+        for (name, line) in [
+            ("errexit-for", "for x in a b; do false; echo no; done"),
+            ("errexit-if", "if true; then false; echo no; fi"),
+            ("errexit-while", "while true; do false; echo no; done"),
+            ("errexit-function", "f() { false; echo no; }; f"),
+        ] {
+            let run = run_script(name, &format!("set -e\n{}\necho no\n", line));
+            assert!(run.output.is_empty(), "{}: {:?}", line, run.output);
+            assert_eq!(run.errors, [stopped("false", 1)], "{}", line);
+            assert_eq!(run.exit_code, 1, "{}", line);
+        }
+    }
+
+    #[test]
+    fn errexit_inside_a_condition_body_still_counts_as_a_condition() {
+        let run = run_script("errexit-nested-condition", "set -e\nif for x in a; do false; done; then echo no; fi\necho fin\n");
+        assert_eq!(run.output, ["fin"]);
+        assert!(run.errors.is_empty());
+    }
+
+    #[test]
+    fn errexit_stops_the_scripts_that_loaded_the_failing_one() {
+        let inner = Script::new("errexit-inner", "echo dentro\nfalse\necho no\n");
+        let run = run_script("errexit-outer", &format!("set -e\nsource {}\necho no\n", inner.0.display()));
+        assert_eq!(run.output, ["dentro"]);
+        assert_eq!(run.errors, [stopped("false", 1)]);
+        assert_eq!(run.exit_code, 1);
+    }
+
+    #[test]
+    fn patterns_expand_through_the_directory_repository() {
+        // Las pruebas se ejecutan desde la raíz del paquete, donde está Cargo.toml
+        let (mut executor, _events) = executor();
+        assert_eq!(last_step(&mut executor, "test Cargo.tom? = Cargo.toml").1, 0);
+        assert_eq!(last_step(&mut executor, "test \"Cargo.tom?\" = Cargo.toml").1, 1);
+    }
+
+    #[test]
+    fn without_errexit_the_script_continues() {
+        let run = run_script("no-errexit", "false\necho sigue\n");
+        assert_eq!(run.output, ["sigue"]);
+        assert_eq!(run.exit_code, 0);
+    }
+
+    /// Código del último paso de la línea y su texto
+    fn last_step(executor: &mut Executor, line: &str) -> (String, i32) {
+        let steps = executor.execute(line).expect("la línea es válida");
+        let step = steps.last().expect("la línea tiene pasos");
+        (step.text.clone(), step.command.exit_code)
+    }
+
+    #[test]
+    fn if_reports_its_own_status() {
+        let (mut executor, _events) = executor();
+        let line = "if false; then true; fi";
+        assert_eq!(last_step(&mut executor, line), (line.to_string(), 0));
+        assert_eq!(last_step(&mut executor, "if true; then false; fi").1, 1);
+        assert_eq!(last_step(&mut executor, "if false; then true; else false; fi").1, 1);
+        assert_eq!(last_step(&mut executor, "if false; then true; elif true; then true; fi").1, 0);
+    }
+
+    #[test]
+    fn loops_report_the_status_of_their_last_body() {
+        let (mut executor, _events) = executor();
+        assert_eq!(last_step(&mut executor, "while false; do true; done").1, 0);
+        assert_eq!(last_step(&mut executor, "for x in a b; do test $x = a; done").1, 1);
+        assert_eq!(last_step(&mut executor, "for x in b a; do test $x = a; done").1, 0);
+        assert_eq!(last_step(&mut executor, "for x in $NADA; do false; done").1, 0);
+        assert_eq!(last_step(&mut executor, "i=0; while test $i = 0; do i=1; false; done").1, 1);
+    }
+
+    #[test]
+    fn compound_steps_follow_the_steps_of_their_body() {
+        let (mut executor, _events) = executor();
+        let steps = executor.execute("if true; then false; fi; true").expect("la línea es válida");
+        let texts: Vec<(&str, i32)> = steps.iter().map(|step| (step.text.as_str(), step.command.exit_code)).collect();
+        assert_eq!(texts, [("true", 0), ("false", 1), ("if true; then false; fi", 1), ("true", 0)]);
+    }
+
+    #[test]
+    fn compound_status_is_visible_to_the_next_command() {
+        let (mut executor, _events) = executor();
+        executor.execute("if true; then false; fi").expect("la línea es válida");
+        assert_eq!(last_step(&mut executor, "test $? = 1").1, 0);
+        executor.execute("while false; do true; done").expect("la línea es válida");
+        assert_eq!(last_step(&mut executor, "test $? = 0").1, 0);
+        assert_eq!(last_step(&mut executor, "f() { false; }").1, 0);
+        assert_eq!(last_step(&mut executor, "f").1, 1);
+    }
+
+    #[test]
+    fn pipelines_connect_their_stages() {
+        let run = run_script("pipe-wc", "printf 'a\\nb' | wc -l\n");
+        assert_eq!(run.output.iter().map(|line| line.trim()).collect::<Vec<_>>(), ["1"]);
+
+        let script = "printf 'c\\nb\\na\\n' | sort | head -n 2\necho uno dos | tr ' ' '\\n' | wc -l\n";
+        let run = run_script("pipe-three", script);
+        assert_eq!(run.output.iter().map(|line| line.trim()).collect::<Vec<_>>(), ["a", "b", "2"]);
+        assert_eq!(run.exit_code, 0);
+    }
+
+    #[test]
+    fn pipeline_status_is_the_last_stage_unless_pipefail() {
+        assert_eq!(run_script("pipe-last", "false | true\n").exit_code, 0);
+        assert_eq!(run_script("pipe-last-fails", "true | false\n").exit_code, 1);
+        assert_eq!(run_script("pipefail", "set -o pipefail\nfalse | true\n").exit_code, 1);
+        // Con pipefail vale la última etapa que falló
+        let script = "set -o pipefail\nsh -c 'exit 3' | sh -c 'exit 4' | true\necho $?\n";
+        let run = run_script("pipefail-last", script);
+        assert_eq!(run.output, ["4"]);
+        assert_eq!(run_script("pipefail-ok", "set -o pipefail\ntrue | true\n").exit_code, 0);
+    }
+
+    /// Directorio temporal para los archivos de las redirecciones
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!("termirust-{}-{}", name, std::process::id()));
+            std::fs::create_dir_all(&path).expect("se puede crear el directorio");
+            Self(path)
+        }
+
+        fn path(&self, name: &str) -> String {
+            self.0.join(name).to_string_lossy().to_string()
+        }
+
+        fn read(&self, name: &str) -> String {
+            std::fs::read_to_string(self.0.join(name)).expect("el archivo existe")
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn output_redirections_truncate_append_and_feed_input() {
+        let dir = TempDir::new("redir-salida");
+        let file = dir.path("f");
+        let script = format!(
+            "echo viejo > {f}\necho uno > {f}\nprintf 'dos\\n' >> {f}\ncat < {f}\nwc -l < {f}\n",
+            f = file
+        );
+        let run = run_script("redir-salida", &script);
+        assert_eq!(dir.read("f"), "uno\ndos\n");
+        assert_eq!(run.output.iter().map(|line| line.trim()).collect::<Vec<_>>(), ["uno", "dos", "2"]);
+        assert!(run.errors.is_empty());
+    }
+
+    #[test]
+    fn stderr_redirections_follow_their_order() {
+        let dir = TempDir::new("redir-orden");
+        let both = "sh -c 'echo salida; echo error >&2'";
+        let script = format!(
+            "{both} 2> {e}\n{both} > {f} 2>&1\n{both} 2>&1 > {g}\n",
+            both = both,
+            e = dir.path("e"),
+            f = dir.path("f"),
+            g = dir.path("g")
+        );
+        let run = run_script("redir-orden", &script);
+        assert_eq!(dir.read("e"), "error\n");
+        // `> f 2>&1`: las dos salidas van al archivo
+        assert_eq!(dir.read("f"), "salida\nerror\n");
+        // `2>&1 > f`: los errores van a donde iba la salida antes de redirigirla
+        assert_eq!(dir.read("g"), "salida\n");
+        assert_eq!(run.output, ["salida", "error"]);
+        assert!(run.error_output.is_empty());
+    }
+
+    #[test]
+    fn builtins_honor_stderr_redirections() {
+        let dir = TempDir::new("redir-interno");
+        let missing = dir.path("nada");
+        let script = format!(
+            "cd {m} 2> {e}\necho $?\ncd {m} 2>> {e}\necho hola 2> {o} > {o}\n",
+            m = missing,
+            e = dir.path("e"),
+            o = dir.path("o")
+        );
+        let run = run_script("redir-interno", &script);
+        let error = TermirustError::NotFound { command: "cd".to_string(), path: Some(missing) };
+        assert_eq!(dir.read("e"), format!("{0}\n{0}\n", ErrorMessages::format(&error)));
+        assert_eq!(dir.read("o"), "hola\n");
+        assert_eq!(run.output, ["1"]);
+        assert!(run.errors.is_empty());
+    }
+
+    #[test]
+    fn each_step_of_a_list_is_echoed_and_short_circuits_on_its_status() {
+        let (mut executor, events) = executor();
+        let line = "true && false; echo a || echo b; false || echo c && false";
+        let steps = executor.execute(line).expect("la línea es válida");
+        let texts: Vec<(&str, i32)> = steps.iter().map(|step| (step.text.as_str(), step.command.exit_code)).collect();
+        assert_eq!(texts, [("true", 0), ("false", 1), ("echo a", 0), ("false", 1), ("echo c", 0), ("false", 1)]);
+
+        drop(executor);
+        let lines: Vec<String> = events
+            .try_iter()
+            .filter_map(|event| match event {
+                ExecutionEvent::Output(line) => Some(line),
+                _ => None,
+            })
+            .collect();
+        let echoed: Vec<&str> = lines.iter().filter_map(|line| line.split_once(":~$ ").map(|(_, text)| text)).collect();
+        assert_eq!(echoed, ["true", "false", "echo a", "false", "echo c", "false"]);
+        assert_eq!(lines.iter().filter(|line| !line.contains(":~$ ")).collect::<Vec<_>>(), ["a", "c"]);
+    }
+
+    #[test]
+    fn set_accepts_grouped_letters_and_rejects_bad_usage_with_status_2() {
+        let (mut executor, _events) = executor();
+        assert_eq!(last_step(&mut executor, "set -eo pipefail").1, 0);
+        let options = &executor.terminal_service.get_state().options;
+        assert!(options.errexit && options.pipefail);
+        assert_eq!(last_step(&mut executor, "set +e -o pty").1, 0);
+        let options = &executor.terminal_service.get_state().options;
+        assert!(!options.errexit && options.pty);
+
+        assert_eq!(last_step(&mut executor, "set -z").1, 2);
+        assert_eq!(last_step(&mut executor, "set -o nada").1, 2);
+        assert_eq!(last_step(&mut executor, "set nada").1, 2);
+    }
+
+    #[test]
+    fn builtin_errors_stay_typed_until_they_are_redirected() {
+        let (mut executor, events) = executor();
+        let missing = std::env::temp_dir().join(format!("termirust-nada-{}", std::process::id()));
+        let file = Script::new("ls-errores", "");
+        executor.execute(&format!("ls {}", missing.display())).expect("la línea es válida");
+        executor.execute(&format!("ls {} 2> {}", missing.display(), file.0.display())).expect("la línea es válida");
+        drop(executor);
+
+        let errors: Vec<TermirustError> = events
+            .try_iter()
+            .filter_map(|event| match event {
+                ExecutionEvent::Error(error) => Some(error),
+                _ => None,
+            })
+            .collect();
+        assert!(matches!(errors.as_slice(), [TermirustError::NotFound { path: Some(_), .. }]), "{:?}", errors);
+        let written = std::fs::read_to_string(&file.0).expect("se puede leer el archivo");
+        assert_eq!(written.trim_end(), ErrorMessages::format(&errors[0]));
+    }
+
+    /// Especificación de prueba: `herramienta -C <dir> elegir <a|b> rama <salida>`
+    struct MemorySpecs;
+
+    impl CompletionSpecRepository for MemorySpecs {
+        fn load(&self) -> Vec<Result<CompletionSpec, TermirustError>> {
+            let spec = |name: &str, arguments: Option<ArgumentKind>| CompletionSpec {
+                name: name.to_string(),
+                description: String::new(),
+                aliases: Vec::new(),
+                subcommands: Vec::new(),
+                options: Vec::new(),
+                arguments,
+            };
+            let mut tool = spec("herramienta", None);
+            tool.options = vec![crate::domain::completion_spec::FlagSpec {
+                names: vec!["-C".to_string()],
+                description: String::new(),
+                value: Some(ArgumentKind::Directory),
+            }];
+            tool.subcommands = vec![
+                spec("elegir", Some(ArgumentKind::Choice(vec!["a".to_string(), "b".to_string()]))),
+                spec("rama", Some(ArgumentKind::Command("ramas".to_string()))),
+                spec("abrir", Some(ArgumentKind::File)),
+            ];
+            vec![Ok(tool)]
+        }
+
+        fn command_output(&self, command: &str, _environment: &[(String, String)]) -> Vec<String> {
+            assert_eq!(command, "ramas");
+            vec!["main".to_string(), "dev".to_string()]
+        }
+    }
+
+    fn completed(executor: &mut Executor, line: &str) -> Vec<String> {
+        executor.complete(line).candidates.into_iter().map(|candidate| candidate.value).collect()
+    }
+
+    #[test]
+    fn specs_decide_the_kind_of_each_argument() {
+        let directories = crate::domain::glob::tests::MemoryDirectories::new(Path::new("."), &["src/", "notas.txt"]);
+        let (executor, _events) = executor();
+        let mut executor = executor
+            .with_directory_repository(Box::new(directories))
+            .with_completion_spec_repository(Box::new(MemorySpecs));
+
+        assert_eq!(completed(&mut executor, "herramienta "), ["abrir", "elegir", "rama"]);
+        assert_eq!(completed(&mut executor, "herramienta -C "), ["src/"]);
+        assert_eq!(completed(&mut executor, "herramienta abrir "), ["notas.txt", "src/"]);
+        assert_eq!(completed(&mut executor, "herramienta elegir "), ["a", "b"]);
+
+        // La salida del comando se pide fuera de la interfaz y queda guardada
+        let completion = executor.complete("herramienta rama ");
+        assert_eq!(completion.pending.as_deref(), Some("ramas"));
+        assert_eq!(executor.complete_with_commands("herramienta rama ").candidates.len(), 2);
+        assert_eq!(completed(&mut executor, "herramienta rama d"), ["dev"]);
+    }
+
+    /// Espera la primera línea de salida que contiene `text`, como el aviso de un trabajo
+    fn output_with(events: &Receiver<ExecutionEvent>, text: &str) -> String {
+        loop {
+            match events.recv_timeout(std::time::Duration::from_secs(5)).expect("llega la línea") {
+                ExecutionEvent::Output(line) if line.contains(text) => return line,
+                _ => {}
+            }
+        }
+    }
+
+    #[test]
+    fn background_jobs_are_announced_listed_and_reported_when_they_finish() {
+        let (mut executor, events) = executor();
+        let steps = executor.execute("sleep 0.2 &").expect("la línea es válida");
+        assert_eq!(steps[0].command.exit_code, 0);
+        assert!(steps[0].command.output.starts_with("[1] "));
+
+        let steps = executor.execute("jobs").expect("la línea es válida");
+        assert_eq!(steps[0].command.output.trim_end(), "[1]  Ejecutando   sleep 0.2");
+        assert_eq!(output_with(&events, "Hecho"), "[1]  Hecho        sleep 0.2");
+
+        // La tabla quedó vacía, así que el número se reutiliza
+        executor.execute("sh -c 'exit 3' &").expect("la línea es válida");
+        assert_eq!(output_with(&events, "Salida"), "[1]  Salida 3     sh -c 'exit 3'");
+    }
+
+    /// Ejecutor con un comando interno más registrado, como el de un plugin
+    fn executor_with(builtin: Box<dyn crate::domain::builtins::BuiltinCommand>) -> Executor {
+        let mut repository = SystemCommandRepository::new().with_error_formatter(ErrorMessages::format);
+        repository.register_builtin(builtin);
+        let (events, _) = mpsc::channel();
+        ExecuteCommandUseCase::new(CommandService::new(repository), TerminalService::new(), events)
+    }
+
+    fn output(executor: &mut Executor, line: &str) -> String {
+        let steps = executor.execute(line).expect("la línea es válida");
+        steps.last().expect("la línea tiene pasos").command.output.clone()
+    }
+
+    #[test]
+    fn help_lists_every_registered_command_with_its_aliases() {
+        let greeting = Greeting { aliases: &["hola"], ..Greeting::new("saludo", "Saludar") };
+        let mut executor = executor_with(Box::new(greeting));
+        let help = output(&mut executor, "help");
+        assert!(help.contains("- ls, dir: "));
+        assert!(help.contains("- saludo, hola: Saludar\n"));
+    }
+
+    #[test]
+    fn command_help_is_rendered_from_what_the_command_declares() {
+        let mut executor = executor_with(Box::new(Greeting::new("saludo", "Saludar")));
+        let expected = "Uso: saludo [nombre]\n\nSaludar\n";
+        assert_eq!(output(&mut executor, "help saludo"), expected);
+        assert_eq!(output(&mut executor, "saludo --help"), expected);
+        // Después de `--`, `--help` es un argumento más
+        assert_eq!(output(&mut executor, "saludo -- --help"), "saludo -- --help\n");
+
+        let ls_help = output(&mut executor, "help ls");
+        assert!(ls_help.contains("ls [opciones]"));
+        assert_eq!(output(&mut executor, "ls --help"), ls_help);
+        assert_eq!(output(&mut executor, "dir --help"), ls_help);
+    }
+
+    #[test]
+    fn exit_help_does_not_close_the_terminal() {
+        let (mut executor, _events) = executor();
+        assert_eq!(output(&mut executor, "exit --help"), output(&mut executor, "help exit"));
+        assert!(!executor.should_exit());
+    }
+
+    #[test]
+    fn help_for_an_unknown_topic_fails() {
+        let (mut executor, _events) = executor();
+        let steps = executor.execute("help nada-termirust").expect("la línea es válida");
+        let error = TermirustError::NotFound { command: "help".to_string(), path: Some("nada-termirust".to_string()) };
+        assert_eq!(steps[0].command.errors, [error]);
+        assert_eq!(steps[0].command.exit_code, 1);
+    }
+}
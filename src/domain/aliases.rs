@@ -0,0 +1,212 @@
+use std::collections::BTreeMap;
+
+use crate::domain::ast::{AndOrList, Pipeline, SimpleCommand, Statement, WordPart};
+use crate::domain::environment::Environment;
+use crate::domain::errors::{AliasError, TermirustError};
+use crate::domain::parser::Parser;
+
+/// Alias de la sesión (`alias ll='ls -la'`). Se reemplazan en la primera palabra de
+/// cada comando antes de expandir variables y ejecutarlo.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Aliases {
+    aliases: BTreeMap<String, String>,
+}
+
+impl Aliases {
+    /// Verifica si el texto es un nombre de alias válido: no vacío y sin espacios,
+    /// comillas, `=` ni caracteres especiales de la línea de comandos
+    pub fn is_valid_name(name: &str) -> bool {
+        !name.is_empty()
+            && !name.chars().any(|ch| {
+                ch.is_whitespace() || matches!(ch, '=' | '\'' | '"' | '\\' | '`' | '$' | '|' | '&' | ';' | '<' | '>' | '(' | ')' | '/')
+            })
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.aliases.get(name).map(String::as_str)
+    }
+
+    /// Define un alias. El valor debe ser un comando o una tubería.
+    pub fn set(&mut self, name: &str, value: String) -> Result<(), TermirustError> {
+        if !Self::is_valid_name(name) {
+            return Err(Self::error(AliasError::InvalidName(name.to_string())));
+        }
+        Self::parse_value(name, &value)?;
+        self.aliases.insert(name.to_string(), value);
+        Ok(())
+    }
+
+    /// Elimina un alias. Devuelve `false` si no existía.
+    pub fn remove(&mut self, name: &str) -> bool {
+        self.aliases.remove(name).is_some()
+    }
+
+    pub fn clear(&mut self) {
+        self.aliases.clear();
+    }
+
+    /// Todos los alias ordenados por nombre
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.aliases.iter().map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+
+    /// Definición de un alias tal como se escribe para volver a crearlo
+    pub fn definition(name: &str, value: &str) -> String {
+        format!("alias {}={}", name, Environment::quote(value))
+    }
+
+    /// Reemplaza los alias al inicio de cada comando de la tubería. El texto original
+    /// de la tubería se conserva para el eco y el historial.
+    pub fn expand(&self, pipeline: &Pipeline) -> Result<Pipeline, TermirustError> {
+        if self.aliases.is_empty() {
+            return Ok(pipeline.clone());
+        }
+        let mut commands = Vec::with_capacity(pipeline.commands.len());
+        for command in &pipeline.commands {
+            commands.extend(self.expand_command(command, &mut Vec::new())?.0);
+        }
+        Ok(Pipeline::new(commands).with_text(pipeline.text.clone()))
+    }
+
+    /// Expande el alias del comando. `chain` son los alias ya reemplazados para
+    /// llegar a él: un alias que se usa a sí mismo (`alias ls='ls -F'`) se detiene
+    /// ahí, pero volver a uno anterior (`a` → `b` → `a`) es un bucle. Si el valor
+    /// termina en un espacio, la palabra siguiente también puede ser un alias
+    /// (`alias sudo='sudo '` permite `sudo ll`). Devuelve además si el texto que
+    /// reemplazó al comando terminó así, para que lo aproveche el alias que lo usó.
+    fn expand_command(
+        &self,
+        command: &SimpleCommand,
+        chain: &mut Vec<String>,
+    ) -> Result<(Vec<SimpleCommand>, bool), TermirustError> {
+        let Some((name, value)) = Self::head(command).and_then(|name| self.aliases.get_key_value(name)) else {
+            return Ok((vec![command.clone()], false));
+        };
+        if chain.last() == Some(name) {
+            return Ok((vec![command.clone()], false));
+        }
+        if chain.contains(name) {
+            let mut aliases = chain.clone();
+            aliases.push(name.clone());
+            return Err(Self::error(AliasError::Loop(aliases)));
+        }
+        chain.push(name.clone());
+
+        let mut stages = Self::parse_value(name, value)?;
+        if stages.is_empty() {
+            stages.push(SimpleCommand::default());
+        }
+        let first = stages.remove(0);
+        // Un alias de una sola palabra termina como el alias al que se reemplaza
+        let single_word = stages.is_empty() && first.words.len() == 1;
+        let (mut commands, inner_blank) = self.expand_command(&first, chain)?;
+        commands.extend(stages);
+        let blank = value.ends_with([' ', '\t']) || (single_word && inner_blank);
+
+        // El resto de las palabras y las redirecciones siguen al último comando del alias.
+        // Cada palabra se expande como mucho una vez, así que no hace falta `chain`.
+        let mut rest = SimpleCommand::new(command.words[1..].to_vec(), command.redirections.clone());
+        let mut following = Vec::new();
+        if blank && !rest.words.is_empty() {
+            following = self.expand_command(&rest, &mut Vec::new())?.0;
+            rest = following.remove(0);
+        }
+        if let Some(last) = commands.last_mut() {
+            last.words.extend(rest.words);
+            last.redirections.extend(rest.redirections);
+        }
+        commands.extend(following);
+        Ok((commands, blank && command.words.len() == 1))
+    }
+
+    /// Nombre del comando si puede ser un alias: una palabra sin comillas ni escapes
+    fn head(command: &SimpleCommand) -> Option<&str> {
+        match &command.words.first()?.parts[..] {
+            [WordPart::Unquoted(name)] => Some(name),
+            _ => None,
+        }
+    }
+
+    /// Analiza el valor de un alias, que solo puede ser un comando o una tubería
+    fn parse_value(name: &str, value: &str) -> Result<Vec<SimpleCommand>, TermirustError> {
+        let command_line = Parser::parse(value)
+            .map_err(|error| Self::error(AliasError::Syntax { name: name.to_string(), error }))?;
+        match &command_line.items[..] {
+            [] => Ok(Vec::new()),
+            [AndOrList { first: Statement::Pipeline(pipeline), rest, background: false }] if rest.is_empty() => {
+                Ok(pipeline.commands.clone())
+            }
+            _ => Err(Self::error(AliasError::NotSimple(name.to_string()))),
+        }
+    }
+
+    fn error(reason: AliasError) -> TermirustError {
+        TermirustError::Alias { command: "alias".to_string(), reason }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn aliases(definitions: &[(&str, &str)]) -> Aliases {
+        let mut aliases = Aliases::default();
+        for (name, value) in definitions {
+            aliases.set(name, value.to_string()).expect("el alias es válido");
+        }
+        aliases
+    }
+
+    /// Palabras de cada comando de la tubería después de expandir los alias
+    fn expand(aliases: &Aliases, line: &str) -> Result<Vec<Vec<String>>, TermirustError> {
+        let command_line = Parser::parse(line).expect("la línea es válida");
+        let Statement::Pipeline(pipeline) = &command_line.items[0].first else {
+            panic!("la línea es una tubería");
+        };
+        let expanded = aliases.expand(pipeline)?;
+        Ok(expanded
+            .commands
+            .iter()
+            .map(|command| command.words.iter().map(|word| word.to_literal()).collect())
+            .collect())
+    }
+
+    fn loop_error(names: &[&str]) -> TermirustError {
+        Aliases::error(AliasError::Loop(names.iter().map(|name| name.to_string()).collect()))
+    }
+
+    #[test]
+    fn aliases_replace_the_head_and_keep_the_arguments() {
+        let aliases = aliases(&[("ll", "ls -la"), ("filtro", "grep x | sort")]);
+        assert_eq!(expand(&aliases, "ll src").unwrap(), [["ls", "-la", "src"]]);
+        assert_eq!(expand(&aliases, "'ll' src").unwrap(), [["ll", "src"]]);
+        let expanded = expand(&aliases, "cat a | filtro -r").unwrap();
+        assert_eq!(expanded, [vec!["cat", "a"], vec!["grep", "x"], vec!["sort", "-r"]]);
+    }
+
+    #[test]
+    fn an_alias_that_uses_itself_stops_there() {
+        let aliases = aliases(&[("ls", "ls -F"), ("l", "ls -a")]);
+        assert_eq!(expand(&aliases, "ls src").unwrap(), [["ls", "-F", "src"]]);
+        assert_eq!(expand(&aliases, "l").unwrap(), [["ls", "-F", "-a"]]);
+    }
+
+    #[test]
+    fn mutual_aliases_are_a_loop() {
+        let aliases = aliases(&[("a", "b"), ("b", "c -v"), ("c", "a")]);
+        assert_eq!(expand(&aliases, "a"), Err(loop_error(&["a", "b", "c", "a"])));
+        assert_eq!(expand(&aliases, "b x"), Err(loop_error(&["b", "c", "a", "b"])));
+    }
+
+    #[test]
+    fn a_trailing_space_expands_the_next_word_too() {
+        let aliases = aliases(&[("sudo", "sudo "), ("ll", "ls -la"), ("run", "sudo"), ("ver", "less")]);
+        assert_eq!(expand(&aliases, "sudo ll src").unwrap(), [["sudo", "ls", "-la", "src"]]);
+        assert_eq!(expand(&aliases, "sudo sudo ll").unwrap(), [["sudo", "sudo", "ls", "-la"]]);
+        // Solo la palabra siguiente, y solo si el valor termina en espacio
+        assert_eq!(expand(&aliases, "sudo echo ll").unwrap(), [["sudo", "echo", "ll"]]);
+        assert_eq!(expand(&aliases, "ver ll").unwrap(), [["less", "ll"]]);
+        // Un alias de una palabra termina como el alias que reemplaza
+        assert_eq!(expand(&aliases, "run ll").unwrap(), [["sudo", "ls", "-la"]]);
+    }
+}
@@ -0,0 +1,212 @@
+use crate::domain::entities::RedirectMode;
+
+/// Fragmento de una palabra junto con el tipo de entrecomillado que lo produjo
+#[derive(Debug, Clone, PartialEq)]
+pub enum WordPart {
+    /// Texto sin comillas
+    Unquoted(String),
+    /// Texto literal: entre comillas simples o escapado con barra invertida
+    Quoted(String),
+    /// Texto entre comillas dobles
+    DoubleQuoted(String),
+}
+
+impl WordPart {
+    /// Obtiene el texto del fragmento sin comillas
+    pub fn text(&self) -> &str {
+        match self {
+            WordPart::Unquoted(text) | WordPart::Quoted(text) | WordPart::DoubleQuoted(text) => text,
+        }
+    }
+}
+
+/// Palabra de la línea de comandos compuesta por uno o más fragmentos
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Word {
+    pub parts: Vec<WordPart>,
+}
+
+impl Word {
+    /// Crea una palabra a partir de sus fragmentos
+    pub fn new(parts: Vec<WordPart>) -> Self {
+        Self { parts }
+    }
+
+    /// Obtiene el valor literal de la palabra, ya sin comillas ni escapes
+    pub fn to_literal(&self) -> String {
+        self.parts.iter().map(WordPart::text).collect()
+    }
+}
+
+/// Redirección de entrada/salida tal como aparece en la línea de comandos
+#[derive(Debug, Clone, PartialEq)]
+pub struct Redirection {
+    pub fd: u32,
+    pub mode: RedirectMode,
+    pub target: Word,
+}
+
+/// Comando simple: nombre del programa seguido de sus argumentos y redirecciones
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SimpleCommand {
+    pub words: Vec<Word>,
+    pub redirections: Vec<Redirection>,
+}
+
+impl SimpleCommand {
+    /// Crea un comando simple a partir de sus palabras y redirecciones
+    pub fn new(words: Vec<Word>, redirections: Vec<Redirection>) -> Self {
+        Self { words, redirections }
+    }
+}
+
+/// Tubería de comandos unidos con `|`; la salida de cada uno alimenta al siguiente
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Pipeline {
+    pub commands: Vec<SimpleCommand>,
+    /// Texto original de la tubería, usado para el eco del prompt y el historial
+    pub text: String,
+}
+
+impl Pipeline {
+    /// Crea una tubería a partir de sus comandos
+    pub fn new(commands: Vec<SimpleCommand>) -> Self {
+        Self {
+            commands,
+            text: String::new(),
+        }
+    }
+
+    /// Asocia el texto original de la tubería
+    pub fn with_text(mut self, text: String) -> Self {
+        self.text = text;
+        self
+    }
+}
+
+/// Conector entre dos tuberías de una lista condicional
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Connector {
+    /// `&&`: ejecuta la siguiente tubería solo si la anterior terminó con éxito
+    And,
+    /// `||`: ejecuta la siguiente tubería solo si la anterior falló
+    Or,
+}
+
+impl Connector {
+    /// Símbolo del conector en la línea de comandos
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Connector::And => "&&",
+            Connector::Or => "||",
+        }
+    }
+
+    /// Verifica si la siguiente tubería debe ejecutarse según el último código de salida
+    pub fn should_run(&self, last_exit_code: i32) -> bool {
+        match self {
+            Connector::And => last_exit_code == 0,
+            Connector::Or => last_exit_code != 0,
+        }
+    }
+}
+
+/// `if condición; then ...; elif condición; then ...; else ...; fi`
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct IfClause {
+    /// Condición y cuerpo del `if` y de cada `elif`, en orden
+    pub branches: Vec<(CommandLine, CommandLine)>,
+    pub else_body: Option<CommandLine>,
+}
+
+/// `for nombre in palabras; do ...; done`. Sin `in` recorre los parámetros `$1..$n`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ForClause {
+    pub variable: String,
+    pub words: Option<Vec<Word>>,
+    pub body: CommandLine,
+}
+
+/// `while condición; do ...; done`
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct WhileClause {
+    pub condition: CommandLine,
+    pub body: CommandLine,
+}
+
+/// `nombre() { ...; }` o `function nombre { ...; }`
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct FunctionDefinition {
+    pub name: String,
+    pub body: CommandLine,
+    /// Texto original de la definición, para mostrarla con `type`
+    pub text: String,
+}
+
+/// Estructura de control del lenguaje de comandos
+#[derive(Debug, Clone, PartialEq)]
+pub enum CompoundCommand {
+    If(IfClause),
+    For(ForClause),
+    While(WhileClause),
+    Function(FunctionDefinition),
+}
+
+/// Elemento de una lista condicional: una tubería o una estructura de control
+#[derive(Debug, Clone, PartialEq)]
+pub enum Statement {
+    Pipeline(Pipeline),
+    Compound { command: CompoundCommand, text: String },
+}
+
+impl Statement {
+    /// Texto original del elemento, usado para el eco del prompt
+    pub fn text(&self) -> &str {
+        match self {
+            Statement::Pipeline(pipeline) => &pipeline.text,
+            Statement::Compound { text, .. } => text,
+        }
+    }
+}
+
+impl Default for Statement {
+    fn default() -> Self {
+        Statement::Pipeline(Pipeline::default())
+    }
+}
+
+/// Lista condicional: tuberías o estructuras de control unidas con `&&` y `||`
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct AndOrList {
+    pub first: Statement,
+    pub rest: Vec<(Connector, Statement)>,
+    /// Terminada con `&`: se lanza en segundo plano
+    pub background: bool,
+}
+
+impl AndOrList {
+    /// Crea una lista condicional que empieza con el elemento indicado
+    pub fn new(first: Statement) -> Self {
+        Self {
+            first,
+            rest: Vec::new(),
+            background: false,
+        }
+    }
+
+    /// Texto de toda la lista, reconstruido a partir del texto de cada elemento
+    pub fn text(&self) -> String {
+        let mut text = self.first.text().to_string();
+        for (connector, statement) in &self.rest {
+            text.push_str(&format!(" {} {}", connector.symbol(), statement.text()));
+        }
+        text
+    }
+}
+
+/// Raíz del árbol sintáctico de una línea de comandos, y también el cuerpo de una
+/// estructura de control: listas separadas por `;`, `&` o saltos de línea
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CommandLine {
+    pub items: Vec<AndOrList>,
+}
@@ -0,0 +1,306 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::domain::aliases::Aliases;
+use crate::domain::entities::{Command, TerminalState};
+use crate::domain::options::OptionSpec;
+use crate::domain::repositories::AliasRepository;
+
+/// Información de un comando interno para la ayuda
+#[derive(Debug, Clone)]
+pub struct CommandInfo {
+    pub aliases: Vec<String>,
+    pub description: String,
+    pub help_text: String,
+}
+
+impl CommandInfo {
+    /// Obtiene el texto de ayuda formateado
+    pub fn get_formatted_help(&self) -> String {
+        self.help_text.clone()
+    }
+}
+
+/// Indica si se pidió la ayuda del comando: `--help` antes de un `--`
+pub fn asks_for_help(args: &[String]) -> bool {
+    args.iter().take_while(|arg| *arg != "--").any(|arg| arg == "--help")
+}
+
+/// Comando interno de Termirust. Se ejecuta dentro del proceso, sin buscarlo en el
+/// `PATH`, y se describe a sí mismo para que `help` no repita su documentación.
+pub trait BuiltinCommand: Send {
+    fn name(&self) -> &str;
+
+    /// Otros nombres con los que se invoca el comando (`dir` para `ls`)
+    fn aliases(&self) -> &[&str] {
+        &[]
+    }
+
+    /// Descripción de una línea para el listado de `help`
+    fn description(&self) -> &str;
+
+    /// Forma de uso, como `cd [directorio]`
+    fn usage(&self) -> &str;
+
+    /// Ayuda detallada: opciones y ejemplos; es lo que muestran `help nombre` y
+    /// `nombre --help`
+    fn help(&self) -> String;
+
+    /// Candidatos para completar los argumentos del comando con Tab
+    fn completions(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Opciones que acepta, para completarlas con Tab (`ls -`)
+    fn options(&self) -> Vec<OptionSpec> {
+        Vec::new()
+    }
+
+    /// Ejecutable que implementa el comando, si lo aporta un plugin
+    fn location(&self) -> Option<&Path> {
+        None
+    }
+
+    /// Indica si el comando atiende estos argumentos; si no, se ejecuta el programa
+    /// del mismo nombre del `PATH` (`env` solo lista el entorno sin argumentos)
+    fn handles(&self, _args: &[String]) -> bool {
+        true
+    }
+
+    /// Ejecuta el comando con los datos de la sesión
+    fn execute(&self, command: &Command, context: &mut BuiltinContext) -> Command;
+
+    /// Información del comando para la ayuda
+    fn info(&self) -> CommandInfo {
+        CommandInfo {
+            aliases: self.aliases().iter().map(|alias| alias.to_string()).collect(),
+            description: self.description().to_string(),
+            help_text: self.help(),
+        }
+    }
+}
+
+/// Datos de la sesión que recibe un comando interno al ejecutarse
+pub struct BuiltinContext<'a> {
+    /// Los demás comandos registrados
+    pub registry: &'a CommandRegistry,
+    /// Variables exportadas de la sesión
+    pub environment: &'a [(String, String)],
+    /// Estado de la sesión, para los comandos que lo consultan o lo cambian
+    pub session: Session<'a>,
+}
+
+/// Estado de la sesión que pueden consultar y cambiar los comandos internos, como las
+/// variables de `export` o los alias de `alias`
+pub struct Session<'a> {
+    pub state: &'a mut TerminalState,
+    pub aliases: &'a mut Aliases,
+    /// Dónde se guardan los alias al cambiarlos
+    pub alias_repository: Option<&'a dyn AliasRepository>,
+}
+
+/// Comandos internos disponibles, en el orden en que se registraron. Se consulta
+/// antes de buscar un programa en el `PATH`.
+#[derive(Default)]
+pub struct CommandRegistry {
+    commands: Vec<Box<dyn BuiltinCommand>>,
+}
+
+impl CommandRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Agrega un comando. Si ya había uno con el mismo nombre, lo reemplaza.
+    pub fn register(&mut self, command: Box<dyn BuiltinCommand>) {
+        match self.commands.iter().position(|existing| existing.name() == command.name()) {
+            Some(index) => self.commands[index] = command,
+            None => self.commands.push(command),
+        }
+    }
+
+    /// Busca un comando por su nombre o por uno de sus alias
+    pub fn find(&self, name: &str) -> Option<&dyn BuiltinCommand> {
+        self.commands
+            .iter()
+            .find(|command| command.name() == name || command.aliases().contains(&name))
+            .map(|command| command.as_ref())
+    }
+
+    /// Comando interno que atiende el comando con sus argumentos, si lo hay
+    pub fn find_for(&self, command: &Command) -> Option<&dyn BuiltinCommand> {
+        self.find(&command.name).filter(|builtin| builtin.handles(&command.args))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &dyn BuiltinCommand> {
+        self.commands.iter().map(|command| command.as_ref())
+    }
+
+    /// Ejecuta el comando interno que atiende el comando, si lo hay
+    pub fn execute(&self, command: &Command, environment: &[(String, String)], session: Session) -> Option<Command> {
+        let mut context = BuiltinContext { registry: self, environment, session };
+        self.find_for(command).map(|builtin| builtin.execute(command, &mut context))
+    }
+
+    /// Nombres de los comandos registrados (con sus alias), su descripción y lo que
+    /// declaran para completar sus argumentos
+    pub fn catalog(&self) -> CommandCatalog {
+        let mut catalog = CommandCatalog::default();
+        for command in &self.commands {
+            let entry = CatalogEntry {
+                description: command.description().to_string(),
+                completions: command.completions(),
+                options: command.options(),
+                location: command.location().map(Path::to_path_buf),
+            };
+            for alias in command.aliases() {
+                catalog.entries.insert(alias.to_string(), entry.clone());
+            }
+            catalog.entries.insert(command.name().to_string(), entry);
+        }
+        catalog
+    }
+}
+
+/// Lo que se sabe de un comando registrado fuera del hilo que lo ejecuta
+#[derive(Debug, Clone, Default)]
+pub struct CatalogEntry {
+    pub description: String,
+    pub completions: Vec<String>,
+    pub options: Vec<OptionSpec>,
+    pub location: Option<PathBuf>,
+}
+
+/// Copia de los comandos registrados, para completarlos con Tab y sugerirlos sin
+/// recorrer el registro
+#[derive(Debug, Clone, Default)]
+pub struct CommandCatalog {
+    entries: BTreeMap<String, CatalogEntry>,
+}
+
+impl CommandCatalog {
+    pub fn get(&self, name: &str) -> Option<&CatalogEntry> {
+        self.entries.get(name)
+    }
+
+    /// Nombres de todos los comandos, ordenados
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Comando interno de prueba que responde con su nombre y sus argumentos
+    pub(crate) struct Greeting {
+        pub(crate) name: &'static str,
+        pub(crate) aliases: &'static [&'static str],
+        pub(crate) description: &'static str,
+        /// Solo atiende la invocación sin argumentos, como `env`
+        pub(crate) without_args: bool,
+    }
+
+    impl Greeting {
+        pub(crate) fn new(name: &'static str, description: &'static str) -> Self {
+            Self { name, aliases: &[], description, without_args: false }
+        }
+    }
+
+    impl BuiltinCommand for Greeting {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn aliases(&self) -> &[&str] {
+            self.aliases
+        }
+
+        fn description(&self) -> &str {
+            self.description
+        }
+
+        fn usage(&self) -> &str {
+            "saludo [nombre]"
+        }
+
+        fn help(&self) -> String {
+            format!("Uso: {}\n\n{}\n", self.usage(), self.description)
+        }
+
+        fn completions(&self) -> Vec<String> {
+            vec!["mundo".to_string()]
+        }
+
+        fn handles(&self, args: &[String]) -> bool {
+            !self.without_args || args.is_empty()
+        }
+
+        fn execute(&self, command: &Command, _context: &mut BuiltinContext) -> Command {
+            let mut words = vec![self.name.to_string()];
+            words.extend(command.args.iter().cloned());
+            command.clone().with_output(words.join(" ")).with_exit_code(0)
+        }
+    }
+
+    fn command(name: &str, args: &[&str]) -> Command {
+        Command::new(name.to_string(), args.iter().map(|arg| arg.to_string()).collect())
+    }
+
+    fn names(registry: &CommandRegistry) -> Vec<&str> {
+        registry.iter().map(|command| command.name()).collect()
+    }
+
+    #[test]
+    fn registering_a_name_again_replaces_the_command_in_its_place() {
+        let mut registry = CommandRegistry::new();
+        registry.register(Box::new(Greeting::new("saludo", "Saludar")));
+        registry.register(Box::new(Greeting::new("adios", "Despedirse")));
+        registry.register(Box::new(Greeting::new("saludo", "Saludar de nuevo")));
+
+        assert_eq!(names(&registry), ["saludo", "adios"]);
+        assert_eq!(registry.find("saludo").map(|command| command.description()), Some("Saludar de nuevo"));
+    }
+
+    #[test]
+    fn commands_are_found_by_name_or_alias_when_they_handle_the_arguments() {
+        let mut registry = CommandRegistry::new();
+        registry.register(Box::new(Greeting { aliases: &["hola"], ..Greeting::new("saludo", "Saludar") }));
+        registry.register(Box::new(Greeting { without_args: true, ..Greeting::new("entorno", "Listar") }));
+
+        assert_eq!(registry.find("hola").map(|command| command.name()), Some("saludo"));
+        assert!(registry.find("ls").is_none());
+        assert!(registry.find_for(&command("entorno", &[])).is_some());
+        // Con argumentos se ejecuta el programa del PATH
+        assert!(registry.find_for(&command("entorno", &["-i"])).is_none());
+    }
+
+    #[test]
+    fn the_registry_runs_the_command_that_handles_the_invocation() {
+        let mut registry = CommandRegistry::new();
+        registry.register(Box::new(Greeting { aliases: &["hola"], ..Greeting::new("saludo", "Saludar") }));
+        let mut state = TerminalState::default();
+        let mut aliases = Aliases::default();
+        let session = Session { state: &mut state, aliases: &mut aliases, alias_repository: None };
+
+        let result = registry.execute(&command("hola", &["mundo"]), &[], session).expect("hola es un alias");
+        assert_eq!(result.output, "saludo mundo");
+        let session = Session { state: &mut state, aliases: &mut aliases, alias_repository: None };
+        assert!(registry.execute(&command("ls", &[]), &[], session).is_none());
+    }
+
+    #[test]
+    fn the_catalog_lists_names_and_aliases_in_order() {
+        let mut registry = CommandRegistry::new();
+        registry.register(Box::new(Greeting { aliases: &["hola"], ..Greeting::new("saludo", "Saludar") }));
+        registry.register(Box::new(Greeting::new("adios", "Despedirse")));
+
+        let catalog = registry.catalog();
+        assert_eq!(catalog.names().collect::<Vec<_>>(), ["adios", "hola", "saludo"]);
+        let alias = catalog.get("hola").expect("los alias están en el catálogo");
+        assert_eq!(alias.description, "Saludar");
+        assert_eq!(alias.completions, ["mundo"]);
+        assert!(alias.location.is_none());
+    }
+}
@@ -0,0 +1,566 @@
+use std::path::{Path, PathBuf};
+
+use crate::domain::environment::Environment;
+use crate::domain::options::OptionSpec;
+use crate::domain::repositories::DirectoryRepository;
+
+/// Palabras clave después de las cuales sigue un comando
+const COMMAND_KEYWORDS: &[&str] = &["if", "then", "elif", "else", "while", "do", "!", "{"];
+
+/// Caracteres que se escapan con `\` al insertar una palabra sin comillas
+const SPECIAL_CHARS: &str = " \t'\"\\$`|;&<>()*?[]{}#";
+
+/// Lo que se completa según dónde está la palabra en la línea
+#[derive(Debug, Clone, PartialEq)]
+pub enum CompletionTarget {
+    /// Nombre de comando: al principio, después de `|`, `;`, `&&`, `||` o de una
+    /// palabra clave
+    Command,
+    /// Argumento de un comando, con los argumentos que ya se escribieron
+    Argument { command: String, args: Vec<String> },
+    /// Archivo después de `<` o `>`
+    Redirect,
+    /// Nombre de variable después de `$` o `${`
+    Variable { braced: bool },
+}
+
+/// Palabra que se está escribiendo al final de la línea y qué se espera en ella
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompletionRequest {
+    /// Posición (en bytes) desde la que se reemplaza lo escrito
+    pub start: usize,
+    /// Lo escrito, sin comillas ni escapes
+    pub prefix: String,
+    /// Comilla que quedó abierta en la palabra
+    pub quote: Option<char>,
+    pub target: CompletionTarget,
+}
+
+impl CompletionRequest {
+    /// Texto que reemplaza lo escrito desde `start` para que la palabra sea `value`.
+    /// Con `finished` la palabra se cierra: se cierra la comilla y se agrega un espacio.
+    pub fn replacement(&self, value: &str, finished: bool) -> String {
+        let mut text = match &self.target {
+            CompletionTarget::Variable { braced: true } => {
+                format!("${{{}{}", value, if finished { "}" } else { "" })
+            }
+            CompletionTarget::Variable { braced: false } => format!("${}", value),
+            _ => {
+                // `~/` va fuera de las comillas para que se siga expandiendo
+                let tilde = match value.find('/') {
+                    Some(slash) if value.starts_with('~') => slash + 1,
+                    _ => 0,
+                };
+                let (literal, rest) = value.split_at(tilde);
+                let mut text = literal.to_string();
+                match self.quote {
+                    Some(quote) => {
+                        text.push(quote);
+                        text.push_str(&quote_in(quote, rest));
+                        if finished {
+                            text.push(quote);
+                        }
+                    }
+                    None => text.push_str(&escape_word(rest)),
+                }
+                text
+            }
+        };
+        if finished {
+            text.push(' ');
+        }
+        text
+    }
+}
+
+/// Analiza la línea (con el cursor al final) para saber qué palabra se completa.
+/// Sigue las comillas, los escapes y los operadores como lo hace el analizador.
+pub fn analyze(line: &str) -> CompletionRequest {
+    let mut words: Vec<String> = Vec::new();
+    let mut word = String::new();
+    let mut word_start: Option<usize> = None;
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    let mut redirect = false;
+    // Posición del `$` de la variable que se está escribiendo y si lleva llave
+    let mut variable: Option<(usize, bool)> = None;
+
+    for (index, ch) in line.char_indices() {
+        if escaped {
+            escaped = false;
+            word.push(ch);
+            variable = None;
+            continue;
+        }
+        match (quote, ch) {
+            (Some('\''), '\'') | (Some('"'), '"') => quote = None,
+            (Some('\''), _) => word.push(ch),
+            (Some(_), '\\') => escaped = true,
+            (None, ch) if ch.is_whitespace() => {
+                end_word(&mut words, &mut word, &mut word_start, &mut redirect);
+                variable = None;
+            }
+            (None, '|' | ';' | '&' | '(' | ')') => {
+                end_word(&mut words, &mut word, &mut word_start, &mut redirect);
+                words.clear();
+                redirect = false;
+                variable = None;
+            }
+            (None, '<' | '>') => {
+                end_word(&mut words, &mut word, &mut word_start, &mut redirect);
+                redirect = true;
+                variable = None;
+            }
+            (None, '\'' | '"') => {
+                word_start.get_or_insert(index);
+                quote = Some(ch);
+                variable = None;
+            }
+            (None, '\\') => {
+                word_start.get_or_insert(index);
+                escaped = true;
+            }
+            (_, '$') => {
+                word_start.get_or_insert(index);
+                word.push(ch);
+                variable = Some((index, false));
+            }
+            (_, '{') if variable.is_some_and(|(start, braced)| !braced && start + 1 == index) => {
+                word.push(ch);
+                variable = variable.map(|(start, _)| (start, true));
+            }
+            _ => {
+                word_start.get_or_insert(index);
+                word.push(ch);
+                if !(ch.is_ascii_alphanumeric() || ch == '_') {
+                    variable = None;
+                }
+            }
+        }
+    }
+
+    if let Some((start, braced)) = variable.filter(|_| quote != Some('\'')) {
+        let name_start = start + 1 + usize::from(braced);
+        return CompletionRequest {
+            start,
+            prefix: line[name_start..].to_string(),
+            quote: None,
+            target: CompletionTarget::Variable { braced },
+        };
+    }
+
+    let target = if redirect {
+        CompletionTarget::Redirect
+    } else {
+        // Las asignaciones (`NOMBRE=valor`) y las palabras clave no son el comando
+        let command = words.iter().position(|word| {
+            !COMMAND_KEYWORDS.contains(&word.as_str())
+                && !word.split_once('=').is_some_and(|(name, _)| Environment::is_valid_name(name))
+        });
+        match command {
+            Some(command) => CompletionTarget::Argument {
+                command: words[command].clone(),
+                args: words[command + 1..].to_vec(),
+            },
+            None => CompletionTarget::Command,
+        }
+    };
+    CompletionRequest {
+        start: word_start.unwrap_or(line.len()),
+        prefix: word,
+        quote,
+        target,
+    }
+}
+
+/// Termina la palabra en curso. La palabra después de `<` o `>` es el archivo, no un
+/// argumento.
+fn end_word(words: &mut Vec<String>, word: &mut String, word_start: &mut Option<usize>, redirect: &mut bool) {
+    if word_start.take().is_some() {
+        if std::mem::take(redirect) {
+            word.clear();
+        } else {
+            words.push(std::mem::take(word));
+        }
+    }
+}
+
+/// Escapa con `\` los caracteres especiales de una palabra sin comillas
+pub fn escape_word(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        if SPECIAL_CHARS.contains(ch) {
+            escaped.push('\\');
+        }
+        escaped.push(ch);
+    }
+    escaped
+}
+
+/// Escribe el texto dentro de la comilla abierta
+fn quote_in(quote: char, text: &str) -> String {
+    if quote == '\'' {
+        // Dentro de comillas simples no hay escapes: se cierra, se escapa y se reabre
+        return text.replace('\'', "'\\''");
+    }
+    let mut quoted = String::with_capacity(text.len());
+    for ch in text.chars() {
+        if matches!(ch, '"' | '\\' | '$' | '`') {
+            quoted.push('\\');
+        }
+        quoted.push(ch);
+    }
+    quoted
+}
+
+/// Posible valor de la palabra que se completa
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candidate {
+    /// La palabra completa, sin comillas
+    pub value: String,
+    /// Lo que se muestra en la lista: para un archivo, su nombre sin el directorio
+    pub label: String,
+    pub description: Option<String>,
+    /// La palabra puede seguir (un directorio): al elegirla no se agrega un espacio
+    pub open: bool,
+}
+
+impl Candidate {
+    pub fn new(value: &str) -> Self {
+        Self {
+            value: value.to_string(),
+            label: value.to_string(),
+            description: None,
+            open: false,
+        }
+    }
+
+    pub fn with_label(mut self, label: String) -> Self {
+        self.label = label;
+        self
+    }
+
+    pub fn with_description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    pub fn with_open(mut self, open: bool) -> Self {
+        self.open = open;
+        self
+    }
+}
+
+/// Los candidatos que empiezan con lo escrito, ordenados y sin repetir
+#[derive(Debug, Clone, PartialEq)]
+pub struct Completion {
+    pub request: CompletionRequest,
+    pub candidates: Vec<Candidate>,
+    /// Comando de una especificación cuya salida todavía no se tiene; hay que
+    /// ejecutarlo fuera de la interfaz y volver a completar
+    pub pending: Option<String>,
+}
+
+impl Completion {
+    /// Si un valor aparece más de una vez se queda el primero, que suele ser el que
+    /// tiene descripción
+    pub fn new(request: CompletionRequest, candidates: Vec<Candidate>) -> Self {
+        let mut seen = std::collections::HashSet::new();
+        let mut candidates: Vec<Candidate> = candidates
+            .into_iter()
+            .filter(|candidate| candidate.value.starts_with(&request.prefix))
+            .filter(|candidate| seen.insert(candidate.value.clone()))
+            .collect();
+        candidates.sort_by(|a, b| a.value.cmp(&b.value));
+        Self {
+            request,
+            candidates,
+            pending: None,
+        }
+    }
+
+    pub fn with_pending(mut self, command: String) -> Self {
+        self.pending = Some(command);
+        self
+    }
+
+    /// Parte común más larga de todos los candidatos
+    pub fn common_prefix(&self) -> String {
+        let Some((first, rest)) = self.candidates.split_first() else {
+            return String::new();
+        };
+        let mut common: &str = &first.value;
+        for candidate in rest {
+            let length = common
+                .char_indices()
+                .zip(candidate.value.chars())
+                .take_while(|((_, a), b)| a == b)
+                .last()
+                .map_or(0, |((index, ch), _)| index + ch.len_utf8());
+            common = &common[..length];
+        }
+        common.to_string()
+    }
+}
+
+/// Lista de candidatos que se muestra cuando hay varios, con el elegido
+#[derive(Debug, Clone)]
+pub struct CompletionMenu {
+    completion: Completion,
+    selected: usize,
+}
+
+impl CompletionMenu {
+    pub fn new(completion: Completion) -> Self {
+        Self { completion, selected: 0 }
+    }
+
+    pub fn request(&self) -> &CompletionRequest {
+        &self.completion.request
+    }
+
+    pub fn candidates(&self) -> &[Candidate] {
+        &self.completion.candidates
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn selected_candidate(&self) -> Option<&Candidate> {
+        self.completion.candidates.get(self.selected)
+    }
+
+    /// Elige el siguiente; después del último vuelve al primero
+    pub fn select_next(&mut self) {
+        if !self.completion.candidates.is_empty() {
+            self.selected = (self.selected + 1) % self.completion.candidates.len();
+        }
+    }
+
+    /// Elige el anterior; antes del primero vuelve al último
+    pub fn select_previous(&mut self) {
+        let count = self.completion.candidates.len();
+        if count > 0 {
+            self.selected = (self.selected + count - 1) % count;
+        }
+    }
+}
+
+/// Qué archivos se ofrecen al completar una ruta
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathFilter {
+    All,
+    Directories,
+    /// Directorios y programas, para una ruta en posición de comando
+    Executables,
+}
+
+/// Archivos y directorios que completan la ruta escrita, leídos con `directories`.
+/// `~/` se busca en `home`; los archivos ocultos solo se ofrecen si el nombre empieza
+/// con `.`.
+pub fn path_candidates(
+    prefix: &str,
+    home: Option<&str>,
+    filter: PathFilter,
+    directories: &dyn DirectoryRepository,
+) -> Vec<Candidate> {
+    let (directory, name) = match prefix.rfind('/') {
+        Some(slash) => prefix.split_at(slash + 1),
+        None => ("", prefix),
+    };
+    let search: PathBuf = match (directory.strip_prefix("~/"), home) {
+        (Some(rest), Some(home)) => Path::new(home).join(rest),
+        _ if directory.is_empty() => PathBuf::from("."),
+        _ => PathBuf::from(directory),
+    };
+
+    let mut candidates = Vec::new();
+    for entry in directories.list(&search) {
+        if !entry.name.starts_with(name) || (entry.name.starts_with('.') && !name.starts_with('.')) {
+            continue;
+        }
+        let wanted = match filter {
+            PathFilter::All => true,
+            PathFilter::Directories => entry.is_dir,
+            PathFilter::Executables => entry.is_dir || entry.is_executable,
+        };
+        if !wanted {
+            continue;
+        }
+        let label = if entry.is_dir { format!("{}/", entry.name) } else { entry.name };
+        candidates.push(
+            Candidate::new(&format!("{}{}", directory, label))
+                .with_label(label)
+                .with_open(entry.is_dir),
+        );
+    }
+    candidates
+}
+
+/// Opciones de un comando interno que empiezan con lo escrito: con `--` solo las
+/// largas, con `-` las cortas y las largas
+pub fn option_candidates(options: &[OptionSpec], prefix: &str) -> Vec<Candidate> {
+    let mut candidates = Vec::new();
+    for option in options {
+        let description = match option.value {
+            Some(value) => format!("{} ({})", option.description, value),
+            None => option.description.to_string(),
+        };
+        if let Some(short) = option.short.filter(|_| !prefix.starts_with("--")) {
+            candidates.push(Candidate::new(&format!("-{}", short)).with_description(&description));
+        }
+        if let Some(long) = option.long {
+            candidates.push(Candidate::new(&format!("--{}", long)).with_description(&description));
+        }
+    }
+    candidates
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::glob::tests::MemoryDirectories;
+
+    fn argument(command: &str, args: &[&str]) -> CompletionTarget {
+        CompletionTarget::Argument {
+            command: command.to_string(),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn words_in_command_position_complete_commands() {
+        for (line, start) in [("", 0), ("gi", 0), ("ls | gr", 5), ("a && b; c", 8), ("if tr", 3), ("A=1 ec", 4)] {
+            let request = analyze(line);
+            assert_eq!(request.target, CompletionTarget::Command, "{}", line);
+            assert_eq!(request.start, start, "{}", line);
+        }
+    }
+
+    #[test]
+    fn words_after_a_command_complete_its_arguments() {
+        let request = analyze("git chec");
+        assert_eq!(request.target, argument("git", &[]));
+        assert_eq!((request.start, request.prefix.as_str()), (4, "chec"));
+
+        let request = analyze("A=1 git commit -");
+        assert_eq!(request.target, argument("git", &["commit"]));
+        assert_eq!(request.prefix, "-");
+
+        // Con la palabra terminada, se completa una nueva desde el final
+        let request = analyze("git commit ");
+        assert_eq!(request.target, argument("git", &["commit"]));
+        assert_eq!((request.start, request.prefix.as_str()), (11, ""));
+    }
+
+    #[test]
+    fn quoted_and_escaped_words_keep_their_start_and_literal_prefix() {
+        let request = analyze("cat \"mi ar");
+        assert_eq!((request.start, request.prefix.as_str(), request.quote), (4, "mi ar", Some('"')));
+
+        let request = analyze("cat mi\\ ar");
+        assert_eq!((request.start, request.prefix.as_str(), request.quote), (4, "mi ar", None));
+
+        let request = analyze("cat 'a b' c");
+        assert_eq!(request.target, argument("cat", &["a b"]));
+        assert_eq!(request.prefix, "c");
+
+        // Los operadores entre comillas no cortan la palabra
+        let request = analyze("echo 'a | b");
+        assert_eq!(request.target, argument("echo", &[]));
+        assert_eq!(request.prefix, "a | b");
+    }
+
+    #[test]
+    fn words_after_a_redirection_complete_files() {
+        let request = analyze("ls > sa");
+        assert_eq!(request.target, CompletionTarget::Redirect);
+        assert_eq!(request.prefix, "sa");
+        // El archivo no cuenta como argumento del comando
+        assert_eq!(analyze("sort < datos -").target, argument("sort", &[]));
+    }
+
+    #[test]
+    fn dollar_words_complete_variables_except_in_single_quotes() {
+        let request = analyze("echo $HO");
+        assert_eq!(request.target, CompletionTarget::Variable { braced: false });
+        assert_eq!((request.start, request.prefix.as_str()), (5, "HO"));
+
+        let request = analyze("echo \"${PA");
+        assert_eq!(request.target, CompletionTarget::Variable { braced: true });
+        assert_eq!((request.start, request.prefix.as_str()), (6, "PA"));
+
+        let request = analyze("echo '$HO");
+        assert_eq!(request.target, argument("echo", &[]));
+        assert_eq!(request.prefix, "$HO");
+    }
+
+    #[test]
+    fn replacement_escapes_or_quotes_like_the_written_word() {
+        assert_eq!(analyze("cat mi").replacement("mi archivo", true), "mi\\ archivo ");
+        assert_eq!(analyze("cat mi").replacement("src/", false), "src/");
+        assert_eq!(analyze("cat \"mi").replacement("mi \"x\"", true), "\"mi \\\"x\\\"\" ");
+        assert_eq!(analyze("cat 'it").replacement("it's", true), "'it'\\''s' ");
+        assert_eq!(analyze("cat \"~/D").replacement("~/Mis docs/", false), "~/\"Mis docs/");
+    }
+
+    #[test]
+    fn replacement_of_variables_closes_the_brace() {
+        assert_eq!(analyze("echo $HO").replacement("HOME", true), "$HOME ");
+        assert_eq!(analyze("echo ${HO").replacement("HOME", true), "${HOME} ");
+        assert_eq!(analyze("echo ${HO").replacement("HOME", false), "${HOME");
+    }
+
+    #[test]
+    fn escape_word_escapes_shell_characters() {
+        assert_eq!(escape_word("a b$c*"), "a\\ b\\$c\\*");
+        assert_eq!(escape_word("(x)[y]{z}#"), "\\(x\\)\\[y\\]\\{z\\}\\#");
+        assert_eq!(escape_word("normal-1.txt"), "normal-1.txt");
+    }
+
+    fn values(candidates: Vec<Candidate>) -> Vec<String> {
+        let mut values: Vec<String> = candidates.into_iter().map(|candidate| candidate.value).collect();
+        values.sort();
+        values
+    }
+
+    const FILES: &[&str] = &["src/", "script*", "Cargo.toml", ".git/", "docs/guia.md"];
+
+    #[test]
+    fn path_candidates_follow_the_filter() {
+        let directories = MemoryDirectories::new(Path::new("."), FILES);
+        let all = path_candidates("", None, PathFilter::All, &directories);
+        assert_eq!(values(all), ["Cargo.toml", "docs/", "script", "src/"]);
+        let only_directories = path_candidates("s", None, PathFilter::Directories, &directories);
+        assert_eq!(values(only_directories), ["src/"]);
+        let programs = path_candidates("./s", None, PathFilter::Executables, &directories);
+        assert_eq!(values(programs), ["./script", "./src/"]);
+    }
+
+    #[test]
+    fn path_candidates_search_inside_directories_and_home() {
+        let directories = MemoryDirectories::new(Path::new("."), FILES);
+        let candidates = path_candidates("docs/g", None, PathFilter::All, &directories);
+        assert_eq!(candidates, [Candidate::new("docs/guia.md").with_label("guia.md".to_string())]);
+
+        let home = MemoryDirectories::new(Path::new("/home/ana"), &["notas.txt"]);
+        let candidates = path_candidates("~/no", Some("/home/ana"), PathFilter::All, &home);
+        assert_eq!(values(candidates), ["~/notas.txt"]);
+    }
+
+    #[test]
+    fn hidden_files_need_a_leading_dot() {
+        let directories = MemoryDirectories::new(Path::new("."), FILES);
+        assert!(!values(path_candidates("", None, PathFilter::All, &directories)).contains(&".git/".to_string()));
+        assert_eq!(values(path_candidates(".g", None, PathFilter::All, &directories)), [".git/"]);
+    }
+
+    #[test]
+    fn directories_stay_open() {
+        let directories = MemoryDirectories::new(Path::new("."), FILES);
+        let candidates = path_candidates("sr", None, PathFilter::All, &directories);
+        assert!(candidates.iter().all(|candidate| candidate.open && candidate.label == "src/"));
+    }
+}
@@ -0,0 +1,293 @@
+use crate::domain::completion::Candidate;
+
+/// Valor que espera un argumento o una opción de un programa externo
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArgumentKind {
+    File,
+    Directory,
+    /// Uno de estos valores
+    Choice(Vec<String>),
+    /// Las líneas que imprime este comando, como las ramas de `git branch`
+    Command(String),
+    /// Un valor libre, como el mensaje de `git commit -m`: no se completa
+    Text,
+}
+
+/// Opción de un programa externo con sus nombres (`-m`, `--message`) y el valor que
+/// recibe, si recibe uno
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlagSpec {
+    pub names: Vec<String>,
+    pub description: String,
+    pub value: Option<ArgumentKind>,
+}
+
+/// Especificación de completado de un programa externo o de uno de sus subcomandos
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompletionSpec {
+    pub name: String,
+    pub description: String,
+    /// Otros nombres del subcomando (`b` para `cargo build`)
+    pub aliases: Vec<String>,
+    pub subcommands: Vec<CompletionSpec>,
+    pub options: Vec<FlagSpec>,
+    /// Lo que reciben sus argumentos; sin indicarlo, si no hay subcomandos que
+    /// ofrecer se completan archivos
+    pub arguments: Option<ArgumentKind>,
+}
+
+/// Lo que ofrece una especificación para la palabra que se completa
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SpecMatch {
+    /// Subcomandos, opciones o valores fijos
+    pub candidates: Vec<Candidate>,
+    /// Valores que hay que buscar: archivos, directorios o la salida de un comando
+    pub arguments: Option<ArgumentKind>,
+}
+
+impl CompletionSpec {
+    /// Subcomando con ese nombre o alias
+    fn subcommand(&self, name: &str) -> Option<&CompletionSpec> {
+        self.subcommands
+            .iter()
+            .find(|subcommand| subcommand.name == name || subcommand.aliases.iter().any(|alias| alias == name))
+    }
+
+    /// Recorre los argumentos ya escritos para saber en qué subcomando está la
+    /// palabra y qué se espera en ella: el valor de una opción, una opción, un
+    /// subcomando o un argumento
+    pub fn resolve(&self, args: &[String], prefix: &str) -> SpecMatch {
+        // Las opciones de los niveles anteriores siguen valiendo (`git -C dir status`)
+        let mut levels: Vec<&CompletionSpec> = vec![self];
+        let mut positional = 0;
+        let mut options_ended = false;
+        let mut pending: Option<&ArgumentKind> = None;
+
+        for arg in args {
+            if pending.take().is_some() {
+                continue;
+            }
+            if !options_ended && arg == "--" {
+                options_ended = true;
+            } else if !options_ended && arg.starts_with('-') && arg.len() > 1 {
+                // `--opción=valor` ya trae su valor
+                if !arg.contains('=') {
+                    pending = Self::find_flag(&levels, arg).and_then(|flag| flag.value.as_ref());
+                }
+            } else {
+                let current = levels[levels.len() - 1];
+                match current.subcommand(arg).filter(|_| positional == 0) {
+                    Some(subcommand) => {
+                        levels.push(subcommand);
+                        options_ended = false;
+                    }
+                    None => positional += 1,
+                }
+            }
+        }
+
+        let current = levels[levels.len() - 1];
+        if let Some(kind) = pending {
+            return Self::values(kind);
+        }
+        if !options_ended && prefix.starts_with('-') {
+            return SpecMatch {
+                candidates: Self::flag_candidates(&current.options, prefix),
+                arguments: None,
+            };
+        }
+        let subcommands: Vec<Candidate> = if positional == 0 {
+            current
+                .subcommands
+                .iter()
+                .map(|subcommand| Candidate::new(&subcommand.name).with_description(&subcommand.description))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let arguments = match &current.arguments {
+            Some(kind) => Some(kind.clone()),
+            None if subcommands.is_empty() => Some(ArgumentKind::File),
+            None => None,
+        };
+        let mut matched = arguments.map(|kind| Self::values(&kind)).unwrap_or_default();
+        matched.candidates.splice(0..0, subcommands);
+        matched
+    }
+
+    /// Busca la opción en el subcomando actual y, si no está, en los anteriores
+    fn find_flag<'a>(levels: &[&'a CompletionSpec], arg: &str) -> Option<&'a FlagSpec> {
+        levels
+            .iter()
+            .rev()
+            .flat_map(|level| level.options.iter())
+            .find(|flag| flag.names.iter().any(|name| name == arg))
+    }
+
+    /// Los nombres de las opciones; con `--` solo los largos
+    fn flag_candidates(options: &[FlagSpec], prefix: &str) -> Vec<Candidate> {
+        options
+            .iter()
+            .flat_map(|flag| {
+                flag.names
+                    .iter()
+                    .filter(|name| !prefix.starts_with("--") || name.starts_with("--"))
+                    .map(|name| Candidate::new(name).with_description(&flag.description))
+            })
+            .collect()
+    }
+
+    /// Valores fijos como candidatos; el resto se deja para quien sabe buscarlos
+    fn values(kind: &ArgumentKind) -> SpecMatch {
+        match kind {
+            ArgumentKind::Choice(choices) => SpecMatch {
+                candidates: choices.iter().map(|choice| Candidate::new(choice)).collect(),
+                arguments: None,
+            },
+            ArgumentKind::Text => SpecMatch::default(),
+            kind => SpecMatch {
+                candidates: Vec::new(),
+                arguments: Some(kind.clone()),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(name: &str) -> CompletionSpec {
+        CompletionSpec {
+            name: name.to_string(),
+            description: format!("Descripción de {}", name),
+            aliases: Vec::new(),
+            subcommands: Vec::new(),
+            options: Vec::new(),
+            arguments: None,
+        }
+    }
+
+    fn flag(names: &[&str], value: Option<ArgumentKind>) -> FlagSpec {
+        FlagSpec {
+            names: names.iter().map(|name| name.to_string()).collect(),
+            description: format!("Opción {}", names[0]),
+            value,
+        }
+    }
+
+    /// `git` con `-C DIR`, `checkout` (alias `co`) que recibe ramas y `commit` con `-m TEXTO`
+    fn git() -> CompletionSpec {
+        let mut checkout = spec("checkout");
+        checkout.aliases = vec!["co".to_string()];
+        checkout.arguments = Some(ArgumentKind::Command("git branch".to_string()));
+        checkout.options = vec![flag(&["-b"], Some(ArgumentKind::Text)), flag(&["-f", "--force"], None)];
+
+        let mut commit = spec("commit");
+        commit.options = vec![flag(&["-m", "--message"], Some(ArgumentKind::Text))];
+
+        let mut git = spec("git");
+        git.options = vec![
+            flag(&["-C"], Some(ArgumentKind::Directory)),
+            flag(&["--color"], Some(ArgumentKind::Choice(vec!["always".to_string(), "never".to_string()]))),
+        ];
+        git.subcommands = vec![checkout, commit];
+        git
+    }
+
+    fn resolve(spec: &CompletionSpec, args: &[&str], prefix: &str) -> SpecMatch {
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        spec.resolve(&args, prefix)
+    }
+
+    fn values(matched: &SpecMatch) -> Vec<&str> {
+        matched.candidates.iter().map(|candidate| candidate.value.as_str()).collect()
+    }
+
+    #[test]
+    fn offers_subcommands_with_their_description() {
+        let matched = resolve(&git(), &[], "");
+        assert_eq!(values(&matched), ["checkout", "commit"]);
+        assert_eq!(matched.candidates[0].description.as_deref(), Some("Descripción de checkout"));
+        assert_eq!(matched.arguments, None);
+    }
+
+    #[test]
+    fn enters_subcommands_by_name_or_alias() {
+        for name in ["checkout", "co"] {
+            let matched = resolve(&git(), &[name], "");
+            assert!(matched.candidates.is_empty());
+            assert_eq!(matched.arguments, Some(ArgumentKind::Command("git branch".to_string())));
+        }
+    }
+
+    #[test]
+    fn offers_the_options_of_the_current_level() {
+        assert_eq!(values(&resolve(&git(), &["checkout"], "-")), ["-b", "-f", "--force"]);
+        assert_eq!(values(&resolve(&git(), &["checkout"], "--")), ["--force"]);
+        assert_eq!(values(&resolve(&git(), &[], "-")), ["-C", "--color"]);
+    }
+
+    #[test]
+    fn completes_option_values() {
+        assert_eq!(resolve(&git(), &["-C"], "").arguments, Some(ArgumentKind::Directory));
+        assert_eq!(values(&resolve(&git(), &["--color"], "")), ["always", "never"]);
+        assert_eq!(resolve(&git(), &["commit", "-m"], ""), SpecMatch::default());
+    }
+
+    #[test]
+    fn option_values_are_not_taken_as_subcommands() {
+        // `commit` es el valor de `-C`, no el subcomando
+        let matched = resolve(&git(), &["-C", "commit"], "");
+        assert_eq!(values(&matched), ["checkout", "commit"]);
+    }
+
+    #[test]
+    fn inline_values_do_not_consume_the_next_word() {
+        let matched = resolve(&git(), &["--color=never", "checkout"], "");
+        assert_eq!(matched.arguments, Some(ArgumentKind::Command("git branch".to_string())));
+    }
+
+    #[test]
+    fn options_of_previous_levels_still_apply() {
+        assert_eq!(resolve(&git(), &["checkout", "-C"], "").arguments, Some(ArgumentKind::Directory));
+    }
+
+    #[test]
+    fn unknown_options_and_switches_take_no_value() {
+        let matched = resolve(&git(), &["checkout", "-f", "--nada"], "");
+        assert_eq!(matched.arguments, Some(ArgumentKind::Command("git branch".to_string())));
+    }
+
+    #[test]
+    fn subcommands_are_only_offered_before_the_first_argument() {
+        let mut cargo = spec("cargo");
+        cargo.subcommands = vec![spec("build")];
+        cargo.arguments = Some(ArgumentKind::Choice(vec!["x".to_string()]));
+        assert_eq!(values(&resolve(&cargo, &[], "")), ["build", "x"]);
+        // Después de un argumento, `build` ya no es un subcomando
+        assert_eq!(values(&resolve(&cargo, &["x", "build"], "")), ["x"]);
+    }
+
+    #[test]
+    fn double_dash_ends_the_options() {
+        let matched = resolve(&git(), &["checkout", "--"], "-");
+        assert!(matched.candidates.is_empty());
+        assert_eq!(matched.arguments, Some(ArgumentKind::Command("git branch".to_string())));
+        // Un subcomando vuelve a aceptar opciones
+        assert_eq!(values(&resolve(&git(), &["--", "commit"], "-")), ["-m", "--message"]);
+    }
+
+    #[test]
+    fn files_are_the_default_arguments_without_subcommands() {
+        assert_eq!(resolve(&spec("cat"), &["a"], "").arguments, Some(ArgumentKind::File));
+        assert_eq!(resolve(&git(), &["commit"], "").arguments, Some(ArgumentKind::File));
+        assert_eq!(resolve(&git(), &[], "").arguments, None);
+    }
+
+    #[test]
+    fn a_lone_dash_is_an_argument() {
+        assert_eq!(resolve(&spec("cat"), &["-"], "").arguments, Some(ArgumentKind::File));
+        assert_eq!(values(&resolve(&git(), &["-"], "")), Vec::<&str>::new());
+    }
+}
@@ -0,0 +1,388 @@
+/// Fragmento de una línea de documento con su formato
+#[derive(Debug, Clone, PartialEq)]
+pub enum Inline {
+    Text(String),
+    Bold(String),
+    Italic(String),
+    Code(String),
+    /// Texto de un enlace y la dirección a la que apunta
+    Link { text: String, target: String },
+}
+
+impl Inline {
+    pub fn text(&self) -> &str {
+        match self {
+            Inline::Text(text) | Inline::Bold(text) | Inline::Italic(text) | Inline::Code(text) => text,
+            Inline::Link { text, .. } => text,
+        }
+    }
+}
+
+/// Bloque al que pertenece una línea; la interfaz le da estilo según su tipo
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockKind {
+    /// Título de sección, con su nivel (1 es el principal)
+    Heading(u8),
+    Paragraph,
+    /// Elemento de una lista; la viñeta o el número va en `marker`
+    ListItem,
+    /// Bloque de código o texto sin rellenar: se muestra tal cual
+    Code,
+    Quote,
+    /// Fila de una tabla, con las columnas ya alineadas
+    Table,
+    /// Separador horizontal
+    Rule,
+    Blank,
+}
+
+/// Línea lógica de un documento. Los párrafos son una sola línea: la interfaz los
+/// parte según el ancho disponible.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DocumentLine {
+    pub kind: BlockKind,
+    /// Nivel de sangría
+    pub indent: usize,
+    /// Viñeta, número o etiqueta que va delante del texto
+    pub marker: Option<String>,
+    pub inlines: Vec<Inline>,
+}
+
+impl DocumentLine {
+    pub fn new(kind: BlockKind, inlines: Vec<Inline>) -> Self {
+        Self {
+            kind,
+            indent: 0,
+            marker: None,
+            inlines,
+        }
+    }
+
+    pub fn blank() -> Self {
+        Self::new(BlockKind::Blank, Vec::new())
+    }
+
+    pub fn with_indent(mut self, indent: usize) -> Self {
+        self.indent = indent;
+        self
+    }
+
+    pub fn with_marker(mut self, marker: String) -> Self {
+        self.marker = Some(marker);
+        self
+    }
+
+    /// Agrega texto al final, uniendo los fragmentos de texto normal seguidos
+    pub fn push(&mut self, inline: Inline) {
+        if inline.text().is_empty() {
+            return;
+        }
+        match (self.inlines.last_mut(), inline) {
+            (Some(Inline::Text(last)), Inline::Text(text)) => last.push_str(&text),
+            (_, inline) => self.inlines.push(inline),
+        }
+    }
+
+    /// Texto de la línea sin formato
+    pub fn text(&self) -> String {
+        self.inlines.iter().map(Inline::text).collect()
+    }
+
+    /// La línea como se escribe en una tubería: con sangría, viñeta y las direcciones
+    /// de los enlaces
+    pub fn plain(&self) -> String {
+        let mut line = "  ".repeat(self.indent);
+        if let Some(marker) = &self.marker {
+            line.push_str(marker);
+            line.push(' ');
+        }
+        match self.kind {
+            BlockKind::Rule => line.push_str(&"-".repeat(40)),
+            BlockKind::Quote => line.push_str("> "),
+            _ => {}
+        }
+        for inline in &self.inlines {
+            match inline {
+                Inline::Link { text, target } if text != target => {
+                    line.push_str(&format!("{} <{}>", text, target));
+                }
+                inline => line.push_str(inline.text()),
+            }
+        }
+        line
+    }
+}
+
+/// Documento listo para el visor: una página de manual o un archivo Markdown
+#[derive(Debug, Clone, PartialEq)]
+pub struct Document {
+    pub title: String,
+    pub lines: Vec<DocumentLine>,
+}
+
+impl Document {
+    pub fn new(title: &str) -> Self {
+        Self {
+            title: title.to_string(),
+            lines: Vec::new(),
+        }
+    }
+
+    /// Documento de texto sin formato, como la ayuda de un comando
+    pub fn from_text(title: &str, text: &str) -> Self {
+        let mut document = Self::new(title);
+        for line in text.lines() {
+            if line.trim().is_empty() {
+                document.push(DocumentLine::blank());
+            } else {
+                document.push(DocumentLine::new(BlockKind::Code, vec![Inline::Text(line.to_string())]));
+            }
+        }
+        document
+    }
+
+    /// Agrega una línea. Las líneas en blanco al principio o repetidas se descartan.
+    pub fn push(&mut self, line: DocumentLine) {
+        let blank = line.kind == BlockKind::Blank;
+        if blank && self.lines.last().is_none_or(|last| last.kind == BlockKind::Blank) {
+            return;
+        }
+        self.lines.push(line);
+    }
+
+    /// El documento como texto sin formato
+    pub fn plain_text(&self) -> String {
+        let lines: Vec<String> = self.lines.iter().map(DocumentLine::plain).collect();
+        lines.join("\n").trim_end().to_string()
+    }
+}
+
+/// Documento abierto en el visor, con su desplazamiento y la búsqueda en curso
+#[derive(Debug, Clone)]
+pub struct DocumentView {
+    document: Document,
+    /// Primera línea visible
+    scroll: usize,
+    /// Texto de búsqueda que se está escribiendo después de `/`
+    search_input: Option<String>,
+    query: String,
+    /// Líneas que contienen la búsqueda
+    matches: Vec<usize>,
+    current: usize,
+}
+
+impl DocumentView {
+    pub fn new(document: Document) -> Self {
+        Self {
+            document,
+            scroll: 0,
+            search_input: None,
+            query: String::new(),
+            matches: Vec::new(),
+            current: 0,
+        }
+    }
+
+    pub fn document(&self) -> &Document {
+        &self.document
+    }
+
+    pub fn scroll(&self) -> usize {
+        self.scroll
+    }
+
+    /// Desplaza el documento; nunca pasa de la última línea
+    pub fn scroll_by(&mut self, delta: isize) {
+        let last = self.document.lines.len().saturating_sub(1);
+        self.scroll = self.scroll.saturating_add_signed(delta).min(last);
+    }
+
+    pub fn scroll_to(&mut self, line: usize) {
+        self.scroll = line.min(self.document.lines.len().saturating_sub(1));
+    }
+
+    /// Empieza a escribir una búsqueda
+    pub fn start_search(&mut self) {
+        self.search_input = Some(String::new());
+    }
+
+    pub fn search_input(&self) -> Option<&str> {
+        self.search_input.as_deref()
+    }
+
+    pub fn search_input_mut(&mut self) -> Option<&mut String> {
+        self.search_input.as_mut()
+    }
+
+    pub fn cancel_search(&mut self) {
+        self.search_input = None;
+    }
+
+    /// Busca lo escrito (sin distinguir mayúsculas) y va a la primera coincidencia
+    /// desde la línea visible
+    pub fn confirm_search(&mut self) {
+        let Some(query) = self.search_input.take() else {
+            return;
+        };
+        self.query = query;
+        let needle = self.query.to_lowercase();
+        self.matches = if needle.is_empty() {
+            Vec::new()
+        } else {
+            self.document
+                .lines
+                .iter()
+                .enumerate()
+                .filter(|(_, line)| line.text().to_lowercase().contains(&needle))
+                .map(|(index, _)| index)
+                .collect()
+        };
+        self.current = self.matches.iter().position(|&line| line >= self.scroll).unwrap_or(0);
+        if let Some(&line) = self.matches.get(self.current) {
+            self.scroll_to(line);
+        }
+    }
+
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    pub fn matches(&self) -> &[usize] {
+        &self.matches
+    }
+
+    /// Posición de la coincidencia actual en `matches`
+    pub fn current_match(&self) -> Option<usize> {
+        (self.current < self.matches.len()).then_some(self.current)
+    }
+
+    /// Va a la coincidencia siguiente; después de la última vuelve a la primera
+    pub fn next_match(&mut self) {
+        if !self.matches.is_empty() {
+            self.current = (self.current + 1) % self.matches.len();
+            self.scroll_to(self.matches[self.current]);
+        }
+    }
+
+    /// Va a la coincidencia anterior; antes de la primera vuelve a la última
+    pub fn previous_match(&mut self) {
+        if !self.matches.is_empty() {
+            self.current = (self.current + self.matches.len() - 1) % self.matches.len();
+            self.scroll_to(self.matches[self.current]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(kind: BlockKind, text: &str) -> DocumentLine {
+        DocumentLine::new(kind, vec![Inline::Text(text.to_string())])
+    }
+
+    fn document(texts: &[&str]) -> Document {
+        let mut document = Document::new("prueba");
+        for text in texts {
+            document.push(line(BlockKind::Paragraph, text));
+        }
+        document
+    }
+
+    #[test]
+    fn leading_and_repeated_blank_lines_are_dropped() {
+        let mut document = Document::new("prueba");
+        document.push(DocumentLine::blank());
+        document.push(line(BlockKind::Paragraph, "a"));
+        document.push(DocumentLine::blank());
+        document.push(DocumentLine::blank());
+        document.push(line(BlockKind::Paragraph, "b"));
+        document.push(DocumentLine::blank());
+        assert_eq!(document.lines.len(), 4);
+        assert_eq!(document.plain_text(), "a\n\nb");
+    }
+
+    #[test]
+    fn push_joins_plain_text_and_skips_empty_fragments() {
+        let mut line = DocumentLine::new(BlockKind::Paragraph, Vec::new());
+        line.push(Inline::Text("a".to_string()));
+        line.push(Inline::Text(String::new()));
+        line.push(Inline::Text("b".to_string()));
+        line.push(Inline::Bold("c".to_string()));
+        line.push(Inline::Text("d".to_string()));
+        assert_eq!(
+            line.inlines,
+            [Inline::Text("ab".to_string()), Inline::Bold("c".to_string()), Inline::Text("d".to_string())]
+        );
+        assert_eq!(line.text(), "abcd");
+    }
+
+    #[test]
+    fn plain_lines_show_indent_markers_quotes_and_link_targets() {
+        let item = line(BlockKind::ListItem, "uno").with_indent(2).with_marker("•".to_string());
+        assert_eq!(item.plain(), "    • uno");
+        assert_eq!(line(BlockKind::Quote, "cita").plain(), "> cita");
+        assert_eq!(DocumentLine::new(BlockKind::Rule, Vec::new()).plain(), "-".repeat(40));
+
+        let link = |text: &str, target: &str| Inline::Link { text: text.to_string(), target: target.to_string() };
+        let links = DocumentLine::new(
+            BlockKind::Paragraph,
+            vec![link("guía", "guia.md"), Inline::Text(" ".to_string()), link("https://a.org", "https://a.org")],
+        );
+        assert_eq!(links.plain(), "guía <guia.md> https://a.org");
+    }
+
+    #[test]
+    fn text_documents_keep_their_lines_as_code() {
+        let document = Document::from_text("ayuda", "Uso: ls\n\n  -a  todo\n");
+        let kinds: Vec<BlockKind> = document.lines.iter().map(|line| line.kind).collect();
+        assert_eq!(kinds, [BlockKind::Code, BlockKind::Blank, BlockKind::Code]);
+        assert_eq!(document.plain_text(), "Uso: ls\n\n  -a  todo");
+    }
+
+    #[test]
+    fn scrolling_stops_at_both_ends() {
+        let mut view = DocumentView::new(document(&["a", "b", "c"]));
+        view.scroll_by(-5);
+        assert_eq!(view.scroll(), 0);
+        view.scroll_by(10);
+        assert_eq!(view.scroll(), 2);
+        view.scroll_to(1);
+        assert_eq!(view.scroll(), 1);
+    }
+
+    #[test]
+    fn search_ignores_case_starts_at_the_visible_line_and_wraps() {
+        let mut view = DocumentView::new(document(&["Rojo", "verde", "rojizo", "azul", "ROJO"]));
+        view.scroll_to(1);
+        view.start_search();
+        view.search_input_mut().expect("hay una búsqueda").push_str("roj");
+        view.confirm_search();
+        assert_eq!(view.search_input(), None);
+        assert_eq!(view.query(), "roj");
+        assert_eq!(view.matches(), [0, 2, 4]);
+        assert_eq!((view.current_match(), view.scroll()), (Some(1), 2));
+
+        view.next_match();
+        assert_eq!((view.current_match(), view.scroll()), (Some(2), 4));
+        view.next_match();
+        assert_eq!((view.current_match(), view.scroll()), (Some(0), 0));
+        view.previous_match();
+        assert_eq!((view.current_match(), view.scroll()), (Some(2), 4));
+    }
+
+    #[test]
+    fn a_cancelled_or_empty_search_finds_nothing() {
+        let mut view = DocumentView::new(document(&["a", "b"]));
+        view.start_search();
+        view.cancel_search();
+        view.confirm_search();
+        assert_eq!(view.current_match(), None);
+
+        view.start_search();
+        view.confirm_search();
+        assert!(view.matches().is_empty());
+        view.next_match();
+        assert_eq!(view.scroll(), 0);
+    }
+}
@@ -1,4 +1,57 @@
-use std::collections::VecDeque;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::domain::ast::FunctionDefinition;
+use crate::domain::completion::{Completion, CompletionMenu};
+use crate::domain::document::{Document, DocumentView};
+use crate::domain::environment::Environment;
+use crate::domain::errors::{ArgumentError, SourceLocation, TermirustError};
+use crate::domain::jobs::SharedJobTable;
+use crate::domain::screen::Screen;
+use crate::domain::style::StyledLine;
+use crate::domain::suggestions::CommandSuggestion;
+
+/// Modo de una redirección de entrada/salida
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RedirectMode {
+    /// `>`: trunca el archivo y escribe en él
+    Truncate,
+    /// `>>`: agrega al final del archivo
+    Append,
+    /// `<`: lee la entrada desde el archivo
+    Input,
+    /// `>&`: duplica otro descriptor (por ejemplo `2>&1`)
+    Duplicate,
+}
+
+impl RedirectMode {
+    /// Descriptor al que se aplica la redirección cuando no se indica uno
+    pub fn default_fd(&self) -> u32 {
+        match self {
+            RedirectMode::Input => 0,
+            _ => 1,
+        }
+    }
+
+    /// Símbolo del operador en la línea de comandos
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            RedirectMode::Truncate => ">",
+            RedirectMode::Append => ">>",
+            RedirectMode::Input => "<",
+            RedirectMode::Duplicate => ">&",
+        }
+    }
+}
+
+/// Redirección ya resuelta de un comando
+#[derive(Debug, Clone, PartialEq)]
+pub struct Redirect {
+    pub fd: u32,
+    pub mode: RedirectMode,
+    pub target: String,
+}
 
 /// Entidad que representa un comando del sistema
 #[derive(Debug, Clone)]
@@ -6,7 +59,17 @@ pub struct Command {
     pub name: String,
     pub args: Vec<String>,
     pub output: String,
+    pub error_output: String,
+    /// Errores que detectó Termirust al ejecutarlo; lo que un programa escribe en su
+    /// salida de errores va en `error_output`
+    pub errors: Vec<TermirustError>,
     pub exit_code: i32,
+    pub redirects: Vec<Redirect>,
+    /// Asignaciones delante del comando (`NOMBRE=valor comando`), solo para su entorno
+    pub assignments: Vec<(String, String)>,
+    /// Documento que el comando pide abrir en el visor; su texto va además en `output`
+    /// para las tuberías y redirecciones
+    pub document: Option<Document>,
 }
 
 impl Command {
@@ -15,7 +78,12 @@ impl Command {
             name,
             args,
             output: String::new(),
+            error_output: String::new(),
+            errors: Vec::new(),
             exit_code: 0,
+            redirects: Vec::new(),
+            assignments: Vec::new(),
+            document: None,
         }
     }
 
@@ -24,12 +92,250 @@ impl Command {
         self
     }
 
+    pub fn with_error_output(mut self, error_output: String) -> Self {
+        self.error_output = error_output;
+        self
+    }
+
+    pub fn with_error(mut self, error: TermirustError) -> Self {
+        self.errors.push(error);
+        self
+    }
+
+    /// Adjunta un documento para el visor; su texto queda como salida del comando
+    pub fn with_document(mut self, document: Document) -> Self {
+        self.output = document.plain_text();
+        self.document = Some(document);
+        self
+    }
+
+    pub fn with_redirects(mut self, redirects: Vec<Redirect>) -> Self {
+        self.redirects = redirects;
+        self
+    }
+
+    pub fn with_assignments(mut self, assignments: Vec<(String, String)>) -> Self {
+        self.assignments = assignments;
+        self
+    }
+
+    /// Salida estándar seguida de la salida de errores, tal como se muestra en pantalla
+    pub fn combined_output(&self) -> String {
+        match (self.output.is_empty(), self.error_output.is_empty()) {
+            (_, true) => self.output.clone(),
+            (true, false) => self.error_output.clone(),
+            (false, false) => format!("{}\n{}", self.output.trim_end_matches('\n'), self.error_output),
+        }
+    }
+
     pub fn with_exit_code(mut self, exit_code: i32) -> Self {
         self.exit_code = exit_code;
         self
     }
 }
 
+/// Etapa de una tubería ya resuelta por el caso de uso
+#[derive(Debug, Clone)]
+pub enum PipelineStage {
+    /// Comando interno ya ejecutado; su salida alimenta la entrada de la siguiente etapa
+    Builtin(Command),
+    /// Proceso externo que se lanzará conectado con tuberías del sistema operativo
+    External(Command),
+}
+
+/// Paso ejecutado de una línea con `;`, `&&` o `||`: el texto de la tubería y su resultado
+#[derive(Debug, Clone)]
+pub struct CommandStep {
+    pub text: String,
+    pub command: Command,
+}
+
+impl CommandStep {
+    pub fn new(text: String, command: Command) -> Self {
+        Self { text, command }
+    }
+
+    /// Verifica si el paso terminó con éxito
+    pub fn succeeded(&self) -> bool {
+        self.command.exit_code == 0
+    }
+}
+
+/// Evento que el ejecutor envía a la interfaz mientras procesa una línea
+#[derive(Debug, Clone)]
+pub enum ExecutionEvent {
+    /// Línea de salida lista para mostrarse
+    Output(String),
+    /// Error que detectó Termirust; la interfaz lo describe y lo resalta
+    Error(TermirustError),
+    /// Línea que un programa escribió en su salida de errores; la interfaz la resalta.
+    /// Trae el archivo y la línea del script que lo lanzó, si lo lanzó uno y la línea
+    /// no continúa una salida parcial.
+    ErrorOutput { line: String, location: Option<SourceLocation> },
+    /// Un comando no se encontró; trae los nombres parecidos que existen y, si todos
+    /// tienen sugerencia, la línea corregida que se puede ejecutar
+    Suggestions {
+        suggestions: Vec<CommandSuggestion>,
+        corrected: Option<String>,
+    },
+    /// Texto que un programa escribió sin terminar la línea, como una pregunta que
+    /// espera respuesta; la siguiente salida lo continúa
+    PartialOutput(String),
+    /// Igual que `PartialOutput`, en la salida de errores. Si empieza una línea, trae
+    /// la ubicación en el script.
+    PartialErrorOutput { text: String, location: Option<SourceLocation> },
+    /// El comando `clear` pidió limpiar la pantalla
+    Clear,
+    /// `man` o `doc` pidió abrir un documento en el visor
+    Document(Document),
+    /// Un programa empezó a ejecutarse en una pseudoterminal del tamaño indicado
+    PtyStarted { cols: u16, rows: u16 },
+    /// Bytes que el programa escribió en la pseudoterminal, con sus secuencias de escape
+    PtyOutput(Vec<u8>),
+    /// El programa de la pseudoterminal terminó
+    PtyClosed,
+    /// Candidatos de la línea indicada que necesitaban la salida de un comando
+    Completion { line: String, completion: Completion },
+    /// La línea terminó; incluye el resultado de cada paso y si se debe cerrar la aplicación
+    Finished {
+        result: Result<Vec<CommandStep>, TermirustError>,
+        should_exit: bool,
+    },
+}
+
+/// Canal por el que el ejecutor transmite sus eventos a la interfaz
+pub type EventSender = std::sync::mpsc::Sender<ExecutionEvent>;
+
+/// Código de salida de un comando interrumpido con Ctrl+C (128 + SIGINT)
+pub const INTERRUPTED_EXIT_CODE: i32 = 130;
+
+/// Aviso de Ctrl+C compartido entre la interfaz y el ejecutor. Un bucle de comandos
+/// internos no tiene un proceso al que enviar SIGINT, así que las estructuras de
+/// control lo consultan en cada vuelta.
+#[derive(Debug, Clone, Default)]
+pub struct InterruptFlag(Arc<AtomicBool>);
+
+impl InterruptFlag {
+    pub fn raise(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn clear(&self) {
+        self.0.store(false, Ordering::SeqCst);
+    }
+
+    pub fn is_raised(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// Código de salida de un comando detenido con Ctrl+Z (128 + SIGTSTP en Linux)
+pub const SUSPENDED_EXIT_CODE: i32 = 148;
+
+/// Señal que la terminal puede enviar a los procesos que lanzó
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessSignal {
+    /// Ctrl+C: interrumpe el proceso (SIGINT)
+    Interrupt,
+    /// Ctrl+Z: pide al proceso que se detenga (SIGTSTP); puede ignorarla
+    Suspend,
+    /// Detiene el proceso sin que pueda evitarlo (SIGSTOP)
+    Stop,
+    /// Reanuda un proceso detenido (SIGCONT)
+    Continue,
+    /// Pide al proceso que termine (SIGTERM)
+    Terminate,
+    /// Termina el proceso sin que pueda evitarlo (SIGKILL)
+    Kill,
+    /// La terminal se cierra (SIGHUP)
+    Hangup,
+}
+
+impl ProcessSignal {
+    /// Todas las señales, en el orden de sus números habituales
+    pub const ALL: [ProcessSignal; 7] = [
+        ProcessSignal::Hangup,
+        ProcessSignal::Interrupt,
+        ProcessSignal::Kill,
+        ProcessSignal::Terminate,
+        ProcessSignal::Continue,
+        ProcessSignal::Stop,
+        ProcessSignal::Suspend,
+    ];
+
+    /// Interpreta el nombre de una señal como lo acepta `kill` (`-KILL`, `-SIGTERM`).
+    /// Los números dependen del sistema y los resuelve la infraestructura.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim_start_matches('-').to_uppercase();
+        let name = name.strip_prefix("SIG").unwrap_or(&name);
+        match name {
+            "INT" => Some(ProcessSignal::Interrupt),
+            "TSTP" => Some(ProcessSignal::Suspend),
+            "STOP" => Some(ProcessSignal::Stop),
+            "CONT" => Some(ProcessSignal::Continue),
+            "TERM" => Some(ProcessSignal::Terminate),
+            "KILL" => Some(ProcessSignal::Kill),
+            "HUP" => Some(ProcessSignal::Hangup),
+            _ => None,
+        }
+    }
+}
+
+/// Cómo se lanza una tubería
+#[derive(Debug, Clone, Default)]
+pub struct PipelineOptions {
+    /// Texto original de la tubería, usado para nombrar el trabajo
+    pub text: String,
+    /// El código de salida es el de la última etapa que falló
+    pub pipefail: bool,
+    /// No esperar a los procesos: se registran como trabajo en segundo plano
+    pub background: bool,
+    /// Lanzar los programas interactivos en una pseudoterminal
+    pub pty: bool,
+    /// Variables exportadas de la sesión; con `None` los procesos heredan el entorno
+    /// de la terminal
+    pub environment: Option<Vec<(String, String)>>,
+    /// Archivo y línea del script que ejecuta la tubería, para ubicar lo que sus
+    /// programas escriben en la salida de errores
+    pub location: Option<SourceLocation>,
+}
+
+/// Opciones de la sesión que modifican cómo se ejecutan los comandos
+#[derive(Debug, Clone, Default)]
+pub struct ShellOptions {
+    /// Un script se detiene en el primer comando que falla (`set -e`)
+    pub errexit: bool,
+    /// El código de salida de una tubería es el de la última etapa que falló
+    pub pipefail: bool,
+    /// Los comandos externos se ejecutan en una pseudoterminal emulada
+    pub pty: bool,
+}
+
+impl ShellOptions {
+    /// Activa o desactiva una opción por su nombre
+    pub fn set(&mut self, name: &str, enabled: bool) -> Result<(), TermirustError> {
+        match name {
+            "errexit" => self.errexit = enabled,
+            "pipefail" => self.pipefail = enabled,
+            "pty" => self.pty = enabled,
+            _ => {
+                return Err(TermirustError::invalid_argument("", ArgumentError::UnknownOption(name.to_string())))
+            }
+        }
+        Ok(())
+    }
+
+    /// Lista las opciones con su estado actual
+    pub fn describe(&self) -> Vec<String> {
+        let state = |enabled: bool| if enabled { "on" } else { "off" };
+        vec![
+            format!("errexit\t\t{}", state(self.errexit)),
+            format!("pipefail\t{}", state(self.pipefail)),
+            format!("pty\t\t{}", state(self.pty)),
+        ]
+    }
+}
+
 /// Entidad que representa el estado de la terminal
 #[derive(Debug, Clone)]
 pub struct TerminalState {
@@ -37,9 +343,32 @@ pub struct TerminalState {
     pub cursor_position: usize,
     pub command_history: VecDeque<String>,
     pub history_index: usize,
-    pub output_lines: Vec<String>,
+    /// Líneas de salida con los estilos de sus secuencias ANSI
+    pub output_lines: Vec<StyledLine>,
+    /// La última línea de salida quedó sin terminar (un programa escribió sin salto de
+    /// línea, como en `Continuar? [s/N] `); lo siguiente se escribe a continuación
+    pub open_line: bool,
     pub mode: TerminalMode,
     pub should_exit: bool,
+    pub options: ShellOptions,
+    /// Variables de la sesión; las exportadas forman el entorno de los procesos
+    pub environment: Environment,
+    /// Código de salida del último paso ejecutado, el valor de `$?`
+    pub last_exit_code: i32,
+    /// Funciones definidas en la sesión, por nombre
+    pub functions: BTreeMap<String, FunctionDefinition>,
+    /// Parámetros posicionales `$1..$n` de la función o el script en curso
+    pub positional: Vec<String>,
+    /// Línea que se está ejecutando en segundo plano, si la hay
+    pub running_command: Option<String>,
+    /// Trabajos en segundo plano o detenidos, compartidos con el repositorio de comandos
+    pub jobs: SharedJobTable,
+    /// Pantalla emulada del programa que se ejecuta en una pseudoterminal
+    pub screen: Option<Screen>,
+    /// Documento abierto en el visor (`man`, `doc`)
+    pub document: Option<DocumentView>,
+    /// Candidatos de Tab que se están mostrando
+    pub completion_menu: Option<CompletionMenu>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -57,8 +386,19 @@ impl Default for TerminalState {
             command_history: VecDeque::new(),
             history_index: 0,
             output_lines: Vec::new(),
+            open_line: false,
             mode: TerminalMode::Matrix,
             should_exit: false,
+            options: ShellOptions::default(),
+            environment: Environment::from_process(),
+            last_exit_code: 0,
+            functions: BTreeMap::new(),
+            positional: Vec::new(),
+            running_command: None,
+            jobs: SharedJobTable::default(),
+            screen: None,
+            document: None,
+            completion_menu: None,
         }
     }
 }
@@ -81,15 +421,34 @@ impl TerminalState {
 
     pub fn insert_char(&mut self, ch: char) {
         self.command_buffer.insert(self.cursor_position, ch);
-        self.cursor_position += 1;
+        self.cursor_position += ch.len_utf8();
     }
 
     pub fn delete_char(&mut self) {
-        if self.cursor_position > 0 {
-            self.command_buffer.remove(self.cursor_position - 1);
-            self.cursor_position -= 1;
+        if let Some(ch) = self.command_buffer[..self.cursor_position].chars().next_back() {
+            self.cursor_position -= ch.len_utf8();
+            self.command_buffer.remove(self.cursor_position);
         }
     }
+
+    /// Reemplaza lo escrito desde `start` hasta el final y deja el cursor al final
+    pub fn replace_from(&mut self, start: usize, text: &str) {
+        self.command_buffer.truncate(start);
+        self.command_buffer.push_str(text);
+        self.cursor_position = self.command_buffer.len();
+    }
+}
+
+/// Entrada de un directorio tal como la devuelve `DirectoryRepository`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirectoryEntry {
+    pub name: String,
+    /// Es un directorio o un enlace a uno
+    pub is_dir: bool,
+    /// Es un enlace simbólico; al recorrer directorios no se sigue
+    pub is_symlink: bool,
+    /// Es un archivo (o un enlace a uno) que se puede ejecutar
+    pub is_executable: bool,
 }
 
 /// Entidad que representa información de un archivo o directorio
@@ -183,14 +542,14 @@ impl LsResult {
     }
 
     pub fn sort_by_name(&mut self) {
-        self.files.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+        self.files.sort_by_key(|file| file.name.to_lowercase());
     }
 
     pub fn sort_by_size(&mut self) {
-        self.files.sort_by(|a, b| b.size.cmp(&a.size));
+        self.files.sort_by_key(|file| std::cmp::Reverse(file.size));
     }
 
     pub fn sort_by_modified(&mut self) {
-        self.files.sort_by(|a, b| b.modified.cmp(&a.modified));
+        self.files.sort_by_key(|file| std::cmp::Reverse(file.modified));
     }
 }
@@ -0,0 +1,163 @@
+use std::collections::BTreeMap;
+
+/// Variable de la sesión
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Variable {
+    pub value: String,
+    /// Se pasa en el entorno de los procesos que lanza la terminal
+    pub exported: bool,
+}
+
+/// Variables de la sesión: las heredadas del proceso, las exportadas con `export` y
+/// las variables propias de la terminal (`NOMBRE=valor`)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Environment {
+    variables: BTreeMap<String, Variable>,
+}
+
+impl Environment {
+    /// Crea el entorno con las variables del proceso, todas exportadas
+    pub fn from_process() -> Self {
+        let variables = std::env::vars()
+            .map(|(name, value)| (name, Variable { value, exported: true }))
+            .collect();
+        Self { variables }
+    }
+
+    /// Verifica si el texto es un nombre de variable válido: letras, dígitos y `_`,
+    /// sin empezar por un dígito
+    pub fn is_valid_name(name: &str) -> bool {
+        let mut chars = name.chars();
+        matches!(chars.next(), Some(ch) if ch.is_ascii_alphabetic() || ch == '_')
+            && chars.all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.variables.get(name).map(|variable| variable.value.as_str())
+    }
+
+    /// Asigna una variable; si ya estaba exportada lo sigue estando
+    pub fn set(&mut self, name: &str, value: String) {
+        match self.variables.get_mut(name) {
+            Some(variable) => variable.value = value,
+            None => {
+                let variable = Variable { value, exported: false };
+                self.variables.insert(name.to_string(), variable);
+            }
+        }
+    }
+
+    /// Marca una variable como exportada, asignándole un valor si se indica. Una
+    /// variable que no existía se exporta vacía.
+    pub fn export(&mut self, name: &str, value: Option<String>) {
+        let variable = self.variables.entry(name.to_string()).or_insert_with(|| Variable {
+            value: String::new(),
+            exported: true,
+        });
+        variable.exported = true;
+        if let Some(value) = value {
+            variable.value = value;
+        }
+    }
+
+    /// Elimina una variable. Devuelve `false` si no existía.
+    pub fn unset(&mut self, name: &str) -> bool {
+        self.variables.remove(name).is_some()
+    }
+
+    /// Todas las variables ordenadas por nombre
+    pub fn variables(&self) -> impl Iterator<Item = (&str, &Variable)> {
+        self.variables.iter().map(|(name, variable)| (name.as_str(), variable))
+    }
+
+    /// Variables exportadas, tal como las recibe un proceso hijo
+    pub fn exported(&self) -> Vec<(String, String)> {
+        self.variables()
+            .filter(|(_, variable)| variable.exported)
+            .map(|(name, variable)| (name.to_string(), variable.value.clone()))
+            .collect()
+    }
+
+    /// Escribe un valor de forma que pueda volver a leerse como una sola palabra
+    pub fn quote(value: &str) -> String {
+        let is_safe = |ch: char| ch.is_ascii_alphanumeric() || "_-./:,+@%=".contains(ch);
+        if !value.is_empty() && value.chars().all(is_safe) {
+            value.to_string()
+        } else {
+            format!("'{}'", value.replace('\'', "'\\''"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::ast::{Word, WordPart};
+    use crate::domain::expansion::Expander;
+
+    /// Expande `"${...}"` con el entorno dado
+    fn expand(environment: &Environment, body: &str) -> String {
+        let word = Word::new(vec![WordPart::DoubleQuoted(format!("${{{}}}", body))]);
+        Expander::new(environment, 0).expand_word(&word).expect("la palabra se expande").join(" ")
+    }
+
+    #[test]
+    fn set_keeps_the_export_flag() {
+        let mut environment = Environment::default();
+        environment.set("LOCAL", "1".to_string());
+        environment.export("EXPORTADA", Some("a".to_string()));
+        environment.set("EXPORTADA", "b".to_string());
+        assert_eq!(environment.get("EXPORTADA"), Some("b"));
+        assert_eq!(environment.exported(), [("EXPORTADA".to_string(), "b".to_string())]);
+    }
+
+    #[test]
+    fn export_without_value_keeps_or_creates_an_empty_value() {
+        let mut environment = Environment::default();
+        environment.set("LOCAL", "1".to_string());
+        environment.export("LOCAL", None);
+        environment.export("NUEVA", None);
+        assert_eq!(
+            environment.exported(),
+            [("LOCAL".to_string(), "1".to_string()), ("NUEVA".to_string(), String::new())]
+        );
+    }
+
+    #[test]
+    fn unset_removes_the_variable_and_its_export() {
+        let mut environment = Environment::default();
+        environment.export("X", Some("1".to_string()));
+        assert!(environment.unset("X"));
+        assert!(!environment.unset("X"));
+        assert_eq!(environment.get("X"), None);
+        assert!(environment.exported().is_empty());
+    }
+
+    #[test]
+    fn defaults_apply_to_unset_and_empty_variables() {
+        let mut environment = Environment::default();
+        environment.set("VACIA", String::new());
+        environment.set("LLENA", "valor".to_string());
+        assert_eq!(expand(&environment, "NADA:-defecto"), "defecto");
+        assert_eq!(expand(&environment, "VACIA:-defecto"), "defecto");
+        assert_eq!(expand(&environment, "LLENA:-defecto"), "valor");
+        environment.unset("LLENA");
+        assert_eq!(expand(&environment, "LLENA:-$VACIA"), "");
+        assert_eq!(expand(&environment, "LLENA"), "");
+    }
+
+    #[test]
+    fn names_start_with_a_letter_or_underscore() {
+        assert!(Environment::is_valid_name("_a1"));
+        assert!(!Environment::is_valid_name("1A"));
+        assert!(!Environment::is_valid_name("A-B"));
+        assert!(!Environment::is_valid_name(""));
+    }
+
+    #[test]
+    fn quote_leaves_safe_values_bare() {
+        assert_eq!(Environment::quote("/usr/bin:/bin"), "/usr/bin:/bin");
+        assert_eq!(Environment::quote(""), "''");
+        assert_eq!(Environment::quote("it's"), "'it'\\''s'");
+    }
+}
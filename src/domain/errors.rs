@@ -0,0 +1,260 @@
+use std::io;
+
+use crate::domain::parser::ParseError;
+
+/// Error de Termirust con el contexto necesario para describirlo. No trae texto para
+/// el usuario: lo arma la capa de presentación (`ErrorMessages::describe`).
+#[derive(Debug, Clone, PartialEq)]
+pub enum TermirustError {
+    /// No existe el archivo o directorio `path`; sin ruta, el que no existe es el
+    /// programa `command`
+    NotFound { command: String, path: Option<String> },
+    /// No hay permiso sobre `path`; sin ruta, no se puede ejecutar `command`
+    PermissionDenied { command: String, path: Option<String> },
+    /// Uso incorrecto del comando: opción desconocida, valor que falta, etc.
+    InvalidArgument { command: String, reason: ArgumentError },
+    /// Fallo de entrada/salida del sistema, con el mensaje del sistema operativo
+    Io {
+        command: String,
+        path: Option<String>,
+        kind: io::ErrorKind,
+        detail: String,
+    },
+    /// Error de sintaxis en la línea de comandos
+    Parse(ParseError),
+    /// Una palabra de la línea no se pudo expandir
+    Expansion(ExpansionError),
+    /// Alias mal definido, inexistente o que no se puede expandir
+    Alias { command: String, reason: AliasError },
+    /// Trabajo inexistente o al que no se pudo enviar una señal
+    Job { command: String, reason: JobError },
+    /// Construcción que no se admite en ese lugar, como una función en una tubería
+    Unsupported { command: String, construct: Construct },
+    /// Se superó el máximo de scripts o de llamadas a funciones anidados
+    TooDeep { command: String, nesting: Nesting },
+    /// No se sabe dónde está el directorio de configuración (falta `HOME`)
+    NoConfigDirectory,
+    /// Archivo de configuración que no se pudo interpretar
+    InvalidFile { path: String, reason: FileError },
+    /// El plugin `name` (el ejecutable `path`) falló o respondió algo que no se entiende
+    Plugin { name: String, path: String, reason: PluginError },
+    /// Un script se detuvo con `set -e` porque el comando falló
+    ScriptStopped { command: String, exit_code: i32 },
+    /// Error en una línea de un archivo: un script o el archivo de alias
+    Located { location: SourceLocation, error: Box<TermirustError> },
+}
+
+/// Convierte un error en el texto que se escribe cuando la salida de errores de un
+/// comando interno va a un archivo o a una tubería (`ls nada 2> errores.txt`). Lo
+/// aporta la capa de presentación.
+pub type ErrorFormatter = fn(&TermirustError) -> String;
+
+/// Archivo y línea en la que ocurrió un error
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceLocation {
+    pub path: String,
+    pub line: usize,
+}
+
+impl SourceLocation {
+    pub fn new(path: &str, line: usize) -> Self {
+        Self { path: path.to_string(), line }
+    }
+}
+
+/// Motivo de un `TermirustError::Expansion`
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExpansionError {
+    /// La redirección a `destino` se expandió a varias palabras o a ninguna
+    AmbiguousRedirect(String),
+    /// Sustitución de comandos sin su `)` o su `` ` `` de cierre
+    UnterminatedSubstitution(char),
+    /// `${...` sin su `}`, con lo que se alcanzó a leer
+    UnterminatedBrace(String),
+    /// `${...}` con un nombre o una operación que no se reconoce
+    BadSubstitution(String),
+    /// Sustitución de comandos donde no se pueden ejecutar comandos
+    SubstitutionUnavailable(String),
+}
+
+/// Motivo de un `TermirustError::Alias`
+#[derive(Debug, Clone, PartialEq)]
+pub enum AliasError {
+    InvalidName(String),
+    NotFound(String),
+    /// Alias que terminan reemplazándose a sí mismos, en el orden en que se expandieron
+    Loop(Vec<String>),
+    /// El valor del alias no es una línea válida
+    Syntax { name: String, error: ParseError },
+    /// El valor tiene más que un comando o una tubería
+    NotSimple(String),
+    /// Se esperaba `nombre=valor`
+    NotADefinition(String),
+    /// Línea del archivo de alias que no es `alias nombre=valor`
+    ExpectedDefinition,
+}
+
+/// Motivo de un `TermirustError::Job`
+#[derive(Debug, Clone, PartialEq)]
+pub enum JobError {
+    /// Ni `%n` ni un número de proceso
+    InvalidSpec(String),
+    /// No existe el trabajo indicado
+    NoSuchJob(String),
+    /// No hay trabajos y no se indicó ninguno
+    NoJobs,
+    /// La señal no se pudo enviar, con el mensaje del sistema operativo
+    Signal { target: String, detail: String },
+    /// El sistema no tiene señales (solo fuera de Unix)
+    #[cfg(not(unix))]
+    SignalsUnsupported,
+}
+
+/// Motivo de un `TermirustError::InvalidFile`
+#[derive(Debug, Clone, PartialEq)]
+pub enum FileError {
+    /// El contenido no es TOML o JSON válido o no tiene los campos esperados, con el
+    /// detalle del intérprete
+    Format(String),
+    /// Tipo de argumento de una especificación de completado que no existe
+    UnknownArgumentKind(String),
+}
+
+/// Motivo de un `TermirustError::Plugin`
+#[derive(Debug, Clone, PartialEq)]
+pub enum PluginError {
+    /// No se pudo ejecutar o esperar, con el mensaje del sistema operativo
+    Io(String),
+    /// No respondió en esa cantidad de segundos y se terminó
+    Timeout(u64),
+    /// Terminó con ese código sin responder, con lo que escribió en su salida de errores
+    NoResponse { code: i32, stderr: String },
+    /// Lo terminó esa señal antes de responder
+    Signal(i32),
+    /// La respuesta no es el JSON que espera el protocolo
+    InvalidResponse(String),
+}
+
+/// Construcción de `TermirustError::Unsupported`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Construct {
+    /// Una función en una tubería, con redirecciones o en segundo plano
+    FunctionInPipeline,
+    /// `source` en una tubería o en segundo plano
+    SourceInPipeline,
+    /// `&` después de una lista con `&&`, `||` o de una estructura de control
+    BackgroundList,
+}
+
+/// Límite de `TermirustError::TooDeep`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Nesting {
+    Scripts,
+    Calls,
+}
+
+/// Motivo de un `TermirustError::InvalidArgument`
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArgumentError {
+    /// Opción no declarada, escrita como en la línea (`-z`, `--zeta`)
+    UnknownOption(String),
+    /// Valor dado a una opción que no lo admite (`--all=3`)
+    UnexpectedValue(String),
+    /// Opción que necesita un valor y no lo recibió
+    MissingValue { option: String, value_name: String },
+    /// Número mal escrito para una opción
+    InvalidNumber { option: String, value: String },
+    /// Operando que el comando no acepta
+    UnexpectedOperand(String),
+    /// Se esperaba un directorio y la ruta es otra cosa
+    NotADirectory(String),
+    /// El comando no corresponde al caso de uso que lo recibió
+    WrongCommand(String),
+    /// Nombre que no sirve para una variable (`export 1A=x`)
+    InvalidIdentifier(String),
+    /// Señal que no se reconoce (`kill -FOO`)
+    UnknownSignal(String),
+    /// Argumentos que no corresponden a ninguna forma de uso; trae la forma de uso
+    Usage(String),
+}
+
+impl TermirustError {
+    pub fn invalid_argument(command: &str, reason: ArgumentError) -> Self {
+        TermirustError::InvalidArgument { command: command.to_string(), reason }
+    }
+
+    /// Fallo del sistema que no se refiere a un archivo del usuario, como crear una
+    /// tubería o esperar un proceso
+    pub fn io(command: &str, error: &io::Error) -> Self {
+        TermirustError::Io {
+            command: command.to_string(),
+            path: None,
+            kind: error.kind(),
+            detail: error.to_string(),
+        }
+    }
+
+    /// Clasifica un error del sistema: "no existe" y "sin permiso" tienen su propia
+    /// variante; el resto queda como `Io`
+    pub fn from_io(command: &str, path: Option<&str>, error: &io::Error) -> Self {
+        let command = command.to_string();
+        let path = path.map(str::to_string);
+        match error.kind() {
+            io::ErrorKind::NotFound => TermirustError::NotFound { command, path },
+            io::ErrorKind::PermissionDenied => TermirustError::PermissionDenied { command, path },
+            kind => TermirustError::Io { command, path, kind, detail: error.to_string() },
+        }
+    }
+
+    /// Usa la forma de uso de un comando: los argumentos no corresponden a ninguna
+    pub fn usage(command: &str, usage: &str) -> Self {
+        Self::invalid_argument(command, ArgumentError::Usage(usage.to_string()))
+    }
+
+    /// Ubica el error en una línea de un archivo
+    pub fn at(self, location: SourceLocation) -> Self {
+        TermirustError::Located { location, error: Box::new(self) }
+    }
+
+    /// Código de salida convencional: 127 si el programa no existe, 126 si no se
+    /// puede ejecutar, 2 para el mal uso y los errores de sintaxis, 1 para el resto
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            TermirustError::NotFound { path: None, .. } => 127,
+            TermirustError::PermissionDenied { path: None, .. } => 126,
+            // Como en `cd archivo`: la ruta existe pero no sirve, no es un mal uso
+            TermirustError::InvalidArgument { reason: ArgumentError::NotADirectory(_), .. } => 1,
+            TermirustError::InvalidArgument { .. } | TermirustError::Parse(_) => 2,
+            TermirustError::ScriptStopped { exit_code, .. } => *exit_code,
+            // Como un programa externo: 130 si se interrumpió con Ctrl+C
+            TermirustError::Plugin { reason: PluginError::Signal(signal), .. } => 128 + signal,
+            TermirustError::Located { error, .. } => error.exit_code(),
+            _ => 1,
+        }
+    }
+
+    /// Indica el comando en el que ocurrió el error, si todavía no se sabía. Los
+    /// repositorios no conocen el comando que los usa; lo agrega quien los llama.
+    pub fn in_command(mut self, name: &str) -> Self {
+        match &mut self {
+            TermirustError::NotFound { command, .. }
+            | TermirustError::PermissionDenied { command, .. }
+            | TermirustError::InvalidArgument { command, .. }
+            | TermirustError::Io { command, .. }
+            | TermirustError::Alias { command, .. }
+            | TermirustError::Job { command, .. }
+                if command.is_empty() =>
+            {
+                *command = name.to_string();
+            }
+            _ => {}
+        }
+        self
+    }
+}
+
+impl From<ParseError> for TermirustError {
+    fn from(error: ParseError) -> Self {
+        TermirustError::Parse(error)
+    }
+}
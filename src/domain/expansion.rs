@@ -0,0 +1,817 @@
+use std::cell::RefCell;
+use std::iter::Peekable;
+use std::path::PathBuf;
+use std::str::Chars;
+
+use crate::domain::ast::{SimpleCommand, Word, WordPart};
+use crate::domain::entities::{Command, Redirect};
+use crate::domain::environment::Environment;
+use crate::domain::errors::{ExpansionError, TermirustError};
+use crate::domain::glob::Glob;
+use crate::domain::parser::{scan_backquoted, scan_command_substitution};
+use crate::domain::repositories::{DirectoryRepository, UserRepository};
+
+/// Límite de palabras de un rango `{a..b}`; uno mayor se deja sin expandir
+const MAX_BRACE_ITEMS: usize = 100_000;
+
+/// Ejecuta el comando de una sustitución `$(...)` y devuelve su salida estándar
+pub type CommandRunner<'a> = dyn FnMut(&str) -> Result<String, TermirustError> + 'a;
+
+/// Palabra ya expandida. Si tiene comodines sin comillas, `pattern` es el patrón de
+/// nombres de archivo con los caracteres entrecomillados escapados.
+struct Field {
+    text: String,
+    pattern: Option<String>,
+}
+
+/// Palabras resultantes de una expansión. El texto de una variable sin comillas se
+/// divide en varias palabras por sus espacios, como en un shell POSIX.
+#[derive(Default)]
+struct Fields {
+    fields: Vec<Field>,
+    current: String,
+    pattern: String,
+    has_wildcards: bool,
+    /// La palabra actual existe aunque esté vacía (por ejemplo `""`)
+    started: bool,
+    /// Hubo un espacio de una expansión: lo siguiente empieza otra palabra
+    pending_split: bool,
+}
+
+impl Fields {
+    /// Agrega texto a la palabra actual. Los comodines de un texto sin comillas
+    /// (`unquoted`) se expanden después como nombres de archivo.
+    fn push(&mut self, text: &str, unquoted: bool) {
+        if self.pending_split {
+            self.finish_field();
+            self.pending_split = false;
+        }
+        self.current.push_str(text);
+        for ch in text.chars() {
+            let is_wildcard = matches!(ch, '*' | '?' | '[');
+            if ch == '\\' || (!unquoted && (is_wildcard || ch == ']')) {
+                self.pattern.push('\\');
+            }
+            self.has_wildcards |= unquoted && is_wildcard;
+            self.pattern.push(ch);
+        }
+        self.started = true;
+    }
+
+    fn push_split(&mut self, text: &str) {
+        for ch in text.chars() {
+            if matches!(ch, ' ' | '\t' | '\n') {
+                self.pending_split |= self.started;
+            } else {
+                self.push(ch.encode_utf8(&mut [0; 4]), true);
+            }
+        }
+    }
+
+    fn finish_field(&mut self) {
+        let pattern = std::mem::take(&mut self.pattern);
+        self.fields.push(Field {
+            text: std::mem::take(&mut self.current),
+            pattern: std::mem::take(&mut self.has_wildcards).then_some(pattern),
+        });
+    }
+
+    fn finish(mut self) -> Vec<Field> {
+        if self.started {
+            self.finish_field();
+        }
+        self.fields
+    }
+}
+
+/// Expande las palabras de un comando justo antes de ejecutarlo, en este orden:
+/// llaves (`{a,b}`, `{1..10}`), `~`, variables (`$NOMBRE`, `${NOMBRE:-valor}`, `$?`,
+/// `$1`, `$#`, `$@`) y sustitución de comandos (`$(...)`), división en palabras y
+/// nombres de archivo
+pub struct Expander<'a> {
+    environment: &'a Environment,
+    last_exit_code: i32,
+    /// Parámetros posicionales `$1..$n`
+    positional: &'a [String],
+    /// Directorio de la sesión desde el que se buscan los nombres de archivo
+    working_dir: PathBuf,
+    /// Dónde se buscan los nombres de archivo; sin él los patrones quedan tal cual
+    directories: Option<&'a dyn DirectoryRepository>,
+    /// Dónde se busca la carpeta de `~usuario`; sin él queda tal cual
+    users: Option<&'a dyn UserRepository>,
+    /// Ejecuta las sustituciones de comandos; sin él son un error
+    command_runner: Option<RefCell<&'a mut CommandRunner<'a>>>,
+}
+
+impl<'a> Expander<'a> {
+    pub fn new(environment: &'a Environment, last_exit_code: i32) -> Self {
+        Self {
+            environment,
+            last_exit_code,
+            positional: &[],
+            working_dir: std::env::current_dir().unwrap_or_default(),
+            directories: None,
+            users: None,
+            command_runner: None,
+        }
+    }
+
+    /// Usa `positional` como los parámetros `$1..$n` de una función o un script
+    pub fn with_positional(mut self, positional: &'a [String]) -> Self {
+        self.positional = positional;
+        self
+    }
+
+    /// Expande los nombres de archivo (`*.rs`) leyendo los directorios de `directories`
+    pub fn with_directories(mut self, directories: &'a dyn DirectoryRepository) -> Self {
+        self.directories = Some(directories);
+        self
+    }
+
+    /// Expande `~usuario` con la carpeta personal que indique `users`
+    pub fn with_users(mut self, users: &'a dyn UserRepository) -> Self {
+        self.users = Some(users);
+        self
+    }
+
+    /// Usa `runner` para ejecutar las sustituciones `$(...)` y `` `...` ``
+    pub fn with_command_substitution(mut self, runner: &'a mut CommandRunner<'a>) -> Self {
+        self.command_runner = Some(RefCell::new(runner));
+        self
+    }
+
+    /// Expande un comando simple: sus asignaciones iniciales, sus palabras y los
+    /// destinos de sus redirecciones
+    pub fn expand_command(&self, command: &SimpleCommand) -> Result<Command, TermirustError> {
+        let mut words = command.words.iter().peekable();
+        let mut assignments = Vec::new();
+        while let Some((name, value)) = words.peek().and_then(|word| Self::split_assignment(word)) {
+            words.next();
+            assignments.push((name, self.expand_joined(&value)?));
+        }
+
+        let mut args = Vec::new();
+        for word in words {
+            args.extend(self.expand_word(word)?);
+        }
+
+        let mut redirects = Vec::with_capacity(command.redirections.len());
+        for redirection in &command.redirections {
+            let mut targets = self.expand_word(&redirection.target)?;
+            if targets.len() != 1 {
+                return Err(TermirustError::Expansion(ExpansionError::AmbiguousRedirect(redirection.target.to_literal())));
+            }
+            redirects.push(Redirect {
+                fd: redirection.fd,
+                mode: redirection.mode,
+                target: targets.remove(0),
+            });
+        }
+
+        let name = if args.is_empty() { String::new() } else { args.remove(0) };
+        Ok(Command::new(name, args)
+            .with_redirects(redirects)
+            .with_assignments(assignments))
+    }
+
+    /// Expande una palabra. Puede producir varias palabras, o ninguna si es una
+    /// variable vacía sin comillas. Un patrón sin coincidencias queda tal cual.
+    pub fn expand_word(&self, word: &Word) -> Result<Vec<String>, TermirustError> {
+        let mut words = Vec::new();
+        for word in Self::expand_braces(word) {
+            words.extend(self.expand_fields(&word)?);
+        }
+        Ok(words)
+    }
+
+    /// Expande una palabra ya sin llaves
+    fn expand_fields(&self, word: &Word) -> Result<Vec<String>, TermirustError> {
+        let mut fields = Fields::default();
+        for (index, part) in word.parts.iter().enumerate() {
+            match part {
+                WordPart::Unquoted(text) => match self.expand_tilde(text).filter(|_| index == 0) {
+                    // La carpeta personal no se divide ni se usa como patrón
+                    Some((home, rest)) => {
+                        fields.push(&home, false);
+                        self.expand_text(rest, true, &mut fields)?;
+                    }
+                    None => self.expand_text(text, true, &mut fields)?,
+                },
+                WordPart::DoubleQuoted(text) => {
+                    fields.push("", false);
+                    self.expand_text(text, false, &mut fields)?;
+                }
+                WordPart::Quoted(text) => fields.push(text, false),
+            }
+        }
+
+        let mut words = Vec::new();
+        for field in fields.finish() {
+            let matches = match (&field.pattern, self.directories) {
+                (Some(pattern), Some(directories)) => Glob::expand(pattern, &self.working_dir, directories),
+                _ => Vec::new(),
+            };
+            if matches.is_empty() {
+                words.push(field.text);
+            } else {
+                words.extend(matches);
+            }
+        }
+        Ok(words)
+    }
+
+    /// Expande una palabra sin dividirla, como el valor de una asignación
+    fn expand_joined(&self, word: &Word) -> Result<String, TermirustError> {
+        let mut value = String::new();
+        for (index, part) in word.parts.iter().enumerate() {
+            match part {
+                WordPart::Unquoted(text) => match self.expand_tilde(text).filter(|_| index == 0) {
+                    Some((home, rest)) => {
+                        value.push_str(&home);
+                        value.push_str(&self.expand_string(rest)?);
+                    }
+                    None => value.push_str(&self.expand_string(text)?),
+                },
+                WordPart::DoubleQuoted(text) => value.push_str(&self.expand_string(text)?),
+                WordPart::Quoted(text) => value.push_str(text),
+            }
+        }
+        Ok(value)
+    }
+
+    fn expand_string(&self, text: &str) -> Result<String, TermirustError> {
+        let mut fields = Fields::default();
+        self.expand_text(text, false, &mut fields)?;
+        Ok(fields.finish().into_iter().map(|field| field.text).collect())
+    }
+
+    /// Reemplaza las variables y las sustituciones de comandos del texto. Sin
+    /// comillas, su valor se divide en palabras.
+    fn expand_text(&self, text: &str, unquoted: bool, fields: &mut Fields) -> Result<(), TermirustError> {
+        let mut chars = text.chars().peekable();
+        let mut literal = String::new();
+        while let Some(ch) = chars.next() {
+            let expansion = match ch {
+                // Entre comillas, `"$@"` da una palabra por parámetro
+                '$' if !unquoted && chars.peek() == Some(&'@') => {
+                    chars.next();
+                    if !literal.is_empty() {
+                        fields.push(&std::mem::take(&mut literal), false);
+                    }
+                    for (index, parameter) in self.positional.iter().enumerate() {
+                        fields.pending_split |= index > 0;
+                        fields.push(parameter, false);
+                    }
+                    continue;
+                }
+                '$' => self.read_parameter(&mut chars)?,
+                '`' => {
+                    let command = scan_backquoted(&mut || chars.next())
+                        .ok_or(TermirustError::Expansion(ExpansionError::UnterminatedSubstitution('`')))?;
+                    Some(self.substitute(&Self::unescape_backquoted(&command))?)
+                }
+                _ => {
+                    literal.push(ch);
+                    continue;
+                }
+            };
+            match expansion {
+                Some(value) => {
+                    if !literal.is_empty() {
+                        fields.push(&std::mem::take(&mut literal), unquoted);
+                    }
+                    if unquoted {
+                        fields.push_split(&value);
+                    } else {
+                        fields.push(&value, false);
+                    }
+                }
+                // Un `$` que no inicia una variable se deja tal cual
+                None => literal.push('$'),
+            }
+        }
+        if !literal.is_empty() {
+            fields.push(&literal, unquoted);
+        }
+        Ok(())
+    }
+
+    /// Lee la variable o la sustitución `$(...)` que sigue a un `$` y devuelve su valor
+    fn read_parameter(&self, chars: &mut Peekable<Chars>) -> Result<Option<String>, TermirustError> {
+        match chars.peek().copied() {
+            Some('(') => {
+                chars.next();
+                let command = scan_command_substitution(&mut || chars.next())
+                    .ok_or(TermirustError::Expansion(ExpansionError::UnterminatedSubstitution(')')))?;
+                self.substitute(&command).map(Some)
+            }
+            Some('?') => {
+                chars.next();
+                Ok(Some(self.last_exit_code.to_string()))
+            }
+            Some(ch @ ('#' | '@' | '*' | '0'..='9')) => {
+                chars.next();
+                Ok(Some(self.special_parameter(&ch.to_string())))
+            }
+            Some('{') => {
+                chars.next();
+                let mut body = String::new();
+                let mut depth = 0;
+                loop {
+                    match chars.next() {
+                        Some('}') if depth == 0 => break,
+                        Some(ch) => {
+                            match ch {
+                                '{' => depth += 1,
+                                '}' => depth -= 1,
+                                _ => {}
+                            }
+                            body.push(ch);
+                        }
+                        None => return Err(TermirustError::Expansion(ExpansionError::UnterminatedBrace(body))),
+                    }
+                }
+                self.expand_braced(&body).map(Some)
+            }
+            Some(ch) if ch.is_ascii_alphabetic() || ch == '_' => {
+                let mut name = String::new();
+                while let Some(ch) = chars.next_if(|ch| ch.is_ascii_alphanumeric() || *ch == '_') {
+                    name.push(ch);
+                }
+                Ok(Some(self.lookup(&name)))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn bad_substitution(body: &str) -> TermirustError {
+        TermirustError::Expansion(ExpansionError::BadSubstitution(body.to_string()))
+    }
+
+    /// Expande el contenido de `${...}`: `NOMBRE`, `?`, `10`, `#` o
+    /// `NOMBRE:-valor por defecto`
+    fn expand_braced(&self, body: &str) -> Result<String, TermirustError> {
+        let name_len = if body.starts_with(['?', '#', '@', '*']) {
+            1
+        } else if body.starts_with(|ch: char| ch.is_ascii_digit()) {
+            body.find(|ch: char| !ch.is_ascii_digit()).unwrap_or(body.len())
+        } else {
+            body.find(|ch: char| !ch.is_ascii_alphanumeric() && ch != '_').unwrap_or(body.len())
+        };
+        let (name, operation) = body.split_at(name_len);
+        let is_special = !name.is_empty() && !Environment::is_valid_name(name);
+        let is_valid = is_special || Environment::is_valid_name(name);
+        let value = match name {
+            "?" => self.last_exit_code.to_string(),
+            _ if is_special => self.special_parameter(name),
+            _ => self.lookup(name),
+        };
+
+        match operation.strip_prefix(":-") {
+            _ if !is_valid => Err(Self::bad_substitution(body)),
+            _ if operation.is_empty() => Ok(value),
+            Some(default) if value.is_empty() => self.expand_string(default),
+            Some(_) => Ok(value),
+            None => Err(Self::bad_substitution(body)),
+        }
+    }
+
+    /// Ejecuta el comando de una sustitución. Su salida reemplaza a la sustitución
+    /// sin los saltos de línea finales.
+    fn substitute(&self, command: &str) -> Result<String, TermirustError> {
+        let Some(runner) = &self.command_runner else {
+            return Err(TermirustError::Expansion(ExpansionError::SubstitutionUnavailable(command.to_string())));
+        };
+        let mut output = (runner.borrow_mut())(command)?;
+        output.truncate(output.trim_end_matches('\n').len());
+        Ok(output)
+    }
+
+    /// Dentro de comillas invertidas `\` solo escapa a `` ` ``, `\\` y `$`
+    fn unescape_backquoted(command: &str) -> String {
+        let mut unescaped = String::with_capacity(command.len());
+        let mut chars = command.chars().peekable();
+        while let Some(ch) = chars.next() {
+            match chars.peek() {
+                Some('`' | '\\' | '$') if ch == '\\' => unescaped.extend(chars.next()),
+                _ => unescaped.push(ch),
+            }
+        }
+        unescaped
+    }
+
+    /// Carpeta personal de un prefijo `~` al inicio del texto, hasta la primera `/`:
+    /// `~` es `HOME`, `~usuario` la de ese usuario, `~+` es `PWD` y `~-` es `OLDPWD`.
+    /// Devuelve la carpeta y el resto del texto, o `None` si no se puede expandir.
+    fn expand_tilde<'t>(&self, text: &'t str) -> Option<(String, &'t str)> {
+        let prefix = text.strip_prefix('~')?;
+        let end = prefix.find('/').unwrap_or(prefix.len());
+        let (user, rest) = prefix.split_at(end);
+        let home = match user {
+            "" => self.environment.get("HOME").map(str::to_string),
+            "+" => self.environment.get("PWD").map(str::to_string),
+            "-" => self.environment.get("OLDPWD").map(str::to_string),
+            user => self.users.and_then(|users| users.home_directory(user)),
+        }?;
+        Some((home, rest))
+    }
+
+    /// Expande las llaves de una palabra: `a{b,c}d` produce `abd acd` y `{1..3}`
+    /// produce `1 2 3`. Solo se consideran las llaves sin comillas; las que no forman
+    /// una lista ni un rango quedan tal cual.
+    fn expand_braces(word: &Word) -> Vec<Word> {
+        for (index, part) in word.parts.iter().enumerate() {
+            let WordPart::Unquoted(text) = part else { continue };
+            let Some((open, close, items)) = Self::find_brace_group(text) else { continue };
+            return items
+                .iter()
+                .flat_map(|item| {
+                    let mut parts = word.parts.clone();
+                    parts[index] = WordPart::Unquoted(format!("{}{}{}", &text[..open], item, &text[close + 1..]));
+                    Self::expand_braces(&Word::new(parts))
+                })
+                .collect();
+        }
+        vec![word.clone()]
+    }
+
+    /// Busca el primer grupo `{...}` expandible. Devuelve la posición de sus llaves
+    /// y las palabras que produce. Se saltan `${...}`, `$(...)` y `` `...` ``.
+    fn find_brace_group(text: &str) -> Option<(usize, usize, Vec<String>)> {
+        let chars: Vec<(usize, char)> = text.char_indices().collect();
+        let mut index = 0;
+        while index < chars.len() {
+            match chars[index].1 {
+                '$' if matches!(chars.get(index + 1), Some((_, '{' | '('))) => {
+                    index = Self::skip_group(&chars, index + 1)?;
+                }
+                '`' => {
+                    index += 1;
+                    while chars.get(index).is_some_and(|&(_, ch)| ch != '`') {
+                        index += 1;
+                    }
+                }
+                '{' => {
+                    if let Some(close) = Self::skip_group(&chars, index) {
+                        let (open, end) = (chars[index].0, chars[close].0);
+                        if let Some(items) = Self::brace_items(&text[open + 1..end]) {
+                            return Some((open, end, items));
+                        }
+                    }
+                }
+                _ => {}
+            }
+            index += 1;
+        }
+        None
+    }
+
+    /// Posición del carácter que cierra el grupo abierto en `start` (`{` o `(`)
+    fn skip_group(chars: &[(usize, char)], start: usize) -> Option<usize> {
+        let (opening, closing) = if chars[start].1 == '{' { ('{', '}') } else { ('(', ')') };
+        let mut depth = 0;
+        for (index, &(_, ch)) in chars.iter().enumerate().skip(start) {
+            if ch == opening {
+                depth += 1;
+            } else if ch == closing {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(index);
+                }
+            }
+        }
+        None
+    }
+
+    /// Palabras del interior de unas llaves: una lista separada por comas de primer
+    /// nivel o un rango `a..b[..paso]` de números o de letras
+    fn brace_items(body: &str) -> Option<Vec<String>> {
+        let mut items = Vec::new();
+        let mut depth = 0;
+        let mut start = 0;
+        for (index, ch) in body.char_indices() {
+            match ch {
+                '{' | '(' => depth += 1,
+                '}' | ')' => depth -= 1,
+                ',' if depth == 0 => {
+                    items.push(body[start..index].to_string());
+                    start = index + 1;
+                }
+                _ => {}
+            }
+        }
+        if !items.is_empty() {
+            items.push(body[start..].to_string());
+            return Some(items);
+        }
+        Self::brace_range(body)
+    }
+
+    fn brace_range(body: &str) -> Option<Vec<String>> {
+        let bounds: Vec<&str> = body.split("..").collect();
+        let (first, last, step) = match bounds[..] {
+            [first, last] => (first, last, 1),
+            [first, last, step] => (first, last, step.parse::<i64>().ok()?.unsigned_abs().max(1)),
+            _ => return None,
+        };
+
+        if let (Ok(start), Ok(end)) = (first.parse::<i64>(), last.parse::<i64>()) {
+            // `{01..10}`: con un cero a la izquierda todos los números tienen el mismo ancho
+            let padded = |bound: &str| bound.trim_start_matches('-').len() > 1 && bound.trim_start_matches('-').starts_with('0');
+            let width = if padded(first) || padded(last) { first.len().max(last.len()) } else { 0 };
+            let count = start.abs_diff(end) / step + 1;
+            if count > MAX_BRACE_ITEMS as u64 {
+                return None;
+            }
+            let direction = if start <= end { 1 } else { -1 };
+            let step = step as i64 * direction;
+            return Some((0..count as i64).map(|index| format!("{:0width$}", start + index * step)).collect());
+        }
+
+        let mut first_chars = first.chars();
+        let mut last_chars = last.chars();
+        match (first_chars.next(), first_chars.next(), last_chars.next(), last_chars.next()) {
+            (Some(start), None, Some(end), None) if start.is_ascii_alphabetic() && end.is_ascii_alphabetic() => {
+                let (start, end) = (start as u8, end as u8);
+                let letters: Vec<u8> = if start <= end {
+                    (start..=end).step_by(step as usize).collect()
+                } else {
+                    (end..=start).rev().step_by(step as usize).collect()
+                };
+                Some(letters.into_iter().map(|ch| (ch as char).to_string()).collect())
+            }
+            _ => None,
+        }
+    }
+
+    /// Valor de una variable; las que no existen se expanden a texto vacío
+    /// Valor de un parámetro posicional (`0`, `1`, `10`) o de `#`, `@` y `*`
+    fn special_parameter(&self, name: &str) -> String {
+        match name {
+            "#" => self.positional.len().to_string(),
+            "@" | "*" => self.positional.join(" "),
+            "0" => "termirust".to_string(),
+            _ => name
+                .parse::<usize>()
+                .ok()
+                .and_then(|index| self.positional.get(index.checked_sub(1)?))
+                .cloned()
+                .unwrap_or_default(),
+        }
+    }
+
+    fn lookup(&self, name: &str) -> String {
+        self.environment.get(name).unwrap_or_default().to_string()
+    }
+
+    /// Separa una asignación `NOMBRE=valor` en su nombre y la palabra del valor. El
+    /// nombre y el `=` deben estar sin comillas.
+    fn split_assignment(word: &Word) -> Option<(String, Word)> {
+        let WordPart::Unquoted(first) = word.parts.first()? else {
+            return None;
+        };
+        let (name, value) = first.split_once('=')?;
+        if !Environment::is_valid_name(name) {
+            return None;
+        }
+        let mut parts = Vec::with_capacity(word.parts.len());
+        if !value.is_empty() {
+            parts.push(WordPart::Unquoted(value.to_string()));
+        }
+        parts.extend(word.parts[1..].iter().cloned());
+        Some((name.to_string(), Word::new(parts)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::ast::Statement;
+    use crate::domain::glob::tests::MemoryDirectories;
+    use crate::domain::parser::Parser;
+
+    fn environment() -> Environment {
+        let mut environment = Environment::default();
+        environment.set("HOME", "/home/ana".to_string());
+        environment.set("PWD", "/srv/proyecto".to_string());
+        environment.set("NOMBRE", "a b".to_string());
+        environment
+    }
+
+    fn first_command(line: &str) -> SimpleCommand {
+        let command_line = Parser::parse(line).expect("la línea es válida");
+        match &command_line.items[0].first {
+            Statement::Pipeline(pipeline) => pipeline.commands[0].clone(),
+            Statement::Compound { .. } => panic!("se esperaba una tubería"),
+        }
+    }
+
+    /// Cuentas de prueba: solo existe `ana`
+    struct Users;
+
+    impl UserRepository for Users {
+        fn home_directory(&self, user: &str) -> Option<String> {
+            (user == "ana").then(|| "/home/ana".to_string())
+        }
+    }
+
+    /// Directorio de trabajo con un único archivo, `Cargo.toml`
+    fn directories() -> MemoryDirectories {
+        MemoryDirectories::new(&std::env::current_dir().unwrap_or_default(), &["Cargo.toml"])
+    }
+
+    /// Expande la línea; cada sustitución de comandos devuelve su comando entre
+    /// corchetes seguido de un salto de línea
+    fn expand(line: &str) -> Command {
+        let environment = environment();
+        let directories = directories();
+        let mut runner = |command: &str| Ok(format!("[{}]\n", command));
+        let expander = Expander::new(&environment, 0)
+            .with_directories(&directories)
+            .with_users(&Users)
+            .with_command_substitution(&mut runner);
+        expander.expand_command(&first_command(line)).expect("la línea se expande")
+    }
+
+    fn args(line: &str) -> Vec<String> {
+        expand(line).args
+    }
+
+    /// Palabra sin comillas, como la escribiría el usuario
+    fn unquoted(text: &str) -> Word {
+        Word::new(vec![WordPart::Unquoted(text.to_string())])
+    }
+
+    #[test]
+    fn tilde_expands_only_unquoted_at_the_start_of_a_word() {
+        assert_eq!(
+            args("echo ~ ~/src a~ \"~\" '~' \\~ ~+ ~+/x"),
+            ["/home/ana", "/home/ana/src", "a~", "~", "~", "~", "/srv/proyecto", "/srv/proyecto/x"]
+        );
+    }
+
+    #[test]
+    fn tilde_of_an_unknown_user_stays_literal() {
+        assert_eq!(args("echo ~usuario-que-no-existe/x"), ["~usuario-que-no-existe/x"]);
+    }
+
+    #[test]
+    fn tilde_of_a_user_is_their_home_directory() {
+        assert_eq!(args("echo ~ana ~ana/src \"~ana\""), ["/home/ana", "/home/ana/src", "~ana"]);
+    }
+
+    #[test]
+    fn tilde_of_a_user_stays_literal_without_users() {
+        let environment = environment();
+        let command = Expander::new(&environment, 0).expand_command(&first_command("echo ~ana")).unwrap();
+        assert_eq!(command.args, ["~ana"]);
+    }
+
+    #[test]
+    fn tilde_expands_at_the_start_of_an_assignment() {
+        let command = expand("DESTINO=~/bin env");
+        assert_eq!(command.assignments, [("DESTINO".to_string(), "/home/ana/bin".to_string())]);
+        assert_eq!(command.name, "env");
+    }
+
+    #[test]
+    fn brace_lists_expand_in_order_and_combine() {
+        assert_eq!(args("echo a{b,c}d"), ["abd", "acd"]);
+        assert_eq!(args("echo {a,b}{1,2}"), ["a1", "a2", "b1", "b2"]);
+        assert_eq!(args("echo {x,{y,z}}"), ["x", "y", "z"]);
+        assert_eq!(args("echo {a,}b"), ["ab", "b"]);
+    }
+
+    #[test]
+    fn brace_ranges_count_up_down_with_step_and_padding() {
+        assert_eq!(args("echo {1..3}"), ["1", "2", "3"]);
+        assert_eq!(args("echo {3..1}"), ["3", "2", "1"]);
+        assert_eq!(args("echo {1..10..4}"), ["1", "5", "9"]);
+        assert_eq!(args("echo {-1..1}"), ["-1", "0", "1"]);
+        assert_eq!(args("echo {08..10}"), ["08", "09", "10"]);
+        assert_eq!(args("echo {a..c}"), ["a", "b", "c"]);
+        assert_eq!(args("echo {c..a..2}"), ["c", "a"]);
+    }
+
+    #[test]
+    fn braces_that_are_not_a_list_or_range_stay_literal() {
+        assert_eq!(args("echo {solo} {} {1..b} {a,b"), ["{solo}", "{}", "{1..b}", "{a,b"]);
+        assert_eq!(args("echo {1..1000000}"), ["{1..1000000}"]);
+    }
+
+    #[test]
+    fn quoted_braces_stay_literal() {
+        assert_eq!(args("echo \"{a,b}\" '{1..3}' \\{a,b\\}"), ["{a,b}", "{1..3}", "{a,b}"]);
+        assert_eq!(args("echo \"x\"{a,b}"), ["xa", "xb"]);
+    }
+
+    #[test]
+    fn braces_inside_parameters_are_not_expanded() {
+        assert_eq!(args("echo ${NADA:-{a,b}}"), ["{a,b}"]);
+    }
+
+    #[test]
+    fn command_substitution_drops_trailing_newlines_and_splits_unquoted() {
+        assert_eq!(args("echo $(date)"), ["[date]"]);
+        assert_eq!(args("echo x$(pwd)y"), ["x[pwd]y"]);
+        assert_eq!(args("echo $(ls -l)"), ["[ls", "-l]"]);
+        assert_eq!(args("echo \"$(ls -l)\""), ["[ls -l]"]);
+    }
+
+    #[test]
+    fn backquotes_are_command_substitution() {
+        assert_eq!(args("echo `whoami`"), ["[whoami]"]);
+        assert_eq!(args("echo \"`ls -a`\""), ["[ls -a]"]);
+        assert_eq!(args("echo \"`echo \\`date\\``\""), ["[echo `date`]"]);
+    }
+
+    #[test]
+    fn single_quotes_keep_substitutions_and_variables_literal() {
+        assert_eq!(args("echo '$(date)' '`date`' '$NOMBRE'"), ["$(date)", "`date`", "$NOMBRE"]);
+        assert_eq!(args("echo \\$(date)"), ["$(date)"]);
+    }
+
+    #[test]
+    fn variables_split_only_without_quotes() {
+        assert_eq!(args("echo $NOMBRE \"$NOMBRE\" x${NOMBRE}y"), ["a", "b", "a b", "xa", "by"]);
+        assert_eq!(args("echo $NADA \"$NADA\""), [""]);
+    }
+
+    #[test]
+    fn braces_inside_substitutions_are_left_to_the_command() {
+        assert_eq!(args("echo $(a{1,2}) `b{1,2}`"), ["[a{1,2}]", "[b{1,2}]"]);
+    }
+
+    #[test]
+    fn substitution_without_a_runner_is_an_error() {
+        let environment = environment();
+        let expander = Expander::new(&environment, 0);
+        let error = expander.expand_word(&unquoted("$(date)")).unwrap_err();
+        assert_eq!(
+            error,
+            TermirustError::Expansion(ExpansionError::SubstitutionUnavailable("date".to_string()))
+        );
+    }
+
+    #[test]
+    fn unterminated_substitutions_are_errors() {
+        let environment = environment();
+        let expander = Expander::new(&environment, 0);
+        let unterminated = |text: &str| expander.expand_word(&unquoted(text)).unwrap_err();
+        assert_eq!(
+            unterminated("$(date"),
+            TermirustError::Expansion(ExpansionError::UnterminatedSubstitution(')'))
+        );
+        assert_eq!(
+            unterminated("`date"),
+            TermirustError::Expansion(ExpansionError::UnterminatedSubstitution('`'))
+        );
+    }
+
+    #[test]
+    fn runner_errors_are_returned() {
+        let environment = environment();
+        let mut runner = |command: &str| Err(TermirustError::NotFound { command: command.to_string(), path: None });
+        let expander = Expander::new(&environment, 0).with_command_substitution(&mut runner);
+        let error = expander.expand_word(&unquoted("$(nada)")).unwrap_err();
+        assert_eq!(error, TermirustError::NotFound { command: "nada".to_string(), path: None });
+    }
+
+    #[test]
+    fn quoted_glob_patterns_stay_literal() {
+        assert_eq!(args("echo Cargo.tom?"), ["Cargo.toml"]);
+        assert_eq!(args("echo \"Cargo.tom?\" 'Cargo.tom?' Cargo.tom\\?"), ["Cargo.tom?", "Cargo.tom?", "Cargo.tom?"]);
+        assert_eq!(args("echo \"Cargo\".tom?"), ["Cargo.toml"]);
+    }
+
+    #[test]
+    fn glob_without_matches_stays_literal() {
+        assert_eq!(args("echo *.no-existe"), ["*.no-existe"]);
+    }
+
+    #[test]
+    fn variable_values_are_not_glob_patterns_inside_quotes() {
+        let mut environment = environment();
+        environment.set("PATRON", "Cargo.tom?".to_string());
+        let directories = directories();
+        let expander = Expander::new(&environment, 0).with_directories(&directories);
+        let command = expander.expand_command(&first_command("echo $PATRON \"$PATRON\"")).unwrap();
+        assert_eq!(command.args, ["Cargo.toml", "Cargo.tom?"]);
+    }
+
+    #[test]
+    fn patterns_stay_literal_without_directories() {
+        let environment = environment();
+        let expander = Expander::new(&environment, 0);
+        let command = expander.expand_command(&first_command("echo Cargo.tom?")).unwrap();
+        assert_eq!(command.args, ["Cargo.tom?"]);
+    }
+
+    #[test]
+    fn ambiguous_redirect_is_an_error() {
+        let environment = environment();
+        let expander = Expander::new(&environment, 0);
+        let error = expander.expand_command(&first_command("echo > $NOMBRE")).unwrap_err();
+        assert_eq!(error, TermirustError::Expansion(ExpansionError::AmbiguousRedirect("$NOMBRE".to_string())));
+    }
+}
@@ -0,0 +1,420 @@
+use std::path::{Path, PathBuf};
+
+use crate::domain::repositories::DirectoryRepository;
+
+/// Elemento de un componente de un patrón de nombres de archivo
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Literal(char),
+    /// `*`: cualquier secuencia de caracteres
+    AnySequence,
+    /// `?`: un carácter cualquiera
+    AnyChar,
+    /// `[...]`: un carácter del conjunto; `[!...]` o `[^...]` lo niegan
+    Class { negated: bool, ranges: Vec<(char, char)> },
+}
+
+/// Patrón de un solo componente de ruta (sin `/`)
+#[derive(Debug, Clone, PartialEq)]
+struct ComponentPattern {
+    tokens: Vec<Token>,
+}
+
+impl ComponentPattern {
+    /// Interpreta un componente. Un carácter precedido de `\` es siempre literal.
+    fn parse(text: &str) -> Self {
+        let chars: Vec<char> = text.chars().collect();
+        let mut tokens = Vec::new();
+        let mut index = 0;
+        while index < chars.len() {
+            let token = match chars[index] {
+                '\\' if index + 1 < chars.len() => {
+                    index += 1;
+                    Token::Literal(chars[index])
+                }
+                '*' => Token::AnySequence,
+                '?' => Token::AnyChar,
+                '[' => match Self::parse_class(&chars, index + 1) {
+                    Some((token, end)) => {
+                        index = end;
+                        token
+                    }
+                    None => Token::Literal('['),
+                },
+                ch => Token::Literal(ch),
+            };
+            tokens.push(token);
+            index += 1;
+        }
+        Self { tokens }
+    }
+
+    /// Lee un conjunto que empieza después de `[`. Devuelve el token y la posición
+    /// del `]` de cierre, o `None` si no se cierra (y entonces `[` es literal).
+    fn parse_class(chars: &[char], start: usize) -> Option<(Token, usize)> {
+        let mut index = start;
+        let negated = matches!(chars.get(index), Some('!') | Some('^'));
+        if negated {
+            index += 1;
+        }
+        let mut ranges = Vec::new();
+        let mut first = true;
+        loop {
+            let mut ch = *chars.get(index)?;
+            // Un `]` al principio del conjunto es un carácter más
+            if ch == ']' && !first {
+                return Some((Token::Class { negated, ranges }, index));
+            }
+            if ch == '\\' {
+                index += 1;
+                ch = *chars.get(index)?;
+            }
+            first = false;
+            match (chars.get(index + 1), chars.get(index + 2)) {
+                (Some('-'), Some(&end)) if end != ']' => {
+                    ranges.push((ch, end));
+                    index += 3;
+                }
+                _ => {
+                    ranges.push((ch, ch));
+                    index += 1;
+                }
+            }
+        }
+    }
+
+    /// El nombre coincide con el patrón. Los nombres que empiezan con `.` solo
+    /// coinciden si el patrón también empieza con un `.` literal.
+    fn matches(&self, name: &str) -> bool {
+        if name.starts_with('.') && self.tokens.first() != Some(&Token::Literal('.')) {
+            return false;
+        }
+        let name: Vec<char> = name.chars().collect();
+        Self::match_from(&self.tokens, &name)
+    }
+
+    fn match_from(tokens: &[Token], name: &[char]) -> bool {
+        match tokens.split_first() {
+            None => name.is_empty(),
+            Some((Token::AnySequence, rest)) => (0..=name.len()).any(|skip| Self::match_from(rest, &name[skip..])),
+            Some((token, rest)) => match name.split_first() {
+                Some((&ch, remaining)) => Self::match_char(token, ch) && Self::match_from(rest, remaining),
+                None => false,
+            },
+        }
+    }
+
+    fn match_char(token: &Token, ch: char) -> bool {
+        match token {
+            Token::Literal(literal) => *literal == ch,
+            Token::AnyChar => true,
+            Token::Class { negated, ranges } => {
+                ranges.iter().any(|&(start, end)| (start..=end).contains(&ch)) != *negated
+            }
+            Token::AnySequence => true,
+        }
+    }
+
+    /// El componente no tiene comodines: es el nombre tal cual
+    fn literal(&self) -> Option<String> {
+        self.tokens
+            .iter()
+            .map(|token| match token {
+                Token::Literal(ch) => Some(*ch),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// Expansión de nombres de archivo (`*`, `?`, `[...]` y `**`) como en un shell
+pub struct Glob;
+
+impl Glob {
+    /// Devuelve las rutas que coinciden con el patrón, ordenadas. Las rutas relativas
+    /// se buscan desde `base` y se devuelven tal como se escribieron en el patrón.
+    /// `**` como componente completo abarca cero o más directorios.
+    pub fn expand(pattern: &str, base: &Path, directories: &dyn DirectoryRepository) -> Vec<String> {
+        let (root, rest) = match pattern.strip_prefix('/') {
+            Some(rest) => ("/".to_string(), rest),
+            None => (String::new(), pattern),
+        };
+        let directories_only = rest.ends_with('/');
+        let components: Vec<&str> = rest.split('/').filter(|component| !component.is_empty()).collect();
+        if components.is_empty() {
+            return Vec::new();
+        }
+
+        let resolve = |display: &str| -> PathBuf {
+            if display.is_empty() {
+                base.to_path_buf()
+            } else {
+                base.join(display)
+            }
+        };
+
+        let mut candidates = vec![root];
+        for (index, component) in components.iter().enumerate() {
+            let is_last = index + 1 == components.len();
+            let mut next = Vec::new();
+            for candidate in &candidates {
+                let directory = resolve(candidate);
+                if *component == "**" {
+                    if !is_last {
+                        next.push(candidate.clone());
+                    }
+                    Self::walk(directories, &directory, candidate, is_last, &mut next);
+                    continue;
+                }
+                let pattern = ComponentPattern::parse(component);
+                match pattern.literal() {
+                    Some(name) => {
+                        let display = Self::join(candidate, &name);
+                        if directories.exists(&resolve(&display)) {
+                            next.push(display);
+                        }
+                    }
+                    None => {
+                        let mut names: Vec<String> = directories
+                            .list(&directory)
+                            .into_iter()
+                            .map(|entry| entry.name)
+                            .filter(|name| pattern.matches(name))
+                            .collect();
+                        names.sort();
+                        next.extend(names.iter().map(|name| Self::join(candidate, name)));
+                    }
+                }
+            }
+            // Los componentes intermedios deben ser directorios
+            if !is_last || directories_only {
+                next.retain(|display| directories.is_directory(&resolve(display)));
+            }
+            candidates = next;
+        }
+
+        if directories_only {
+            for candidate in &mut candidates {
+                candidate.push('/');
+            }
+        }
+        candidates.sort();
+        candidates.dedup();
+        candidates
+    }
+
+    /// Agrega los subdirectorios (y con `include_files` también los archivos) de
+    /// `directory`, recursivamente y sin entrar en ocultos ni seguir enlaces
+    fn walk(
+        directories: &dyn DirectoryRepository,
+        directory: &Path,
+        display: &str,
+        include_files: bool,
+        found: &mut Vec<String>,
+    ) {
+        let mut entries = directories.list(directory);
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+        for entry in entries {
+            if entry.name.starts_with('.') {
+                continue;
+            }
+            let child = Self::join(display, &entry.name);
+            if entry.is_dir && !entry.is_symlink {
+                found.push(child.clone());
+                Self::walk(directories, &directory.join(&entry.name), &child, include_files, found);
+            } else if include_files {
+                found.push(child);
+            }
+        }
+    }
+
+    fn join(display: &str, name: &str) -> String {
+        if display.is_empty() {
+            name.to_string()
+        } else if display.ends_with('/') {
+            format!("{}{}", display, name)
+        } else {
+            format!("{}/{}", display, name)
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::collections::{BTreeMap, BTreeSet};
+    use std::path::Component;
+    use std::time::{Duration, SystemTime};
+
+    use super::*;
+    use crate::domain::entities::DirectoryEntry;
+
+    /// Árbol de archivos en memoria para probar sin tocar el disco
+    pub(crate) struct MemoryDirectories {
+        base: PathBuf,
+        directories: BTreeSet<PathBuf>,
+        files: BTreeSet<PathBuf>,
+        executables: BTreeSet<PathBuf>,
+        /// Cambios de cada directorio, que hacen de fecha de modificación
+        changes: BTreeMap<PathBuf, u64>,
+    }
+
+    impl MemoryDirectories {
+        /// Crea los archivos bajo `base`: los que terminan en `/` son directorios y los
+        /// que terminan en `*`, programas (como en `ls -F`)
+        pub(crate) fn new(base: &Path, paths: &[&str]) -> Self {
+            let base = Self::normalize(base);
+            let mut tree = Self {
+                directories: base.ancestors().map(Path::to_path_buf).collect(),
+                base,
+                files: BTreeSet::new(),
+                executables: BTreeSet::new(),
+                changes: BTreeMap::new(),
+            };
+            for path in paths {
+                tree.add(path);
+            }
+            tree
+        }
+
+        /// Ruta sin componentes `.`, para que `./src` y `src/` sean la misma
+        fn normalize(path: &Path) -> PathBuf {
+            path.components().filter(|component| *component != Component::CurDir).collect()
+        }
+
+        /// Agrega un archivo, lo que cambia la fecha de su directorio
+        pub(crate) fn add(&mut self, path: &str) {
+            let full = self.base.join(path.trim_end_matches(['/', '*']));
+            self.directories.extend(full.ancestors().skip(1).map(Path::to_path_buf));
+            if let Some(parent) = full.parent() {
+                *self.changes.entry(parent.to_path_buf()).or_default() += 1;
+            }
+            if path.ends_with('/') {
+                self.directories.insert(full);
+            } else {
+                if path.ends_with('*') {
+                    self.executables.insert(full.clone());
+                }
+                self.files.insert(full);
+            }
+        }
+    }
+
+    impl DirectoryRepository for MemoryDirectories {
+        fn list(&self, directory: &Path) -> Vec<DirectoryEntry> {
+            let directory = Self::normalize(directory);
+            let directory = directory.as_path();
+            let entry = |path: &PathBuf, is_dir: bool| {
+                let name = path.file_name()?.to_string_lossy().to_string();
+                (path.parent() == Some(directory)).then_some(DirectoryEntry {
+                    name,
+                    is_dir,
+                    is_symlink: false,
+                    is_executable: self.executables.contains(path),
+                })
+            };
+            let directories = self.directories.iter().filter_map(|path| entry(path, true));
+            directories.chain(self.files.iter().filter_map(|path| entry(path, false))).collect()
+        }
+
+        fn exists(&self, path: &Path) -> bool {
+            let path = Self::normalize(path);
+            self.directories.contains(&path) || self.files.contains(&path)
+        }
+
+        fn is_directory(&self, path: &Path) -> bool {
+            self.directories.contains(&Self::normalize(path))
+        }
+
+        fn modified(&self, directory: &Path) -> Option<SystemTime> {
+            let changes = self.changes.get(&Self::normalize(directory)).copied().unwrap_or_default();
+            self.is_directory(directory).then(|| SystemTime::UNIX_EPOCH + Duration::from_secs(changes))
+        }
+    }
+
+    /// Archivos de prueba bajo una carpeta ficticia
+    struct TestDir(MemoryDirectories);
+
+    impl TestDir {
+        const BASE: &'static str = "/prueba";
+
+        fn new(files: &[&str]) -> Self {
+            Self(MemoryDirectories::new(Path::new(Self::BASE), files))
+        }
+
+        fn expand(&self, pattern: &str) -> Vec<String> {
+            Glob::expand(pattern, Path::new(Self::BASE), &self.0)
+        }
+    }
+
+    const FILES: &[&str] = &["a.rs", "b.rs", "ab.txt", ".oculto.rs", "src/c.rs", "src/.git/d.rs", "vacio/"];
+
+    #[test]
+    fn wildcards_match_sorted_names() {
+        let dir = TestDir::new(FILES);
+        assert_eq!(dir.expand("*.rs"), ["a.rs", "b.rs"]);
+        assert_eq!(dir.expand("?.rs"), ["a.rs", "b.rs"]);
+        assert_eq!(dir.expand("a*"), ["a.rs", "ab.txt"]);
+        assert_eq!(dir.expand("src/*.rs"), ["src/c.rs"]);
+    }
+
+    #[test]
+    fn classes_match_ranges_and_negations() {
+        let dir = TestDir::new(FILES);
+        assert_eq!(dir.expand("[a].rs"), ["a.rs"]);
+        assert_eq!(dir.expand("[a-b].rs"), ["a.rs", "b.rs"]);
+        assert_eq!(dir.expand("[!a].rs"), ["b.rs"]);
+        assert_eq!(dir.expand("[^a].rs"), ["b.rs"]);
+    }
+
+    #[test]
+    fn hidden_files_need_a_literal_dot() {
+        let dir = TestDir::new(FILES);
+        assert!(!dir.expand("*").contains(&".oculto.rs".to_string()));
+        assert!(dir.expand("?oculto.rs").is_empty());
+        assert!(dir.expand("[.]oculto.rs").is_empty());
+        assert_eq!(dir.expand(".*.rs"), [".oculto.rs"]);
+    }
+
+    #[test]
+    fn double_star_walks_directories_without_hidden_ones() {
+        let dir = TestDir::new(FILES);
+        assert_eq!(dir.expand("**/*.rs"), ["a.rs", "b.rs", "src/c.rs"]);
+        assert_eq!(dir.expand("**"), ["a.rs", "ab.txt", "b.rs", "src", "src/c.rs", "vacio"]);
+    }
+
+    #[test]
+    fn trailing_slash_keeps_only_directories() {
+        let dir = TestDir::new(FILES);
+        assert_eq!(dir.expand("*/"), ["src/", "vacio/"]);
+    }
+
+    #[test]
+    fn pattern_without_matches_gives_nothing() {
+        let dir = TestDir::new(FILES);
+        assert!(dir.expand("*.md").is_empty());
+        assert!(dir.expand("vacio/*").is_empty());
+        assert!(dir.expand("no-existe/*.rs").is_empty());
+    }
+
+    #[test]
+    fn escaped_wildcards_are_literal() {
+        let dir = TestDir::new(&["*.rs", "a.rs", "[a].rs"]);
+        assert_eq!(dir.expand("\\*.rs"), ["*.rs"]);
+        assert_eq!(dir.expand("\\[a\\].rs"), ["[a].rs"]);
+        assert_eq!(dir.expand("[a].rs"), ["a.rs"]);
+    }
+
+    #[test]
+    fn absolute_patterns_ignore_the_base() {
+        let dir = TestDir::new(FILES);
+        assert_eq!(dir.expand("/prueba/src/*"), ["/prueba/src/c.rs"]);
+        assert_eq!(dir.expand("/prueba/src/c.rs"), ["/prueba/src/c.rs"]);
+        assert!(dir.expand("/prueba/src/x.rs").is_empty());
+    }
+
+    #[test]
+    fn unclosed_class_is_a_literal_bracket() {
+        let dir = TestDir::new(&["[a.rs", "a.rs"]);
+        assert_eq!(dir.expand("[a*"), ["[a.rs"]);
+    }
+}
@@ -0,0 +1,146 @@
+use std::fmt;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+use crate::domain::errors::JobError;
+
+/// Estado de un trabajo lanzado por la terminal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobStatus {
+    /// Sus procesos se están ejecutando
+    Running,
+    /// Fue detenido (Ctrl+Z o SIGSTOP) y espera `fg` o `bg`
+    Stopped,
+    /// Todos sus procesos terminaron con el código indicado
+    Done(i32),
+}
+
+impl JobStatus {
+    /// Texto del estado tal como lo muestra `jobs`
+    pub fn label(&self) -> String {
+        match self {
+            JobStatus::Running => "Ejecutando".to_string(),
+            JobStatus::Stopped => "Detenido".to_string(),
+            JobStatus::Done(0) => "Hecho".to_string(),
+            JobStatus::Done(code) => format!("Salida {}", code),
+        }
+    }
+}
+
+/// Extremo de escritura de la entrada estándar de una tubería
+pub type JobInput = Box<dyn Write + Send>;
+
+/// Trabajo: una tubería lanzada en su propio grupo de procesos
+pub struct Job {
+    pub id: usize,
+    /// Grupo de procesos que recibe las señales de `fg`, `bg` y `kill`
+    pub pgid: u32,
+    /// Texto original de la tubería
+    pub command: String,
+    pub status: JobStatus,
+    /// Hay un `fg` esperando a este trabajo; no se anuncia su finalización
+    pub foreground: bool,
+    /// Entrada que el trabajo leía del prompt antes de detenerse; `fg` la vuelve a
+    /// conectar para que no lea el final del archivo
+    pub input: Option<JobInput>,
+}
+
+impl fmt::Debug for Job {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Job")
+            .field("id", &self.id)
+            .field("pgid", &self.pgid)
+            .field("command", &self.command)
+            .field("status", &self.status)
+            .field("foreground", &self.foreground)
+            .field("input", &self.input.is_some())
+            .finish()
+    }
+}
+
+impl Job {
+    /// Línea que describe el trabajo, como en `jobs` o en los avisos de la terminal
+    pub fn describe(&self) -> String {
+        format!("[{}]  {:<12} {}", self.id, self.status.label(), self.command)
+    }
+}
+
+/// Tabla de trabajos en segundo plano o detenidos de la sesión
+#[derive(Debug, Default)]
+pub struct JobTable {
+    jobs: Vec<Job>,
+}
+
+/// Tabla de trabajos compartida entre el repositorio, el estado de la terminal y los
+/// hilos que esperan a los procesos
+pub type SharedJobTable = Arc<Mutex<JobTable>>;
+
+impl JobTable {
+    /// Registra un trabajo nuevo y devuelve su número
+    pub fn add(&mut self, pgid: u32, command: String, status: JobStatus) -> usize {
+        // Como en bash, los números se reutilizan cuando la tabla se vacía
+        let id = self.jobs.iter().map(|job| job.id).max().unwrap_or(0) + 1;
+        self.jobs.push(Job {
+            id,
+            pgid,
+            command,
+            status,
+            foreground: false,
+            input: None,
+        });
+        id
+    }
+
+    pub fn get(&self, id: usize) -> Option<&Job> {
+        self.jobs.iter().find(|job| job.id == id)
+    }
+
+    pub fn get_mut(&mut self, id: usize) -> Option<&mut Job> {
+        self.jobs.iter_mut().find(|job| job.id == id)
+    }
+
+    pub fn remove(&mut self, id: usize) -> Option<Job> {
+        let index = self.jobs.iter().position(|job| job.id == id)?;
+        Some(self.jobs.remove(index))
+    }
+
+    /// Trabajos en orden de creación
+    pub fn list(&self) -> &[Job] {
+        &self.jobs
+    }
+
+    /// Número de trabajos que siguen vivos (en ejecución o detenidos)
+    pub fn active_count(&self) -> usize {
+        self.jobs
+            .iter()
+            .filter(|job| !matches!(job.status, JobStatus::Done(_)))
+            .count()
+    }
+
+    /// Resuelve una especificación de trabajo: `%n`, `n`, `%+`, `%%`, `%-` o `%prefijo`.
+    /// Sin especificación se usa el trabajo más reciente.
+    pub fn resolve(&self, spec: Option<&str>) -> Result<usize, JobError> {
+        let current = || self.jobs.last().map(|job| job.id);
+        let id = match spec {
+            None | Some("%") | Some("%%") | Some("%+") => current(),
+            Some("%-") => self.jobs.iter().rev().nth(1).map(|job| job.id),
+            Some(spec) => {
+                let name = spec.strip_prefix('%').unwrap_or(spec);
+                match name.parse::<usize>() {
+                    Ok(id) => self.get(id).map(|job| job.id),
+                    Err(_) if spec.starts_with('%') => self
+                        .jobs
+                        .iter()
+                        .rev()
+                        .find(|job| job.command.starts_with(name))
+                        .map(|job| job.id),
+                    Err(_) => return Err(JobError::InvalidSpec(spec.to_string())),
+                }
+            }
+        };
+        id.ok_or_else(|| match spec {
+            Some(spec) => JobError::NoSuchJob(spec.to_string()),
+            None => JobError::NoJobs,
+        })
+    }
+}
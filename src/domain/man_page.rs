@@ -0,0 +1,556 @@
+use crate::domain::document::{BlockKind, Document, DocumentLine, Inline};
+
+/// Convierte una página de manual (troff con las macros `man`) en un documento para
+/// el visor. Es una versión reducida de groff: entiende las secciones, párrafos,
+/// listas con etiqueta, sangrías, texto sin rellenar y los cambios de fuente; el resto
+/// de las peticiones se ignora.
+pub fn parse(name: &str, source: &str) -> Document {
+    let mut parser = ManParser {
+        document: Document::new(name),
+        paragraph: None,
+        font: Font::Roman,
+        previous_font: Font::Roman,
+        fill: true,
+        indent: 0,
+        tag_pending: false,
+    };
+    for line in source.lines() {
+        parser.line(line);
+    }
+    parser.flush();
+    parser.document
+}
+
+/// Fuente del texto
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Font {
+    Roman,
+    Bold,
+    Italic,
+}
+
+impl Font {
+    fn inline(self, text: String) -> Inline {
+        match self {
+            Font::Roman => Inline::Text(text),
+            Font::Bold => Inline::Bold(text),
+            Font::Italic => Inline::Italic(text),
+        }
+    }
+
+    /// Fuente de `\fX`, `\f(XX` o `\f[XX]`; con un nombre desconocido sigue la actual
+    fn from_name(name: &str, current: Font) -> Font {
+        match name {
+            "B" | "3" | "BI" | "CB" => Font::Bold,
+            "I" | "2" | "CI" => Font::Italic,
+            "R" | "1" | "CR" | "CW" | "" => Font::Roman,
+            _ => current,
+        }
+    }
+}
+
+struct ManParser {
+    document: Document,
+    /// Párrafo en curso: en modo de relleno las líneas de texto se unen
+    paragraph: Option<DocumentLine>,
+    /// Fuente que dejaron activa los `\f` de las líneas anteriores
+    font: Font,
+    /// Fuente anterior a la actual, a la que vuelve `\fP`
+    previous_font: Font,
+    /// `false` entre `.nf` y `.fi`: cada línea se muestra tal cual
+    fill: bool,
+    /// Sangría de `.RS`; el cuerpo de las secciones va un nivel más adentro
+    indent: usize,
+    /// Después de `.TP` la siguiente línea es la etiqueta del elemento
+    tag_pending: bool,
+}
+
+impl ManParser {
+    fn line(&mut self, line: &str) {
+        if line.starts_with(".\\\"") || line.starts_with("'\\\"") || line.starts_with("\\\"") {
+            return;
+        }
+        if let Some(request) = line.strip_prefix('.').or_else(|| line.strip_prefix('\'')) {
+            self.request(request.trim_start());
+            return;
+        }
+        if !self.fill {
+            self.flush();
+            let inlines = self.inlines(line);
+            self.document.push(DocumentLine::new(BlockKind::Code, inlines).with_indent(self.body_indent()));
+        } else if line.trim().is_empty() {
+            self.flush();
+            self.document.push(DocumentLine::blank());
+        } else {
+            let inlines = self.inlines(line);
+            self.text(inlines);
+        }
+    }
+
+    /// Sangría del texto de una sección
+    fn body_indent(&self) -> usize {
+        self.indent + 1
+    }
+
+    /// Agrega texto al párrafo en curso, o lo usa como etiqueta después de `.TP`
+    fn text(&mut self, inlines: Vec<Inline>) {
+        if self.tag_pending {
+            self.tag_pending = false;
+            self.flush();
+            self.document.push(DocumentLine::new(BlockKind::Paragraph, inlines).with_indent(self.body_indent()));
+            self.paragraph = Some(DocumentLine::new(BlockKind::Paragraph, Vec::new()).with_indent(self.body_indent() + 1));
+            return;
+        }
+        let indent = self.body_indent();
+        let paragraph = self
+            .paragraph
+            .get_or_insert_with(|| DocumentLine::new(BlockKind::Paragraph, Vec::new()).with_indent(indent));
+        if !paragraph.inlines.is_empty() {
+            paragraph.push(Inline::Text(" ".to_string()));
+        }
+        for inline in inlines {
+            paragraph.push(inline);
+        }
+    }
+
+    fn flush(&mut self) {
+        if let Some(paragraph) = self.paragraph.take() {
+            if !paragraph.inlines.is_empty() {
+                self.document.push(paragraph);
+            }
+        }
+    }
+
+    /// Empieza un párrafo nuevo, con una línea en blanco antes
+    fn new_paragraph(&mut self) {
+        self.flush();
+        self.document.push(DocumentLine::blank());
+    }
+
+    fn request(&mut self, request: &str) {
+        let (name, rest) = request.split_once(char::is_whitespace).unwrap_or((request, ""));
+        let args = arguments(rest);
+        match name {
+            "TH" => {
+                if let Some(title) = args.first() {
+                    self.document.title = match args.get(1) {
+                        Some(section) => format!("{}({})", title, section),
+                        None => title.clone(),
+                    };
+                }
+            }
+            "SH" | "SS" => {
+                self.new_paragraph();
+                self.indent = 0;
+                self.tag_pending = false;
+                let level = if name == "SH" { 1 } else { 2 };
+                let inlines = self.inlines(&args.join(" "));
+                self.document.push(DocumentLine::new(BlockKind::Heading(level), inlines));
+            }
+            "PP" | "P" | "LP" | "HP" => {
+                self.new_paragraph();
+                self.tag_pending = false;
+            }
+            "TP" | "TQ" => {
+                if name == "TP" {
+                    self.new_paragraph();
+                } else {
+                    self.flush();
+                }
+                self.tag_pending = true;
+            }
+            "IP" => {
+                self.new_paragraph();
+                self.tag_pending = false;
+                let mut paragraph = DocumentLine::new(BlockKind::ListItem, Vec::new()).with_indent(self.body_indent());
+                if let Some(tag) = args.first().filter(|tag| !tag.is_empty()) {
+                    let marker: String = self.inlines(tag).iter().map(Inline::text).collect();
+                    paragraph = paragraph.with_marker(marker);
+                }
+                self.paragraph = Some(paragraph);
+            }
+            "RS" => {
+                self.flush();
+                self.indent += 1;
+            }
+            "RE" => {
+                self.flush();
+                self.indent = self.indent.saturating_sub(1);
+            }
+            "nf" | "EX" => {
+                self.flush();
+                self.fill = false;
+            }
+            "fi" | "EE" => {
+                self.fill = true;
+            }
+            "br" => self.flush(),
+            "sp" => self.new_paragraph(),
+            "B" | "I" | "SB" => {
+                let font = if name == "I" { Font::Italic } else { Font::Bold };
+                let text = args.join(" ");
+                let mut inlines = Vec::new();
+                for inline in self.inlines(&text) {
+                    inlines.push(match inline {
+                        Inline::Text(text) => font.inline(text),
+                        inline => inline,
+                    });
+                }
+                self.text(inlines);
+            }
+            // Fuentes alternadas entre argumentos, sin espacios: `.BR ls (1)`
+            "BR" | "RB" | "BI" | "IB" | "RI" | "IR" => {
+                let fonts: Vec<Font> = name
+                    .chars()
+                    .map(|ch| match ch {
+                        'B' => Font::Bold,
+                        'I' => Font::Italic,
+                        _ => Font::Roman,
+                    })
+                    .collect();
+                let mut inlines = Vec::new();
+                for (index, arg) in args.iter().enumerate() {
+                    let font = fonts[index % 2];
+                    for inline in self.inlines(arg) {
+                        inlines.push(match inline {
+                            Inline::Text(text) => font.inline(text),
+                            inline => inline,
+                        });
+                    }
+                }
+                self.text(inlines);
+            }
+            "UR" | "MT" => {
+                if let Some(target) = args.first() {
+                    let text = target.clone();
+                    self.text(vec![Inline::Link { text, target: target.clone() }]);
+                }
+            }
+            "UE" | "ME" => {
+                if let Some(punctuation) = args.first() {
+                    if let Some(paragraph) = self.paragraph.as_mut() {
+                        paragraph.push(Inline::Text(punctuation.clone()));
+                    }
+                }
+            }
+            // Sinopsis de las páginas nuevas: `.SY cmd` ... `.YS`
+            "SY" => {
+                self.new_paragraph();
+                let command = args.join(" ");
+                self.text(vec![Inline::Bold(command)]);
+            }
+            "OP" => {
+                let mut option = format!("[{}", args.first().cloned().unwrap_or_default());
+                if let Some(value) = args.get(1) {
+                    option.push(' ');
+                    option.push_str(value);
+                }
+                option.push(']');
+                self.text(vec![Inline::Text(option)]);
+            }
+            "YS" => self.flush(),
+            _ => {}
+        }
+    }
+
+    /// Interpreta los escapes de una línea de texto y la parte según la fuente
+    fn inlines(&mut self, text: &str) -> Vec<Inline> {
+        let mut line = DocumentLine::new(BlockKind::Paragraph, Vec::new());
+        let mut current = String::new();
+        let mut chars = text.chars().peekable();
+
+        while let Some(ch) = chars.next() {
+            if ch != '\\' {
+                current.push(ch);
+                continue;
+            }
+            let Some(escape) = chars.next() else {
+                break;
+            };
+            match escape {
+                'f' => {
+                    let name = escape_name(&mut chars);
+                    line.push(self.font.inline(std::mem::take(&mut current)));
+                    let font = if name == "P" { self.previous_font } else { Font::from_name(&name, self.font) };
+                    self.previous_font = self.font;
+                    self.font = font;
+                }
+                '(' | '[' => {
+                    let name = if escape == '(' {
+                        chars.by_ref().take(2).collect()
+                    } else {
+                        chars.by_ref().take_while(|ch| *ch != ']').collect::<String>()
+                    };
+                    match name.strip_prefix('u').and_then(|hex| u32::from_str_radix(hex, 16).ok()) {
+                        Some(code) => current.extend(char::from_u32(code)),
+                        None => current.push_str(special_character(&name)),
+                    }
+                }
+                '*' => {
+                    let name = escape_name(&mut chars);
+                    current.push_str(match name.as_str() {
+                        "R" => "®",
+                        "Tm" => "™",
+                        "lq" => "“",
+                        "rq" => "”",
+                        _ => "",
+                    });
+                }
+                // Tamaño de letra: `\s+1`, `\s-2`, `\s0`
+                's' => {
+                    if matches!(chars.peek(), Some('+' | '-')) {
+                        chars.next();
+                    }
+                    while chars.peek().is_some_and(char::is_ascii_digit) {
+                        chars.next();
+                    }
+                }
+                // Registros y movimientos que no se representan: `\n(xx`, `\h'...'`
+                'n' | 'k' | 'g' | 'm' | 'F' | 'Y' => {
+                    escape_name(&mut chars);
+                }
+                'h' | 'v' | 'w' | 'X' | 'o' | 'b' | 'l' | 'L' | 'D' | 'x' | 'N' | 'Z' | 'A' | 'B' | 'C' | 'R' => {
+                    if let Some(delimiter) = chars.next() {
+                        let text: String = chars.by_ref().take_while(|ch| *ch != delimiter).collect();
+                        if escape == 'C' {
+                            current.push_str(special_character(text.trim_matches('\'')));
+                        }
+                    }
+                }
+                '"' => break,
+                '-' => current.push('-'),
+                'e' | '\\' => current.push('\\'),
+                '~' | ' ' | '0' => current.push(' '),
+                '\'' => current.push('´'),
+                '`' => current.push('`'),
+                '.' => current.push('.'),
+                '&' | '|' | '^' | '%' | ':' | 'c' | ')' | '/' | ',' | '{' | '}' | 'p' | 'd' | 'u' | 'r' | 't' => {}
+                other => current.push(other),
+            }
+        }
+        line.push(self.font.inline(current));
+        line.inlines
+    }
+}
+
+/// Nombre de un escape: una letra (`\fB`), dos tras `(` (`\f(CW`) o entre corchetes
+/// (`\f[CW]`)
+fn escape_name(chars: &mut std::iter::Peekable<std::str::Chars>) -> String {
+    match chars.next() {
+        Some('(') => chars.by_ref().take(2).collect(),
+        Some('[') => chars.by_ref().take_while(|ch| *ch != ']').collect(),
+        Some(ch) => ch.to_string(),
+        None => String::new(),
+    }
+}
+
+/// Carácter especial de troff (`\(em`, `\[bu]`); los `\[uXXXX]` se resuelven aparte
+fn special_character(name: &str) -> &'static str {
+    match name {
+        "bu" => "•",
+        "em" => "—",
+        "en" => "–",
+        "hy" | "mi" | "-" => "-",
+        "aq" | "cq" | "oq" => "'",
+        "dq" => "\"",
+        "lq" | "rq" | "Lq" | "Rq" => "\"",
+        "ga" => "`",
+        "ha" => "^",
+        "ti" => "~",
+        "rs" => "\\",
+        "sl" => "/",
+        "ba" | "or" => "|",
+        "br" => "│",
+        "ul" => "_",
+        "pl" => "+",
+        "eq" => "=",
+        "mu" => "×",
+        "di" => "÷",
+        "+-" => "±",
+        "<=" => "≤",
+        ">=" => "≥",
+        "!=" => "≠",
+        "->" => "→",
+        "<-" => "←",
+        "co" => "©",
+        "rg" => "®",
+        "tm" => "™",
+        "sc" => "§",
+        "de" => "°",
+        "ps" => "¶",
+        "Fo" | "fo" => "«",
+        "Fc" | "fc" => "»",
+        "lB" => "[",
+        "rB" => "]",
+        "lC" => "{",
+        "rC" => "}",
+        "la" => "⟨",
+        "ra" => "⟩",
+        "at" => "@",
+        "sh" => "#",
+        "Do" => "$",
+        _ => "",
+    }
+}
+
+/// Argumentos de una petición: separados por espacios, con comillas dobles para los
+/// que llevan espacios (`""` dentro de comillas es una comilla)
+fn arguments(text: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut chars = text.chars().peekable();
+    loop {
+        while chars.peek().is_some_and(|ch| ch.is_whitespace()) {
+            chars.next();
+        }
+        let Some(first) = chars.next() else {
+            break;
+        };
+        let mut arg = String::new();
+        if first == '"' {
+            while let Some(ch) = chars.next() {
+                if ch == '"' {
+                    if chars.peek() == Some(&'"') {
+                        chars.next();
+                        arg.push('"');
+                    } else {
+                        break;
+                    }
+                } else {
+                    arg.push(ch);
+                }
+            }
+        } else {
+            arg.push(first);
+            while let Some(ch) = chars.peek().copied() {
+                if ch.is_whitespace() {
+                    break;
+                }
+                arg.push(ch);
+                chars.next();
+            }
+        }
+        args.push(arg);
+    }
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tipo, sangría y fragmentos de cada línea
+    fn lines(source: &str) -> Vec<(BlockKind, usize, Vec<Inline>)> {
+        parse("prueba", source).lines.into_iter().map(|line| (line.kind, line.indent, line.inlines)).collect()
+    }
+
+    fn text(text: &str) -> Inline {
+        Inline::Text(text.to_string())
+    }
+
+    fn bold(text: &str) -> Inline {
+        Inline::Bold(text.to_string())
+    }
+
+    fn italic(text: &str) -> Inline {
+        Inline::Italic(text.to_string())
+    }
+
+    #[test]
+    fn th_sets_the_title_with_its_section() {
+        assert_eq!(parse("ls", ".TH LS 1 \"2024-01-01\" \"GNU\"").title, "LS(1)");
+        assert_eq!(parse("ls", ".TH LS").title, "LS");
+        assert_eq!(parse("ls", ".SH NOMBRE").title, "ls");
+    }
+
+    #[test]
+    fn sh_starts_a_section_whose_text_is_filled_and_indented() {
+        assert_eq!(
+            lines(".\\\" comentario\n.SH \"VER TAMBIÉN\"\nuna\nlínea\n.SS Detalles\notra"),
+            [
+                (BlockKind::Heading(1), 0, vec![text("VER TAMBIÉN")]),
+                (BlockKind::Paragraph, 1, vec![text("una línea")]),
+                (BlockKind::Blank, 0, vec![]),
+                (BlockKind::Heading(2), 0, vec![text("Detalles")]),
+                (BlockKind::Paragraph, 1, vec![text("otra")]),
+            ]
+        );
+    }
+
+    #[test]
+    fn b_and_i_change_the_font_of_their_arguments() {
+        assert_eq!(
+            lines(".B ls \\-l\n.I archivo\n.BR cat (1)\n.IR nombre ,"),
+            [(
+                BlockKind::Paragraph,
+                1,
+                vec![
+                    bold("ls -l"),
+                    text(" "),
+                    italic("archivo"),
+                    text(" "),
+                    bold("cat"),
+                    text("(1) "),
+                    italic("nombre"),
+                    text(","),
+                ]
+            )]
+        );
+    }
+
+    #[test]
+    fn font_escapes_last_until_changed() {
+        assert_eq!(
+            lines("usa \\fBnegrita\\fR y \\fIcursiva\nsigue\\fP aquí \\f(CWfijo"),
+            [(
+                BlockKind::Paragraph,
+                1,
+                vec![
+                    text("usa "),
+                    bold("negrita"),
+                    text(" y "),
+                    italic("cursiva"),
+                    text(" "),
+                    italic("sigue"),
+                    text(" aquí fijo"),
+                ]
+            )]
+        );
+    }
+
+    #[test]
+    fn tp_uses_the_next_line_as_the_tag_of_an_indented_item() {
+        assert_eq!(
+            lines(".SH OPCIONES\n.TP\n\\fB\\-a\\fR, \\fB\\-\\-all\\fR\nmuestra\ntodo\n.TP\n.B \\-l\nlargo"),
+            [
+                (BlockKind::Heading(1), 0, vec![text("OPCIONES")]),
+                (BlockKind::Blank, 0, vec![]),
+                (BlockKind::Paragraph, 1, vec![bold("-a"), text(", "), bold("--all")]),
+                (BlockKind::Paragraph, 2, vec![text("muestra todo")]),
+                (BlockKind::Blank, 0, vec![]),
+                (BlockKind::Paragraph, 1, vec![bold("-l")]),
+                (BlockKind::Paragraph, 2, vec![text("largo")]),
+            ]
+        );
+    }
+
+    #[test]
+    fn ip_rs_and_unfilled_text() {
+        assert_eq!(
+            lines(".IP \\(bu 2\nuno\n.RS\n.nf\n  a   b\n.fi\ntexto\n.RE\nfin"),
+            [
+                (BlockKind::ListItem, 1, vec![text("uno")]),
+                (BlockKind::Code, 2, vec![text("  a   b")]),
+                (BlockKind::Paragraph, 2, vec![text("texto")]),
+                (BlockKind::Paragraph, 1, vec![text("fin")]),
+            ]
+        );
+        assert_eq!(parse("prueba", ".IP \\(bu 2\nuno").lines[0].marker.as_deref(), Some("•"));
+    }
+
+    #[test]
+    fn special_characters_and_arguments_with_quotes() {
+        let line = lines("a\\(em b \\[u00E9] \\(lq c\\(rq \\e");
+        assert_eq!(line, [(BlockKind::Paragraph, 1, vec![text("a— b é \" c\" \\")])]);
+        assert_eq!(arguments(r#"uno "dos tres" "con ""comillas""""#), ["uno", "dos tres", "con \"comillas\""]);
+    }
+}
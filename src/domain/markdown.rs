@@ -0,0 +1,410 @@
+use crate::domain::document::{BlockKind, Document, DocumentLine, Inline};
+
+/// Convierte un texto Markdown en un documento para el visor. Reconoce títulos,
+/// párrafos, listas, citas, bloques de código, tablas y separadores; en el texto,
+/// negrita, cursiva, código y enlaces.
+pub fn parse(title: &str, source: &str) -> Document {
+    let mut parser = MarkdownParser {
+        document: Document::new(title),
+        current: None,
+        table: Vec::new(),
+    };
+    let mut fence: Option<&str> = None;
+
+    for raw in source.lines() {
+        let trimmed = raw.trim_start();
+        if let Some(marker) = fence {
+            if trimmed.starts_with(marker) {
+                fence = None;
+            } else {
+                parser.document.push(DocumentLine::new(BlockKind::Code, vec![Inline::Text(raw.to_string())]));
+            }
+            continue;
+        }
+        if !trimmed.starts_with('|') {
+            parser.flush_table();
+        }
+        if let Some(marker) = ["```", "~~~"].into_iter().find(|marker| trimmed.starts_with(marker)) {
+            parser.flush();
+            fence = Some(marker);
+        } else if trimmed.is_empty() {
+            parser.flush();
+            parser.document.push(DocumentLine::blank());
+        } else if let Some((level, text)) = heading(trimmed) {
+            parser.flush();
+            parser.document.push(DocumentLine::blank());
+            parser.document.push(DocumentLine::new(BlockKind::Heading(level), parse_inlines(text)));
+        } else if is_rule(trimmed) {
+            parser.flush();
+            parser.document.push(DocumentLine::new(BlockKind::Rule, Vec::new()));
+        } else if trimmed.starts_with('|') {
+            parser.flush();
+            let cells = table_cells(trimmed);
+            if !cells.is_empty() {
+                parser.table.push(cells);
+            }
+        } else if let Some((marker, text)) = list_item(trimmed) {
+            parser.flush();
+            let indent = (raw.len() - trimmed.len()) / 2;
+            let line = DocumentLine::new(BlockKind::ListItem, parse_inlines(text.trim_end()))
+                .with_indent(indent)
+                .with_marker(marker);
+            parser.current = Some(line);
+        } else if let Some(text) = trimmed.strip_prefix('>') {
+            parser.continue_or_start(BlockKind::Quote, text.trim_start());
+        } else if raw.starts_with("    ") && parser.current.is_none() {
+            parser.document.push(DocumentLine::new(BlockKind::Code, vec![Inline::Text(raw[4..].to_string())]));
+        } else {
+            parser.continue_or_start(BlockKind::Paragraph, trimmed);
+        }
+        // Dos espacios al final fuerzan un salto de línea
+        if raw.ends_with("  ") {
+            parser.flush();
+        }
+    }
+    parser.flush();
+    parser.flush_table();
+    parser.document
+}
+
+struct MarkdownParser {
+    document: Document,
+    /// Párrafo, elemento de lista o cita que puede seguir en la próxima línea
+    current: Option<DocumentLine>,
+    /// Filas de la tabla en curso, ya separadas en celdas
+    table: Vec<Vec<String>>,
+}
+
+impl MarkdownParser {
+    /// Une la línea al bloque abierto si es del mismo tipo (o una lista que continúa);
+    /// si no, empieza uno nuevo
+    fn continue_or_start(&mut self, kind: BlockKind, text: &str) {
+        let text = text.trim_end();
+        match &mut self.current {
+            Some(line) if line.kind == kind || (kind == BlockKind::Paragraph && line.kind == BlockKind::ListItem) => {
+                line.push(Inline::Text(" ".to_string()));
+                for inline in parse_inlines(text) {
+                    line.push(inline);
+                }
+            }
+            _ => {
+                self.flush();
+                self.current = Some(DocumentLine::new(kind, parse_inlines(text)));
+            }
+        }
+    }
+
+    fn flush(&mut self) {
+        if let Some(line) = self.current.take() {
+            self.document.push(line);
+        }
+    }
+
+    /// Agrega la tabla en curso con las columnas alineadas
+    fn flush_table(&mut self) {
+        let rows = std::mem::take(&mut self.table);
+        let mut widths: Vec<usize> = Vec::new();
+        let rows: Vec<Vec<Vec<Inline>>> = rows
+            .iter()
+            .map(|row| row.iter().map(|cell| parse_inlines(cell)).collect())
+            .collect();
+        for row in &rows {
+            for (column, cell) in row.iter().enumerate() {
+                let width: usize = cell.iter().map(|inline| inline.text().chars().count()).sum();
+                if column == widths.len() {
+                    widths.push(width);
+                } else {
+                    widths[column] = widths[column].max(width);
+                }
+            }
+        }
+        for row in rows {
+            let mut line = DocumentLine::new(BlockKind::Table, Vec::new());
+            for (column, cell) in row.into_iter().enumerate() {
+                if column > 0 {
+                    line.push(Inline::Text(" │ ".to_string()));
+                }
+                let width: usize = cell.iter().map(|inline| inline.text().chars().count()).sum();
+                for inline in cell {
+                    line.push(inline);
+                }
+                line.push(Inline::Text(" ".repeat(widths[column] - width)));
+            }
+            self.document.push(line);
+        }
+    }
+}
+
+/// Nivel y texto de un título (`## Título`)
+fn heading(line: &str) -> Option<(u8, &str)> {
+    let level = line.chars().take_while(|ch| *ch == '#').count();
+    let text = line[level..].strip_prefix(' ')?;
+    (1..=6).contains(&level).then(|| (level as u8, text.trim().trim_end_matches('#').trim_end()))
+}
+
+/// `---`, `***` o `___`, con o sin espacios
+fn is_rule(line: &str) -> bool {
+    let marks: String = line.chars().filter(|ch| !ch.is_whitespace()).collect();
+    marks.len() >= 3 && ["-", "*", "_"].iter().any(|mark| marks.chars().all(|ch| ch.to_string() == *mark))
+}
+
+/// Viñeta (o número) y texto de un elemento de lista
+fn list_item(line: &str) -> Option<(String, &str)> {
+    for bullet in ["- ", "* ", "+ "] {
+        if let Some(text) = line.strip_prefix(bullet) {
+            return Some(("•".to_string(), text));
+        }
+    }
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    let rest = &line[digits..];
+    if digits > 0 && (rest.starts_with(". ") || rest.starts_with(") ")) {
+        return Some((line[..digits + 1].to_string(), &rest[2..]));
+    }
+    None
+}
+
+/// Celdas de una fila de tabla. La fila que separa el encabezado (`|---|:--:|`) no
+/// tiene celdas.
+fn table_cells(line: &str) -> Vec<String> {
+    let inner = line.trim().trim_start_matches('|').trim_end_matches('|');
+    if inner.chars().all(|ch| matches!(ch, '-' | ':' | '|' | ' ')) {
+        return Vec::new();
+    }
+    inner.split('|').map(|cell| cell.trim().to_string()).collect()
+}
+
+/// Formato dentro de una línea: `código`, **negrita**, *cursiva*, [enlaces](url),
+/// <url> y escapes con `\`
+pub fn parse_inlines(text: &str) -> Vec<Inline> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut line = DocumentLine::new(BlockKind::Paragraph, Vec::new());
+    let mut plain = String::new();
+    let mut index = 0;
+
+    let flush = |line: &mut DocumentLine, plain: &mut String| {
+        line.push(Inline::Text(std::mem::take(plain)));
+    };
+    let find = |from: usize, pattern: &[char]| {
+        (from..=chars.len().saturating_sub(pattern.len())).find(|&at| chars[at..].starts_with(pattern))
+    };
+    let collect = |range: std::ops::Range<usize>| chars[range].iter().collect::<String>();
+
+    while index < chars.len() {
+        let ch = chars[index];
+        let previous = index.checked_sub(1).map(|at| chars[at]);
+        match ch {
+            '\\' if index + 1 < chars.len() && chars[index + 1].is_ascii_punctuation() => {
+                plain.push(chars[index + 1]);
+                index += 2;
+                continue;
+            }
+            '`' => {
+                let ticks = chars[index..].iter().take_while(|ch| **ch == '`').count();
+                let fence = vec!['`'; ticks];
+                if let Some(end) = find(index + ticks, &fence) {
+                    flush(&mut line, &mut plain);
+                    line.push(Inline::Code(collect(index + ticks..end).trim().to_string()));
+                    index = end + ticks;
+                    continue;
+                }
+            }
+            '*' | '_' if chars.get(index + 1) == Some(&ch) => {
+                if let Some(end) = find(index + 2, &[ch, ch]).filter(|&end| end > index + 2) {
+                    flush(&mut line, &mut plain);
+                    line.push(Inline::Bold(plain_text(&collect(index + 2..end))));
+                    index = end + 2;
+                    continue;
+                }
+            }
+            // El guion bajo solo marca cursiva al borde de una palabra (no en NOMBRE_LARGO)
+            '*' | '_'
+                if chars.get(index + 1).is_some_and(|next| !next.is_whitespace())
+                    && (ch == '*' || !previous.is_some_and(char::is_alphanumeric)) =>
+            {
+                let end = (index + 1..chars.len()).find(|&at| {
+                    chars[at] == ch
+                        && !chars[at - 1].is_whitespace()
+                        && (ch == '*' || !chars.get(at + 1).is_some_and(|next| next.is_alphanumeric()))
+                });
+                if let Some(end) = end {
+                    flush(&mut line, &mut plain);
+                    line.push(Inline::Italic(plain_text(&collect(index + 1..end))));
+                    index = end + 1;
+                    continue;
+                }
+            }
+            '[' | '!' => {
+                let start = if ch == '!' { index + 1 } else { index };
+                if chars.get(start) == Some(&'[') {
+                    if let Some(close) = find(start + 1, &[']', '(']) {
+                        if let Some(end) = find(close + 2, &[')']) {
+                            flush(&mut line, &mut plain);
+                            line.push(Inline::Link {
+                                text: plain_text(&collect(start + 1..close)),
+                                target: collect(close + 2..end),
+                            });
+                            index = end + 1;
+                            continue;
+                        }
+                    }
+                }
+            }
+            '<' => {
+                if let Some(end) = find(index + 1, &['>']) {
+                    let target = collect(index + 1..end);
+                    if target.starts_with("http://") || target.starts_with("https://") || target.starts_with("mailto:") {
+                        flush(&mut line, &mut plain);
+                        line.push(Inline::Link { text: target.clone(), target });
+                        index = end + 1;
+                        continue;
+                    }
+                }
+            }
+            _ => {}
+        }
+        plain.push(ch);
+        index += 1;
+    }
+    flush(&mut line, &mut plain);
+    line.inlines
+}
+
+/// Texto sin las marcas de formato, para lo que va dentro de negrita, cursiva o enlaces
+fn plain_text(text: &str) -> String {
+    parse_inlines(text).iter().map(Inline::text).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tipo y texto sin formato de cada línea
+    fn blocks(source: &str) -> Vec<(BlockKind, String)> {
+        parse("prueba", source).lines.iter().map(|line| (line.kind, line.plain())).collect()
+    }
+
+    fn text(text: &str) -> Inline {
+        Inline::Text(text.to_string())
+    }
+
+    #[test]
+    fn headings_get_their_level_and_a_blank_line_before() {
+        assert_eq!(
+            blocks("# Título\ntexto\n## Sección ##\n####### no"),
+            [
+                (BlockKind::Heading(1), "Título".to_string()),
+                (BlockKind::Paragraph, "texto".to_string()),
+                (BlockKind::Blank, String::new()),
+                (BlockKind::Heading(2), "Sección".to_string()),
+                (BlockKind::Paragraph, "####### no".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn paragraphs_join_their_lines_until_a_blank_or_a_hard_break() {
+        assert_eq!(
+            blocks("uno\ndos  \ntres\n\ncuatro"),
+            [
+                (BlockKind::Paragraph, "uno dos".to_string()),
+                (BlockKind::Paragraph, "tres".to_string()),
+                (BlockKind::Blank, String::new()),
+                (BlockKind::Paragraph, "cuatro".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn list_items_keep_their_marker_indent_and_continuation() {
+        let document = parse("prueba", "- uno\n  * dos\n  sigue\n3. tres\n4) cuatro");
+        let items: Vec<(usize, Option<&str>, String)> = document
+            .lines
+            .iter()
+            .map(|line| (line.indent, line.marker.as_deref(), line.text()))
+            .collect();
+        assert_eq!(
+            items,
+            [
+                (0, Some("•"), "uno".to_string()),
+                (1, Some("•"), "dos sigue".to_string()),
+                (0, Some("3."), "tres".to_string()),
+                (0, Some("4)"), "cuatro".to_string()),
+            ]
+        );
+        assert!(document.lines.iter().all(|line| line.kind == BlockKind::ListItem));
+    }
+
+    #[test]
+    fn code_blocks_are_kept_as_written() {
+        assert_eq!(
+            blocks("```rust\nlet **x** = 1;\n  # no es título\n```\n\n    sangrado\ntexto"),
+            [
+                (BlockKind::Code, "let **x** = 1;".to_string()),
+                (BlockKind::Code, "  # no es título".to_string()),
+                (BlockKind::Blank, String::new()),
+                (BlockKind::Code, "sangrado".to_string()),
+                (BlockKind::Paragraph, "texto".to_string()),
+            ]
+        );
+        // Una línea sangrada dentro de un párrafo lo continúa
+        assert_eq!(blocks("texto\n    sigue"), [(BlockKind::Paragraph, "texto sigue".to_string())]);
+    }
+
+    #[test]
+    fn quotes_rules_and_tables() {
+        assert_eq!(
+            blocks("> cita\n> sigue\n\n---\n| a | bb |\n|---|:--:|\n| ccc | d |"),
+            [
+                (BlockKind::Quote, "> cita sigue".to_string()),
+                (BlockKind::Blank, String::new()),
+                (BlockKind::Rule, "-".repeat(40)),
+                (BlockKind::Table, "a   │ bb".to_string()),
+                (BlockKind::Table, "ccc │ d ".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn inline_styles() {
+        assert_eq!(
+            parse_inlines("a **b** *c* __d__ `e` ``f ` g``"),
+            [
+                text("a "),
+                Inline::Bold("b".to_string()),
+                text(" "),
+                Inline::Italic("c".to_string()),
+                text(" "),
+                Inline::Bold("d".to_string()),
+                text(" "),
+                Inline::Code("e".to_string()),
+                text(" "),
+                Inline::Code("f ` g".to_string()),
+            ]
+        );
+        // El formato dentro de negrita se descarta
+        assert_eq!(parse_inlines("**a `b`**"), [Inline::Bold("a b".to_string())]);
+    }
+
+    #[test]
+    fn marks_that_do_not_close_or_sit_inside_words_stay_as_text() {
+        assert_eq!(parse_inlines("NOMBRE_LARGO_X y 2 * 3"), [text("NOMBRE_LARGO_X y 2 * 3")]);
+        assert_eq!(parse_inlines("**abierto y `sin cerrar"), [text("**abierto y `sin cerrar")]);
+        assert_eq!(parse_inlines(r"\*no\* \[x\]"), [text("*no* [x]")]);
+    }
+
+    #[test]
+    fn links_and_autolinks() {
+        let link = |text: &str, target: &str| Inline::Link { text: text.to_string(), target: target.to_string() };
+        assert_eq!(
+            parse_inlines("ver [la **guía**](docs/guia.md) o <https://ejemplo.org> y <no>"),
+            [
+                text("ver "),
+                link("la guía", "docs/guia.md"),
+                text(" o "),
+                link("https://ejemplo.org", "https://ejemplo.org"),
+                text(" y <no>"),
+            ]
+        );
+        assert_eq!(parse_inlines("![logo](logo.png)"), [link("logo", "logo.png")]);
+    }
+}
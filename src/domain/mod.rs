@@ -1,3 +1,21 @@
+pub mod aliases;
+pub mod ast;
+pub mod builtins;
+pub mod completion;
+pub mod completion_spec;
+pub mod document;
 pub mod entities;
+pub mod environment;
+pub mod errors;
+pub mod expansion;
+pub mod glob;
+pub mod jobs;
+pub mod man_page;
+pub mod markdown;
+pub mod options;
+pub mod parser;
 pub mod repositories;
+pub mod screen;
 pub mod services;
+pub mod style;
+pub mod suggestions;
@@ -0,0 +1,383 @@
+use crate::domain::errors::{ArgumentError, TermirustError};
+
+/// Opción de un comando interno: corta (`-l`), larga (`--long`) o ambas, con o sin
+/// valor (`-n 10`, `--recent=10`)
+#[derive(Debug, Clone)]
+pub struct OptionSpec {
+    pub short: Option<char>,
+    pub long: Option<&'static str>,
+    /// Nombre del valor que recibe, como `N` en `-n N`; sin él es un interruptor
+    pub value: Option<&'static str>,
+    pub description: &'static str,
+}
+
+impl OptionSpec {
+    pub fn new(description: &'static str) -> Self {
+        Self {
+            short: None,
+            long: None,
+            value: None,
+            description,
+        }
+    }
+
+    pub fn with_short(mut self, short: char) -> Self {
+        self.short = Some(short);
+        self
+    }
+
+    pub fn with_long(mut self, long: &'static str) -> Self {
+        self.long = Some(long);
+        self
+    }
+
+    pub fn with_value(mut self, value: &'static str) -> Self {
+        self.value = Some(value);
+        self
+    }
+
+    /// Nombre con el que se consulta la opción en `ParsedArgs`: el largo o, si no
+    /// tiene, la letra corta
+    pub fn key(&self) -> String {
+        match (self.long, self.short) {
+            (Some(long), _) => long.to_string(),
+            (None, Some(short)) => short.to_string(),
+            (None, None) => String::new(),
+        }
+    }
+
+    /// Cómo se escribe la opción en los mensajes: `-n` o `--recent`
+    fn display_name(&self) -> String {
+        match (self.short, self.long) {
+            (Some(short), _) => format!("-{}", short),
+            (None, Some(long)) => format!("--{}", long),
+            (None, None) => String::new(),
+        }
+    }
+}
+
+/// Opciones reconocidas y operandos de una invocación, en el orden en que se escribieron
+#[derive(Debug, Clone, Default)]
+pub struct ParsedArgs {
+    options: Vec<(String, Option<String>)>,
+    pub operands: Vec<String>,
+    /// Se pidió `--help`: el comando debe mostrar la ayuda y no hacer nada más
+    pub help: bool,
+}
+
+impl ParsedArgs {
+    /// Indica si se usó la opción (por su nombre largo o su letra)
+    pub fn flag(&self, key: &str) -> bool {
+        self.options.iter().any(|(name, _)| name == key)
+    }
+
+    /// Último valor dado a la opción
+    #[cfg(test)]
+    pub fn value(&self, key: &str) -> Option<&str> {
+        self.options
+            .iter()
+            .rev()
+            .find(|(name, _)| name == key)
+            .and_then(|(_, value)| value.as_deref())
+    }
+
+    /// Opciones usadas, en orden, para las que se pisan entre sí (la última gana)
+    pub fn iter(&self) -> impl Iterator<Item = (&str, Option<&str>)> {
+        self.options.iter().map(|(name, value)| (name.as_str(), value.as_deref()))
+    }
+}
+
+/// Analizador declarativo de los argumentos de un comando interno. Admite opciones
+/// cortas combinadas (`-la`), largas con valor (`--recent=10` o `--recent 10`), `--`
+/// para terminar las opciones y genera el texto de `--help`.
+#[derive(Debug, Clone)]
+pub struct OptionParser {
+    command: &'static str,
+    usage: &'static str,
+    description: &'static str,
+    options: Vec<OptionSpec>,
+}
+
+impl OptionParser {
+    pub fn new(command: &'static str, usage: &'static str) -> Self {
+        Self {
+            command,
+            usage,
+            description: "",
+            options: Vec::new(),
+        }
+    }
+
+    pub fn with_description(mut self, description: &'static str) -> Self {
+        self.description = description;
+        self
+    }
+
+    pub fn with_option(mut self, option: OptionSpec) -> Self {
+        self.options.push(option);
+        self
+    }
+
+    /// Opciones declaradas, para completarlas con Tab
+    pub fn options(&self) -> &[OptionSpec] {
+        &self.options
+    }
+
+    /// Separa las opciones de los operandos. Los operandos pueden ir antes o después
+    /// de las opciones; un `-` solo es un operando.
+    pub fn parse<S: AsRef<str>>(&self, args: &[S]) -> Result<ParsedArgs, TermirustError> {
+        let mut parsed = ParsedArgs::default();
+        let mut args = args.iter().map(AsRef::as_ref);
+        while let Some(arg) = args.next() {
+            if arg == "--" {
+                parsed.operands.extend(args.by_ref().map(str::to_string));
+                break;
+            }
+            if let Some(long) = arg.strip_prefix("--") {
+                let (name, inline_value) = match long.split_once('=') {
+                    Some((name, value)) => (name, Some(value.to_string())),
+                    None => (long, None),
+                };
+                let Some(option) = self.options.iter().find(|option| option.long == Some(name)) else {
+                    if name == "help" {
+                        parsed.help = true;
+                        continue;
+                    }
+                    return Err(self.error(ArgumentError::UnknownOption(format!("--{}", name))));
+                };
+                let value = match (option.value, inline_value) {
+                    (Some(_), Some(value)) => Some(value),
+                    (Some(_), None) => Some(self.next_value(option, &mut args)?),
+                    (None, Some(_)) => return Err(self.error(ArgumentError::UnexpectedValue(format!("--{}", name)))),
+                    (None, None) => None,
+                };
+                parsed.options.push((option.key(), value));
+            } else if let Some(shorts) = arg.strip_prefix('-').filter(|shorts| !shorts.is_empty()) {
+                for (index, short) in shorts.char_indices() {
+                    let Some(option) = self.options.iter().find(|option| option.short == Some(short)) else {
+                        return Err(self.error(ArgumentError::UnknownOption(format!("-{}", short))));
+                    };
+                    if option.value.is_none() {
+                        parsed.options.push((option.key(), None));
+                        continue;
+                    }
+                    // El resto de la palabra es el valor (`-n10`); si no queda nada, la siguiente
+                    let rest = &shorts[index + short.len_utf8()..];
+                    let value = if rest.is_empty() { self.next_value(option, &mut args)? } else { rest.to_string() };
+                    parsed.options.push((option.key(), Some(value)));
+                    break;
+                }
+            } else {
+                parsed.operands.push(arg.to_string());
+            }
+        }
+        Ok(parsed)
+    }
+
+    fn next_value<'a>(
+        &self,
+        option: &OptionSpec,
+        args: &mut impl Iterator<Item = &'a str>,
+    ) -> Result<String, TermirustError> {
+        args.next().map(str::to_string).ok_or_else(|| {
+            self.error(ArgumentError::MissingValue {
+                option: option.display_name(),
+                value_name: option.value.unwrap_or_default().to_string(),
+            })
+        })
+    }
+
+    /// Error de uso de este comando
+    pub fn error(&self, reason: ArgumentError) -> TermirustError {
+        TermirustError::invalid_argument(self.command, reason)
+    }
+
+    /// Texto de `--help` generado a partir de las opciones declaradas
+    pub fn help(&self) -> String {
+        let mut lines = vec![format!("Uso: {}", self.usage)];
+        if !self.description.is_empty() {
+            lines.push(String::new());
+            lines.push(self.description.to_string());
+        }
+        lines.push(String::new());
+        lines.push("Opciones:".to_string());
+
+        let help_option = OptionSpec::new("Mostrar esta ayuda").with_long("help");
+        let has_own_help = self.options.iter().any(|option| option.long == Some("help"));
+        let options = self.options.iter().chain((!has_own_help).then_some(&help_option));
+        let rows: Vec<(String, &str)> = options
+            .map(|option| {
+                let mut names = match option.short {
+                    Some(short) => format!("-{}", short),
+                    None => "  ".to_string(),
+                };
+                if let Some(long) = option.long {
+                    names.push_str(if option.short.is_some() { ", " } else { "  " });
+                    names.push_str("--");
+                    names.push_str(long);
+                }
+                if let Some(value) = option.value {
+                    names.push(' ');
+                    names.push_str(value);
+                }
+                (names, option.description)
+            })
+            .collect();
+        let width = rows.iter().map(|(names, _)| names.chars().count()).max().unwrap_or(0);
+        for (names, description) in rows {
+            lines.push(format!("  {:<width$}  {}", names, description, width = width));
+        }
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parser() -> OptionParser {
+        OptionParser::new("history", "history [-c] [-n N] [ARCHIVO]...")
+            .with_description("Muestra el historial")
+            .with_option(OptionSpec::new("Borrar el historial").with_short('c').with_long("clear"))
+            .with_option(OptionSpec::new("Mostrar solo N entradas").with_short('n').with_long("recent").with_value("N"))
+            .with_option(OptionSpec::new("Sin numerar").with_short('q'))
+            .with_option(OptionSpec::new("Ordenar por fecha").with_long("sort").with_value("CAMPO"))
+    }
+
+    fn parse(args: &[&str]) -> ParsedArgs {
+        parser().parse(args).expect("los argumentos son válidos")
+    }
+
+    fn error(args: &[&str]) -> ArgumentError {
+        match parser().parse(args) {
+            Err(TermirustError::InvalidArgument { command, reason }) => {
+                assert_eq!(command, "history");
+                reason
+            }
+            other => panic!("se esperaba un error de argumentos: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn flags_are_found_by_long_name_or_short_letter() {
+        let parsed = parse(&["-c", "-q"]);
+        assert!(parsed.flag("clear"));
+        assert!(parsed.flag("q"));
+        assert!(!parsed.flag("recent"));
+        assert!(parse(&["--clear"]).flag("clear"));
+    }
+
+    #[test]
+    fn short_flags_can_be_combined() {
+        let parsed = parse(&["-cq"]);
+        assert!(parsed.flag("clear") && parsed.flag("q"));
+    }
+
+    #[test]
+    fn values_in_every_form() {
+        for args in [
+            &["-n", "10"][..],
+            &["-n10"],
+            &["-qn10"],
+            &["-qn", "10"],
+            &["--recent", "10"],
+            &["--recent=10"],
+        ] {
+            assert_eq!(parse(args).value("recent"), Some("10"), "{:?}", args);
+        }
+        assert_eq!(parse(&["--sort="]).value("sort"), Some(""));
+        assert_eq!(parse(&["-n", "-c"]).value("recent"), Some("-c"));
+    }
+
+    #[test]
+    fn last_value_wins_and_order_is_kept() {
+        let parsed = parse(&["-n", "1", "-c", "--recent=2"]);
+        assert_eq!(parsed.value("recent"), Some("2"));
+        let options: Vec<(&str, Option<&str>)> = parsed.iter().collect();
+        assert_eq!(options, [("recent", Some("1")), ("clear", None), ("recent", Some("2"))]);
+    }
+
+    #[test]
+    fn operands_may_surround_options() {
+        let parsed = parse(&["a", "-c", "b", "-", "c"]);
+        assert_eq!(parsed.operands, ["a", "b", "-", "c"]);
+        assert!(parsed.flag("clear"));
+    }
+
+    #[test]
+    fn double_dash_ends_the_options() {
+        let parsed = parse(&["-c", "--", "-q", "--recent=1", "--"]);
+        assert_eq!(parsed.operands, ["-q", "--recent=1", "--"]);
+        assert!(!parsed.flag("q"));
+    }
+
+    #[test]
+    fn help_is_recognized_unless_declared() {
+        let parsed = parse(&["-c", "--help", "x"]);
+        assert!(parsed.help);
+        assert_eq!(parsed.operands, ["x"]);
+        assert!(!parse(&["-c"]).help);
+    }
+
+    #[test]
+    fn unknown_options_are_errors() {
+        assert_eq!(error(&["-x"]), ArgumentError::UnknownOption("-x".to_string()));
+        assert_eq!(error(&["-cx"]), ArgumentError::UnknownOption("-x".to_string()));
+        assert_eq!(error(&["--nada"]), ArgumentError::UnknownOption("--nada".to_string()));
+        assert_eq!(error(&["--nada=1"]), ArgumentError::UnknownOption("--nada".to_string()));
+        assert_eq!(error(&["-h"]), ArgumentError::UnknownOption("-h".to_string()));
+    }
+
+    #[test]
+    fn missing_values_name_the_option() {
+        let missing = ArgumentError::MissingValue {
+            option: "-n".to_string(),
+            value_name: "N".to_string(),
+        };
+        assert_eq!(error(&["-n"]), missing);
+        assert_eq!(error(&["--recent"]), missing);
+        assert_eq!(
+            error(&["--sort"]),
+            ArgumentError::MissingValue {
+                option: "--sort".to_string(),
+                value_name: "CAMPO".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn switches_reject_inline_values() {
+        assert_eq!(error(&["--clear=1"]), ArgumentError::UnexpectedValue("--clear".to_string()));
+    }
+
+    #[test]
+    fn help_text_lists_the_options_aligned() {
+        let help = parser().help();
+        let lines: Vec<&str> = help.lines().collect();
+        assert_eq!(
+            lines,
+            [
+                "Uso: history [-c] [-n N] [ARCHIVO]...",
+                "",
+                "Muestra el historial",
+                "",
+                "Opciones:",
+                "  -c, --clear       Borrar el historial",
+                "  -n, --recent N    Mostrar solo N entradas",
+                "  -q                Sin numerar",
+                "      --sort CAMPO  Ordenar por fecha",
+                "      --help        Mostrar esta ayuda",
+            ]
+        );
+    }
+
+    #[test]
+    fn own_help_option_replaces_the_generated_one() {
+        let parser = OptionParser::new("man", "man TEMA")
+            .with_option(OptionSpec::new("Ayuda propia").with_long("help"));
+        let help = parser.help();
+        assert!(help.contains("--help  Ayuda propia"));
+        assert!(!help.contains("Mostrar esta ayuda"));
+        assert!(!parser.parse(&["--help"]).expect("es válido").help);
+    }
+}
@@ -0,0 +1,1024 @@
+use std::fmt;
+use std::iter::Peekable;
+use std::ops::Range;
+use std::str::CharIndices;
+
+use crate::domain::ast::{
+    AndOrList, CommandLine, CompoundCommand, Connector, ForClause, FunctionDefinition, IfClause, Pipeline, Redirection,
+    SimpleCommand, Statement, WhileClause, Word, WordPart,
+};
+use crate::domain::environment::Environment;
+use crate::domain::entities::RedirectMode;
+
+/// Error producido al analizar una línea de comandos
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseError {
+    /// Comilla abierta en la posición indicada que nunca se cierra
+    UnterminatedQuote { quote: char, position: usize },
+    /// Barra invertida al final de la línea sin carácter que escapar
+    DanglingEscape { position: usize },
+    /// Expansión `${` o `$(` abierta en la posición indicada que nunca se cierra
+    UnterminatedExpansion { position: usize, closing: char },
+    /// Operador en una posición donde se esperaba un comando
+    UnexpectedOperator(Operator),
+    /// La línea termina justo después de un operador que requiere otro comando
+    MissingCommand(Operator),
+    /// Redirección sin archivo o descriptor de destino
+    MissingRedirectTarget(Operator),
+    /// Redirección cuyo destino no es válido (por ejemplo `2>&x`)
+    InvalidRedirectTarget { operator: Operator, target: String },
+    /// Palabra fuera de lugar, como un `fi` sin `if` o un nombre de variable inválido
+    UnexpectedWord(String),
+    /// La entrada termina sin la palabra que cierra una estructura (`fi`, `done`, `}`)
+    MissingKeyword { expected: &'static str, opened: &'static str, position: usize },
+}
+
+impl ParseError {
+    /// La línea está incompleta y podría continuar en la siguiente, como un `if`
+    /// sin su `fi` o una comilla sin cerrar
+    pub fn is_incomplete(&self) -> bool {
+        matches!(
+            self,
+            ParseError::UnterminatedQuote { .. }
+                | ParseError::UnterminatedExpansion { .. }
+                | ParseError::MissingCommand(_)
+                | ParseError::MissingKeyword { .. }
+        )
+    }
+}
+
+/// Operadores de control reconocidos fuera de comillas
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operator {
+    /// `|`: conecta la salida de un comando con la entrada del siguiente
+    Pipe,
+    /// `;`: ejecuta los comandos uno detrás de otro
+    Semicolon,
+    /// Salto de línea: separa comandos igual que `;`
+    Newline,
+    /// `&&`: ejecuta el siguiente comando solo si el anterior tuvo éxito
+    And,
+    /// `||`: ejecuta el siguiente comando solo si el anterior falló
+    Or,
+    /// `&`: lanza la lista anterior en segundo plano
+    Background,
+    /// `>`, `>>`, `<` o `>&`, con un descriptor explícito opcional (`2>`)
+    Redirect { fd: Option<u32>, mode: RedirectMode },
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Operator::Pipe => write!(f, "|"),
+            Operator::Semicolon => write!(f, ";"),
+            Operator::Newline => write!(f, "salto de línea"),
+            Operator::And => write!(f, "&&"),
+            Operator::Or => write!(f, "||"),
+            Operator::Background => write!(f, "&"),
+            Operator::Redirect { fd: Some(fd), mode } => write!(f, "{}{}", fd, mode.symbol()),
+            Operator::Redirect { fd: None, mode } => write!(f, "{}", mode.symbol()),
+        }
+    }
+}
+
+/// Token producido por el analizador léxico
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+    Word(Word),
+    Operator(Operator),
+}
+
+/// Token junto con el rango de bytes que ocupa en la entrada
+pub type SpannedToken = (Token, Range<usize>);
+
+/// Analizador léxico que respeta comillas y escapes
+pub struct Lexer<'a> {
+    chars: Peekable<CharIndices<'a>>,
+    len: usize,
+}
+
+impl<'a> Lexer<'a> {
+    /// Crea un analizador léxico para la entrada indicada
+    pub fn new(input: &'a str) -> Self {
+        Self {
+            chars: input.char_indices().peekable(),
+            len: input.len(),
+        }
+    }
+
+    /// Divide la entrada completa en tokens con su posición
+    pub fn tokenize(mut self) -> Result<Vec<SpannedToken>, ParseError> {
+        let mut tokens = Vec::new();
+        loop {
+            self.skip_whitespace();
+            self.skip_comment();
+            let start = self.position();
+            match self.next_token()? {
+                Some(token) => tokens.push((token, start..self.position())),
+                None => break,
+            }
+        }
+        Ok(tokens)
+    }
+
+    /// Posición en bytes del siguiente carácter por leer
+    fn position(&mut self) -> usize {
+        self.chars.peek().map_or(self.len, |&(position, _)| position)
+    }
+
+    fn next_token(&mut self) -> Result<Option<Token>, ParseError> {
+        match self.chars.peek().copied() {
+            None => Ok(None),
+            Some((_, '|')) => {
+                self.chars.next();
+                let operator = if self.next_if_char('|') { Operator::Or } else { Operator::Pipe };
+                Ok(Some(Token::Operator(operator)))
+            }
+            Some((_, ';')) => {
+                self.chars.next();
+                Ok(Some(Token::Operator(Operator::Semicolon)))
+            }
+            Some((_, '\n')) => {
+                self.chars.next();
+                Ok(Some(Token::Operator(Operator::Newline)))
+            }
+            Some((_, '&')) => {
+                self.chars.next();
+                let operator = if self.next_if_char('&') { Operator::And } else { Operator::Background };
+                Ok(Some(Token::Operator(operator)))
+            }
+            Some((_, '<')) | Some((_, '>')) => Ok(Some(self.read_redirect(None))),
+            Some(_) => match self.read_io_number() {
+                Some(fd) => Ok(Some(self.read_redirect(Some(fd)))),
+                None => self.read_word().map(|word| Some(Token::Word(word))),
+            },
+        }
+    }
+
+    /// Consume el siguiente carácter solo si coincide con el indicado
+    fn next_if_char(&mut self, expected: char) -> bool {
+        self.chars.next_if(|&(_, ch)| ch == expected).is_some()
+    }
+
+    /// Verifica si el carácter termina una palabra sin comillas
+    fn is_word_boundary(ch: char) -> bool {
+        ch.is_whitespace() || matches!(ch, '|' | '<' | '>' | ';' | '&')
+    }
+
+    /// Lee un número de descriptor (`2` en `2>`) solo si va seguido de una redirección
+    fn read_io_number(&mut self) -> Option<u32> {
+        let mut lookahead = self.chars.clone();
+        let mut digits = String::new();
+        while let Some(&(_, ch)) = lookahead.peek() {
+            if !ch.is_ascii_digit() {
+                break;
+            }
+            digits.push(ch);
+            lookahead.next();
+        }
+        if digits.is_empty() || !matches!(lookahead.peek(), Some(&(_, '<')) | Some(&(_, '>'))) {
+            return None;
+        }
+        let fd = digits.parse().ok()?;
+        self.chars = lookahead;
+        Some(fd)
+    }
+
+    /// Lee un operador de redirección: `<`, `>`, `>>` o `>&`
+    fn read_redirect(&mut self, fd: Option<u32>) -> Token {
+        let mode = match self.chars.next() {
+            Some((_, '<')) => RedirectMode::Input,
+            _ => match self.chars.peek() {
+                Some(&(_, '>')) => {
+                    self.chars.next();
+                    RedirectMode::Append
+                }
+                Some(&(_, '&')) => {
+                    self.chars.next();
+                    RedirectMode::Duplicate
+                }
+                _ => RedirectMode::Truncate,
+            },
+        };
+        Token::Operator(Operator::Redirect { fd, mode })
+    }
+
+    /// Salta los espacios; los saltos de línea son separadores de comandos
+    fn skip_whitespace(&mut self) {
+        while let Some(&(_, ch)) = self.chars.peek() {
+            if !ch.is_whitespace() || ch == '\n' {
+                break;
+            }
+            self.chars.next();
+        }
+    }
+
+    /// Un `#` al comienzo de una palabra inicia un comentario hasta el fin de la línea
+    fn skip_comment(&mut self) {
+        if self.next_if_char('#') {
+            while self.chars.next_if(|&(_, ch)| ch != '\n').is_some() {}
+        }
+    }
+
+    /// Lee una palabra completa hasta el siguiente espacio sin comillas
+    fn read_word(&mut self) -> Result<Word, ParseError> {
+        let mut parts = Vec::new();
+        let mut unquoted = String::new();
+
+        while let Some(&(position, ch)) = self.chars.peek() {
+            match ch {
+                c if Self::is_word_boundary(c) => break,
+                '\'' | '"' | '\\' => {
+                    if !unquoted.is_empty() {
+                        parts.push(WordPart::Unquoted(std::mem::take(&mut unquoted)));
+                    }
+                    self.chars.next();
+                    match ch {
+                        '\'' => parts.push(self.read_single_quoted(position)?),
+                        '"' => parts.extend(self.read_double_quoted(position)?),
+                        _ => match self.chars.next() {
+                            // Continuación de línea: se descarta junto con la barra
+                            Some((_, '\n')) => {}
+                            Some((_, escaped)) => parts.push(WordPart::Quoted(escaped.to_string())),
+                            None => return Err(ParseError::DanglingEscape { position }),
+                        },
+                    }
+                }
+                '$' => {
+                    self.chars.next();
+                    unquoted.push(ch);
+                    if self.next_if_char('{') {
+                        self.read_braced_expansion(position, &mut unquoted)?;
+                    } else if self.next_if_char('(') {
+                        self.read_command_substitution(position, &mut unquoted)?;
+                    }
+                }
+                '`' => {
+                    self.chars.next();
+                    self.read_backquoted(position, &mut unquoted)?;
+                }
+                _ => {
+                    unquoted.push(ch);
+                    self.chars.next();
+                }
+            }
+        }
+
+        if !unquoted.is_empty() {
+            parts.push(WordPart::Unquoted(unquoted));
+        }
+        Ok(Word::new(parts))
+    }
+
+    /// Copia sin interpretar una expansión `${...}` hasta su llave de cierre, para que
+    /// los espacios y operadores de su interior (`${VAR:-a b}`) no corten la palabra
+    fn read_braced_expansion(&mut self, start: usize, text: &mut String) -> Result<(), ParseError> {
+        text.push('{');
+        let mut depth = 0;
+        for (_, ch) in self.chars.by_ref() {
+            text.push(ch);
+            match ch {
+                '{' => depth += 1,
+                '}' if depth == 0 => return Ok(()),
+                '}' => depth -= 1,
+                _ => {}
+            }
+        }
+        Err(ParseError::UnterminatedExpansion { position: start, closing: '}' })
+    }
+
+    /// Copia sin interpretar una sustitución `$(...)`; el comando de su interior se
+    /// analiza cuando se expande
+    fn read_command_substitution(&mut self, start: usize, text: &mut String) -> Result<(), ParseError> {
+        let chars = &mut self.chars;
+        let command = scan_command_substitution(&mut || chars.next().map(|(_, ch)| ch))
+            .ok_or(ParseError::UnterminatedExpansion { position: start, closing: ')' })?;
+        text.push('(');
+        text.push_str(&command);
+        text.push(')');
+        Ok(())
+    }
+
+    /// Copia sin interpretar una sustitución entre comillas invertidas
+    fn read_backquoted(&mut self, start: usize, text: &mut String) -> Result<(), ParseError> {
+        let chars = &mut self.chars;
+        let command = scan_backquoted(&mut || chars.next().map(|(_, ch)| ch))
+            .ok_or(ParseError::UnterminatedQuote { quote: '`', position: start })?;
+        text.push('`');
+        text.push_str(&command);
+        text.push('`');
+        Ok(())
+    }
+
+    /// Lee el contenido entre comillas simples; no se interpreta ningún escape
+    fn read_single_quoted(&mut self, start: usize) -> Result<WordPart, ParseError> {
+        let mut text = String::new();
+        for (_, ch) in self.chars.by_ref() {
+            if ch == '\'' {
+                return Ok(WordPart::Quoted(text));
+            }
+            text.push(ch);
+        }
+        Err(ParseError::UnterminatedQuote { quote: '\'', position: start })
+    }
+
+    /// Lee el contenido entre comillas dobles. Los caracteres escapados se
+    /// guardan como fragmentos literales para que las expansiones los respeten.
+    fn read_double_quoted(&mut self, start: usize) -> Result<Vec<WordPart>, ParseError> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+
+        while let Some((position, ch)) = self.chars.next() {
+            match ch {
+                '$' if self.next_if_char('(') => {
+                    text.push('$');
+                    self.read_command_substitution(position, &mut text)?;
+                }
+                '`' => self.read_backquoted(position, &mut text)?,
+                '"' => {
+                    if !text.is_empty() || parts.is_empty() {
+                        parts.push(WordPart::DoubleQuoted(text));
+                    }
+                    return Ok(parts);
+                }
+                '\\' => match self.chars.peek() {
+                    Some(&(_, next)) if matches!(next, '"' | '\\' | '$' | '`') => {
+                        self.chars.next();
+                        if !text.is_empty() {
+                            parts.push(WordPart::DoubleQuoted(std::mem::take(&mut text)));
+                        }
+                        parts.push(WordPart::Quoted(next.to_string()));
+                    }
+                    Some(&(_, '\n')) => {
+                        self.chars.next();
+                    }
+                    _ => text.push('\\'),
+                },
+                _ => text.push(ch),
+            }
+        }
+
+        Err(ParseError::UnterminatedQuote { quote: '"', position: start })
+    }
+}
+
+/// Lee el comando de una sustitución `$(...)` ya abierta, hasta su paréntesis de
+/// cierre. Respeta comillas, escapes y paréntesis anidados; devuelve `None` si la
+/// entrada termina antes.
+pub fn scan_command_substitution(next: &mut impl FnMut() -> Option<char>) -> Option<String> {
+    let mut command = String::new();
+    let mut depth = 0;
+    loop {
+        let ch = next()?;
+        match ch {
+            ')' if depth == 0 => return Some(command),
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            '\\' => {
+                command.push(ch);
+                command.push(next()?);
+                continue;
+            }
+            '\'' | '"' | '`' => {
+                command.push(ch);
+                loop {
+                    let quoted = next()?;
+                    command.push(quoted);
+                    if quoted == ch {
+                        break;
+                    }
+                    if quoted == '\\' && ch != '\'' {
+                        command.push(next()?);
+                    }
+                }
+                continue;
+            }
+            _ => {}
+        }
+        command.push(ch);
+    }
+}
+
+/// Lee el comando de una sustitución entre comillas invertidas ya abierta, sin
+/// quitar sus escapes. Devuelve `None` si no se cierra.
+pub fn scan_backquoted(next: &mut impl FnMut() -> Option<char>) -> Option<String> {
+    let mut command = String::new();
+    loop {
+        match next()? {
+            '`' => return Some(command),
+            '\\' => {
+                command.push('\\');
+                command.push(next()?);
+            }
+            ch => command.push(ch),
+        }
+    }
+}
+
+/// Analizador sintáctico que construye el árbol de una línea de comandos
+pub struct Parser<'a> {
+    input: &'a str,
+    tokens: Peekable<std::vec::IntoIter<SpannedToken>>,
+    /// Fin del último token consumido, para recuperar el texto de cada tubería
+    last_end: usize,
+}
+
+impl<'a> Parser<'a> {
+    /// Analiza una línea de comandos completa
+    pub fn parse(input: &'a str) -> Result<CommandLine, ParseError> {
+        let tokens = Lexer::new(input).tokenize()?;
+        let mut parser = Self {
+            input,
+            tokens: tokens.into_iter().peekable(),
+            last_end: 0,
+        };
+        parser.parse_command_line()
+    }
+
+    fn peek(&mut self) -> Option<&Token> {
+        self.tokens.peek().map(|(token, _)| token)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let (token, span) = self.tokens.next()?;
+        self.last_end = span.end;
+        Some(token)
+    }
+
+    /// Analiza la línea completa
+    fn parse_command_line(&mut self) -> Result<CommandLine, ParseError> {
+        self.parse_list(&[])
+    }
+
+    /// Analiza listas condicionales separadas por `;`, `&` o saltos de línea hasta el
+    /// final de la entrada o hasta una de las palabras `terminators` (como `fi` al
+    /// final del cuerpo de un `if`), que queda sin consumir. Se admite un separador final.
+    fn parse_list(&mut self, terminators: &[&str]) -> Result<CommandLine, ParseError> {
+        let mut items = Vec::new();
+        loop {
+            self.skip_newlines();
+            match self.peek() {
+                None => break,
+                Some(Token::Word(word)) if Self::keyword(word).is_some_and(|keyword| terminators.contains(&keyword)) => {
+                    break
+                }
+                _ => {}
+            }
+            let mut list = self.parse_and_or()?;
+            match self.peek() {
+                Some(Token::Operator(Operator::Semicolon | Operator::Newline)) => {
+                    self.next();
+                }
+                Some(Token::Operator(Operator::Background)) => {
+                    self.next();
+                    list.background = true;
+                }
+                Some(Token::Operator(operator)) => return Err(ParseError::UnexpectedOperator(*operator)),
+                // Después de una estructura (`fi`, `done`) solo puede venir un separador
+                Some(Token::Word(word)) => {
+                    let is_terminator = Self::keyword(word).is_some_and(|keyword| terminators.contains(&keyword));
+                    if !is_terminator {
+                        return Err(ParseError::UnexpectedWord(word.to_literal()));
+                    }
+                }
+                None => {}
+            }
+            items.push(list);
+        }
+        Ok(CommandLine { items })
+    }
+
+    fn skip_newlines(&mut self) {
+        while let Some(Token::Operator(Operator::Newline)) = self.peek() {
+            self.next();
+        }
+    }
+
+    /// Texto de la palabra si puede ser una palabra reservada: sin comillas ni escapes
+    fn keyword(word: &Word) -> Option<&str> {
+        match &word.parts[..] {
+            [WordPart::Unquoted(text)] => Some(text),
+            _ => None,
+        }
+    }
+
+    /// Analiza elementos unidos con `&&` y `||`
+    fn parse_and_or(&mut self) -> Result<AndOrList, ParseError> {
+        let mut list = AndOrList::new(self.parse_statement()?);
+        loop {
+            let (connector, operator) = match self.peek() {
+                Some(Token::Operator(Operator::And)) => (Connector::And, Operator::And),
+                Some(Token::Operator(Operator::Or)) => (Connector::Or, Operator::Or),
+                _ => return Ok(list),
+            };
+            self.next();
+            self.skip_newlines();
+            if self.peek().is_none() {
+                return Err(ParseError::MissingCommand(operator));
+            }
+            list.rest.push((connector, self.parse_statement()?));
+        }
+    }
+
+    /// Analiza una tubería o, si empieza con una palabra reservada, una estructura de control
+    fn parse_statement(&mut self) -> Result<Statement, ParseError> {
+        let start = self.tokens.peek().map_or(self.last_end, |(_, span)| span.start);
+        let keyword = match self.peek() {
+            Some(Token::Word(word)) => Self::keyword(word).map(str::to_string),
+            _ => None,
+        };
+        let command = match keyword.as_deref() {
+            Some("if") => CompoundCommand::If(self.parse_if(start)?),
+            Some("for") => CompoundCommand::For(self.parse_for(start)?),
+            Some("while") => CompoundCommand::While(self.parse_while(start)?),
+            Some("function") => {
+                self.next();
+                CompoundCommand::Function(self.parse_function(start)?)
+            }
+            Some(word @ ("then" | "elif" | "else" | "fi" | "do" | "done" | "}")) => {
+                return Err(ParseError::UnexpectedWord(word.to_string()))
+            }
+            _ if self.is_function_definition() => CompoundCommand::Function(self.parse_function(start)?),
+            _ => return Ok(Statement::Pipeline(self.parse_pipeline()?)),
+        };
+        let text = self.input[start..self.last_end].to_string();
+        Ok(Statement::Compound { command, text })
+    }
+
+    /// Consume la palabra reservada esperada. Si la entrada termina antes, falta la
+    /// palabra que cierra la estructura abierta en `position`.
+    fn expect_keyword(&mut self, expected: &'static str, opened: &'static str, position: usize) -> Result<(), ParseError> {
+        match self.next() {
+            Some(Token::Word(word)) if Self::keyword(&word) == Some(expected) => Ok(()),
+            Some(Token::Word(word)) => Err(ParseError::UnexpectedWord(word.to_literal())),
+            Some(Token::Operator(operator)) => Err(ParseError::UnexpectedOperator(operator)),
+            None => Err(ParseError::MissingKeyword { expected, opened, position }),
+        }
+    }
+
+    /// `if condición; then ...; [elif condición; then ...;] [else ...;] fi`
+    fn parse_if(&mut self, start: usize) -> Result<IfClause, ParseError> {
+        self.next();
+        let mut clause = IfClause::default();
+        loop {
+            let condition = self.parse_list(&["then"])?;
+            self.expect_keyword("then", "if", start)?;
+            let body = self.parse_list(&["elif", "else", "fi"])?;
+            clause.branches.push((condition, body));
+            match self.next() {
+                Some(Token::Word(word)) => match Self::keyword(&word) {
+                    Some("elif") => continue,
+                    Some("else") => {
+                        clause.else_body = Some(self.parse_list(&["fi"])?);
+                        self.expect_keyword("fi", "if", start)?;
+                    }
+                    _ => {}
+                },
+                _ => return Err(ParseError::MissingKeyword { expected: "fi", opened: "if", position: start }),
+            }
+            return Ok(clause);
+        }
+    }
+
+    /// `for nombre [in palabras...]; do ...; done`
+    fn parse_for(&mut self, start: usize) -> Result<ForClause, ParseError> {
+        self.next();
+        let variable = match self.next() {
+            Some(Token::Word(word)) if Self::keyword(&word).is_some_and(Environment::is_valid_name) => word.to_literal(),
+            Some(Token::Word(word)) => return Err(ParseError::UnexpectedWord(word.to_literal())),
+            Some(Token::Operator(operator)) => return Err(ParseError::UnexpectedOperator(operator)),
+            None => return Err(ParseError::MissingKeyword { expected: "do", opened: "for", position: start }),
+        };
+
+        self.skip_newlines();
+        let mut words = None;
+        if let Some(Token::Word(word)) = self.peek() {
+            if Self::keyword(word) == Some("in") {
+                self.next();
+                let mut list = Vec::new();
+                while let Some(Token::Word(_)) = self.peek() {
+                    if let Some(Token::Word(word)) = self.next() {
+                        list.push(word);
+                    }
+                }
+                words = Some(list);
+            }
+        }
+        if let Some(Token::Operator(Operator::Semicolon | Operator::Newline)) = self.peek() {
+            self.next();
+        }
+        self.skip_newlines();
+        self.expect_keyword("do", "for", start)?;
+        let body = self.parse_list(&["done"])?;
+        self.expect_keyword("done", "for", start)?;
+        Ok(ForClause { variable, words, body })
+    }
+
+    /// `while condición; do ...; done`
+    fn parse_while(&mut self, start: usize) -> Result<WhileClause, ParseError> {
+        self.next();
+        let condition = self.parse_list(&["do"])?;
+        self.expect_keyword("do", "while", start)?;
+        let body = self.parse_list(&["done"])?;
+        self.expect_keyword("done", "while", start)?;
+        Ok(WhileClause { condition, body })
+    }
+
+    /// El comando empieza con `nombre()` o `nombre ()`: define una función
+    fn is_function_definition(&self) -> bool {
+        let mut tokens = self.tokens.clone().map(|(token, _)| token);
+        let Some(Token::Word(first)) = tokens.next() else {
+            return false;
+        };
+        match Self::keyword(&first) {
+            Some(text) if text.ends_with("()") => Environment::is_valid_name(&text[..text.len() - 2]),
+            Some(text) if Environment::is_valid_name(text) => {
+                matches!(tokens.next(), Some(Token::Word(second)) if Self::keyword(&second) == Some("()"))
+            }
+            _ => false,
+        }
+    }
+
+    /// Analiza `nombre() { ...; }`; con `function` delante los paréntesis son opcionales
+    fn parse_function(&mut self, start: usize) -> Result<FunctionDefinition, ParseError> {
+        let name = match self.next() {
+            Some(Token::Word(word)) => word.to_literal(),
+            Some(Token::Operator(operator)) => return Err(ParseError::UnexpectedOperator(operator)),
+            None => return Err(ParseError::MissingKeyword { expected: "}", opened: "function", position: start }),
+        };
+        let name = match name.strip_suffix("()") {
+            Some(name) => name.to_string(),
+            None => {
+                if let Some(Token::Word(word)) = self.peek() {
+                    if Self::keyword(word) == Some("()") {
+                        self.next();
+                    }
+                }
+                name
+            }
+        };
+        if !Environment::is_valid_name(&name) {
+            return Err(ParseError::UnexpectedWord(name));
+        }
+
+        self.skip_newlines();
+        self.expect_keyword("{", "function", start)?;
+        let body = self.parse_list(&["}"])?;
+        self.expect_keyword("}", "{", start)?;
+        let text = self.input[start..self.last_end].to_string();
+        Ok(FunctionDefinition { name, body, text })
+    }
+
+    /// Analiza una secuencia de comandos simples separados por `|`
+    fn parse_pipeline(&mut self) -> Result<Pipeline, ParseError> {
+        let start = self.tokens.peek().map_or(self.last_end, |(_, span)| span.start);
+        let mut commands = vec![self.parse_simple_command()?];
+        while let Some(Token::Operator(Operator::Pipe)) = self.peek() {
+            self.next();
+            self.skip_newlines();
+            if self.peek().is_none() {
+                return Err(ParseError::MissingCommand(Operator::Pipe));
+            }
+            commands.push(self.parse_simple_command()?);
+        }
+        let text = self.input[start..self.last_end].to_string();
+        Ok(Pipeline::new(commands).with_text(text))
+    }
+
+    /// Analiza las palabras y redirecciones de un comando simple hasta el siguiente operador
+    fn parse_simple_command(&mut self) -> Result<SimpleCommand, ParseError> {
+        let mut words = Vec::new();
+        let mut redirections = Vec::new();
+        loop {
+            match self.peek() {
+                Some(Token::Word(_)) => {
+                    if let Some(Token::Word(word)) = self.next() {
+                        words.push(word);
+                    }
+                }
+                Some(Token::Operator(Operator::Redirect { fd, mode })) => {
+                    let (fd, mode) = (*fd, *mode);
+                    self.next();
+                    let target = self.next();
+                    redirections.push(Self::parse_redirection(fd, mode, target)?);
+                }
+                _ => break,
+            }
+        }
+        if words.is_empty() && redirections.is_empty() {
+            if let Some(Token::Operator(operator)) = self.peek() {
+                return Err(ParseError::UnexpectedOperator(*operator));
+            }
+        }
+        Ok(SimpleCommand::new(words, redirections))
+    }
+    /// Construye una redirección a partir de su operador y la palabra de destino
+    fn parse_redirection(fd: Option<u32>, mode: RedirectMode, target: Option<Token>) -> Result<Redirection, ParseError> {
+        let operator = Operator::Redirect { fd, mode };
+        let target = match target {
+            Some(Token::Word(word)) => word,
+            _ => return Err(ParseError::MissingRedirectTarget(operator)),
+        };
+        if mode == RedirectMode::Duplicate {
+            let literal = target.to_literal();
+            if literal.is_empty() || !literal.chars().all(|ch| ch.is_ascii_digit()) {
+                return Err(ParseError::InvalidRedirectTarget { operator, target: literal });
+            }
+        }
+        Ok(Redirection {
+            fd: fd.unwrap_or_else(|| mode.default_fd()),
+            mode,
+            target,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(line: &str) -> CommandLine {
+        Parser::parse(line).expect("la línea es válida")
+    }
+
+    fn error(line: &str) -> ParseError {
+        Parser::parse(line).expect_err("la línea no es válida")
+    }
+
+    /// Palabras de cada comando de una tubería, ya sin comillas
+    fn commands(statement: &Statement) -> Vec<Vec<String>> {
+        match statement {
+            Statement::Pipeline(pipeline) => pipeline
+                .commands
+                .iter()
+                .map(|command| command.words.iter().map(Word::to_literal).collect())
+                .collect(),
+            Statement::Compound { text, .. } => panic!("se esperaba una tubería: {}", text),
+        }
+    }
+
+    /// Fragmentos de la única palabra `n` del primer comando
+    fn parts(line: &str, n: usize) -> Vec<WordPart> {
+        let command_line = parse(line);
+        match &command_line.items[0].first {
+            Statement::Pipeline(pipeline) => pipeline.commands[0].words[n].parts.clone(),
+            Statement::Compound { .. } => panic!("se esperaba una tubería"),
+        }
+    }
+
+    fn redirections(line: &str) -> Vec<(u32, RedirectMode, String)> {
+        let command_line = parse(line);
+        match &command_line.items[0].first {
+            Statement::Pipeline(pipeline) => pipeline.commands[0]
+                .redirections
+                .iter()
+                .map(|redirection| (redirection.fd, redirection.mode, redirection.target.to_literal()))
+                .collect(),
+            Statement::Compound { .. } => panic!("se esperaba una tubería"),
+        }
+    }
+
+    fn words(words: &[&str]) -> Vec<String> {
+        words.iter().map(|word| word.to_string()).collect()
+    }
+
+    #[test]
+    fn pipe_binds_tighter_than_and_or() {
+        let line = parse("a | b && c | d || e");
+        assert_eq!(line.items.len(), 1);
+        let list = &line.items[0];
+        assert_eq!(commands(&list.first), [words(&["a"]), words(&["b"])]);
+        assert_eq!(list.rest.len(), 2);
+        assert_eq!(list.rest[0].0, Connector::And);
+        assert_eq!(commands(&list.rest[0].1), [words(&["c"]), words(&["d"])]);
+        assert_eq!(list.rest[1].0, Connector::Or);
+        assert_eq!(commands(&list.rest[1].1), [words(&["e"])]);
+    }
+
+    #[test]
+    fn semicolon_and_background_separate_lists() {
+        let line = parse("a && b; c & d\ne;");
+        let texts: Vec<String> = line.items.iter().map(AndOrList::text).collect();
+        assert_eq!(texts, ["a && b", "c", "d", "e"]);
+        let background: Vec<bool> = line.items.iter().map(|list| list.background).collect();
+        assert_eq!(background, [false, true, false, false]);
+    }
+
+    #[test]
+    fn and_or_are_left_associative_in_order() {
+        let line = parse("a || b && c");
+        let connectors: Vec<Connector> = line.items[0].rest.iter().map(|(connector, _)| *connector).collect();
+        assert_eq!(connectors, [Connector::Or, Connector::And]);
+    }
+
+    #[test]
+    fn operators_need_no_spaces() {
+        let line = parse("a|b&&c||d;e&");
+        assert_eq!(commands(&line.items[0].first), [words(&["a"]), words(&["b"])]);
+        assert_eq!(line.items[0].rest.len(), 2);
+        assert!(line.items[1].background);
+    }
+
+    #[test]
+    fn a_line_may_continue_after_an_operator_on_the_next_line() {
+        let line = parse("a &&\nb |\nc");
+        assert_eq!(commands(&line.items[0].rest[0].1), [words(&["b"]), words(&["c"])]);
+    }
+
+    #[test]
+    fn misplaced_operators_are_errors() {
+        assert_eq!(error("| a"), ParseError::UnexpectedOperator(Operator::Pipe));
+        assert_eq!(error("a ; ; b"), ParseError::UnexpectedOperator(Operator::Semicolon));
+        assert_eq!(error("&& a"), ParseError::UnexpectedOperator(Operator::And));
+        assert_eq!(error("a | | b"), ParseError::UnexpectedOperator(Operator::Pipe));
+    }
+
+    #[test]
+    fn trailing_operators_leave_the_line_incomplete() {
+        for (line, operator) in [("a |", Operator::Pipe), ("a &&", Operator::And), ("a ||", Operator::Or)] {
+            let error = error(line);
+            assert_eq!(error, ParseError::MissingCommand(operator));
+            assert!(error.is_incomplete());
+        }
+    }
+
+    #[test]
+    fn quotes_group_words_and_keep_their_kind() {
+        let line = parse("echo 'a b' \"c d\" e'f'\"g\"");
+        assert_eq!(commands(&line.items[0].first), [words(&["echo", "a b", "c d", "efg"])]);
+        assert_eq!(
+            parts("echo e'f'\"g\"", 1),
+            [
+                WordPart::Unquoted("e".to_string()),
+                WordPart::Quoted("f".to_string()),
+                WordPart::DoubleQuoted("g".to_string()),
+            ]
+        );
+        assert_eq!(parts("echo ''", 1), [WordPart::Quoted(String::new())]);
+        assert_eq!(parts("echo \"\"", 1), [WordPart::DoubleQuoted(String::new())]);
+    }
+
+    #[test]
+    fn operators_inside_quotes_are_text() {
+        let line = parse("echo 'a | b' \"c && d; e\"");
+        assert_eq!(line.items.len(), 1);
+        assert_eq!(commands(&line.items[0].first), [words(&["echo", "a | b", "c && d; e"])]);
+    }
+
+    #[test]
+    fn single_quotes_do_not_interpret_escapes() {
+        assert_eq!(parts("echo 'a\\nb\\'", 1), [WordPart::Quoted("a\\nb\\".to_string())]);
+    }
+
+    #[test]
+    fn backslash_escapes_one_character() {
+        assert_eq!(
+            parts("echo a\\ b", 1),
+            [
+                WordPart::Unquoted("a".to_string()),
+                WordPart::Quoted(" ".to_string()),
+                WordPart::Unquoted("b".to_string()),
+            ]
+        );
+        let line = parse("echo \\| \\; \\& \\> \\'");
+        assert_eq!(commands(&line.items[0].first), [words(&["echo", "|", ";", "&", ">", "'"])]);
+    }
+
+    #[test]
+    fn double_quotes_escape_only_special_characters() {
+        assert_eq!(
+            parts("echo \"a\\\"b\\$c\\n\"", 1),
+            [
+                WordPart::DoubleQuoted("a".to_string()),
+                WordPart::Quoted("\"".to_string()),
+                WordPart::DoubleQuoted("b".to_string()),
+                WordPart::Quoted("$".to_string()),
+                WordPart::DoubleQuoted("c\\n".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn backslash_newline_continues_the_word() {
+        let line = parse("echo ab\\\ncd");
+        assert_eq!(commands(&line.items[0].first), [words(&["echo", "abcd"])]);
+    }
+
+    #[test]
+    fn dangling_escape_is_an_error() {
+        let error = error("echo a\\");
+        assert_eq!(error, ParseError::DanglingEscape { position: 6 });
+        assert!(!error.is_incomplete());
+    }
+
+    #[test]
+    fn unterminated_quotes_report_where_they_open() {
+        assert_eq!(error("echo 'abc"), ParseError::UnterminatedQuote { quote: '\'', position: 5 });
+        assert_eq!(error("echo a\"bc"), ParseError::UnterminatedQuote { quote: '"', position: 6 });
+        assert_eq!(error("echo `date"), ParseError::UnterminatedQuote { quote: '`', position: 5 });
+        assert!(error("echo 'abc").is_incomplete());
+    }
+
+    #[test]
+    fn unterminated_expansions_report_the_missing_closing() {
+        assert_eq!(error("echo ${HOME"), ParseError::UnterminatedExpansion { position: 5, closing: '}' });
+        assert_eq!(error("echo $(date"), ParseError::UnterminatedExpansion { position: 5, closing: ')' });
+        assert_eq!(error("echo \"$(date\""), ParseError::UnterminatedExpansion { position: 6, closing: ')' });
+    }
+
+    #[test]
+    fn expansions_keep_spaces_and_operators_inside() {
+        let line = parse("echo ${A:-x y} $(ls | wc -l) `a; b`");
+        assert_eq!(
+            commands(&line.items[0].first),
+            [words(&["echo", "${A:-x y}", "$(ls | wc -l)", "`a; b`"])]
+        );
+    }
+
+    #[test]
+    fn redirections_default_their_descriptor() {
+        assert_eq!(
+            redirections("cmd < in > out >> log"),
+            [
+                (0, RedirectMode::Input, "in".to_string()),
+                (1, RedirectMode::Truncate, "out".to_string()),
+                (1, RedirectMode::Append, "log".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn redirections_take_an_explicit_descriptor() {
+        assert_eq!(
+            redirections("cmd 2> err 2>&1 >&2"),
+            [
+                (2, RedirectMode::Truncate, "err".to_string()),
+                (2, RedirectMode::Duplicate, "1".to_string()),
+                (1, RedirectMode::Duplicate, "2".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn redirections_may_appear_anywhere_in_the_command() {
+        let line = parse("> out echo a 2>err b");
+        assert_eq!(commands(&line.items[0].first), [words(&["echo", "a", "b"])]);
+        assert_eq!(redirections("> out echo a 2>err b").len(), 2);
+    }
+
+    #[test]
+    fn a_number_is_a_descriptor_only_right_before_the_operator() {
+        let line = parse("echo 2 > out");
+        assert_eq!(commands(&line.items[0].first), [words(&["echo", "2"])]);
+        assert_eq!(redirections("echo 2 > out"), [(1, RedirectMode::Truncate, "out".to_string())]);
+        assert_eq!(redirections("echo a2>out"), [(1, RedirectMode::Truncate, "out".to_string())]);
+    }
+
+    #[test]
+    fn quoted_redirection_targets_are_words() {
+        assert_eq!(redirections("cmd > 'mi archivo'"), [(1, RedirectMode::Truncate, "mi archivo".to_string())]);
+    }
+
+    #[test]
+    fn invalid_redirections_are_errors() {
+        let truncate = Operator::Redirect { fd: None, mode: RedirectMode::Truncate };
+        assert_eq!(error("echo >"), ParseError::MissingRedirectTarget(truncate));
+        assert_eq!(error("echo > | cat"), ParseError::MissingRedirectTarget(truncate));
+        assert_eq!(
+            error("echo 2>&x"),
+            ParseError::InvalidRedirectTarget {
+                operator: Operator::Redirect { fd: Some(2), mode: RedirectMode::Duplicate },
+                target: "x".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn comments_start_only_at_the_beginning_of_a_word() {
+        let line = parse("echo a#b '#c' # comentario | d");
+        assert_eq!(line.items.len(), 1);
+        assert_eq!(commands(&line.items[0].first), [words(&["echo", "a#b", "#c"])]);
+    }
+
+    #[test]
+    fn pipeline_text_is_kept_for_the_prompt() {
+        let line = parse("  ls -l |  wc  && echo 'a b'  ");
+        assert_eq!(line.items[0].first.text(), "ls -l |  wc");
+        assert_eq!(line.items[0].rest[0].1.text(), "echo 'a b'");
+    }
+
+    #[test]
+    fn control_structures_nest_and_require_their_closing_word() {
+        let line = parse("if a; then for x in 1 2; do b; done; fi && c");
+        assert!(matches!(line.items[0].first, Statement::Compound { command: CompoundCommand::If(_), .. }));
+        assert_eq!(line.items[0].rest[0].0, Connector::And);
+        assert_eq!(error("if a; then b"), ParseError::MissingKeyword { expected: "fi", opened: "if", position: 0 });
+        assert_eq!(error("fi"), ParseError::UnexpectedWord("fi".to_string()));
+    }
+
+    #[test]
+    fn empty_lines_have_no_commands() {
+        assert!(parse("").items.is_empty());
+        assert!(parse("   \n  # solo un comentario").items.is_empty());
+    }
+}
@@ -1,14 +1,96 @@
-use crate::domain::entities::Command;
+use std::path::Path;
+use std::time::SystemTime;
 
-/// Trait que define el repositorio de comandos
-pub trait CommandRepository {
-    fn execute_command(&self, command: &Command) -> Result<Command, String>;
+use crate::domain::aliases::Aliases;
+use crate::domain::builtins::{BuiltinCommand, CommandCatalog, Session};
+use crate::domain::completion_spec::CompletionSpec;
+use crate::domain::entities::{Command, DirectoryEntry, EventSender, PipelineOptions, PipelineStage};
+use crate::domain::errors::TermirustError;
+use crate::domain::jobs::SharedJobTable;
+
+/// Trait que define el repositorio de comandos. Se usa desde el hilo de trabajo
+/// que ejecuta los comandos, por eso debe poder enviarse entre hilos.
+pub trait CommandRepository: Send + 'static {
+    /// Ejecuta un comando interno con las variables exportadas y el estado de la sesión
+    fn execute_command(
+        &self,
+        command: &Command,
+        environment: &[(String, String)],
+        session: Session,
+    ) -> Result<Command, TermirustError>;
+
+    /// Indica si el repositorio resuelve el comando internamente sin lanzar un proceso
+    fn is_builtin(&self, command: &Command) -> bool;
+
+    /// Agrega un comando interno, que tiene prioridad sobre los programas del `PATH`
+    fn register_builtin(&mut self, command: Box<dyn BuiltinCommand>);
+
+    /// Comandos internos registrados, para completarlos desde la interfaz
+    fn command_catalog(&self) -> CommandCatalog;
+
+    /// Ejecuta una tubería conectando la salida de cada etapa con la entrada de la
+    /// siguiente. El código de salida es el de la última etapa, o el de la última
+    /// que falló si `pipefail` está activo. Si se indica un canal, cada línea capturada
+    /// se envía en cuanto llega, mientras los procesos siguen ejecutándose. Una tubería
+    /// en segundo plano (o detenida con Ctrl+Z) queda registrada en la tabla de trabajos.
+    fn execute_pipeline(
+        &self,
+        stages: Vec<PipelineStage>,
+        options: &PipelineOptions,
+        events: Option<&EventSender>,
+    ) -> Result<Command, TermirustError>;
+
+    /// Tabla de trabajos en segundo plano o detenidos lanzados por este repositorio
+    fn job_table(&self) -> SharedJobTable;
 }
 
-/// Trait que define el repositorio de archivos del sistema
+/// Trait que define el repositorio de archivos del sistema. Sus errores no indican el
+/// comando: lo agrega quien lo usa con `TermirustError::in_command`.
 pub trait FileSystemRepository {
     #[allow(dead_code)]
-    fn list_directory(&self, path: &str) -> Result<Vec<String>, String>;
-    fn get_current_directory(&self) -> Result<String, String>;
-    fn change_directory(&self, path: &str) -> Result<(), String>;
+    fn list_directory(&self, path: &str) -> Result<Vec<String>, TermirustError>;
+    fn get_current_directory(&self) -> Result<String, TermirustError>;
+    fn change_directory(&self, path: &str) -> Result<(), TermirustError>;
+}
+
+/// Trait que define cómo se leen los directorios para expandir nombres de archivo
+/// (`*.rs`, `**/`) y encontrar los programas del `PATH`. Se comparte con el hilo que
+/// ejecuta los comandos.
+pub trait DirectoryRepository: Send + Sync + 'static {
+    /// Entradas del directorio, sin `.` ni `..`; si no se puede leer, ninguna
+    fn list(&self, directory: &Path) -> Vec<DirectoryEntry>;
+
+    /// La ruta existe, aunque sea un enlace roto
+    fn exists(&self, path: &Path) -> bool;
+
+    /// La ruta es un directorio o un enlace a uno
+    fn is_directory(&self, path: &Path) -> bool;
+
+    /// Última modificación del directorio: cambia al agregar o quitar entradas
+    fn modified(&self, directory: &Path) -> Option<SystemTime>;
+}
+
+/// Trait que define cómo se consultan las cuentas del sistema, para expandir `~usuario`
+pub trait UserRepository: Send + Sync + 'static {
+    /// Carpeta personal del usuario, o `None` si no existe
+    fn home_directory(&self, user: &str) -> Option<String>;
+}
+
+/// Trait que define dónde se guardan los alias para que duren entre sesiones
+pub trait AliasRepository: Send + 'static {
+    /// Carga los alias guardados; si todavía no hay ninguno devuelve una lista vacía
+    fn load(&self) -> Result<Aliases, TermirustError>;
+    fn save(&self, aliases: &Aliases) -> Result<(), TermirustError>;
+}
+
+/// Trait que define de dónde salen las especificaciones para completar con Tab los
+/// argumentos de programas externos
+pub trait CompletionSpecRepository: Send + 'static {
+    /// Carga las especificaciones en orden: si dos tienen el mismo nombre, vale la
+    /// última. Un archivo con errores no impide cargar los demás.
+    fn load(&self) -> Vec<Result<CompletionSpec, TermirustError>>;
+
+    /// Líneas que imprime el comando de un argumento (`git branch`); si falla o
+    /// tarda demasiado, ninguna
+    fn command_output(&self, command: &str, environment: &[(String, String)]) -> Vec<String>;
 }
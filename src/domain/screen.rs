@@ -0,0 +1,1021 @@
+use std::collections::VecDeque;
+
+use crate::domain::style::{CellStyle, StyledLine};
+
+/// Separación entre tabulaciones por defecto
+const TAB_WIDTH: usize = 8;
+
+/// Líneas que se conservan después de salir por arriba de la pantalla principal
+const MAX_SCROLLBACK: usize = 1000;
+
+/// Longitud máxima de los parámetros de una secuencia; lo que exceda se descarta
+const MAX_SEQUENCE_LENGTH: usize = 4096;
+
+/// Valor máximo de un parámetro numérico, como en xterm; evita desbordes al sumar
+/// cantidades enormes a la posición del cursor
+const MAX_PARAM: usize = u16::MAX as usize;
+
+/// Una posición de la rejilla: el carácter y sus atributos
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cell {
+    pub ch: char,
+    pub style: CellStyle,
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Self {
+            ch: ' ',
+            style: CellStyle::default(),
+        }
+    }
+}
+
+/// Posición y atributos guardados con `ESC 7` / `CSI s` / `CSI ?1048h`
+#[derive(Debug, Clone, Copy, Default)]
+struct SavedCursor {
+    row: usize,
+    col: usize,
+    style: CellStyle,
+    origin_mode: bool,
+    autowrap: bool,
+}
+
+/// Estado del intérprete de secuencias de escape
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum ParserState {
+    #[default]
+    Ground,
+    /// Se leyó `ESC`
+    Escape,
+    /// Se leyó `ESC` seguido de un intermedio como `(` o `#`
+    EscapeIntermediate(char),
+    /// Dentro de `ESC [`
+    Csi,
+    /// Dentro de `ESC ]`, hasta BEL o `ESC \`
+    Osc,
+    OscEscape,
+    /// Cadenas de control (DCS, SOS, PM, APC) que se ignoran hasta `ESC \`
+    Ignored,
+    IgnoredEscape,
+}
+
+/// Emulador de pantalla VT100/xterm: interpreta el flujo de bytes de un programa
+/// y mantiene la rejilla de celdas que la interfaz dibuja
+#[derive(Debug, Clone)]
+pub struct Screen {
+    cols: usize,
+    rows: usize,
+    grid: Vec<Vec<Cell>>,
+    /// Pantalla principal guardada mientras la alternativa está activa
+    primary: Option<Vec<Vec<Cell>>>,
+    scrollback: VecDeque<Vec<Cell>>,
+    row: usize,
+    col: usize,
+    style: CellStyle,
+    /// El cursor llegó a la última columna; el siguiente carácter pasa a la línea siguiente
+    wrap_pending: bool,
+    saved_cursor: Option<SavedCursor>,
+    scroll_top: usize,
+    scroll_bottom: usize,
+    autowrap: bool,
+    insert_mode: bool,
+    origin_mode: bool,
+    cursor_visible: bool,
+    application_cursor_keys: bool,
+    bracketed_paste: bool,
+    tab_stops: Vec<bool>,
+    state: ParserState,
+    params: String,
+    private_marker: Option<char>,
+    intermediate: Option<char>,
+    osc: String,
+    title: String,
+    last_char: Option<char>,
+    /// Bytes de un carácter UTF-8 que llegó partido entre dos lecturas
+    pending_utf8: Vec<u8>,
+    /// Respuestas a consultas del programa (posición del cursor, atributos del dispositivo)
+    responses: Vec<u8>,
+}
+
+impl Screen {
+    pub fn new(cols: usize, rows: usize) -> Self {
+        let cols = cols.max(1);
+        let rows = rows.max(1);
+        Self {
+            cols,
+            rows,
+            grid: vec![vec![Cell::default(); cols]; rows],
+            primary: None,
+            scrollback: VecDeque::new(),
+            row: 0,
+            col: 0,
+            style: CellStyle::default(),
+            wrap_pending: false,
+            saved_cursor: None,
+            scroll_top: 0,
+            scroll_bottom: rows - 1,
+            autowrap: true,
+            insert_mode: false,
+            origin_mode: false,
+            cursor_visible: true,
+            application_cursor_keys: false,
+            bracketed_paste: false,
+            tab_stops: Self::default_tab_stops(cols),
+            state: ParserState::Ground,
+            params: String::new(),
+            private_marker: None,
+            intermediate: None,
+            osc: String::new(),
+            title: String::new(),
+            last_char: None,
+            pending_utf8: Vec::new(),
+            responses: Vec::new(),
+        }
+    }
+
+    /// Columnas y filas de la rejilla
+    pub fn size(&self) -> (usize, usize) {
+        (self.cols, self.rows)
+    }
+
+    /// Filas visibles, de arriba abajo
+    pub fn lines(&self) -> &[Vec<Cell>] {
+        &self.grid
+    }
+
+    /// Fila y columna del cursor
+    pub fn cursor(&self) -> (usize, usize) {
+        (self.row, self.col)
+    }
+
+    pub fn cursor_visible(&self) -> bool {
+        self.cursor_visible
+    }
+
+    /// Las flechas deben enviarse como `ESC O A` en lugar de `ESC [ A` (DECCKM)
+    pub fn application_cursor_keys(&self) -> bool {
+        self.application_cursor_keys
+    }
+
+    /// El texto pegado debe enmarcarse con `ESC[200~` y `ESC[201~`
+    pub fn bracketed_paste(&self) -> bool {
+        self.bracketed_paste
+    }
+
+    /// Título de la ventana que pidió el programa con `OSC 0` u `OSC 2`
+    #[cfg(test)]
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// Devuelve y vacía las respuestas pendientes de escribir al programa
+    pub fn take_responses(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.responses)
+    }
+
+    /// Líneas de la pantalla principal con sus estilos, incluidas las que salieron por
+    /// arriba. Es lo que queda en la terminal cuando el programa termina.
+    pub fn text_lines(&self) -> Vec<StyledLine> {
+        let primary = self.primary.as_ref().unwrap_or(&self.grid);
+        let mut lines: Vec<StyledLine> = self
+            .scrollback
+            .iter()
+            .chain(primary.iter())
+            .map(|cells| {
+                let mut line = StyledLine::default();
+                for cell in cells {
+                    line.push(cell.ch.encode_utf8(&mut [0; 4]), cell.style);
+                }
+                line.trim_end()
+            })
+            .collect();
+        while lines.last().is_some_and(StyledLine::is_empty) {
+            lines.pop();
+        }
+        lines
+    }
+
+    /// Cambia el tamaño de la rejilla conservando el contenido que cabe
+    pub fn resize(&mut self, cols: usize, rows: usize) {
+        let cols = cols.max(1);
+        let rows = rows.max(1);
+        if (cols, rows) == (self.cols, self.rows) {
+            return;
+        }
+
+        // Al encoger, las líneas de arriba pasan al historial para no perder el cursor;
+        // si no alcanza, se descartan las de abajo
+        let moved = self.grid.len().saturating_sub(rows).min(self.row);
+        let lines: Vec<Vec<Cell>> = self.grid.drain(..moved).collect();
+        for line in lines {
+            self.push_scrollback(line);
+        }
+        self.row -= moved;
+        self.grid.truncate(rows);
+        Self::resize_grid(&mut self.grid, cols, rows);
+        if let Some(primary) = &mut self.primary {
+            let excess = primary.len().saturating_sub(rows);
+            primary.drain(..excess);
+            Self::resize_grid(primary, cols, rows);
+        }
+
+        self.cols = cols;
+        self.rows = rows;
+        self.scroll_top = 0;
+        self.scroll_bottom = rows - 1;
+        self.tab_stops = Self::default_tab_stops(cols);
+        self.row = self.row.min(rows - 1);
+        self.col = self.col.min(cols - 1);
+        self.wrap_pending = false;
+    }
+
+    /// Interpreta los bytes que escribió el programa
+    pub fn feed(&mut self, bytes: &[u8]) {
+        let mut data = std::mem::take(&mut self.pending_utf8);
+        data.extend_from_slice(bytes);
+
+        let mut rest = data.as_slice();
+        loop {
+            match std::str::from_utf8(rest) {
+                Ok(text) => {
+                    text.chars().for_each(|ch| self.process(ch));
+                    break;
+                }
+                Err(error) => {
+                    let (valid, invalid) = rest.split_at(error.valid_up_to());
+                    // SAFETY: `valid_up_to` marca el final de un prefijo UTF-8 válido
+                    let text = unsafe { std::str::from_utf8_unchecked(valid) };
+                    text.chars().for_each(|ch| self.process(ch));
+                    match error.error_len() {
+                        Some(length) => {
+                            self.process(char::REPLACEMENT_CHARACTER);
+                            rest = &invalid[length..];
+                        }
+                        None => {
+                            // Carácter incompleto: se termina con la próxima lectura
+                            self.pending_utf8 = invalid.to_vec();
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn process(&mut self, ch: char) {
+        match self.state {
+            ParserState::Ground => self.ground(ch),
+            ParserState::Escape => self.escape(ch),
+            ParserState::EscapeIntermediate(intermediate) => {
+                self.state = ParserState::Ground;
+                // DECALN (`ESC # 8`) llena la pantalla de 'E'; las tablas de caracteres se ignoran
+                if intermediate == '#' && ch == '8' {
+                    let cell = Cell { ch: 'E', style: CellStyle::default() };
+                    self.grid.iter_mut().for_each(|line| line.fill(cell));
+                }
+            }
+            ParserState::Csi => self.csi(ch),
+            ParserState::Osc => match ch {
+                '\x07' => self.finish_osc(),
+                '\x1b' => self.state = ParserState::OscEscape,
+                _ if self.osc.len() < MAX_SEQUENCE_LENGTH => self.osc.push(ch),
+                _ => {}
+            },
+            ParserState::OscEscape => {
+                self.finish_osc();
+                if ch != '\\' {
+                    self.escape(ch);
+                }
+            }
+            ParserState::Ignored => {
+                if ch == '\x1b' {
+                    self.state = ParserState::IgnoredEscape;
+                }
+            }
+            ParserState::IgnoredEscape => {
+                self.state = if ch == '\\' { ParserState::Ground } else { ParserState::Ignored };
+            }
+        }
+    }
+
+    fn ground(&mut self, ch: char) {
+        if ch.is_control() {
+            self.control(ch);
+        } else {
+            self.print(ch);
+        }
+    }
+
+    /// Caracteres de control C0; también se ejecutan en medio de una secuencia CSI
+    fn control(&mut self, ch: char) {
+        match ch {
+            '\x08' => {
+                self.col = self.col.saturating_sub(1);
+                self.wrap_pending = false;
+            }
+            '\t' => self.tab_forward(1),
+            '\n' | '\x0b' | '\x0c' => self.linefeed(),
+            '\r' => {
+                self.col = 0;
+                self.wrap_pending = false;
+            }
+            '\x1b' => self.state = ParserState::Escape,
+            // CAN y SUB cancelan la secuencia en curso
+            '\x18' | '\x1a' => self.state = ParserState::Ground,
+            // BEL, SO, SI y el resto no cambian la pantalla
+            _ => {}
+        }
+    }
+
+    fn print(&mut self, ch: char) {
+        if self.wrap_pending {
+            self.col = 0;
+            self.linefeed();
+        }
+        if self.insert_mode {
+            self.insert_cells(1);
+        }
+
+        self.grid[self.row][self.col] = Cell { ch, style: self.style };
+        self.last_char = Some(ch);
+        if self.col + 1 < self.cols {
+            self.col += 1;
+        } else {
+            self.wrap_pending = self.autowrap;
+        }
+    }
+
+    fn escape(&mut self, ch: char) {
+        self.state = ParserState::Ground;
+        match ch {
+            '[' => {
+                self.state = ParserState::Csi;
+                self.params.clear();
+                self.private_marker = None;
+                self.intermediate = None;
+            }
+            ']' => {
+                self.state = ParserState::Osc;
+                self.osc.clear();
+            }
+            'P' | 'X' | '^' | '_' => self.state = ParserState::Ignored,
+            '(' | ')' | '*' | '+' | '#' | '%' | ' ' => self.state = ParserState::EscapeIntermediate(ch),
+            '7' => self.save_cursor(),
+            '8' => self.restore_cursor(),
+            'D' => self.linefeed(),
+            'E' => {
+                self.col = 0;
+                self.linefeed();
+            }
+            'M' => self.reverse_index(),
+            'H' => self.tab_stops[self.col] = true,
+            'c' => self.reset(),
+            // Modo numérico o de aplicación del teclado numérico, ST suelto, etc.
+            _ => {}
+        }
+    }
+
+    fn csi(&mut self, ch: char) {
+        match ch {
+            '0'..='9' | ';' | ':' if self.params.len() < MAX_SEQUENCE_LENGTH => self.params.push(ch),
+            '0'..='9' | ';' | ':' => {}
+            '?' | '>' | '<' | '=' if self.params.is_empty() => self.private_marker = Some(ch),
+            ' '..='/' => self.intermediate = Some(ch),
+            '@'..='~' => {
+                self.state = ParserState::Ground;
+                self.dispatch_csi(ch);
+            }
+            _ if ch.is_control() => self.control(ch),
+            _ => {}
+        }
+    }
+
+    /// Valor del parámetro en la posición indicada; 0 o ausente equivale a `default`
+    fn param(&self, index: usize, default: usize) -> usize {
+        match self.raw_param(index) {
+            Some(0) | None => default,
+            Some(value) => value,
+        }
+    }
+
+    fn raw_param(&self, index: usize) -> Option<usize> {
+        self.params
+            .split(';')
+            .nth(index)
+            .and_then(|param| param.split(':').next())
+            .filter(|value| !value.is_empty())
+            // Solo hay dígitos: si no cabe en un número, es demasiado grande
+            .map(|value| value.parse::<usize>().map_or(MAX_PARAM, |value| value.min(MAX_PARAM)))
+    }
+
+    fn dispatch_csi(&mut self, action: char) {
+        if self.intermediate.is_some() {
+            // DECSTR (`CSI ! p`) reinicia los modos; el resto (forma del cursor, etc.) se ignora
+            if self.intermediate == Some('!') && action == 'p' {
+                self.soft_reset();
+            }
+            return;
+        }
+
+        match (self.private_marker, action) {
+            (None, _) => self.dispatch_standard(action),
+            (Some('?'), 'h') => self.set_private_modes(true),
+            (Some('?'), 'l') => self.set_private_modes(false),
+            (Some('?'), 'J') | (Some('?'), 'K') => self.dispatch_standard(action),
+            (Some('?'), 'n') if self.raw_param(0) == Some(6) => {
+                let report = format!("\x1b[?{};{}R", self.row + 1, self.col + 1);
+                self.responses.extend_from_slice(report.as_bytes());
+            }
+            (Some('>'), 'c') => self.responses.extend_from_slice(b"\x1b[>0;10;1c"),
+            _ => {}
+        }
+    }
+
+    fn dispatch_standard(&mut self, action: char) {
+        let count = self.param(0, 1);
+        match action {
+            '@' => self.insert_cells(count),
+            'A' => {
+                let top = if self.row >= self.scroll_top { self.scroll_top } else { 0 };
+                self.move_to(self.row.saturating_sub(count).max(top), self.col);
+            }
+            'B' | 'e' | 'E' => {
+                let bottom = if self.row <= self.scroll_bottom { self.scroll_bottom } else { self.rows - 1 };
+                let col = if action == 'E' { 0 } else { self.col };
+                self.move_to(self.row.saturating_add(count).min(bottom), col);
+            }
+            'C' | 'a' => self.move_to(self.row, self.col.saturating_add(count)),
+            'D' => self.move_to(self.row, self.col.saturating_sub(count)),
+            'F' => {
+                let top = if self.row >= self.scroll_top { self.scroll_top } else { 0 };
+                self.move_to(self.row.saturating_sub(count).max(top), 0);
+            }
+            'G' | '`' => self.move_to(self.row, count - 1),
+            'H' | 'f' => {
+                let row = self.param(0, 1) - 1;
+                let col = self.param(1, 1) - 1;
+                self.move_to_origin(row, col);
+            }
+            'I' => self.tab_forward(count),
+            'Z' => self.tab_backward(count),
+            'J' => self.erase_display(self.raw_param(0).unwrap_or(0)),
+            'K' => self.erase_line(self.raw_param(0).unwrap_or(0)),
+            'L' => self.insert_lines(count),
+            'M' => self.delete_lines(count),
+            'P' => self.delete_cells(count),
+            'S' => self.scroll_up(count),
+            'T' => self.scroll_down(count),
+            'X' => {
+                let end = self.col.saturating_add(count).min(self.cols);
+                let blank = self.blank();
+                self.grid[self.row][self.col..end].fill(blank);
+                self.wrap_pending = false;
+            }
+            'b' => {
+                if let Some(ch) = self.last_char {
+                    for _ in 0..count.min(self.cols * self.rows) {
+                        self.print(ch);
+                    }
+                }
+            }
+            'c' if self.raw_param(0).unwrap_or(0) == 0 => {
+                self.responses.extend_from_slice(b"\x1b[?62;22c");
+            }
+            'd' => {
+                let col = self.col;
+                self.move_to_origin(count - 1, col);
+            }
+            'g' => match self.raw_param(0).unwrap_or(0) {
+                0 => self.tab_stops[self.col] = false,
+                3 => self.tab_stops.fill(false),
+                _ => {}
+            },
+            'h' | 'l' if self.raw_param(0) == Some(4) => self.insert_mode = action == 'h',
+            'm' => {
+                let params = self.params.clone();
+                self.style.apply_sgr(&params);
+            }
+            'n' => match self.raw_param(0) {
+                Some(5) => self.responses.extend_from_slice(b"\x1b[0n"),
+                Some(6) => {
+                    let report = format!("\x1b[{};{}R", self.row + 1, self.col + 1);
+                    self.responses.extend_from_slice(report.as_bytes());
+                }
+                _ => {}
+            },
+            'r' => {
+                let top = self.param(0, 1) - 1;
+                let bottom = self.param(1, self.rows).min(self.rows) - 1;
+                if top < bottom {
+                    self.scroll_top = top;
+                    self.scroll_bottom = bottom;
+                    self.move_to_origin(0, 0);
+                }
+            }
+            's' => self.save_cursor(),
+            'u' => self.restore_cursor(),
+            // De las operaciones de ventana solo se responde al tamaño del área de texto
+            't' if self.raw_param(0) == Some(18) => {
+                let report = format!("\x1b[8;{};{}t", self.rows, self.cols);
+                self.responses.extend_from_slice(report.as_bytes());
+            }
+            _ => {}
+        }
+    }
+
+    fn set_private_modes(&mut self, enabled: bool) {
+        let modes: Vec<usize> = self
+            .params
+            .split(';')
+            .filter_map(|mode| mode.parse().ok())
+            .collect();
+        for mode in modes {
+            match mode {
+                1 => self.application_cursor_keys = enabled,
+                6 => {
+                    self.origin_mode = enabled;
+                    self.move_to_origin(0, 0);
+                }
+                7 => self.autowrap = enabled,
+                25 => self.cursor_visible = enabled,
+                47 | 1047 => self.switch_screen(enabled),
+                1048 => {
+                    if enabled {
+                        self.save_cursor();
+                    } else {
+                        self.restore_cursor();
+                    }
+                }
+                1049 => {
+                    if enabled {
+                        self.save_cursor();
+                        self.switch_screen(true);
+                    } else {
+                        self.switch_screen(false);
+                        self.restore_cursor();
+                    }
+                }
+                2004 => self.bracketed_paste = enabled,
+                // Ratón, parpadeo del cursor y demás modos no afectan a la rejilla
+                _ => {}
+            }
+        }
+    }
+
+    fn finish_osc(&mut self) {
+        self.state = ParserState::Ground;
+        if let Some((code, text)) = self.osc.split_once(';') {
+            if code == "0" || code == "2" {
+                self.title = text.to_string();
+            }
+        }
+        self.osc.clear();
+    }
+
+    /// Activa la pantalla alternativa (vacía) o vuelve a la principal
+    fn switch_screen(&mut self, alternate: bool) {
+        if alternate && self.primary.is_none() {
+            let blank = vec![vec![Cell::default(); self.cols]; self.rows];
+            self.primary = Some(std::mem::replace(&mut self.grid, blank));
+        } else if !alternate {
+            if let Some(primary) = self.primary.take() {
+                self.grid = primary;
+            }
+        }
+        self.wrap_pending = false;
+    }
+
+    fn save_cursor(&mut self) {
+        self.saved_cursor = Some(SavedCursor {
+            row: self.row,
+            col: self.col,
+            style: self.style,
+            origin_mode: self.origin_mode,
+            autowrap: self.autowrap,
+        });
+    }
+
+    fn restore_cursor(&mut self) {
+        let saved = self.saved_cursor.unwrap_or_default();
+        self.row = saved.row.min(self.rows - 1);
+        self.col = saved.col.min(self.cols - 1);
+        self.style = saved.style;
+        self.origin_mode = saved.origin_mode;
+        self.autowrap = self.saved_cursor.is_none_or(|saved| saved.autowrap);
+        self.wrap_pending = false;
+    }
+
+    /// Reinicio completo (`ESC c`); se conservan el tamaño y el historial
+    fn reset(&mut self) {
+        let scrollback = std::mem::take(&mut self.scrollback);
+        let responses = std::mem::take(&mut self.responses);
+        *self = Screen::new(self.cols, self.rows);
+        self.scrollback = scrollback;
+        self.responses = responses;
+    }
+
+    /// Reinicio de modos (DECSTR) sin borrar la pantalla
+    fn soft_reset(&mut self) {
+        self.style = CellStyle::default();
+        self.insert_mode = false;
+        self.origin_mode = false;
+        self.autowrap = true;
+        self.cursor_visible = true;
+        self.application_cursor_keys = false;
+        self.scroll_top = 0;
+        self.scroll_bottom = self.rows - 1;
+        self.saved_cursor = None;
+    }
+
+    /// Celda vacía con el fondo actual, como hace xterm al borrar
+    fn blank(&self) -> Cell {
+        Cell {
+            ch: ' ',
+            style: CellStyle { bg: self.style.bg, ..CellStyle::default() },
+        }
+    }
+
+    fn move_to(&mut self, row: usize, col: usize) {
+        self.row = row.min(self.rows - 1);
+        self.col = col.min(self.cols - 1);
+        self.wrap_pending = false;
+    }
+
+    /// Posiciona el cursor teniendo en cuenta el modo de origen (DECOM)
+    fn move_to_origin(&mut self, row: usize, col: usize) {
+        if self.origin_mode {
+            let row = self.scroll_top.saturating_add(row).min(self.scroll_bottom);
+            self.move_to(row, col);
+        } else {
+            self.move_to(row, col);
+        }
+    }
+
+    fn linefeed(&mut self) {
+        self.wrap_pending = false;
+        if self.row == self.scroll_bottom {
+            self.scroll_up(1);
+        } else if self.row + 1 < self.rows {
+            self.row += 1;
+        }
+    }
+
+    fn reverse_index(&mut self) {
+        self.wrap_pending = false;
+        if self.row == self.scroll_top {
+            self.scroll_down(1);
+        } else if self.row > 0 {
+            self.row -= 1;
+        }
+    }
+
+    /// Desplaza hacia arriba la región de desplazamiento
+    fn scroll_up(&mut self, count: usize) {
+        let count = count.min(self.scroll_bottom - self.scroll_top + 1);
+        for _ in 0..count {
+            let line = self.grid.remove(self.scroll_top);
+            if self.scroll_top == 0 && self.primary.is_none() {
+                self.push_scrollback(line);
+            }
+            self.grid.insert(self.scroll_bottom, vec![self.blank(); self.cols]);
+        }
+    }
+
+    /// Desplaza hacia abajo la región de desplazamiento
+    fn scroll_down(&mut self, count: usize) {
+        let count = count.min(self.scroll_bottom - self.scroll_top + 1);
+        for _ in 0..count {
+            self.grid.remove(self.scroll_bottom);
+            self.grid.insert(self.scroll_top, vec![self.blank(); self.cols]);
+        }
+    }
+
+    fn push_scrollback(&mut self, line: Vec<Cell>) {
+        if self.scrollback.len() == MAX_SCROLLBACK {
+            self.scrollback.pop_front();
+        }
+        self.scrollback.push_back(line);
+    }
+
+    fn insert_lines(&mut self, count: usize) {
+        if self.row < self.scroll_top || self.row > self.scroll_bottom {
+            return;
+        }
+        let count = count.min(self.scroll_bottom - self.row + 1);
+        for _ in 0..count {
+            self.grid.remove(self.scroll_bottom);
+            self.grid.insert(self.row, vec![self.blank(); self.cols]);
+        }
+        self.col = 0;
+        self.wrap_pending = false;
+    }
+
+    fn delete_lines(&mut self, count: usize) {
+        if self.row < self.scroll_top || self.row > self.scroll_bottom {
+            return;
+        }
+        let count = count.min(self.scroll_bottom - self.row + 1);
+        for _ in 0..count {
+            self.grid.remove(self.row);
+            self.grid.insert(self.scroll_bottom, vec![self.blank(); self.cols]);
+        }
+        self.col = 0;
+        self.wrap_pending = false;
+    }
+
+    fn insert_cells(&mut self, count: usize) {
+        let blank = self.blank();
+        let line = &mut self.grid[self.row];
+        let count = count.min(self.cols - self.col);
+        line.truncate(self.cols - count);
+        line.splice(self.col..self.col, std::iter::repeat_n(blank, count));
+        self.wrap_pending = false;
+    }
+
+    fn delete_cells(&mut self, count: usize) {
+        let blank = self.blank();
+        let line = &mut self.grid[self.row];
+        let count = count.min(self.cols - self.col);
+        line.drain(self.col..self.col + count);
+        line.extend(std::iter::repeat_n(blank, count));
+        self.wrap_pending = false;
+    }
+
+    fn erase_display(&mut self, mode: usize) {
+        let blank = self.blank();
+        match mode {
+            0 => {
+                self.grid[self.row][self.col..].fill(blank);
+                self.grid[self.row + 1..].iter_mut().for_each(|line| line.fill(blank));
+            }
+            1 => {
+                self.grid[..self.row].iter_mut().for_each(|line| line.fill(blank));
+                self.grid[self.row][..=self.col].fill(blank);
+            }
+            2 => self.grid.iter_mut().for_each(|line| line.fill(blank)),
+            3 => {
+                self.grid.iter_mut().for_each(|line| line.fill(blank));
+                self.scrollback.clear();
+            }
+            _ => {}
+        }
+        self.wrap_pending = false;
+    }
+
+    fn erase_line(&mut self, mode: usize) {
+        let blank = self.blank();
+        let line = &mut self.grid[self.row];
+        match mode {
+            0 => line[self.col..].fill(blank),
+            1 => line[..=self.col].fill(blank),
+            2 => line.fill(blank),
+            _ => {}
+        }
+        self.wrap_pending = false;
+    }
+
+    fn tab_forward(&mut self, count: usize) {
+        for _ in 0..count {
+            let next = (self.col + 1..self.cols).find(|col| self.tab_stops[*col]);
+            self.col = next.unwrap_or(self.cols - 1);
+        }
+        self.wrap_pending = false;
+    }
+
+    fn tab_backward(&mut self, count: usize) {
+        for _ in 0..count {
+            let previous = (0..self.col).rev().find(|col| self.tab_stops[*col]);
+            self.col = previous.unwrap_or(0);
+        }
+        self.wrap_pending = false;
+    }
+
+    fn default_tab_stops(cols: usize) -> Vec<bool> {
+        (0..cols).map(|col| col > 0 && col % TAB_WIDTH == 0).collect()
+    }
+
+    fn resize_grid(grid: &mut Vec<Vec<Cell>>, cols: usize, rows: usize) {
+        grid.resize_with(rows, || vec![Cell::default(); cols]);
+        grid.iter_mut().for_each(|line| line.resize(cols, Cell::default()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::style::Color;
+
+    fn screen(cols: usize, rows: usize, bytes: &str) -> Screen {
+        let mut screen = Screen::new(cols, rows);
+        screen.feed(bytes.as_bytes());
+        screen
+    }
+
+    /// Texto de cada fila visible, sin los espacios finales
+    fn rows(screen: &Screen) -> Vec<String> {
+        screen
+            .lines()
+            .iter()
+            .map(|line| line.iter().map(|cell| cell.ch).collect::<String>().trim_end().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn prints_and_wraps_at_the_last_column() {
+        let screen = screen(4, 3, "abcdef");
+        assert_eq!(rows(&screen), ["abcd", "ef", ""]);
+        assert_eq!(screen.cursor(), (1, 2));
+    }
+
+    #[test]
+    fn cursor_waits_at_the_last_column_until_the_next_character() {
+        let screen = screen(4, 2, "abcd");
+        assert_eq!(screen.cursor(), (0, 3));
+        assert_eq!(rows(&screen), ["abcd", ""]);
+    }
+
+    #[test]
+    fn autowrap_can_be_disabled() {
+        let screen = screen(4, 2, "\x1b[?7labcdef");
+        assert_eq!(rows(&screen), ["abcf", ""]);
+    }
+
+    #[test]
+    fn control_characters_move_the_cursor() {
+        let screen = screen(20, 3, "abc\rX\nY\tZ\x08W");
+        assert_eq!(rows(&screen), ["Xbc", " Y      W", ""]);
+    }
+
+    #[test]
+    fn linefeed_at_the_bottom_scrolls_into_the_scrollback() {
+        let screen = screen(5, 2, "uno\r\ndos\r\ntres");
+        assert_eq!(rows(&screen), ["dos", "tres"]);
+        let text: Vec<String> = screen.text_lines().iter().map(StyledLine::text).collect();
+        assert_eq!(text, ["uno", "dos", "tres"]);
+    }
+
+    #[test]
+    fn cursor_position_is_one_based_and_clamped() {
+        let mut screen = screen(10, 5, "\x1b[2;3H");
+        assert_eq!(screen.cursor(), (1, 2));
+        screen.feed(b"\x1b[99;99H");
+        assert_eq!(screen.cursor(), (4, 9));
+        screen.feed(b"\x1b[H");
+        assert_eq!(screen.cursor(), (0, 0));
+        screen.feed(b"\x1b[3B\x1b[4C\x1b[A\x1b[2D");
+        assert_eq!(screen.cursor(), (2, 2));
+    }
+
+    #[test]
+    fn huge_parameters_are_clamped_instead_of_overflowing() {
+        let huge = "9".repeat(40);
+        let mut screen = screen(10, 5, "x");
+        for action in ["A", "B", "C", "D", "E", "F", "G", "H", "d", "@", "P", "L", "M", "S", "T", "X", "I", "Z", "b", "r"] {
+            screen.feed(format!("\x1b[{};{}{}", huge, huge, action).as_bytes());
+        }
+        screen.feed(format!("\x1b[{}C\x1b[{}B", usize::MAX, usize::MAX).as_bytes());
+        assert_eq!(screen.cursor(), (4, 9));
+        assert_eq!(screen.size(), (10, 5));
+    }
+
+    #[test]
+    fn overlong_sequences_are_truncated_and_the_parser_recovers() {
+        let params = "1;".repeat(MAX_SEQUENCE_LENGTH);
+        let screen = screen(10, 2, &format!("\x1b[{}m\x1b[1mok", params));
+        assert_eq!(rows(&screen), ["ok", ""]);
+        assert!(screen.lines()[0][0].style.bold);
+    }
+
+    #[test]
+    fn sequences_split_between_reads_are_completed() {
+        let mut screen = Screen::new(10, 2);
+        screen.feed(b"\x1b[");
+        screen.feed(b"31");
+        screen.feed(b"mr\xc3");
+        screen.feed(b"\xa9");
+        assert_eq!(rows(&screen), ["ré", ""]);
+        assert_eq!(screen.lines()[0][0].style.fg, Color::Indexed(1));
+        assert_eq!(screen.lines()[0][1].style.fg, Color::Indexed(1));
+    }
+
+    #[test]
+    fn invalid_utf8_is_replaced() {
+        let mut screen = Screen::new(10, 1);
+        screen.feed(b"a\xffb");
+        assert_eq!(rows(&screen), ["a\u{fffd}b"]);
+    }
+
+    #[test]
+    fn erase_display_and_line() {
+        let mut screen = screen(5, 3, "aaaaa\r\nbbbbb\r\nccccc\x1b[2;3H");
+        screen.feed(b"\x1b[K");
+        assert_eq!(rows(&screen), ["aaaaa", "bb", "ccccc"]);
+        screen.feed(b"\x1b[1J");
+        assert_eq!(rows(&screen), ["", "", "ccccc"]);
+        screen.feed(b"\x1b[2J");
+        assert_eq!(rows(&screen), ["", "", ""]);
+    }
+
+    #[test]
+    fn erase_fills_with_the_current_background() {
+        let screen = screen(3, 1, "\x1b[44m\x1b[2K");
+        assert!(screen.lines()[0].iter().all(|cell| cell.style.bg == Color::Indexed(4)));
+    }
+
+    #[test]
+    fn insert_and_delete_characters() {
+        let mut screen = screen(6, 1, "abcdef\x1b[1;2H\x1b[2@");
+        assert_eq!(rows(&screen), ["a  bcd"]);
+        screen.feed(b"\x1b[3P");
+        assert_eq!(rows(&screen), ["acd"]);
+    }
+
+    #[test]
+    fn scroll_region_limits_linefeeds_and_line_insertion() {
+        let mut screen = screen(3, 4, "1\r\n2\r\n3\r\n4\x1b[2;3r\x1b[3;1H\n");
+        assert_eq!(rows(&screen), ["1", "3", "", "4"]);
+        screen.feed(b"\x1b[2;1H\x1b[L");
+        assert_eq!(rows(&screen), ["1", "", "3", "4"]);
+    }
+
+    #[test]
+    fn alternate_screen_keeps_the_primary_contents() {
+        let mut screen = screen(5, 2, "hola\x1b[?1049h");
+        assert_eq!(rows(&screen), ["", ""]);
+        screen.feed(b"vim");
+        screen.feed(b"\x1b[?1049l");
+        assert_eq!(rows(&screen), ["hola", ""]);
+        assert_eq!(screen.cursor(), (0, 4));
+    }
+
+    #[test]
+    fn save_and_restore_cursor_with_its_style() {
+        let screen = screen(10, 3, "\x1b[2;4H\x1b[1m\x1b7\x1b[0m\x1b[H\x1b8x");
+        assert_eq!(screen.cursor(), (1, 4));
+        assert!(screen.lines()[1][3].style.bold);
+    }
+
+    #[test]
+    fn private_modes_are_reported() {
+        let mut screen = screen(5, 2, "\x1b[?1;2004h\x1b[?25l");
+        assert!(screen.application_cursor_keys());
+        assert!(screen.bracketed_paste());
+        assert!(!screen.cursor_visible());
+        screen.feed(b"\x1b[?1;2004l\x1b[?25h");
+        assert!(!screen.application_cursor_keys());
+        assert!(!screen.bracketed_paste());
+        assert!(screen.cursor_visible());
+    }
+
+    #[test]
+    fn queries_are_answered() {
+        let mut screen = screen(80, 24, "\x1b[3;5H\x1b[6n\x1b[5n\x1b[18t");
+        assert_eq!(screen.take_responses(), b"\x1b[3;5R\x1b[0n\x1b[8;24;80t");
+        assert!(screen.take_responses().is_empty());
+    }
+
+    #[test]
+    fn osc_sets_the_title_and_other_strings_are_ignored() {
+        let screen = screen(10, 1, "\x1b]0;título\x07\x1b]2;otro\x1b\\\x1bPdatos\x1b\\ok");
+        assert_eq!(screen.title(), "otro");
+        assert_eq!(rows(&screen), ["ok"]);
+    }
+
+    #[test]
+    fn resize_moves_lines_above_the_cursor_to_the_scrollback() {
+        let mut screen = screen(5, 3, "a\r\nb\r\nc");
+        screen.resize(3, 2);
+        assert_eq!(rows(&screen), ["b", "c"]);
+        assert_eq!(screen.cursor(), (1, 1));
+        let text: Vec<String> = screen.text_lines().iter().map(StyledLine::text).collect();
+        assert_eq!(text, ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn scrollback_keeps_only_the_newest_lines() {
+        let lines: Vec<String> = (0..MAX_SCROLLBACK + 100).map(|n| n.to_string()).collect();
+        let screen = screen(5, 1, &lines.join("\r\n"));
+        let text: Vec<String> = screen.text_lines().iter().map(StyledLine::text).collect();
+        assert_eq!(text.len(), MAX_SCROLLBACK + 1);
+        assert_eq!(text.first().map(String::as_str), Some("99"));
+        assert_eq!(text.last().map(String::as_str), Some("1099"));
+    }
+
+    #[test]
+    fn resize_drops_lines_below_the_cursor_when_it_is_at_the_top() {
+        let mut screen = screen(5, 3, "a\x1b[Hb");
+        screen.resize(5, 1);
+        assert_eq!(rows(&screen), ["b"]);
+        assert_eq!(screen.text_lines().len(), 1);
+    }
+
+    #[test]
+    fn full_reset_clears_the_screen_but_keeps_the_scrollback() {
+        let screen = screen(5, 1, "uno\r\ndos\x1b[1m\x1bc");
+        assert_eq!(rows(&screen), [""]);
+        assert_eq!(screen.cursor(), (0, 0));
+        let text: Vec<String> = screen.text_lines().iter().map(StyledLine::text).collect();
+        assert_eq!(text, ["uno"]);
+    }
+}
@@ -1,5 +1,11 @@
-use crate::domain::entities::{Command, TerminalState};
+use crate::domain::ast::CommandLine;
+use crate::domain::builtins::{BuiltinCommand, CommandCatalog, Session};
+use crate::domain::entities::{Command, EventSender, PipelineOptions, PipelineStage, TerminalState};
+use crate::domain::errors::TermirustError;
+use crate::domain::jobs::SharedJobTable;
+use crate::domain::parser::{ParseError, Parser};
 use crate::domain::repositories::CommandRepository;
+use crate::domain::style::{CellStyle, StyledLine};
 
 /// Servicio que maneja la lógica de comandos
 pub struct CommandService<R>
@@ -17,20 +23,43 @@ where
         Self { repository }
     }
 
-    pub fn execute_command(&self, command: &Command) -> Result<Command, String> {
-        self.repository.execute_command(command)
+    pub fn execute_command(
+        &self,
+        command: &Command,
+        environment: &[(String, String)],
+        session: Session,
+    ) -> Result<Command, TermirustError> {
+        self.repository.execute_command(command, environment, session)
     }
 
-    pub fn parse_command(&self, input: &str) -> Command {
-        let parts: Vec<&str> = input.trim().split_whitespace().collect();
-        if parts.is_empty() {
-            return Command::new(String::new(), Vec::new());
-        }
+    pub fn is_builtin(&self, command: &Command) -> bool {
+        self.repository.is_builtin(command)
+    }
+
+    pub fn register_builtin(&mut self, command: Box<dyn BuiltinCommand>) {
+        self.repository.register_builtin(command);
+    }
+
+    pub fn command_catalog(&self) -> CommandCatalog {
+        self.repository.command_catalog()
+    }
 
-        let name = parts[0].to_string();
-        let args = parts[1..].iter().map(|s| s.to_string()).collect();
+    pub fn execute_pipeline(
+        &self,
+        stages: Vec<PipelineStage>,
+        options: &PipelineOptions,
+        events: Option<&EventSender>,
+    ) -> Result<Command, TermirustError> {
+        self.repository.execute_pipeline(stages, options, events)
+    }
 
-        Command::new(name, args)
+    pub fn job_table(&self) -> SharedJobTable {
+        self.repository.job_table()
+    }
+
+    /// Analiza la entrada respetando comillas y escapes y devuelve su árbol sintáctico
+    pub fn parse_command(&self, input: &str) -> Result<CommandLine, ParseError> {
+        Parser::parse(input)
     }
 }
 
@@ -56,19 +85,52 @@ impl TerminalService {
         &mut self.state
     }
 
-    pub fn switch_mode(&mut self, mode: crate::domain::entities::TerminalMode) {
-        self.state.mode = mode;
+    /// Agrega una línea de salida interpretando sus secuencias de color ANSI. Si la
+    /// última línea quedó sin terminar, el texto la completa.
+    pub fn add_output_line(&mut self, line: String) {
+        self.append_output(&line);
+        self.state.open_line = false;
     }
 
-    pub fn add_output_line(&mut self, line: String) {
+    /// Escribe texto sin terminar la línea; lo siguiente que llegue la continúa
+    pub fn add_partial_output(&mut self, text: String) {
+        self.append_output(&text);
+        self.state.open_line = true;
+    }
+
+    fn append_output(&mut self, text: &str) {
+        match self.state.output_lines.last_mut() {
+            Some(line) if self.state.open_line => {
+                line.append_ansi(text, CellStyle::default());
+            }
+            _ => self.add_styled_line(StyledLine::parse_ansi(text)),
+        }
+    }
+
+    /// Agrega una línea con sus estilos ya resueltos. Si la última quedó sin terminar,
+    /// la completa, como hace una terminal.
+    pub fn add_styled_line(&mut self, line: StyledLine) {
+        if let (true, Some(last)) = (self.state.open_line, self.state.output_lines.last_mut()) {
+            last.spans.extend(line.spans);
+            self.state.open_line = false;
+            return;
+        }
         self.state.output_lines.push(line);
+        self.state.open_line = false;
         if self.state.output_lines.len() > 1000 {
             self.state.output_lines.remove(0);
         }
     }
 
+    /// Como `add_partial_output`, con los estilos ya resueltos
+    pub fn add_partial_styled_line(&mut self, line: StyledLine) {
+        self.add_styled_line(line);
+        self.state.open_line = true;
+    }
+
     pub fn clear_output(&mut self) {
         self.state.output_lines.clear();
+        self.state.open_line = false;
     }
 
     pub fn should_exit(&self) -> bool {
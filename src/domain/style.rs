@@ -0,0 +1,405 @@
+/// Color de texto o de fondo tal como lo describen las secuencias ANSI
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Color {
+    /// Color por defecto de la terminal
+    #[default]
+    Default,
+    /// Paleta de 256 colores: 0-7 normales, 8-15 brillantes, 16-231 cubo RGB, 232-255 grises
+    Indexed(u8),
+    /// Color verdadero de 24 bits
+    Rgb(u8, u8, u8),
+}
+
+/// Atributos visuales de un carácter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CellStyle {
+    pub fg: Color,
+    pub bg: Color,
+    pub bold: bool,
+    pub dim: bool,
+    pub italic: bool,
+    pub underline: bool,
+    pub inverse: bool,
+    pub hidden: bool,
+    pub strikethrough: bool,
+}
+
+impl CellStyle {
+    /// Aplica los parámetros de una secuencia SGR (`ESC[...m`), por ejemplo `1;31` o
+    /// `38;2;255;128;0`. Se aceptan también subparámetros con `:` (`38:5:208`).
+    pub fn apply_sgr(&mut self, params: &str) {
+        let groups: Vec<Vec<Option<u32>>> = params
+            .split(';')
+            .map(|group| group.split(':').map(|value| value.parse().ok()).collect())
+            .collect();
+
+        let mut index = 0;
+        while index < groups.len() {
+            let group = &groups[index];
+            index += 1;
+            match group[0].unwrap_or(0) {
+                0 => *self = CellStyle::default(),
+                1 => self.bold = true,
+                2 => self.dim = true,
+                3 => self.italic = true,
+                4 => self.underline = group.get(1).copied().flatten().unwrap_or(1) != 0,
+                7 => self.inverse = true,
+                8 => self.hidden = true,
+                9 => self.strikethrough = true,
+                21 => self.underline = true,
+                22 => {
+                    self.bold = false;
+                    self.dim = false;
+                }
+                23 => self.italic = false,
+                24 => self.underline = false,
+                27 => self.inverse = false,
+                28 => self.hidden = false,
+                29 => self.strikethrough = false,
+                code @ 30..=37 => self.fg = Color::Indexed((code - 30) as u8),
+                38 => {
+                    if let Some(color) = Self::extended_color(group, &groups, &mut index) {
+                        self.fg = color;
+                    }
+                }
+                39 => self.fg = Color::Default,
+                code @ 40..=47 => self.bg = Color::Indexed((code - 40) as u8),
+                48 => {
+                    if let Some(color) = Self::extended_color(group, &groups, &mut index) {
+                        self.bg = color;
+                    }
+                }
+                49 => self.bg = Color::Default,
+                code @ 90..=97 => self.fg = Color::Indexed((code - 90 + 8) as u8),
+                code @ 100..=107 => self.bg = Color::Indexed((code - 100 + 8) as u8),
+                // Parpadeo, fuentes alternativas y demás atributos no se representan
+                _ => {}
+            }
+        }
+    }
+
+    /// Lee un color extendido (`38`/`48`): `5;n` para la paleta o `2;r;g;b` para RGB,
+    /// ya sea en los grupos siguientes o como subparámetros del mismo grupo
+    fn extended_color(group: &[Option<u32>], groups: &[Vec<Option<u32>>], index: &mut usize) -> Option<Color> {
+        let channel = |value: Option<u32>| value.unwrap_or(0).min(255) as u8;
+
+        if group.len() > 1 {
+            return match group[1] {
+                Some(5) => group.get(2).map(|value| Color::Indexed(channel(*value))),
+                Some(2) => {
+                    // 38:2:r:g:b o 38:2:espacio:r:g:b
+                    let rgb = if group.len() >= 6 { &group[3..6] } else { group.get(2..5)? };
+                    Some(Color::Rgb(channel(rgb[0]), channel(rgb[1]), channel(rgb[2])))
+                }
+                _ => None,
+            };
+        }
+
+        let value = |offset: usize| groups.get(*index + offset).and_then(|group| group[0]);
+        match value(0) {
+            Some(5) => {
+                let color = Color::Indexed(channel(value(1)));
+                *index += 2;
+                Some(color)
+            }
+            Some(2) => {
+                let color = Color::Rgb(channel(value(1)), channel(value(2)), channel(value(3)));
+                *index += 4;
+                Some(color)
+            }
+            _ => None,
+        }
+    }
+
+    /// Secuencia SGR que reproduce este estilo partiendo del estilo por defecto
+    pub fn to_sgr(self) -> String {
+        let mut codes = vec!["0".to_string()];
+        let flags = [
+            (self.bold, "1"),
+            (self.dim, "2"),
+            (self.italic, "3"),
+            (self.underline, "4"),
+            (self.inverse, "7"),
+            (self.hidden, "8"),
+            (self.strikethrough, "9"),
+        ];
+        codes.extend(flags.iter().filter(|(enabled, _)| *enabled).map(|(_, code)| code.to_string()));
+        codes.extend(Self::color_sgr(self.fg, 30, 90, 38));
+        codes.extend(Self::color_sgr(self.bg, 40, 100, 48));
+        format!("\x1b[{}m", codes.join(";"))
+    }
+
+    fn color_sgr(color: Color, base: u8, bright_base: u8, extended: u8) -> Option<String> {
+        match color {
+            Color::Default => None,
+            Color::Indexed(index @ 0..=7) => Some((base + index).to_string()),
+            Color::Indexed(index @ 8..=15) => Some((bright_base + index - 8).to_string()),
+            Color::Indexed(index) => Some(format!("{};5;{}", extended, index)),
+            Color::Rgb(r, g, b) => Some(format!("{};2;{};{};{}", extended, r, g, b)),
+        }
+    }
+}
+
+/// Fragmento de texto con un mismo estilo
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StyledSpan {
+    pub text: String,
+    pub style: CellStyle,
+}
+
+/// Línea de salida dividida en fragmentos con estilo
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct StyledLine {
+    pub spans: Vec<StyledSpan>,
+}
+
+impl StyledLine {
+    /// Separación entre tabulaciones al expandirlas a espacios
+    const TAB_WIDTH: usize = 8;
+
+    /// Interpreta las secuencias SGR de una línea de salida. Las demás secuencias de
+    /// escape y los caracteres de control se descartan.
+    pub fn parse_ansi(text: &str) -> Self {
+        Self::parse_ansi_from(text, CellStyle::default()).0
+    }
+
+    /// Igual que `parse_ansi`, partiendo de un estilo heredado de líneas anteriores.
+    /// Devuelve también el estilo que queda activo al final de la línea.
+    pub fn parse_ansi_from(text: &str, style: CellStyle) -> (Self, CellStyle) {
+        let mut line = StyledLine::default();
+        let style = line.append_ansi(text, style);
+        (line, style)
+    }
+
+    /// Continúa la línea con más texto, como hace una terminal cuando un programa
+    /// escribe sin salto de línea. Devuelve el estilo activo al final.
+    pub fn append_ansi(&mut self, text: &str, style: CellStyle) -> CellStyle {
+        let mut style = style;
+        let mut column = self.text().chars().count();
+        let mut chars = text.chars().peekable();
+
+        while let Some(ch) = chars.next() {
+            match ch {
+                '\x1b' => match chars.next() {
+                    Some('[') => {
+                        let mut params = String::new();
+                        let mut action = None;
+                        for ch in chars.by_ref() {
+                            if ('@'..='~').contains(&ch) {
+                                action = Some(ch);
+                                break;
+                            }
+                            params.push(ch);
+                        }
+                        let is_private = params.starts_with(['?', '>', '<', '=']);
+                        if action == Some('m') && !is_private {
+                            style.apply_sgr(&params);
+                        }
+                    }
+                    Some(']') => {
+                        // OSC (títulos, hipervínculos): hasta BEL o ESC \
+                        while let Some(ch) = chars.next() {
+                            if ch == '\x07' || (ch == '\x1b' && chars.next_if_eq(&'\\').is_some()) {
+                                break;
+                            }
+                        }
+                    }
+                    _ => {}
+                },
+                // Un retorno de carro sin salto de línea reescribe la línea (barras de progreso)
+                '\r' => {
+                    self.spans.clear();
+                    column = 0;
+                }
+                '\t' => {
+                    let spaces = Self::TAB_WIDTH - column % Self::TAB_WIDTH;
+                    self.push(&" ".repeat(spaces), style);
+                    column += spaces;
+                }
+                _ if ch.is_control() => {}
+                _ => {
+                    self.push(ch.encode_utf8(&mut [0; 4]), style);
+                    column += 1;
+                }
+            }
+        }
+        style
+    }
+
+    /// Quita los espacios finales sin estilo visible, como los del relleno de una pantalla
+    pub fn trim_end(mut self) -> Self {
+        while let Some(span) = self.spans.last_mut() {
+            if span.style.bg != Color::Default || span.style.inverse {
+                break;
+            }
+            let trimmed = span.text.trim_end_matches(' ').len();
+            span.text.truncate(trimmed);
+            if !span.text.is_empty() {
+                break;
+            }
+            self.spans.pop();
+        }
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.spans.iter().all(|span| span.text.is_empty())
+    }
+
+    /// Texto de la línea sin estilos
+    pub fn text(&self) -> String {
+        self.spans.iter().map(|span| span.text.as_str()).collect()
+    }
+
+    /// Agrega texto, uniéndolo al último fragmento si tiene el mismo estilo
+    pub fn push(&mut self, text: &str, style: CellStyle) {
+        match self.spans.last_mut() {
+            Some(span) if span.style == style => span.text.push_str(text),
+            _ => self.spans.push(StyledSpan {
+                text: text.to_string(),
+                style,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sgr(params: &str) -> CellStyle {
+        let mut style = CellStyle::default();
+        style.apply_sgr(params);
+        style
+    }
+
+    #[test]
+    fn attributes_are_set_and_cleared() {
+        let style = sgr("1;2;3;4;7;8;9");
+        assert!(style.bold && style.dim && style.italic && style.underline);
+        assert!(style.inverse && style.hidden && style.strikethrough);
+
+        let mut cleared = style;
+        cleared.apply_sgr("22;23;24;27;28;29");
+        assert_eq!(cleared, CellStyle::default());
+    }
+
+    #[test]
+    fn empty_or_zero_resets_everything() {
+        let mut style = sgr("1;31;44");
+        style.apply_sgr("");
+        assert_eq!(style, CellStyle::default());
+
+        let mut style = sgr("1;31");
+        style.apply_sgr("0");
+        assert_eq!(style, CellStyle::default());
+
+        assert_eq!(sgr("1;;4"), CellStyle { underline: true, ..CellStyle::default() });
+    }
+
+    #[test]
+    fn basic_and_bright_colors() {
+        let style = sgr("31;42");
+        assert_eq!(style.fg, Color::Indexed(1));
+        assert_eq!(style.bg, Color::Indexed(2));
+
+        let style = sgr("97;100");
+        assert_eq!(style.fg, Color::Indexed(15));
+        assert_eq!(style.bg, Color::Indexed(8));
+
+        let mut style = sgr("31;42");
+        style.apply_sgr("39;49");
+        assert_eq!(style, CellStyle::default());
+    }
+
+    #[test]
+    fn extended_colors_with_semicolons() {
+        let style = sgr("38;5;208;48;2;10;20;30;1");
+        assert_eq!(style.fg, Color::Indexed(208));
+        assert_eq!(style.bg, Color::Rgb(10, 20, 30));
+        assert!(style.bold);
+    }
+
+    #[test]
+    fn extended_colors_with_colons() {
+        assert_eq!(sgr("38:5:208").fg, Color::Indexed(208));
+        assert_eq!(sgr("38:2:1:2:3").fg, Color::Rgb(1, 2, 3));
+        // Con el identificador de espacio de color opcional
+        assert_eq!(sgr("48:2::1:2:3").bg, Color::Rgb(1, 2, 3));
+    }
+
+    #[test]
+    fn out_of_range_channels_are_clamped() {
+        assert_eq!(sgr("38;2;300;0;999").fg, Color::Rgb(255, 0, 255));
+        assert_eq!(sgr("38;5;1000").fg, Color::Indexed(255));
+    }
+
+    #[test]
+    fn incomplete_extended_colors_do_not_panic() {
+        assert_eq!(sgr("38").fg, Color::Default);
+        assert_eq!(sgr("38;5").fg, Color::Indexed(0));
+        assert_eq!(sgr("38;2;1").fg, Color::Rgb(1, 0, 0));
+        assert_eq!(sgr("38:2:1").fg, Color::Default);
+        assert_eq!(sgr("38;7;1").fg, Color::Default);
+    }
+
+    #[test]
+    fn underline_subparameter_zero_removes_it() {
+        assert!(sgr("4:3").underline);
+        assert!(!sgr("4;4:0").underline);
+    }
+
+    #[test]
+    fn unknown_codes_are_ignored() {
+        assert_eq!(sgr("5;6;51;x"), CellStyle::default());
+        assert!(sgr("999;1").bold);
+    }
+
+    #[test]
+    fn to_sgr_round_trips() {
+        for params in ["1;3;31;44", "38;5;208", "48;2;1;2;3;7", "92;103;9", ""] {
+            let style = sgr(params);
+            let sequence = style.to_sgr();
+            let inner = &sequence[2..sequence.len() - 1];
+            assert_eq!(sgr(inner), style, "{}", sequence);
+        }
+        assert_eq!(CellStyle::default().to_sgr(), "\x1b[0m");
+    }
+
+    #[test]
+    fn parse_ansi_splits_spans_by_style() {
+        let line = StyledLine::parse_ansi("a\x1b[1mb\x1b[0mc");
+        let spans: Vec<(&str, bool)> = line.spans.iter().map(|span| (span.text.as_str(), span.style.bold)).collect();
+        assert_eq!(spans, [("a", false), ("b", true), ("c", false)]);
+    }
+
+    #[test]
+    fn parse_ansi_drops_other_sequences() {
+        let line = StyledLine::parse_ansi("\x1b[2Ja\x1b]0;título\x07b\x1b]8;;url\x1b\\c\x1b[?25l\x07d");
+        assert_eq!(line.text(), "abcd");
+        assert!(line.spans.iter().all(|span| span.style == CellStyle::default()));
+    }
+
+    #[test]
+    fn style_carries_over_between_lines() {
+        let (first, style) = StyledLine::parse_ansi_from("\x1b[31mrojo", CellStyle::default());
+        assert_eq!(first.spans[0].style.fg, Color::Indexed(1));
+        let (second, _) = StyledLine::parse_ansi_from("sigue", style);
+        assert_eq!(second.spans[0].style.fg, Color::Indexed(1));
+    }
+
+    #[test]
+    fn tabs_expand_and_carriage_return_rewrites() {
+        assert_eq!(StyledLine::parse_ansi("ab\tc").text(), "ab      c");
+        assert_eq!(StyledLine::parse_ansi("50%\r100%").text(), "100%");
+    }
+
+    #[test]
+    fn trim_end_keeps_visible_backgrounds() {
+        let mut line = StyledLine::default();
+        line.push("ab  ", CellStyle::default());
+        assert_eq!(line.clone().trim_end().text(), "ab");
+        line.push("  ", CellStyle { bg: Color::Indexed(4), ..CellStyle::default() });
+        assert_eq!(line.trim_end().text(), "ab    ");
+    }
+}
@@ -0,0 +1,231 @@
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use crate::domain::repositories::DirectoryRepository;
+
+/// Máximo de sugerencias que se ofrecen para un comando desconocido
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Comando que no se encontró, con los nombres parecidos que sí existen
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandSuggestion {
+    pub command: String,
+    pub candidates: Vec<String>,
+}
+
+/// Distancia de edición entre dos palabras: inserciones, borrados, sustituciones y
+/// transposiciones de dos letras vecinas, para que `gti` quede a 1 de `git`
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    // Tres filas de la tabla: la transposición mira dos filas atrás
+    let mut before: Vec<usize> = vec![0; b.len() + 1];
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for i in 1..=a.len() {
+        current[0] = i;
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            current[j] = (previous[j] + 1).min(current[j - 1] + 1).min(previous[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                current[j] = current[j].min(before[j - 2] + 1);
+            }
+        }
+        std::mem::swap(&mut before, &mut previous);
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+/// Los nombres más parecidos a `name`, del más cercano al más lejano. Solo se admiten
+/// pocos cambios: uno para las palabras cortas y uno más cada cuatro letras.
+pub fn closest<'a>(name: &str, candidates: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let limit = (name.chars().count() / 4).clamp(1, 3);
+    let mut matches: Vec<(usize, &str)> = candidates
+        .into_iter()
+        .filter(|candidate| *candidate != name)
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= limit)
+        .collect();
+    matches.sort();
+    matches.dedup();
+    matches.into_iter().take(MAX_SUGGESTIONS).map(|(_, candidate)| candidate.to_string()).collect()
+}
+
+/// Nombres de los programas del `PATH`. Recorrer todos sus directorios es lento, así
+/// que se guardan y solo se vuelven a leer cuando cambia el valor de `PATH` o se
+/// modifica alguno de sus directorios (se instaló o se borró un programa).
+#[derive(Debug, Default)]
+pub struct ExecutableCache {
+    path: Option<String>,
+    /// Última modificación de cada directorio del `PATH` al leerlo
+    modified: Vec<Option<SystemTime>>,
+    names: BTreeSet<String>,
+}
+
+impl ExecutableCache {
+    /// Programas que se encuentran con ese `PATH`
+    pub fn names(&mut self, path: &str, directories: &dyn DirectoryRepository) -> &BTreeSet<String> {
+        let paths: Vec<PathBuf> = std::env::split_paths(path).collect();
+        let modified: Vec<Option<SystemTime>> = paths.iter().map(|directory| directories.modified(directory)).collect();
+        if self.path.as_deref() != Some(path) || self.modified != modified {
+            self.names = paths
+                .iter()
+                .flat_map(|directory| directories.list(directory))
+                .filter(|entry| entry.is_executable)
+                .map(|entry| entry.name)
+                .collect();
+            self.path = Some(path.to_string());
+            self.modified = modified;
+        }
+        &self.names
+    }
+}
+
+/// Reemplaza en la línea cada comando desconocido por su sugerencia más cercana. Solo
+/// se cambian palabras en posición de comando: al principio o después de `|`.
+/// Devuelve `None` si algún comando no tiene sugerencia o no aparece escrito tal cual
+/// (por ejemplo, porque venía de un alias).
+pub fn correct_line(line: &str, suggestions: &[CommandSuggestion]) -> Option<String> {
+    let mut corrected = line.to_string();
+    for suggestion in suggestions {
+        let replacement = suggestion.candidates.first()?;
+        let start = command_positions(&corrected).into_iter().find(|&start| {
+            let rest = &corrected[start..];
+            rest.starts_with(&suggestion.command)
+                && rest[suggestion.command.len()..].chars().next().is_none_or(|ch| ch.is_whitespace() || ch == '|')
+        })?;
+        corrected.replace_range(start..start + suggestion.command.len(), replacement);
+    }
+    Some(corrected)
+}
+
+/// Posiciones donde empieza una palabra en posición de comando
+fn command_positions(line: &str) -> Vec<usize> {
+    let mut positions = Vec::new();
+    let mut expecting = true;
+    for (index, ch) in line.char_indices() {
+        if ch == '|' {
+            expecting = true;
+        } else if expecting && !ch.is_whitespace() {
+            positions.push(index);
+            expecting = false;
+        }
+    }
+    positions
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::domain::glob::tests::MemoryDirectories;
+
+    fn suggestion(command: &str, candidates: &[&str]) -> CommandSuggestion {
+        CommandSuggestion {
+            command: command.to_string(),
+            candidates: candidates.iter().map(|candidate| candidate.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn edit_distance_of_equal_and_empty_words() {
+        assert_eq!(edit_distance("git", "git"), 0);
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("", "ls"), 2);
+        assert_eq!(edit_distance("ls", ""), 2);
+    }
+
+    #[test]
+    fn edit_distance_counts_single_edits() {
+        assert_eq!(edit_distance("gt", "git"), 1);
+        assert_eq!(edit_distance("gitt", "git"), 1);
+        assert_eq!(edit_distance("gut", "git"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn edit_distance_counts_a_transposition_as_one() {
+        assert_eq!(edit_distance("gti", "git"), 1);
+        assert_eq!(edit_distance("sl", "ls"), 1);
+        assert_eq!(edit_distance("pyhton", "python"), 1);
+        // Dos transposiciones separadas
+        assert_eq!(edit_distance("igtsatus", "gitstatus"), 2);
+    }
+
+    #[test]
+    fn edit_distance_is_symmetric() {
+        for (a, b) in [("gti", "git"), ("cagro", "cargo"), ("abc", "ca"), ("ñandú", "nandu")] {
+            assert_eq!(edit_distance(a, b), edit_distance(b, a), "{} {}", a, b);
+        }
+    }
+
+    #[test]
+    fn edit_distance_compares_characters_not_bytes() {
+        assert_eq!(edit_distance("ñ", "n"), 1);
+        assert_eq!(edit_distance("café", "cafe"), 1);
+    }
+
+    #[test]
+    fn closest_orders_by_distance_and_name_up_to_the_maximum() {
+        assert_eq!(closest("gti", ["gist", "gitk", "git"]), ["git"]);
+        assert_eq!(closest("carg", ["carp", "cargo", "cat", "care", "card"]), ["card", "care", "cargo"]);
+        assert_eq!(closest("ssytemctl", ["asytemctx", "systemctl"]), ["systemctl", "asytemctx"]);
+    }
+
+    #[test]
+    fn closest_allows_more_edits_for_longer_names() {
+        assert_eq!(closest("ls", ["cd", "lsd"]), ["lsd"]);
+        assert!(closest("ls", ["lsblk"]).is_empty());
+        assert_eq!(closest("systemclt", ["systemctl", "sysctl"]), ["systemctl"]);
+        assert_eq!(closest("docekr-compos", ["docker-compose"]), ["docker-compose"]);
+    }
+
+    #[test]
+    fn closest_skips_the_name_itself_and_duplicates() {
+        assert_eq!(closest("git", ["git", "gti", "gti"]), ["gti"]);
+    }
+
+    #[test]
+    fn correct_line_replaces_commands_only() {
+        let suggestions = [suggestion("gti", &["git"])];
+        assert_eq!(correct_line("gti status gti", &suggestions).as_deref(), Some("git status gti"));
+        assert_eq!(
+            correct_line("echo gti | sl -a", &[suggestion("sl", &["ls"])]).as_deref(),
+            Some("echo gti | ls -a")
+        );
+    }
+
+    #[test]
+    fn correct_line_requires_a_whole_word() {
+        assert_eq!(correct_line("gtix", &[suggestion("gti", &["git"])]), None);
+        assert_eq!(correct_line("gti|wc", &[suggestion("gti", &["git"])]).as_deref(), Some("git|wc"));
+    }
+
+    #[test]
+    fn correct_line_fails_without_a_candidate_or_the_command() {
+        assert_eq!(correct_line("gti", &[suggestion("gti", &[])]), None);
+        assert_eq!(correct_line("ll", &[suggestion("gti", &["git"])]), None);
+    }
+
+    #[test]
+    fn executable_cache_lists_programs_of_every_path_directory() {
+        let directories = MemoryDirectories::new(Path::new("/"), &["bin/ls*", "bin/LEEME", "usr/bin/git*", "usr/bin/sub/"]);
+        let mut cache = ExecutableCache::default();
+        let names: Vec<&String> = cache.names("/bin:/usr/bin:/no-existe", &directories).iter().collect();
+        assert_eq!(names, ["git", "ls"]);
+        assert!(cache.names("/usr/bin", &directories).contains("git"));
+        assert!(!cache.names("/usr/bin", &directories).contains("ls"));
+    }
+
+    #[test]
+    fn executable_cache_rereads_a_directory_that_changed() {
+        let mut directories = MemoryDirectories::new(Path::new("/"), &["bin/ls*"]);
+        let mut cache = ExecutableCache::default();
+        assert_eq!(cache.names("/bin", &directories).len(), 1);
+        directories.add("bin/cargo*");
+        assert!(cache.names("/bin", &directories).contains("cargo"));
+    }
+}
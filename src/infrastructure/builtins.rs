@@ -0,0 +1,746 @@
+use crate::application::commands::exit_commands::ExitCommandsUseCase;
+use crate::application::ls_commands::{LsCommand, LsCommandParser, LsCommandsUseCase};
+use crate::domain::builtins::{BuiltinCommand, BuiltinContext, CommandRegistry};
+use crate::domain::document::Document;
+use crate::domain::entities::{Command, ProcessSignal, SUSPENDED_EXIT_CODE};
+use crate::domain::errors::{ArgumentError, JobError, TermirustError};
+use crate::domain::jobs::SharedJobTable;
+use crate::domain::options::OptionSpec;
+use crate::domain::repositories::FileSystemRepository;
+use crate::domain::{man_page, markdown};
+use crate::infrastructure::{documents, jobs, signals};
+use crate::infrastructure::repositories::SystemFileSystemRepository;
+use crate::presentation::commands::exit_display::ExitDisplayComponent;
+use crate::presentation::commands::ls_display::{LsDisplayComponent, LsDisplayFormatter};
+use std::io::Read;
+use std::path::Path;
+use std::process::{self, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Sintaxis del lenguaje que `help` muestra después de los comandos registrados
+const SYNTAX_HELP: &str = r#"
+Sintaxis:
+- NOMBRE=valor, $NOMBRE, ${NOMBRE:-valor}, $?: Variables de la sesión
+- ~, {a,b}, {1..10}, $(comando): Carpeta personal, llaves y sustitución de comandos
+- if/for/while, nombre() { ... }: Control de flujo y funciones con $1..$n
+- comando &: Ejecutar en segundo plano
+- Tab: Completar comandos, archivos, opciones (ls -) y variables ($HO); ↑↓ y Enter eligen en la lista
+- F2 tras un comando desconocido: Ejecutar la corrección sugerida (gti -> git)
+- Enter / Ctrl+D durante un comando: Enviar la línea escrita / fin de archivo a su entrada
+"#;
+
+/// Tiempo que se espera la ayuda de un programa externo antes de abandonarlo
+const EXTERNAL_HELP_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Crea el registro con los comandos internos que no dependen de la sesión, más los
+/// que controlan sus trabajos
+pub fn default_registry(jobs: SharedJobTable) -> CommandRegistry {
+    let mut registry = CommandRegistry::new();
+    registry.register(Box::new(LsBuiltin));
+    registry.register(Box::new(CdBuiltin));
+    registry.register(Box::new(PwdBuiltin));
+    registry.register(Box::new(ClearBuiltin));
+    registry.register(Box::new(HelpBuiltin));
+    registry.register(Box::new(ManBuiltin));
+    registry.register(Box::new(DocBuiltin));
+    registry.register(Box::new(JobsBuiltin { jobs: jobs.clone() }));
+    registry.register(Box::new(FgBuiltin { jobs: jobs.clone() }));
+    registry.register(Box::new(BgBuiltin { jobs: jobs.clone() }));
+    registry.register(Box::new(KillBuiltin { jobs: jobs.clone() }));
+    registry.register(Box::new(ExitBuiltin { jobs }));
+    registry
+}
+
+/// Resultado de un comando interno que falló, con el error y su código de salida
+fn error_result(command: &Command, error: TermirustError) -> Command {
+    let error = error.in_command(&command.name);
+    let exit_code = error.exit_code();
+    command.clone().with_error(error).with_exit_code(exit_code)
+}
+
+/// Salida de un programa que termina bien, sin entrada y con un tiempo límite
+pub fn capture(program: &str, args: &[&str], environment: &[(String, String)], timeout: Duration) -> Option<String> {
+    let mut child = process::Command::new(program)
+        .args(args)
+        .env_clear()
+        .envs(environment.iter().map(|(name, value)| (name, value)))
+        .env("PAGER", "cat")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+    // La salida se lee aparte para que una salida larga no llene la tubería y deje
+    // al programa esperando
+    let mut stdout = child.stdout.take()?;
+    let reader = thread::spawn(move || {
+        let mut text = String::new();
+        stdout.read_to_string(&mut text).map(|_| text)
+    });
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() < deadline => thread::sleep(Duration::from_millis(20)),
+            _ => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return None;
+            }
+        }
+    };
+    let text = reader.join().ok()?.ok()?;
+    (status.success() && !text.trim().is_empty()).then(|| text.trim_end().to_string())
+}
+
+/// `ls`: lista archivos y directorios
+pub struct LsBuiltin;
+
+impl BuiltinCommand for LsBuiltin {
+    fn name(&self) -> &str {
+        "ls"
+    }
+
+    fn aliases(&self) -> &[&str] {
+        &["dir"]
+    }
+
+    fn description(&self) -> &str {
+        "Listar archivos y directorios (con opciones: -a, -l, -h, -S, -t)"
+    }
+
+    fn usage(&self) -> &str {
+        "ls [opciones] [ruta...]"
+    }
+
+    fn help(&self) -> String {
+        LsDisplayFormatter::format_help().join("\n")
+    }
+
+    fn options(&self) -> Vec<OptionSpec> {
+        LsCommandParser::options().options().to_vec()
+    }
+
+    fn execute(&self, command: &Command, _context: &mut BuiltinContext) -> Command {
+        // Usar nuestro comando ls avanzado
+        let ls_use_case = LsCommandsUseCase::new();
+        let parsed = LsCommandParser::parse_args(&command.args);
+        if let LsCommand::Help(help) = &parsed {
+            return command.clone().with_output(help.clone()).with_exit_code(0);
+        }
+        let options = parsed.options();
+
+        let mut output_lines = Vec::new();
+        let mut errors = Vec::new();
+        let mut exit_code = 0;
+        for listing in parsed.execute(&ls_use_case) {
+            match listing {
+                Ok(result) => {
+                    // Cada grupo listado se separa del anterior con una línea en blanco
+                    if !output_lines.is_empty() {
+                        output_lines.push(String::new());
+                    }
+                    output_lines.extend(LsDisplayComponent::render(&result, &options));
+                }
+                Err(e) => {
+                    exit_code = exit_code.max(e.exit_code());
+                    errors.push(e.in_command(&command.name));
+                }
+            }
+        }
+        let result = command.clone().with_output(output_lines.join("\n"));
+        errors.into_iter().fold(result, Command::with_error).with_exit_code(exit_code)
+    }
+}
+
+/// `cd`: cambia el directorio de trabajo
+pub struct CdBuiltin;
+
+impl BuiltinCommand for CdBuiltin {
+    fn name(&self) -> &str {
+        "cd"
+    }
+
+    fn description(&self) -> &str {
+        "Cambiar directorio (sin argumentos, a la carpeta personal)"
+    }
+
+    fn usage(&self) -> &str {
+        "cd [directorio]"
+    }
+
+    fn help(&self) -> String {
+        r#"Uso: cd [directorio]
+
+Cambia el directorio de trabajo de la sesión.
+  cd          - Va a la carpeta personal ($HOME)
+  cd ..       - Sube un nivel
+  cd ~/src    - Va a una carpeta dentro de la personal"#
+            .to_string()
+    }
+
+    fn execute(&self, command: &Command, context: &mut BuiltinContext) -> Command {
+        // Sin argumentos, `cd` vuelve a la carpeta personal de la sesión
+        let path = command.args.first().cloned()
+            .or_else(|| context.session.state.environment.get("HOME").map(str::to_string))
+            .unwrap_or_else(|| ".".to_string());
+        let fs_repo = SystemFileSystemRepository;
+        match fs_repo.change_directory(&path) {
+            Ok(_) => command.clone().with_output("".to_string()).with_exit_code(0),
+            Err(e) => error_result(command, e),
+        }
+    }
+}
+
+/// `pwd`: muestra el directorio de trabajo
+pub struct PwdBuiltin;
+
+impl BuiltinCommand for PwdBuiltin {
+    fn name(&self) -> &str {
+        "pwd"
+    }
+
+    fn description(&self) -> &str {
+        "Mostrar directorio actual"
+    }
+
+    fn usage(&self) -> &str {
+        "pwd"
+    }
+
+    fn help(&self) -> String {
+        "Uso: pwd\n\nMuestra la ruta completa del directorio de trabajo.".to_string()
+    }
+
+    fn execute(&self, command: &Command, _context: &mut BuiltinContext) -> Command {
+        let fs_repo = SystemFileSystemRepository;
+        match fs_repo.get_current_directory() {
+            Ok(path) => command.clone().with_output(path).with_exit_code(0),
+            Err(e) => error_result(command, e),
+        }
+    }
+}
+
+/// `clear`: limpia la pantalla. No escribe nada: la sesión reconoce el comando por su
+/// nombre y avisa a la interfaz, así que en una tubería o redirigido no deja rastro.
+pub struct ClearBuiltin;
+
+impl BuiltinCommand for ClearBuiltin {
+    fn name(&self) -> &str {
+        "clear"
+    }
+
+    fn description(&self) -> &str {
+        "Limpiar pantalla"
+    }
+
+    fn usage(&self) -> &str {
+        "clear"
+    }
+
+    fn help(&self) -> String {
+        "Uso: clear\n\nBorra la salida de la terminal.".to_string()
+    }
+
+    fn execute(&self, command: &Command, _context: &mut BuiltinContext) -> Command {
+        command.clone().with_exit_code(0)
+    }
+}
+
+/// `help`: lista los comandos registrados y la sintaxis de la sesión; con nombres,
+/// muestra la ayuda de cada uno
+pub struct HelpBuiltin;
+
+impl HelpBuiltin {
+    /// Ayuda de un comando: la de un comando interno a partir de lo que declara, o la
+    /// de un programa externo
+    fn topic(name: &str, context: &BuiltinContext) -> Option<String> {
+        context
+            .registry
+            .find(name)
+            .map(|builtin| builtin.info().get_formatted_help())
+            .or_else(|| Self::external_help(name, context.environment))
+    }
+
+    /// Comandos registrados, la sintaxis y los alias y funciones de la sesión
+    fn overview(context: &BuiltinContext) -> String {
+        let mut help_text = String::from("\nComandos disponibles:\n");
+        for builtin in context.registry.iter() {
+            let mut names = vec![builtin.name()];
+            names.extend(builtin.aliases());
+            help_text.push_str(&format!("- {}: {}\n", names.join(", "), builtin.description()));
+        }
+        help_text.push_str(SYNTAX_HELP);
+
+        let session = &context.session;
+        if session.aliases.iter().next().is_some() {
+            help_text.push_str("\nAlias definidos:\n");
+            for (name, value) in session.aliases.iter() {
+                help_text.push_str(&format!("- {}: {}\n", name, value));
+            }
+        }
+        if !session.state.functions.is_empty() {
+            help_text.push_str("\nFunciones definidas:\n");
+            for name in session.state.functions.keys() {
+                help_text.push_str(&format!("- {}\n", name));
+            }
+        }
+        help_text
+    }
+
+    /// Ayuda de un programa externo: lo que imprime con `--help` o, si no la tiene,
+    /// su página de manual
+    fn external_help(name: &str, environment: &[(String, String)]) -> Option<String> {
+        if name.starts_with('-') {
+            return None;
+        }
+        capture(name, &["--help"], environment, EXTERNAL_HELP_TIMEOUT).or_else(|| {
+            let manpath = environment.iter().find(|(variable, _)| variable == "MANPATH").map(|(_, value)| value.as_str());
+            let page = documents::find_man_page(name, None, manpath)?;
+            let source = documents::read_man_page(&page).ok()?;
+            Some(man_page::parse(name, &source).plain_text())
+        })
+    }
+}
+
+impl BuiltinCommand for HelpBuiltin {
+    fn name(&self) -> &str {
+        "help"
+    }
+
+    fn description(&self) -> &str {
+        "Mostrar esta ayuda (help comando: ayuda de un comando)"
+    }
+
+    fn usage(&self) -> &str {
+        "help [comando...]"
+    }
+
+    fn help(&self) -> String {
+        r#"Uso: help [comando...]
+
+Sin argumentos lista los comandos internos, la sintaxis de Termirust y los
+alias y funciones de la sesión.
+Con nombres muestra la ayuda de cada comando; 'comando --help' hace lo mismo.
+Para los programas externos se usa su --help o, si no la tienen, su manual.
+  help          - Lista los comandos
+  help ls       - Opciones y ejemplos de ls
+  help git      - Ayuda de git"#
+            .to_string()
+    }
+
+    fn execute(&self, command: &Command, context: &mut BuiltinContext) -> Command {
+        if command.args.is_empty() {
+            return command.clone().with_output(Self::overview(context)).with_exit_code(0);
+        }
+
+        let mut topics = Vec::new();
+        let mut errors = Vec::new();
+        for name in &command.args {
+            match Self::topic(name, context) {
+                Some(text) => topics.push(text),
+                None => errors.push(TermirustError::NotFound {
+                    command: command.name.clone(),
+                    path: Some(name.clone()),
+                }),
+            }
+        }
+        let exit_code = if errors.is_empty() { 0 } else { 1 };
+        errors
+            .into_iter()
+            .fold(command.clone().with_output(topics.join("\n\n")), Command::with_error)
+            .with_exit_code(exit_code)
+    }
+}
+
+/// `man`: muestra una página de manual del sistema en el visor
+pub struct ManBuiltin;
+
+impl ManBuiltin {
+    /// Documento con la página pedida: un archivo si el nombre es una ruta, la página
+    /// del manual del sistema o, si no la hay, la ayuda de un comando interno
+    fn document(section: Option<&str>, name: &str, context: &BuiltinContext) -> Result<Document, TermirustError> {
+        let not_found = || TermirustError::NotFound {
+            command: String::new(),
+            path: Some(name.to_string()),
+        };
+        if name.contains('/') {
+            let source = documents::read_man_page(Path::new(name))?;
+            return Ok(man_page::parse(name, &source));
+        }
+        let manpath = context
+            .environment
+            .iter()
+            .find(|(variable, _)| variable == "MANPATH")
+            .map(|(_, value)| value.as_str());
+        if let Some(page) = documents::find_man_page(name, section, manpath) {
+            let source = documents::read_man_page(&page)?;
+            return Ok(man_page::parse(name, &source));
+        }
+        if section.is_some() {
+            return Err(not_found());
+        }
+        context
+            .registry
+            .find(name)
+            .map(|builtin| builtin.help())
+            .map(|help| Document::from_text(name, &help))
+            .ok_or_else(not_found)
+    }
+}
+
+impl BuiltinCommand for ManBuiltin {
+    fn name(&self) -> &str {
+        "man"
+    }
+
+    fn description(&self) -> &str {
+        "Mostrar una página del manual en el visor"
+    }
+
+    fn usage(&self) -> &str {
+        "man [sección] nombre"
+    }
+
+    fn help(&self) -> String {
+        r#"Uso: man [sección] nombre
+
+Muestra la página del manual en el visor. Busca en MANPATH o en /usr/share/man y
+lee las páginas comprimidas (.gz). Para los comandos internos sin página muestra
+su ayuda. Con una ruta (man ./prog.1) lee ese archivo.
+  man ls        - Página de ls
+  man 5 passwd  - Página de la sección 5
+
+En el visor: flechas, RePág/AvPág, Inicio/Fin y la rueda del ratón desplazan;
+/ busca, n y N van a la coincidencia siguiente y anterior, Esc o q cierran."#
+            .to_string()
+    }
+
+    fn execute(&self, command: &Command, context: &mut BuiltinContext) -> Command {
+        let (section, name) = match command.args.as_slice() {
+            [name] => (None, name),
+            [section, name] if section.starts_with(|ch: char| ch.is_ascii_digit()) => (Some(section.as_str()), name),
+            _ => return error_result(command, TermirustError::usage(&command.name, self.usage())),
+        };
+        match Self::document(section, name, context) {
+            Ok(document) => command.clone().with_document(document).with_exit_code(0),
+            Err(e) => error_result(command, e),
+        }
+    }
+}
+
+/// `doc`: muestra en el visor la documentación de Termirust o un archivo Markdown
+pub struct DocBuiltin;
+
+impl BuiltinCommand for DocBuiltin {
+    fn name(&self) -> &str {
+        "doc"
+    }
+
+    fn description(&self) -> &str {
+        "Leer la documentación de Termirust o un archivo Markdown"
+    }
+
+    fn usage(&self) -> &str {
+        "doc [nombre|archivo.md]"
+    }
+
+    fn help(&self) -> String {
+        r#"Uso: doc [nombre|archivo.md]
+
+Sin argumentos lista la documentación incluida. Con un nombre la abre en el visor
+(basta el principio del nombre si no hay otro igual); con la ruta de un archivo
+Markdown muestra ese archivo.
+  doc                   - Lista los documentos
+  doc command_language  - El lenguaje de comandos
+  doc plugins           - Cómo escribir plugins
+  doc notas.md          - Un archivo Markdown cualquiera"#
+            .to_string()
+    }
+
+    fn completions(&self) -> Vec<String> {
+        documents::bundled_names().map(str::to_string).collect()
+    }
+
+    fn execute(&self, command: &Command, _context: &mut BuiltinContext) -> Command {
+        let Some(name) = command.args.first() else {
+            let mut lines = vec!["Documentación disponible (doc nombre):".to_string()];
+            lines.extend(documents::bundled_names().map(|name| format!("- {}", name)));
+            return command.clone().with_output(lines.join("\n")).with_exit_code(0);
+        };
+        let document = if Path::new(name).is_file() {
+            documents::read_text(Path::new(name)).map(|source| markdown::parse(name, &source))
+        } else {
+            documents::bundled(name)
+                .map(|(title, source)| markdown::parse(title, source))
+                .ok_or_else(|| TermirustError::NotFound {
+                    command: String::new(),
+                    path: Some(name.clone()),
+                })
+        };
+        match document {
+            Ok(document) => command.clone().with_document(document).with_exit_code(0),
+            Err(e) => error_result(command, e),
+        }
+    }
+}
+
+/// `exit`: cierra Termirust. Con trabajos activos pide `--force` y, al cerrar, les
+/// envía SIGHUP.
+pub struct ExitBuiltin {
+    jobs: SharedJobTable,
+}
+
+impl ExitBuiltin {
+    /// Número de trabajos que siguen en ejecución o detenidos
+    fn active_jobs(&self) -> usize {
+        self.jobs.lock().map(|table| table.active_count()).unwrap_or(0)
+    }
+}
+
+impl BuiltinCommand for ExitBuiltin {
+    fn name(&self) -> &str {
+        "exit"
+    }
+
+    fn aliases(&self) -> &[&str] {
+        &["quit"]
+    }
+
+    fn description(&self) -> &str {
+        "Cerrar la aplicación Termirust (exit --force si hay trabajos activos)"
+    }
+
+    fn usage(&self) -> &str {
+        "exit [--force]"
+    }
+
+    fn help(&self) -> String {
+        ExitDisplayComponent::render_help(&ExitCommandsUseCase::new().get_exit_info()).join("\n")
+    }
+
+    fn options(&self) -> Vec<OptionSpec> {
+        ExitCommandsUseCase::options().options().to_vec()
+    }
+
+    fn execute(&self, command: &Command, _context: &mut BuiltinContext) -> Command {
+        // Usar nuestro comando exit avanzado
+        let exit_use_case = ExitCommandsUseCase::new();
+
+        match exit_use_case.execute_exit(command, self.active_jobs()) {
+            Ok(result) => {
+                if result.should_exit {
+                    jobs::hang_up_all(&self.jobs);
+                }
+                let output_lines = ExitDisplayComponent::render(&result);
+                let output = output_lines.join("\n");
+                command.clone().with_output(output).with_exit_code(result.exit_code)
+            }
+            Err(e) => error_result(command, e),
+        }
+    }
+}
+
+/// `jobs`: lista los trabajos en segundo plano o detenidos
+pub struct JobsBuiltin {
+    jobs: SharedJobTable,
+}
+
+impl BuiltinCommand for JobsBuiltin {
+    fn name(&self) -> &str {
+        "jobs"
+    }
+
+    fn description(&self) -> &str {
+        "Listar trabajos en segundo plano o detenidos"
+    }
+
+    fn usage(&self) -> &str {
+        "jobs [-l]"
+    }
+
+    fn help(&self) -> String {
+        "Uso: jobs [-l]\n\nLista los trabajos en segundo plano o detenidos; -l agrega su pid.".to_string()
+    }
+
+    fn options(&self) -> Vec<OptionSpec> {
+        vec![OptionSpec::new("Mostrar el pid de cada trabajo").with_short('l')]
+    }
+
+    fn execute(&self, command: &Command, _context: &mut BuiltinContext) -> Command {
+        let long_format = match command.args.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+            [] => false,
+            ["-l"] => true,
+            _ => return error_result(command, TermirustError::usage(&command.name, self.usage())),
+        };
+        let jobs = match self.jobs.lock() {
+            Ok(jobs) => jobs,
+            Err(_) => return command.clone().with_exit_code(1),
+        };
+        let lines: Vec<String> = jobs
+            .list()
+            .iter()
+            .map(|job| {
+                if long_format {
+                    format!("[{}]  {:<8} {:<12} {}", job.id, job.pgid, job.status.label(), job.command)
+                } else {
+                    job.describe()
+                }
+            })
+            .collect();
+        command.clone().with_output(lines.join("\n")).with_exit_code(0)
+    }
+}
+
+/// Número del trabajo indicado como `%n`, o el del último si no se indica
+fn resolve_job(jobs: &SharedJobTable, spec: Option<&String>) -> Result<usize, JobError> {
+    jobs.lock().map_err(|_| JobError::NoJobs)?.resolve(spec.map(String::as_str))
+}
+
+/// Error de un comando de control de trabajos
+fn job_error(command: &Command, reason: JobError) -> Command {
+    error_result(command, TermirustError::Job { command: command.name.clone(), reason })
+}
+
+/// `fg`: continúa un trabajo en primer plano y espera a que termine
+pub struct FgBuiltin {
+    jobs: SharedJobTable,
+}
+
+impl BuiltinCommand for FgBuiltin {
+    fn name(&self) -> &str {
+        "fg"
+    }
+
+    fn description(&self) -> &str {
+        "Continuar un trabajo en primer plano"
+    }
+
+    fn usage(&self) -> &str {
+        "fg [%n]"
+    }
+
+    fn help(&self) -> String {
+        "Uso: fg [%n]\n\nContinúa en primer plano un trabajo (el último si no se indica).".to_string()
+    }
+
+    fn execute(&self, command: &Command, _context: &mut BuiltinContext) -> Command {
+        let result = resolve_job(&self.jobs, command.args.first()).and_then(|id| jobs::foreground(&self.jobs, id));
+        match result {
+            Ok((exit_code, description)) if exit_code == SUSPENDED_EXIT_CODE => {
+                command.clone().with_error_output(description).with_exit_code(exit_code)
+            }
+            Ok((exit_code, _)) => command.clone().with_exit_code(exit_code),
+            Err(e) => job_error(command, e),
+        }
+    }
+}
+
+/// `bg`: continúa un trabajo detenido en segundo plano
+pub struct BgBuiltin {
+    jobs: SharedJobTable,
+}
+
+impl BuiltinCommand for BgBuiltin {
+    fn name(&self) -> &str {
+        "bg"
+    }
+
+    fn description(&self) -> &str {
+        "Continuar un trabajo detenido en segundo plano"
+    }
+
+    fn usage(&self) -> &str {
+        "bg [%n]"
+    }
+
+    fn help(&self) -> String {
+        "Uso: bg [%n]\n\nContinúa en segundo plano un trabajo detenido (el último si no se indica).".to_string()
+    }
+
+    fn execute(&self, command: &Command, _context: &mut BuiltinContext) -> Command {
+        match resolve_job(&self.jobs, command.args.first()).and_then(|id| jobs::background(&self.jobs, id)) {
+            Ok(description) => command.clone().with_output(description).with_exit_code(0),
+            Err(e) => job_error(command, e),
+        }
+    }
+}
+
+/// `kill`: envía una señal a trabajos o procesos; la señal por defecto es SIGTERM
+pub struct KillBuiltin {
+    jobs: SharedJobTable,
+}
+
+/// PID de un proceso para `kill`: positivo y dentro del rango del sistema
+fn parse_pid(target: &str) -> Option<i32> {
+    target.parse::<i32>().ok().filter(|pid| *pid > 0)
+}
+
+impl BuiltinCommand for KillBuiltin {
+    fn name(&self) -> &str {
+        "kill"
+    }
+
+    fn description(&self) -> &str {
+        "Enviar una señal a trabajos o procesos (TERM por defecto)"
+    }
+
+    fn usage(&self) -> &str {
+        "kill [-SEÑAL] %n|pid..."
+    }
+
+    fn help(&self) -> String {
+        r#"Uso: kill [-SEÑAL] %n|pid...
+
+Envía una señal a trabajos (%n) o procesos (pid). Sin señal envía TERM.
+  kill %1         - Termina el trabajo 1
+  kill -STOP %2   - Detiene el trabajo 2 (bg o fg lo continúan)
+  kill -9 1234    - Mata el proceso 1234"#
+            .to_string()
+    }
+
+    fn execute(&self, command: &Command, _context: &mut BuiltinContext) -> Command {
+        let mut args = command.args.iter().peekable();
+        let mut signal = ProcessSignal::Terminate;
+        if let Some(name) = args.next_if(|arg| arg.starts_with('-') && arg.len() > 1) {
+            match signals::parse_signal(name) {
+                Some(parsed) => signal = parsed,
+                None => {
+                    let error = TermirustError::invalid_argument(&command.name, ArgumentError::UnknownSignal(name.clone()));
+                    return error_result(command, error);
+                }
+            }
+        }
+        if args.peek().is_none() {
+            return error_result(command, TermirustError::usage(&command.name, self.usage()));
+        }
+
+        // Se validan todos los PID antes de enviar ninguna señal
+        let mut targets = Vec::new();
+        for target in args {
+            let pid = if target.starts_with('%') {
+                None
+            } else {
+                match parse_pid(target) {
+                    Some(pid) => Some(pid),
+                    None => return error_result(command, TermirustError::usage(&command.name, self.usage())),
+                }
+            };
+            targets.push((target, pid));
+        }
+
+        let mut errors = Vec::new();
+        for (target, pid) in targets {
+            let result = match pid {
+                Some(pid) => signals::send_to_process(pid, signal),
+                None => resolve_job(&self.jobs, Some(target)).and_then(|id| jobs::signal(&self.jobs, id, signal)),
+            };
+            if let Err(reason) = result {
+                errors.push(TermirustError::Job { command: command.name.clone(), reason });
+            }
+        }
+        let exit_code = if errors.is_empty() { 0 } else { 1 };
+        errors.into_iter().fold(command.clone(), Command::with_error).with_exit_code(exit_code)
+    }
+}
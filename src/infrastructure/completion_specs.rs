@@ -0,0 +1,227 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::domain::completion_spec::{ArgumentKind, CompletionSpec, FlagSpec};
+use crate::domain::errors::{FileError, TermirustError};
+use crate::domain::repositories::CompletionSpecRepository;
+use crate::infrastructure::builtins::capture;
+use crate::infrastructure::repositories::config_directory;
+
+/// Especificaciones incluidas en el ejecutable para los programas más usados
+const BUNDLED_SPECS: &[(&str, &str)] = &[
+    ("git.toml", include_str!("../../completions/git.toml")),
+    ("cargo.toml", include_str!("../../completions/cargo.toml")),
+];
+
+/// Tiempo que se espera la salida de un comando al completar. Se ejecuta en el hilo
+/// de trabajo, así que un comando lento solo retrasa sus candidatos.
+const COMMAND_OUTPUT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Subcomando o programa tal como se escribe en el archivo
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SpecFile {
+    name: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    aliases: Vec<String>,
+    #[serde(default)]
+    subcommands: Vec<SpecFile>,
+    #[serde(default)]
+    options: Vec<FlagFile>,
+    arguments: Option<ArgumentFile>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FlagFile {
+    names: Vec<String>,
+    #[serde(default)]
+    description: String,
+    value: Option<ArgumentFile>,
+}
+
+/// `"file"`, `"directory"` o `"text"`; `{ choices = [...] }` o `{ command = "..." }`
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ArgumentFile {
+    Kind(String),
+    Choices { choices: Vec<String> },
+    Command { command: String },
+}
+
+impl SpecFile {
+    fn into_spec(self) -> Result<CompletionSpec, FileError> {
+        Ok(CompletionSpec {
+            name: self.name,
+            description: self.description,
+            aliases: self.aliases,
+            subcommands: self.subcommands.into_iter().map(SpecFile::into_spec).collect::<Result<_, _>>()?,
+            options: self
+                .options
+                .into_iter()
+                .map(|flag| {
+                    Ok(FlagSpec {
+                        names: flag.names,
+                        description: flag.description,
+                        value: flag.value.map(ArgumentFile::into_kind).transpose()?,
+                    })
+                })
+                .collect::<Result<_, FileError>>()?,
+            arguments: self.arguments.map(ArgumentFile::into_kind).transpose()?,
+        })
+    }
+}
+
+impl ArgumentFile {
+    fn into_kind(self) -> Result<ArgumentKind, FileError> {
+        match self {
+            ArgumentFile::Kind(kind) => match kind.as_str() {
+                "file" => Ok(ArgumentKind::File),
+                "directory" => Ok(ArgumentKind::Directory),
+                "text" => Ok(ArgumentKind::Text),
+                _ => Err(FileError::UnknownArgumentKind(kind)),
+            },
+            ArgumentFile::Choices { choices } => Ok(ArgumentKind::Choice(choices)),
+            ArgumentFile::Command { command } => Ok(ArgumentKind::Command(command)),
+        }
+    }
+}
+
+/// Lee las especificaciones incluidas y las del directorio `completions` de la
+/// configuración, en TOML o JSON. Las del usuario van después, así que reemplazan a
+/// las incluidas con el mismo nombre.
+pub struct FileCompletionSpecRepository {
+    directory: Option<PathBuf>,
+}
+
+impl FileCompletionSpecRepository {
+    /// Usa `~/.config/termirust/completions`
+    pub fn new() -> Self {
+        Self {
+            directory: config_directory().map(|directory| directory.join("completions")),
+        }
+    }
+
+    /// Usa otro directorio en lugar del predeterminado
+    #[cfg(test)]
+    pub fn with_directory(mut self, directory: PathBuf) -> Self {
+        self.directory = Some(directory);
+        self
+    }
+
+    /// Interpreta un archivo según su extensión
+    fn parse(file_name: &str, content: &str) -> Result<CompletionSpec, FileError> {
+        let spec: SpecFile = if file_name.ends_with(".json") {
+            serde_json::from_str(content).map_err(|e| FileError::Format(e.to_string()))?
+        } else {
+            toml::from_str(content).map_err(|e| FileError::Format(e.to_string()))?
+        };
+        spec.into_spec()
+    }
+
+    fn user_files(&self) -> Vec<PathBuf> {
+        let Some(entries) = self.directory.as_deref().and_then(|directory| std::fs::read_dir(directory).ok()) else {
+            return Vec::new();
+        };
+        let mut files: Vec<PathBuf> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|extension| extension == "toml" || extension == "json"))
+            .collect();
+        files.sort();
+        files
+    }
+
+    fn load_file(path: &Path) -> Result<CompletionSpec, TermirustError> {
+        let display = path.to_string_lossy().to_string();
+        let content = std::fs::read_to_string(path).map_err(|e| TermirustError::from_io("", Some(&display), &e))?;
+        let file_name = path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+        Self::parse(&file_name, &content).map_err(|reason| TermirustError::InvalidFile { path: display, reason })
+    }
+}
+
+impl CompletionSpecRepository for FileCompletionSpecRepository {
+    fn load(&self) -> Vec<Result<CompletionSpec, TermirustError>> {
+        let bundled = BUNDLED_SPECS.iter().map(|(file_name, content)| {
+            Self::parse(file_name, content)
+                .map_err(|reason| TermirustError::InvalidFile { path: file_name.to_string(), reason })
+        });
+        let user = self.user_files().into_iter().map(|path| Self::load_file(&path));
+        bundled.chain(user).collect()
+    }
+
+    /// El comando se ejecuta sin shell: el programa y sus argumentos separados por
+    /// espacios
+    fn command_output(&self, command: &str, environment: &[(String, String)]) -> Vec<String> {
+        let mut words = command.split_whitespace();
+        let Some(program) = words.next() else {
+            return Vec::new();
+        };
+        let args: Vec<&str> = words.collect();
+        capture(program, &args, environment, COMMAND_OUTPUT_TIMEOUT)
+            .map(|output| output.lines().map(str::trim).filter(|line| !line.is_empty()).map(str::to_string).collect())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Directorio temporal que se borra al terminar la prueba
+    struct Directory(PathBuf);
+
+    impl Directory {
+        fn new(name: &str, files: &[(&str, &str)]) -> Self {
+            let path = std::env::temp_dir().join(format!("termirust-{}-{}", name, std::process::id()));
+            std::fs::create_dir_all(&path).expect("se puede crear el directorio");
+            for (file_name, content) in files {
+                std::fs::write(path.join(file_name), content).expect("se puede escribir el archivo");
+            }
+            Self(path)
+        }
+    }
+
+    impl Drop for Directory {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn bundled_specs_are_valid() {
+        for (file_name, content) in BUNDLED_SPECS {
+            assert!(FileCompletionSpecRepository::parse(file_name, content).is_ok(), "{}", file_name);
+        }
+    }
+
+    #[test]
+    fn user_files_load_after_the_bundled_ones_and_report_their_errors() {
+        let directory = Directory::new(
+            "completions",
+            &[
+                ("a.json", r#"{ "name": "propio", "options": [{ "names": ["-v"] }] }"#),
+                ("b.toml", "name = \"malo\"\narguments = \"archivo\"\n"),
+                ("c.json", "{"),
+                ("notas.txt", "no es una especificación"),
+            ],
+        );
+        let repository = FileCompletionSpecRepository::new().with_directory(directory.0.clone());
+        let loaded = repository.load();
+        let user = &loaded[BUNDLED_SPECS.len()..];
+        assert_eq!(user.len(), 3);
+
+        let spec = user[0].as_ref().expect("la especificación es válida");
+        assert_eq!(spec.name, "propio");
+        assert_eq!(spec.options[0].names, ["-v"]);
+
+        let path = directory.0.join("b.toml").to_string_lossy().to_string();
+        let reason = FileError::UnknownArgumentKind("archivo".to_string());
+        assert_eq!(user[1], Err(TermirustError::InvalidFile { path, reason }));
+        assert!(matches!(&user[2], Err(TermirustError::InvalidFile { reason: FileError::Format(_), .. })));
+    }
+}
@@ -0,0 +1,143 @@
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use flate2::read::GzDecoder;
+
+use crate::domain::errors::TermirustError;
+
+/// Documentación para usuarios incluida en el ejecutable, para leerla con `doc`. Es
+/// una lista cerrada: el resto de `docs/` son notas de desarrollo.
+const BUNDLED_DOCS: &[(&str, &str)] = &[
+    ("README", include_str!("../../README.md")),
+    ("COMMAND_LANGUAGE", include_str!("../../docs/COMMAND_LANGUAGE.md")),
+    ("PLUGINS", include_str!("../../docs/PLUGINS.md")),
+    ("LOGGING_SYSTEM", include_str!("../../docs/LOGGING_SYSTEM.md")),
+];
+
+/// Directorios de manuales cuando no hay `MANPATH`
+const DEFAULT_MANPATH: &[&str] = &["/usr/local/share/man", "/usr/share/man", "/usr/local/man", "/usr/man"];
+
+/// Orden en que se buscan las secciones si no se indica una, como hace `man`
+const MAN_SECTIONS: &[&str] = &["1", "8", "6", "2", "3", "5", "4", "7", "9"];
+
+/// Veces que se sigue un `.so` (una página que solo apunta a otra)
+const MAX_SO_REDIRECTS: usize = 5;
+
+/// Nombres de los documentos incluidos
+pub fn bundled_names() -> impl Iterator<Item = &'static str> {
+    BUNDLED_DOCS.iter().map(|(name, _)| *name)
+}
+
+/// Busca un documento incluido por su nombre, sin distinguir mayúsculas y con o sin
+/// `.md`; si no, por el principio del nombre cuando solo uno coincide (`doc plugins`,
+/// `doc command`)
+pub fn bundled(name: &str) -> Option<(&'static str, &'static str)> {
+    let wanted = name.trim_end_matches(".md").to_uppercase();
+    if let Some(doc) = BUNDLED_DOCS.iter().find(|(doc, _)| *doc == wanted) {
+        return Some(*doc);
+    }
+    let mut candidates = BUNDLED_DOCS.iter().filter(|(doc, _)| doc.starts_with(&wanted));
+    match (candidates.next(), candidates.next()) {
+        (Some(doc), None) => Some(*doc),
+        _ => None,
+    }
+}
+
+/// Lee un archivo de texto, descomprimiéndolo si termina en `.gz`
+pub fn read_text(path: &Path) -> Result<String, TermirustError> {
+    let display = path.display().to_string();
+    let file = std::fs::File::open(path).map_err(|e| TermirustError::from_io("", Some(&display), &e))?;
+    let mut text = String::new();
+    let result = if path.extension().is_some_and(|extension| extension == "gz") {
+        GzDecoder::new(file).read_to_string(&mut text)
+    } else {
+        std::io::BufReader::new(file).read_to_string(&mut text)
+    };
+    result.map_err(|e| TermirustError::from_io("", Some(&display), &e))?;
+    Ok(text)
+}
+
+/// Busca la página de manual de `name` en los directorios de `manpath` (o en los
+/// habituales), en la sección pedida o en el orden de `man`
+pub fn find_man_page(name: &str, section: Option<&str>, manpath: Option<&str>) -> Option<PathBuf> {
+    let roots = man_roots(manpath);
+    let sections: Vec<&str> = match section {
+        Some(section) => vec![section],
+        None => MAN_SECTIONS.to_vec(),
+    };
+    for section in sections {
+        // La sección puede tener sufijo, como `3ssl` o `1p`: se busca en `man3`
+        let directory = format!("man{}", section.chars().next()?);
+        for root in &roots {
+            let Ok(entries) = std::fs::read_dir(root.join(&directory)) else {
+                continue;
+            };
+            let mut pages: Vec<PathBuf> = entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| is_page_of(path, name, section))
+                .collect();
+            pages.sort();
+            if let Some(page) = pages.into_iter().next() {
+                return Some(page);
+            }
+        }
+    }
+    None
+}
+
+/// Lee una página de manual y sigue los `.so` que apuntan a otra página
+pub fn read_man_page(path: &Path) -> Result<String, TermirustError> {
+    let mut path = path.to_path_buf();
+    let mut text = read_text(&path)?;
+    for _ in 0..MAX_SO_REDIRECTS {
+        let Some(target) = text.lines().find_map(|line| line.strip_prefix(".so ")).map(str::trim) else {
+            break;
+        };
+        // La ruta del `.so` es relativa a la raíz del manual (`man1/otra.1`)
+        let Some(root) = path.parent().and_then(Path::parent) else {
+            break;
+        };
+        let target = root.join(target);
+        let found = [target.clone(), PathBuf::from(format!("{}.gz", target.display()))]
+            .into_iter()
+            .find(|candidate| candidate.is_file());
+        let Some(found) = found else {
+            break;
+        };
+        text = read_text(&found)?;
+        path = found;
+    }
+    Ok(text)
+}
+
+/// Directorios raíz de los manuales: los de `MANPATH` (un elemento vacío equivale a
+/// los habituales) o los habituales
+fn man_roots(manpath: Option<&str>) -> Vec<PathBuf> {
+    let defaults = || DEFAULT_MANPATH.iter().map(PathBuf::from);
+    match manpath.filter(|manpath| !manpath.is_empty()) {
+        Some(manpath) => manpath
+            .split(':')
+            .flat_map(|entry| -> Vec<PathBuf> {
+                if entry.is_empty() {
+                    defaults().collect()
+                } else {
+                    vec![PathBuf::from(entry)]
+                }
+            })
+            .collect(),
+        None => defaults().collect(),
+    }
+}
+
+/// Indica si el archivo es la página `name` de la sección: `ls.1`, `ls.1.gz`, `x.3ssl.gz`
+fn is_page_of(path: &Path, name: &str, section: &str) -> bool {
+    let Some(file_name) = path.file_name().and_then(|file_name| file_name.to_str()) else {
+        return false;
+    };
+    let file_name = file_name.strip_suffix(".gz").unwrap_or(file_name);
+    file_name
+        .strip_prefix(name)
+        .and_then(|rest| rest.strip_prefix('.'))
+        .is_some_and(|suffix| suffix.starts_with(section))
+}
@@ -0,0 +1,209 @@
+use std::process::Child;
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+
+use crate::domain::entities::{EventSender, ExecutionEvent, ProcessSignal, SUSPENDED_EXIT_CODE};
+use crate::domain::errors::JobError;
+use crate::domain::jobs::{JobInput, JobStatus, JobTable, SharedJobTable};
+use crate::infrastructure::signals::{self, ChildStatus};
+use crate::infrastructure::stdin;
+
+/// Cantidad de cambios de estado de los trabajos; los hilos que esperan procesos la
+/// incrementan después de cada `waitpid` para despertar a `fg`
+static JOB_CHANGES: Mutex<u64> = Mutex::new(0);
+static JOB_CHANGED: Condvar = Condvar::new();
+
+/// Procesos de una tubería que todavía no terminaron, junto con sus capturas de salida
+pub struct JobProcesses {
+    /// Procesos vivos con su posición dentro de la tubería
+    pub children: Vec<(usize, Child)>,
+    /// Código de salida de cada etapa; se completa a medida que terminan
+    pub exit_codes: Vec<i32>,
+    pub pipefail: bool,
+    /// Hilos que leen la salida estándar y la de errores
+    pub captures: Vec<JoinHandle<String>>,
+    /// Entrada que leía la tubería en primer plano antes de detenerse
+    pub input: Option<JobInput>,
+}
+
+/// Bloquea la tabla aunque otro hilo haya fallado mientras la usaba
+fn lock(jobs: &SharedJobTable) -> MutexGuard<'_, JobTable> {
+    jobs.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn changes() -> MutexGuard<'static, u64> {
+    JOB_CHANGES.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Avisa a quien espera con `fg` que un trabajo cambió de estado
+fn notify_change() {
+    *changes() += 1;
+    JOB_CHANGED.notify_all();
+}
+
+/// Espera hasta que algún trabajo cambie de estado después de `seen`
+fn wait_change(seen: u64) {
+    let mut count = changes();
+    while *count == seen {
+        count = JOB_CHANGED.wait(count).unwrap_or_else(|poisoned| poisoned.into_inner());
+    }
+}
+
+/// Registra los procesos como trabajo y los espera en un hilo propio. Al terminar,
+/// si nadie lo espera con `fg`, se anuncia su finalización con el código de salida.
+pub fn watch(
+    jobs: &SharedJobTable,
+    pgid: u32,
+    command: String,
+    status: JobStatus,
+    processes: JobProcesses,
+    events: Option<EventSender>,
+) -> usize {
+    let JobProcesses { children, mut exit_codes, pipefail, captures, input } = processes;
+    let id = {
+        let mut table = lock(jobs);
+        let id = table.add(pgid, command, status);
+        if let Some(job) = table.get_mut(id) {
+            job.input = input;
+        }
+        id
+    };
+    let jobs = SharedJobTable::clone(jobs);
+
+    thread::spawn(move || {
+        for (index, mut child) in children {
+            loop {
+                match signals::wait_child(&mut child) {
+                    Ok(ChildStatus::Stopped) => {
+                        if let Some(job) = lock(&jobs).get_mut(id) {
+                            job.status = JobStatus::Stopped;
+                        }
+                        notify_change();
+                    }
+                    Ok(ChildStatus::Exited(code)) => {
+                        exit_codes[index] = code;
+                        break;
+                    }
+                    Err(_) => {
+                        exit_codes[index] = 1;
+                        break;
+                    }
+                }
+            }
+        }
+        // Esperar a que se envíe toda la salida antes del aviso de finalización
+        for capture in captures {
+            let _ = capture.join();
+        }
+
+        let exit_code = pipeline_exit_code(&exit_codes, pipefail);
+        let notice = {
+            let mut table = lock(&jobs);
+            match table.get_mut(id) {
+                Some(job) if job.foreground => {
+                    job.status = JobStatus::Done(exit_code);
+                    None
+                }
+                Some(job) => {
+                    job.status = JobStatus::Done(exit_code);
+                    let notice = job.describe();
+                    table.remove(id);
+                    Some(notice)
+                }
+                None => None,
+            }
+        };
+        notify_change();
+        if let (Some(notice), Some(events)) = (notice, events) {
+            let _ = events.send(ExecutionEvent::Output(notice));
+        }
+    });
+
+    id
+}
+
+/// Calcula el código de salida de la tubería a partir de cada etapa
+pub fn pipeline_exit_code(exit_codes: &[i32], pipefail: bool) -> i32 {
+    if pipefail {
+        exit_codes.iter().rev().copied().find(|code| *code != 0).unwrap_or(0)
+    } else {
+        exit_codes.last().copied().unwrap_or(0)
+    }
+}
+
+/// Reanuda el trabajo en primer plano y espera a que termine o se detenga de nuevo.
+/// Devuelve su código de salida (148 si se detuvo) y la descripción del trabajo.
+pub fn foreground(jobs: &SharedJobTable, id: usize) -> Result<(i32, String), JobError> {
+    let (pgid, command) = {
+        let mut table = lock(jobs);
+        let job = table.get_mut(id).ok_or_else(|| JobError::NoSuchJob(format!("%{}", id)))?;
+        job.foreground = true;
+        job.status = JobStatus::Running;
+        // Lo que se escriba en el prompt vuelve a llegar al trabajo
+        stdin::set_foreground_input(job.input.take());
+        (job.pgid, job.command.clone())
+    };
+
+    signals::set_foreground_group(Some(pgid));
+    let resumed = signals::send_to_group(pgid, ProcessSignal::Continue);
+
+    let result = loop {
+        if let Err(e) = &resumed {
+            break Err(e.clone());
+        }
+        // Se lee el contador antes de revisar la tabla para no perder un cambio
+        let seen = *changes();
+        let mut table = lock(jobs);
+        match table.get(id).map(|job| job.status) {
+            Some(JobStatus::Running) => {
+                drop(table);
+                wait_change(seen);
+            }
+            Some(JobStatus::Stopped) => {
+                let job = table.get_mut(id).expect("el trabajo existe");
+                job.foreground = false;
+                job.input = stdin::take_foreground_input();
+                break Ok((SUSPENDED_EXIT_CODE, job.describe()));
+            }
+            Some(JobStatus::Done(code)) => {
+                table.remove(id);
+                break Ok((code, command.clone()));
+            }
+            None => break Ok((0, command.clone())),
+        }
+    };
+
+    signals::set_foreground_group(None);
+    stdin::set_foreground_input(None);
+    result
+}
+
+/// Reanuda un trabajo detenido sin esperarlo
+pub fn background(jobs: &SharedJobTable, id: usize) -> Result<String, JobError> {
+    let mut table = lock(jobs);
+    let job = table.get_mut(id).ok_or_else(|| JobError::NoSuchJob(format!("%{}", id)))?;
+    signals::send_to_group(job.pgid, ProcessSignal::Continue)?;
+    job.status = JobStatus::Running;
+    job.foreground = false;
+    Ok(format!("{} &", job.describe()))
+}
+
+/// Envía una señal a todos los procesos de un trabajo. Un trabajo detenido se
+/// reanuda después para que pueda atender la señal.
+pub fn signal(jobs: &SharedJobTable, id: usize, signal: ProcessSignal) -> Result<(), JobError> {
+    let table = lock(jobs);
+    let job = table.get(id).ok_or_else(|| JobError::NoSuchJob(format!("%{}", id)))?;
+    signals::send_to_group(job.pgid, signal)?;
+    if job.status == JobStatus::Stopped && !matches!(signal, ProcessSignal::Suspend | ProcessSignal::Stop) {
+        let _ = signals::send_to_group(job.pgid, ProcessSignal::Continue);
+    }
+    Ok(())
+}
+
+/// Cierra todos los trabajos activos, como hace un shell al recibir SIGHUP
+pub fn hang_up_all(jobs: &SharedJobTable) {
+    let ids: Vec<usize> = lock(jobs).list().iter().map(|job| job.id).collect();
+    for id in ids {
+        let _ = signal(jobs, id, ProcessSignal::Hangup);
+    }
+}
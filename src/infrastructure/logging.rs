@@ -85,7 +85,7 @@ impl Log for Logger {
             }
             
             // También escribir a la consola para errores críticos
-            if record.level() >= log::Level::Error {
+            if record.level() <= log::Level::Error {
                 eprintln!("{}", log_entry);
             }
         }
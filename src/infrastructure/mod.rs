@@ -1,2 +1,12 @@
+pub mod builtins;
+pub mod completion_specs;
+pub mod documents;
 pub mod repositories;
 pub mod logging;
+pub mod jobs;
+pub mod pipeline;
+pub mod plugins;
+pub mod pty;
+pub mod signals;
+pub mod stdin;
+pub mod worker;
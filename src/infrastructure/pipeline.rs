@@ -0,0 +1,543 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, PipeReader, PipeWriter, Read, Write};
+use std::process::{Child, Command as ProcessCommand, Stdio};
+use std::thread::{self, JoinHandle};
+
+use crate::domain::entities::{
+    Command, EventSender, ExecutionEvent, PipelineOptions, PipelineStage, Redirect, RedirectMode,
+};
+use crate::domain::errors::{ErrorFormatter, SourceLocation, TermirustError};
+use crate::domain::jobs::{JobInput, JobStatus, SharedJobTable};
+use crate::domain::style::{CellStyle, StyledLine};
+use crate::infrastructure::jobs::{self, JobProcesses};
+use crate::infrastructure::pty;
+use crate::infrastructure::signals::{self, ChildStatus};
+use crate::infrastructure::stdin;
+
+/// Bytes finales de la salida que se conservan para el historial cuando la salida ya
+/// se mostró a medida que llegaba
+const STREAMED_TAIL_BYTES: usize = 64 * 1024;
+
+/// Origen de la entrada estándar de una etapa
+enum InputSource {
+    Null,
+    /// La entrada estándar de Termirust, cuando se ejecuta sin ventana
+    Inherit,
+    Pipe(PipeReader),
+    File(File),
+}
+
+impl From<InputSource> for Stdio {
+    fn from(source: InputSource) -> Self {
+        match source {
+            InputSource::Null => Stdio::null(),
+            InputSource::Inherit => Stdio::inherit(),
+            InputSource::Pipe(reader) => Stdio::from(reader),
+            InputSource::File(file) => Stdio::from(file),
+        }
+    }
+}
+
+/// Destino de un descriptor de salida de una etapa
+enum OutputTarget {
+    /// Tubería hacia la siguiente etapa o hacia la captura de la terminal
+    Pipe(PipeWriter),
+    /// Archivo abierto por una redirección
+    File(File),
+}
+
+impl OutputTarget {
+    fn try_clone(&self) -> io::Result<Self> {
+        match self {
+            OutputTarget::Pipe(writer) => writer.try_clone().map(OutputTarget::Pipe),
+            OutputTarget::File(file) => file.try_clone().map(OutputTarget::File),
+        }
+    }
+}
+
+impl Write for OutputTarget {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            OutputTarget::Pipe(writer) => writer.write(buf),
+            OutputTarget::File(file) => file.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            OutputTarget::Pipe(writer) => writer.flush(),
+            OutputTarget::File(file) => file.flush(),
+        }
+    }
+}
+
+impl From<OutputTarget> for Stdio {
+    fn from(target: OutputTarget) -> Self {
+        match target {
+            OutputTarget::Pipe(writer) => Stdio::from(writer),
+            OutputTarget::File(file) => Stdio::from(file),
+        }
+    }
+}
+
+/// Descriptores estándar de una etapa después de aplicar sus redirecciones
+struct StageIo {
+    stdin: InputSource,
+    stdout: OutputTarget,
+    stderr: OutputTarget,
+}
+
+impl StageIo {
+    /// Aplica las redirecciones en orden, de izquierda a derecha, como en un shell POSIX
+    fn apply(&mut self, command: &str, redirects: &[Redirect]) -> Result<(), TermirustError> {
+        for redirect in redirects {
+            let file_error = |e: io::Error| TermirustError::from_io(command, Some(&redirect.target), &e);
+            match (redirect.fd, redirect.mode) {
+                (0, RedirectMode::Input) => {
+                    let file = File::open(&redirect.target).map_err(file_error)?;
+                    self.stdin = InputSource::File(file);
+                }
+                (fd @ (1 | 2), RedirectMode::Truncate | RedirectMode::Append) => {
+                    let file = OpenOptions::new()
+                        .write(true)
+                        .create(true)
+                        .append(redirect.mode == RedirectMode::Append)
+                        .truncate(redirect.mode == RedirectMode::Truncate)
+                        .open(&redirect.target)
+                        .map_err(file_error)?;
+                    *self.output_mut(fd) = OutputTarget::File(file);
+                }
+                (fd @ (1 | 2), RedirectMode::Duplicate) => {
+                    let source = match redirect.target.as_str() {
+                        "1" => &self.stdout,
+                        "2" => &self.stderr,
+                        _ => {
+                            let error = io::Error::new(io::ErrorKind::Unsupported, format!("{}>&{}", fd, redirect.target));
+                            return Err(TermirustError::io(command, &error));
+                        }
+                    };
+                    let duplicated = source.try_clone().map_err(|e| TermirustError::io(command, &e))?;
+                    *self.output_mut(fd) = duplicated;
+                }
+                (fd, mode) => {
+                    let error = io::Error::new(io::ErrorKind::Unsupported, format!("{}{}", fd, mode.symbol()));
+                    return Err(TermirustError::io(command, &error));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn output_mut(&mut self, fd: u32) -> &mut OutputTarget {
+        if fd == 2 {
+            &mut self.stderr
+        } else {
+            &mut self.stdout
+        }
+    }
+}
+
+/// Flujo que lee una captura: la salida estándar o la de errores, con el archivo y la
+/// línea del script que lanzó la tubería
+enum Stream {
+    Output,
+    Errors(Option<SourceLocation>),
+}
+
+impl Stream {
+    /// Ubicación de una línea de errores que empieza; la que continúa una salida
+    /// parcial ya la mostró
+    fn location(&self, line_open: bool) -> Option<SourceLocation> {
+        match self {
+            Stream::Errors(location) if !line_open => location.clone(),
+            _ => None,
+        }
+    }
+
+    /// Línea terminada, que completa la salida parcial anterior si la hay
+    fn line_event(&self, text: String, line_open: bool) -> ExecutionEvent {
+        match self {
+            Stream::Output => ExecutionEvent::Output(text),
+            Stream::Errors(_) => ExecutionEvent::ErrorOutput { line: text, location: self.location(line_open) },
+        }
+    }
+
+    /// Texto sin salto de línea
+    fn partial_event(&self, text: String, line_open: bool) -> ExecutionEvent {
+        match self {
+            Stream::Output => ExecutionEvent::PartialOutput(text),
+            Stream::Errors(_) => ExecutionEvent::PartialErrorOutput { text, location: self.location(line_open) },
+        }
+    }
+}
+
+/// Ejecutor de tuberías entre procesos externos y comandos internos
+pub struct PipelineExecutor;
+
+impl PipelineExecutor {
+    /// Lanza todas las etapas conectadas con tuberías reales y espera a que terminen.
+    /// Las líneas que llegan a la terminal se envían por `events` a medida que se leen.
+    /// En segundo plano, o si se detiene con Ctrl+Z, la tubería pasa a la tabla de trabajos.
+    /// Los errores de un comando interno con la salida de errores redirigida se escriben
+    /// con `describe_error`.
+    pub fn run(
+        stages: Vec<PipelineStage>,
+        options: &PipelineOptions,
+        job_table: &SharedJobTable,
+        events: Option<&EventSender>,
+        describe_error: ErrorFormatter,
+    ) -> Result<Command, TermirustError> {
+        if let Some(command) = Self::pty_command(&stages, options) {
+            return pty::run(command, options, events);
+        }
+        if let (Some(events), Some(command)) = (events, Self::document_command(&stages, options)) {
+            if let Some(document) = &command.document {
+                let _ = events.send(ExecutionEvent::Document(document.clone()));
+            }
+            return Ok(command.clone().with_output(String::new()));
+        }
+
+        let pipe_error = |e: io::Error| TermirustError::io("", &e);
+        let (stdout_reader, stdout_writer) = io::pipe().map_err(pipe_error)?;
+        let (stderr_reader, stderr_writer) = io::pipe().map_err(pipe_error)?;
+        let stdout_capture = Self::drain(stdout_reader, events.cloned(), Stream::Output);
+        let stderr_capture = Self::drain(stderr_reader, events.cloned(), Stream::Errors(options.location.clone()));
+
+        // En primer plano, la primera etapa lee lo que el usuario escribe en el prompt
+        let (mut previous, input_writer) = if options.background {
+            (InputSource::Null, None)
+        } else if stdin::inherits_input() {
+            (InputSource::Inherit, None)
+        } else {
+            let (reader, writer) = io::pipe().map_err(pipe_error)?;
+            (InputSource::Pipe(reader), Some(Box::new(writer) as JobInput))
+        };
+
+        let stage_count = stages.len();
+        let mut children: Vec<Option<Child>> = Vec::with_capacity(stage_count);
+        let mut exit_codes = Vec::with_capacity(stage_count);
+        let mut errors = Vec::new();
+        let mut last_command = Command::new(String::new(), Vec::new());
+        // Todas las etapas comparten el grupo de procesos de la primera que se lanza
+        let mut process_group = None;
+
+        for (index, stage) in stages.into_iter().enumerate() {
+            let is_last = index + 1 == stage_count;
+            let (next_reader, stdout) = if is_last {
+                (None, stdout_writer.try_clone().map_err(pipe_error)?)
+            } else {
+                let (reader, writer) = io::pipe().map_err(pipe_error)?;
+                (Some(reader), writer)
+            };
+            let mut io = StageIo {
+                stdin: std::mem::replace(&mut previous, InputSource::Null),
+                stdout: OutputTarget::Pipe(stdout),
+                stderr: OutputTarget::Pipe(stderr_writer.try_clone().map_err(pipe_error)?),
+            };
+            if let Some(reader) = next_reader {
+                previous = InputSource::Pipe(reader);
+            }
+
+            let command = match &stage {
+                PipelineStage::Builtin(command) | PipelineStage::External(command) => command,
+            };
+            if let Err(error) = io.apply(&command.name, &command.redirects) {
+                exit_codes.push(error.exit_code());
+                errors.push(error);
+                children.push(None);
+                last_command = command.clone();
+                continue;
+            }
+
+            match stage {
+                PipelineStage::Builtin(mut command) => {
+                    // Los comandos internos no leen su entrada; escriben sus líneas ya renderizadas.
+                    // Sus errores pasan a texto solo si la salida de errores está redirigida.
+                    if !command.redirects.iter().any(|redirect| redirect.fd == 2) {
+                        errors.append(&mut command.errors);
+                    }
+                    Self::write_builtin_output(&command, io, describe_error);
+                    exit_codes.push(command.exit_code);
+                    children.push(None);
+                    last_command = command;
+                }
+                PipelineStage::External(command) => {
+                    match Self::spawn(&command, options, io, process_group) {
+                        Ok(child) => {
+                            if process_group.is_none() {
+                                process_group = Some(child.id());
+                                if !options.background {
+                                    signals::set_foreground_group(process_group);
+                                }
+                            }
+                            exit_codes.push(0);
+                            children.push(Some(child));
+                        }
+                        Err(error) => {
+                            exit_codes.push(error.exit_code());
+                            errors.push(error);
+                            children.push(None);
+                        }
+                    }
+                    last_command = command;
+                }
+            }
+        }
+
+        // Cerrar nuestras copias de los extremos de escritura para que las capturas terminen
+        drop(stdout_writer);
+        drop(stderr_writer);
+        // Los errores de las etapas los muestra quien ejecuta la tubería, que sabe dónde
+        // ocurrieron
+        last_command.errors = errors;
+
+        if let (true, Some(pgid)) = (options.background, process_group) {
+            let running = children
+                .into_iter()
+                .enumerate()
+                .filter_map(|(index, child)| child.map(|child| (index, child)))
+                .collect();
+            let processes = JobProcesses {
+                children: running,
+                exit_codes,
+                pipefail: options.pipefail,
+                captures: vec![stdout_capture, stderr_capture],
+                input: None,
+            };
+            let id = jobs::watch(job_table, pgid, options.text.clone(), JobStatus::Running, processes, events.cloned());
+            let notice = format!("[{}] {}", id, pgid);
+            if let Some(events) = events {
+                let _ = events.send(ExecutionEvent::Output(notice.clone()));
+            }
+            return Ok(last_command.with_output(notice).with_exit_code(0));
+        }
+
+        stdin::set_foreground_input(input_writer);
+        let mut stopped = Vec::new();
+        for (index, child) in children.into_iter().enumerate() {
+            if let Some(mut child) = child {
+                let status = match signals::wait_child(&mut child) {
+                    Ok(status) => status,
+                    Err(e) => {
+                        signals::set_foreground_group(None);
+                        stdin::set_foreground_input(None);
+                        return Err(TermirustError::io(&options.text, &e));
+                    }
+                };
+                exit_codes[index] = status.exit_code();
+                if status == ChildStatus::Stopped {
+                    stopped.push((index, child));
+                }
+            }
+        }
+        signals::set_foreground_group(None);
+        let input = stdin::take_foreground_input();
+
+        if let (false, Some(pgid)) = (stopped.is_empty(), process_group) {
+            // Los procesos detenidos conservan sus tuberías, también la de entrada: la
+            // captura sigue en su hilo y el trabajo se espera desde la tabla de trabajos
+            let exit_code = jobs::pipeline_exit_code(&exit_codes, true);
+            let processes = JobProcesses {
+                children: stopped,
+                exit_codes,
+                pipefail: options.pipefail,
+                captures: vec![stdout_capture, stderr_capture],
+                input,
+            };
+            let id = jobs::watch(job_table, pgid, options.text.clone(), JobStatus::Stopped, processes, events.cloned());
+            let notice = job_table
+                .lock()
+                .ok()
+                .and_then(|table| table.get(id).map(|job| job.describe()))
+                .unwrap_or_default();
+            if let Some(events) = events {
+                let _ = events.send(ExecutionEvent::Output(notice.clone()));
+            }
+            return Ok(last_command.with_error_output(notice).with_exit_code(exit_code));
+        }
+
+        drop(input);
+        let stdout = stdout_capture.join().unwrap_or_default();
+        let stderr = stderr_capture.join().unwrap_or_default();
+
+        Ok(last_command
+            .with_output(stdout)
+            .with_error_output(stderr)
+            .with_exit_code(jobs::pipeline_exit_code(&exit_codes, options.pipefail)))
+    }
+
+    /// Con `set -o pty`, un único comando externo en primer plano y sin redirecciones se
+    /// ejecuta en una pseudoterminal para que los programas interactivos funcionen
+    fn pty_command<'a>(stages: &'a [PipelineStage], options: &PipelineOptions) -> Option<&'a Command> {
+        if !options.pty || options.background || !pty::SUPPORTED {
+            return None;
+        }
+        match stages {
+            [PipelineStage::External(command)] if command.redirects.is_empty() => Some(command),
+            _ => None,
+        }
+    }
+
+    /// Un comando interno que trae un documento (`man`, `doc`), solo, en primer plano y
+    /// sin redirecciones se abre en el visor; en una tubería se escribe su texto
+    fn document_command<'a>(stages: &'a [PipelineStage], options: &PipelineOptions) -> Option<&'a Command> {
+        match stages {
+            [PipelineStage::Builtin(command)]
+                if command.document.is_some() && command.redirects.is_empty() && !options.background =>
+            {
+                Some(command)
+            }
+            _ => None,
+        }
+    }
+
+    /// Lanza un proceso de la tubería con sus descriptores ya configurados
+    fn spawn(
+        command: &Command,
+        options: &PipelineOptions,
+        io: StageIo,
+        process_group: Option<u32>,
+    ) -> Result<Child, TermirustError> {
+        let mut process = ProcessCommand::new(&command.name);
+        process.args(&command.args);
+        Self::apply_environment(&mut process, command, options);
+        signals::join_process_group(&mut process, process_group);
+        process.stdin(Stdio::from(io.stdin));
+        process.stdout(Stdio::from(io.stdout));
+        process.stderr(Stdio::from(io.stderr));
+
+        process.spawn().map_err(|e| Self::spawn_error(&command.name, e))
+    }
+
+    /// Entorno del proceso: las variables exportadas de la sesión más las asignaciones
+    /// escritas delante del comando
+    pub fn apply_environment(process: &mut ProcessCommand, command: &Command, options: &PipelineOptions) {
+        if let Some(environment) = &options.environment {
+            process.env_clear();
+            process.envs(environment.iter().map(|(name, value)| (name, value)));
+        }
+        process.envs(command.assignments.iter().map(|(name, value)| (name, value)));
+    }
+
+    /// Error cuando no se puede lanzar un proceso: el programa no existe (127), no se
+    /// puede ejecutar (126) u otro fallo del sistema
+    pub fn spawn_error(name: &str, error: io::Error) -> TermirustError {
+        TermirustError::from_io(name, None, &error)
+    }
+
+    /// Escribe la salida de un comando interno desde un hilo, para no bloquear si la
+    /// siguiente etapa todavía no lee
+    fn write_builtin_output(command: &Command, io: StageIo, describe_error: ErrorFormatter) {
+        let output = command.output.clone();
+        let mut error_output = command.error_output.clone();
+        for error in &command.errors {
+            if !error_output.is_empty() && !error_output.ends_with('\n') {
+                error_output.push('\n');
+            }
+            error_output.push_str(&describe_error(error));
+        }
+        let StageIo { mut stdout, mut stderr, .. } = io;
+        thread::spawn(move || {
+            let _ = Self::write_text(&mut stdout, &output);
+            let _ = Self::write_text(&mut stderr, &error_output);
+        });
+    }
+
+    fn write_text(target: &mut OutputTarget, text: &str) -> io::Result<()> {
+        if text.is_empty() {
+            return Ok(());
+        }
+        // Una sola escritura: la línea llega completa y no como salida parcial
+        let mut text = text.to_string();
+        if !text.ends_with('\n') {
+            text.push('\n');
+        }
+        target.write_all(text.as_bytes())?;
+        target.flush()
+    }
+
+    /// Lee por completo un flujo en un hilo aparte para evitar bloqueos, enviando
+    /// cada línea en cuanto está completa. El texto sin salto de línea (una pregunta
+    /// que espera respuesta) se envía igualmente como salida parcial. Un color que
+    /// sigue activo se repite al principio de cada envío, ya que se muestran por separado.
+    /// Las líneas completas de la salida de errores se envían como errores. Sin eventos
+    /// se devuelve todo el texto; con eventos, solo las últimas líneas para el historial.
+    fn drain<R: Read + Send + 'static>(mut stream: R, events: Option<EventSender>, kind: Stream) -> JoinHandle<String> {
+        thread::spawn(move || {
+            let mut buffer = Vec::new();
+            let mut chunk = [0u8; 4096];
+            let mut pending = Vec::new();
+            let mut line_open = false;
+            let mut style = CellStyle::default();
+            loop {
+                let read = match stream.read(&mut chunk) {
+                    Ok(0) => break,
+                    Ok(read) => read,
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(_) => break,
+                };
+                buffer.extend_from_slice(&chunk[..read]);
+                let Some(events) = &events else { continue };
+                Self::keep_tail(&mut buffer);
+
+                pending.extend_from_slice(&chunk[..read]);
+                while let Some(end) = pending.iter().position(|byte| *byte == b'\n') {
+                    let line: Vec<u8> = pending.drain(..=end).collect();
+                    let text = String::from_utf8_lossy(&line);
+                    let text = Self::styled_text(text.trim_end_matches(['\n', '\r']), &mut style);
+                    let _ = events.send(kind.line_event(text, line_open));
+                    line_open = false;
+                }
+                // Sin cortar un carácter UTF-8 que todavía no llegó completo. Un `\r` final
+                // se guarda para que lo siguiente reescriba la línea (barras de progreso).
+                let mut complete = match std::str::from_utf8(&pending) {
+                    Ok(_) => pending.len(),
+                    Err(error) if error.error_len().is_none() => error.valid_up_to(),
+                    Err(_) => pending.len(),
+                };
+                if pending[..complete].ends_with(b"\r") {
+                    complete -= 1;
+                }
+                if complete > 0 {
+                    let partial: Vec<u8> = pending.drain(..complete).collect();
+                    let text = Self::styled_text(&String::from_utf8_lossy(&partial), &mut style);
+                    let _ = events.send(kind.partial_event(text, line_open));
+                    line_open = true;
+                }
+            }
+
+            if let Some(events) = &events {
+                if !pending.is_empty() || line_open {
+                    let text = Self::styled_text(&String::from_utf8_lossy(&pending), &mut style);
+                    let _ = events.send(kind.line_event(text, line_open));
+                }
+            }
+            String::from_utf8_lossy(&buffer).to_string()
+        })
+    }
+
+    /// Recorta el texto ya enviado a los últimos `STREAMED_TAIL_BYTES`, empezando en una
+    /// línea completa. Recorta solo al pasar del doble para no mover el texto en cada lectura.
+    fn keep_tail(buffer: &mut Vec<u8>) {
+        if buffer.len() <= 2 * STREAMED_TAIL_BYTES {
+            return;
+        }
+        let start = buffer.len() - STREAMED_TAIL_BYTES;
+        let start = buffer[start..]
+            .iter()
+            .position(|byte| *byte == b'\n')
+            .map_or(start, |offset| start + offset + 1);
+        buffer.drain(..start);
+    }
+
+    /// Antepone el color heredado al texto y actualiza el que queda activo después
+    fn styled_text(text: &str, style: &mut CellStyle) -> String {
+        let inherited = *style;
+        *style = StyledLine::parse_ansi_from(text, inherited).1;
+        if inherited == CellStyle::default() {
+            text.to_string()
+        } else {
+            format!("{}{}", inherited.to_sgr(), text)
+        }
+    }
+}
@@ -0,0 +1,390 @@
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command as ProcessCommand, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::domain::builtins::{BuiltinCommand, BuiltinContext};
+use crate::domain::entities::Command;
+use crate::domain::errors::{PluginError, TermirustError};
+use crate::domain::style::{CellStyle, Color};
+use crate::infrastructure::pty;
+use crate::infrastructure::signals;
+use crate::infrastructure::repositories::{config_directory, is_executable};
+
+/// Prefijo de los ejecutables que Termirust reconoce como plugins
+const PLUGIN_PREFIX: &str = "termirust-";
+
+/// Versión del protocolo que se envía en cada petición
+const PROTOCOL_VERSION: u32 = 1;
+
+/// Tiempo máximo para que un plugin se describa al iniciar Termirust
+const DESCRIBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Directorio de plugins del usuario: `~/.config/termirust/plugins`
+pub fn plugin_directory() -> Option<PathBuf> {
+    config_directory().map(|directory| directory.join("plugins"))
+}
+
+/// Busca los plugins `termirust-<nombre>` del directorio de plugins y del `PATH`. Si
+/// dos tienen el mismo nombre gana el primero: el del directorio de plugins y después
+/// el orden del `PATH`. Solo se ejecutan para pedirles su descripción los del
+/// directorio de plugins, que el usuario instaló para Termirust; los del `PATH` se
+/// registran con una descripción genérica.
+pub fn discover() -> Vec<PluginCommand> {
+    let mut found = BTreeMap::new();
+    if let Some(directory) = plugin_directory() {
+        found.extend(plugins_in(&directory).into_iter().map(|(name, path)| (name, (path, true))));
+    }
+    if let Some(path) = std::env::var_os("PATH") {
+        for directory in std::env::split_paths(&path) {
+            for (name, path) in plugins_in(&directory) {
+                found.entry(name).or_insert((path, false));
+            }
+        }
+    }
+
+    // Cada plugin se describe en su propio hilo para no sumar sus tiempos al inicio
+    let handles: Vec<_> = found
+        .into_iter()
+        .map(|(name, (path, describe))| {
+            thread::spawn(move || {
+                if describe {
+                    PluginCommand::describe(name, path)
+                } else {
+                    PluginCommand::new(name, path, PluginDescription::default())
+                }
+            })
+        })
+        .collect();
+    handles.into_iter().filter_map(|handle| handle.join().ok()).collect()
+}
+
+/// Ejecutables `termirust-<nombre>` de un directorio, con su nombre de comando
+fn plugins_in(directory: &Path) -> Vec<(String, PathBuf)> {
+    let Ok(entries) = std::fs::read_dir(directory) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| Some((plugin_name(&entry.file_name())?, entry.path())))
+        .filter(|(_, path)| is_executable(path))
+        .collect()
+}
+
+/// Nombre del comando de un ejecutable `termirust-<nombre>`, sin extensión
+fn plugin_name(file_name: &OsStr) -> Option<String> {
+    let file_name = file_name.to_str()?;
+    let name = file_name.strip_prefix(PLUGIN_PREFIX)?;
+    let name = if cfg!(windows) { name.strip_suffix(".exe").unwrap_or(name) } else { name };
+    let is_valid = !name.is_empty()
+        && name.chars().all(|ch| ch.is_alphanumeric() || matches!(ch, '-' | '_' | '.'));
+    is_valid.then(|| name.to_string())
+}
+
+/// Petición que recibe el plugin por su entrada estándar, en una sola línea JSON
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum PluginRequest<'a> {
+    /// Al iniciar Termirust: el plugin responde con un `PluginDescription`
+    Describe { version: u32 },
+    /// Al ejecutar el comando: el plugin responde con un `PluginResponse`
+    Run {
+        version: u32,
+        name: &'a str,
+        args: &'a [String],
+        cwd: String,
+        env: BTreeMap<&'a str, &'a str>,
+        terminal: TerminalSize,
+    },
+}
+
+#[derive(Serialize)]
+struct TerminalSize {
+    columns: u16,
+    rows: u16,
+}
+
+/// Respuesta a `describe`; todos los campos son opcionales
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct PluginDescription {
+    description: Option<String>,
+    usage: Option<String>,
+    help: Option<String>,
+    /// Candidatos para completar los argumentos con Tab
+    completions: Vec<String>,
+}
+
+/// Respuesta a `run`
+#[derive(Deserialize)]
+struct PluginResponse {
+    #[serde(default)]
+    output: Vec<OutputLine>,
+    #[serde(default)]
+    errors: Vec<OutputLine>,
+    #[serde(default)]
+    exit_code: i32,
+}
+
+/// Línea de salida: texto simple o una lista de fragmentos con estilo
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum OutputLine {
+    Text(String),
+    Spans(Vec<OutputSpan>),
+}
+
+#[derive(Deserialize)]
+struct OutputSpan {
+    text: String,
+    /// Nombre (`red`, `bright_blue`), índice de la paleta (`208`) o `#rrggbb`
+    fg: Option<String>,
+    bg: Option<String>,
+    #[serde(default)]
+    bold: bool,
+    #[serde(default)]
+    dim: bool,
+    #[serde(default)]
+    italic: bool,
+    #[serde(default)]
+    underline: bool,
+}
+
+impl OutputLine {
+    /// Texto de la línea con los estilos como secuencias ANSI, que la interfaz ya
+    /// sabe dibujar
+    fn to_ansi(&self) -> String {
+        match self {
+            OutputLine::Text(text) => text.clone(),
+            OutputLine::Spans(spans) => {
+                let mut line = String::new();
+                for span in spans {
+                    let style = span.style();
+                    if style == CellStyle::default() {
+                        line.push_str(&span.text);
+                    } else {
+                        line.push_str(&style.to_sgr());
+                        line.push_str(&span.text);
+                        line.push_str("\x1b[0m");
+                    }
+                }
+                line
+            }
+        }
+    }
+}
+
+impl OutputSpan {
+    fn style(&self) -> CellStyle {
+        CellStyle {
+            fg: self.fg.as_deref().and_then(parse_color).unwrap_or_default(),
+            bg: self.bg.as_deref().and_then(parse_color).unwrap_or_default(),
+            bold: self.bold,
+            dim: self.dim,
+            italic: self.italic,
+            underline: self.underline,
+            ..CellStyle::default()
+        }
+    }
+}
+
+/// Interpreta un color de un fragmento; uno desconocido se deja con el color por defecto
+fn parse_color(name: &str) -> Option<Color> {
+    const NAMES: [&str; 8] = ["black", "red", "green", "yellow", "blue", "magenta", "cyan", "white"];
+    if let Some(hex) = name.strip_prefix('#') {
+        let value = u32::from_str_radix(hex, 16).ok().filter(|_| hex.len() == 6)?;
+        return Some(Color::Rgb((value >> 16) as u8, (value >> 8) as u8, value as u8));
+    }
+    if let Ok(index) = name.parse::<u8>() {
+        return Some(Color::Indexed(index));
+    }
+    let (base, bright) = match name.strip_prefix("bright_") {
+        Some(base) => (base, 8),
+        None => (name, 0),
+    };
+    let index = NAMES.iter().position(|color| *color == base)?;
+    Some(Color::Indexed(index as u8 + bright))
+}
+
+/// Comando aportado por un ejecutable `termirust-<nombre>`. Recibe una petición JSON
+/// por la entrada estándar y responde con JSON por la salida estándar; lo que
+/// escriba en su salida de errores se muestra como error.
+#[derive(Debug, Clone)]
+pub struct PluginCommand {
+    name: String,
+    path: PathBuf,
+    description: String,
+    usage: String,
+    help: Option<String>,
+    completions: Vec<String>,
+}
+
+impl PluginCommand {
+    /// Pide al plugin su descripción. Si no responde a tiempo o con JSON válido se
+    /// registra igual, con una descripción genérica.
+    fn describe(name: String, path: PathBuf) -> Self {
+        let description = Self::request(&path, &PluginRequest::Describe { version: PROTOCOL_VERSION }, Some(DESCRIBE_TIMEOUT))
+            .and_then(|(stdout, _)| {
+                serde_json::from_slice::<PluginDescription>(&stdout)
+                    .map_err(|e| PluginError::InvalidResponse(e.to_string()))
+            })
+            .unwrap_or_else(|e| {
+                log::warn!("El plugin '{}' no se pudo describir: {:?}", path.display(), e);
+                PluginDescription::default()
+            });
+        Self::new(name, path, description)
+    }
+
+    /// Plugin con la descripción que dio; lo que falte se completa con valores genéricos
+    fn new(name: String, path: PathBuf, description: PluginDescription) -> Self {
+        Self {
+            description: description
+                .description
+                .unwrap_or_else(|| format!("Plugin externo ({})", path.display())),
+            usage: description.usage.unwrap_or_else(|| format!("{} [argumentos...]", name)),
+            help: description.help,
+            completions: description.completions,
+            name,
+            path,
+        }
+    }
+
+    /// Ejecuta el plugin con la petición en su entrada estándar y devuelve su salida
+    /// estándar y su salida de errores. Con `timeout`, un plugin que tarda más se termina.
+    fn request(path: &Path, request: &PluginRequest, timeout: Option<Duration>) -> Result<(Vec<u8>, String), PluginError> {
+        let mut child = Self::spawn(path, request)?;
+        if let Some(timeout) = timeout {
+            Self::wait_until(&mut child, timeout)?;
+        }
+        let output = child.wait_with_output();
+        if matches!(request, PluginRequest::Run { .. }) {
+            signals::set_foreground_group(None);
+        }
+        let output = output.map_err(|e| PluginError::Io(e.to_string()))?;
+        let stderr = String::from_utf8_lossy(&output.stderr).trim_end().to_string();
+        if output.stdout.iter().all(u8::is_ascii_whitespace) {
+            return Err(match (output.status.code(), Self::signal(&output.status)) {
+                (None, Some(signal)) => PluginError::Signal(signal),
+                (code, _) => PluginError::NoResponse { code: code.unwrap_or(1), stderr },
+            });
+        }
+        Ok((output.stdout, stderr))
+    }
+
+    /// Señal que terminó al plugin
+    #[cfg(unix)]
+    fn signal(status: &std::process::ExitStatus) -> Option<i32> {
+        use std::os::unix::process::ExitStatusExt;
+        status.signal()
+    }
+
+    #[cfg(not(unix))]
+    fn signal(_status: &std::process::ExitStatus) -> Option<i32> {
+        None
+    }
+
+    /// Al ejecutar el comando, el plugin va en su propio grupo de procesos en primer
+    /// plano, como un programa externo, para que Ctrl+C lo interrumpa. No es un trabajo
+    /// que se pueda retomar con `fg`, así que ignora Ctrl+Z.
+    fn spawn(path: &Path, request: &PluginRequest) -> Result<Child, PluginError> {
+        let mut process = ProcessCommand::new(path);
+        process.stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::piped());
+        if let PluginRequest::Run { cwd, env, .. } = request {
+            process.current_dir(cwd).env_clear().envs(env);
+            signals::join_process_group(&mut process, None);
+            signals::ignore_suspend(&mut process);
+        }
+        let mut child = process.spawn().map_err(|e| PluginError::Io(e.to_string()))?;
+        if matches!(request, PluginRequest::Run { .. }) {
+            signals::set_foreground_group(Some(child.id()));
+        }
+
+        let mut line = serde_json::to_string(request).map_err(|e| PluginError::Io(e.to_string()))?;
+        line.push('\n');
+        if let Some(mut stdin) = child.stdin.take() {
+            // Un plugin que no lee su entrada la cierra; eso no es un error
+            let _ = stdin.write_all(line.as_bytes());
+        }
+        Ok(child)
+    }
+
+    fn wait_until(child: &mut Child, timeout: Duration) -> Result<(), PluginError> {
+        let deadline = Instant::now() + timeout;
+        while child.try_wait().map_err(|e| PluginError::Io(e.to_string()))?.is_none() {
+            if Instant::now() >= deadline {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(PluginError::Timeout(timeout.as_secs()));
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+        Ok(())
+    }
+}
+
+impl BuiltinCommand for PluginCommand {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn usage(&self) -> &str {
+        &self.usage
+    }
+
+    fn help(&self) -> String {
+        self.help.clone().unwrap_or_else(|| format!("Uso: {}\n\n{}", self.usage, self.description))
+    }
+
+    fn completions(&self) -> Vec<String> {
+        self.completions.clone()
+    }
+
+    fn location(&self) -> Option<&Path> {
+        Some(&self.path)
+    }
+
+    fn execute(&self, command: &Command, context: &mut BuiltinContext) -> Command {
+        let (columns, rows) = pty::window_size();
+        let request = PluginRequest::Run {
+            version: PROTOCOL_VERSION,
+            name: &self.name,
+            args: &command.args,
+            cwd: std::env::current_dir().unwrap_or_default().to_string_lossy().to_string(),
+            env: context.environment.iter().map(|(name, value)| (name.as_str(), value.as_str())).collect(),
+            terminal: TerminalSize { columns, rows },
+        };
+
+        let result = Self::request(&self.path, &request, None).and_then(|(stdout, stderr)| {
+            let response: PluginResponse =
+                serde_json::from_slice(&stdout).map_err(|e| PluginError::InvalidResponse(e.to_string()))?;
+            Ok((response, stderr))
+        });
+        match result {
+            Ok((response, stderr)) => {
+                let output: Vec<String> = response.output.iter().map(OutputLine::to_ansi).collect();
+                let mut errors: Vec<String> = response.errors.iter().map(OutputLine::to_ansi).collect();
+                errors.extend(stderr.lines().map(str::to_string));
+                command
+                    .clone()
+                    .with_output(output.join("\n"))
+                    .with_error_output(errors.join("\n"))
+                    .with_exit_code(response.exit_code)
+            }
+            Err(reason) => {
+                let path = self.path.to_string_lossy().to_string();
+                let error = TermirustError::Plugin { name: self.name.clone(), path, reason };
+                let exit_code = error.exit_code();
+                command.clone().with_error(error).with_exit_code(exit_code)
+            }
+        }
+    }
+}
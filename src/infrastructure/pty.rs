@@ -0,0 +1,243 @@
+use std::fs::File;
+use std::io::Write;
+use std::sync::{Mutex, MutexGuard};
+
+#[cfg(not(unix))]
+use crate::domain::entities::{Command, EventSender, PipelineOptions};
+use crate::domain::errors::TermirustError;
+use std::io;
+
+/// Tamaño de la pseudoterminal hasta que la interfaz informa el de su rejilla
+const DEFAULT_WINDOW_SIZE: (u16, u16) = (80, 24);
+
+/// Columnas y filas de la rejilla que dibuja la interfaz
+static WINDOW_SIZE: Mutex<(u16, u16)> = Mutex::new(DEFAULT_WINDOW_SIZE);
+
+/// Extremo maestro de la pseudoterminal en primer plano: recibe las teclas y los
+/// cambios de tamaño de la ventana
+static FOREGROUND_PTY: Mutex<Option<File>> = Mutex::new(None);
+
+/// Las pseudoterminales solo están disponibles en sistemas Unix
+pub const SUPPORTED: bool = cfg!(unix);
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Registra el tamaño de la rejilla de la interfaz y lo aplica a la pseudoterminal en
+/// primer plano, que avisa al programa con SIGWINCH
+pub fn update_window_size(cols: u16, rows: u16) {
+    *lock(&WINDOW_SIZE) = (cols, rows);
+    if let Some(master) = lock(&FOREGROUND_PTY).as_ref() {
+        let _ = set_window_size(master, cols, rows);
+    }
+}
+
+/// Columnas y filas de la rejilla de la interfaz
+pub fn window_size() -> (u16, u16) {
+    *lock(&WINDOW_SIZE)
+}
+
+/// Escribe en la pseudoterminal en primer plano las teclas que pulsó el usuario.
+/// Devuelve `false` si no hay ninguna.
+pub fn write_foreground(bytes: &[u8]) -> bool {
+    match lock(&FOREGROUND_PTY).as_mut() {
+        Some(master) => master.write_all(bytes).is_ok(),
+        None => false,
+    }
+}
+
+#[cfg(unix)]
+pub use unix::run;
+
+#[cfg(unix)]
+fn set_window_size(master: &File, cols: u16, rows: u16) -> Result<(), TermirustError> {
+    use std::os::fd::AsRawFd;
+
+    let size = libc::winsize {
+        ws_row: rows,
+        ws_col: cols,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    };
+    // SAFETY: `size` es un winsize válido durante toda la llamada
+    if unsafe { libc::ioctl(master.as_raw_fd(), libc::TIOCSWINSZ, &size) } == 0 {
+        Ok(())
+    } else {
+        Err(TermirustError::io("", &io::Error::last_os_error()))
+    }
+}
+
+#[cfg(not(unix))]
+fn set_window_size(_master: &File, _cols: u16, _rows: u16) -> Result<(), TermirustError> {
+    Err(TermirustError::io("", &io::Error::from(io::ErrorKind::Unsupported)))
+}
+
+#[cfg(not(unix))]
+pub fn run(command: &Command, _options: &PipelineOptions, _events: Option<&EventSender>) -> Result<Command, TermirustError> {
+    Err(TermirustError::io(&command.name, &io::Error::from(io::ErrorKind::Unsupported)))
+}
+
+#[cfg(unix)]
+mod unix {
+    use std::fs::File;
+    use std::io::{self, Read};
+    use std::os::fd::{AsRawFd, FromRawFd};
+    use std::os::unix::process::CommandExt;
+    use std::process::{Command as ProcessCommand, Stdio};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+
+    use super::{lock, FOREGROUND_PTY, WINDOW_SIZE};
+    use crate::domain::entities::{Command, EventSender, ExecutionEvent, PipelineOptions, ProcessSignal};
+    use crate::domain::errors::TermirustError;
+    use crate::infrastructure::pipeline::PipelineExecutor;
+    use crate::infrastructure::signals::{self, ChildStatus};
+
+    /// Milisegundos que espera el lector antes de comprobar si el programa terminó
+    const READ_POLL_MS: libc::c_int = 50;
+
+    /// Ejecuta el comando en una pseudoterminal nueva y espera a que termine. Lo que
+    /// escribe se envía sin procesar por `events` para que la interfaz lo emule.
+    pub fn run(
+        command: &Command,
+        options: &PipelineOptions,
+        events: Option<&EventSender>,
+    ) -> Result<Command, TermirustError> {
+        let pty_error = |e: io::Error| TermirustError::io(&command.name, &e);
+        let (cols, rows) = *lock(&WINDOW_SIZE);
+        let (master, slave) = open(cols, rows).map_err(pty_error)?;
+
+        let mut process = ProcessCommand::new(&command.name);
+        process.args(&command.args);
+        PipelineExecutor::apply_environment(&mut process, command, options);
+        process.env("TERM", "xterm-256color");
+        let terminal = |file: &File| {
+            file.try_clone().map(Stdio::from).map_err(pty_error)
+        };
+        process.stdin(terminal(&slave)?);
+        process.stdout(terminal(&slave)?);
+        process.stderr(terminal(&slave)?);
+        // SAFETY: entre fork y exec solo se usan llamadas al sistema seguras
+        unsafe {
+            process.pre_exec(|| {
+                // Sesión propia con la pseudoterminal como terminal de control, para que
+                // Ctrl+C y Ctrl+Z lleguen al programa a través de la disciplina de línea
+                if libc::setsid() == -1 || libc::ioctl(0, libc::TIOCSCTTY as _, 0) == -1 {
+                    return Err(io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+
+        let spawned = process.spawn();
+        // El lector solo recibe el final del archivo cuando se cierran todas las copias del esclavo
+        drop(process);
+        drop(slave);
+        let mut child = spawned.map_err(|e| PipelineExecutor::spawn_error(&command.name, e))?;
+
+        let pid = child.id();
+        let reader_master = master.try_clone().map_err(pty_error)?;
+        *lock(&FOREGROUND_PTY) = Some(master);
+        signals::set_foreground_group(Some(pid));
+        if let Some(events) = events {
+            let _ = events.send(ExecutionEvent::PtyStarted { cols, rows });
+        }
+
+        let finished = Arc::new(AtomicBool::new(false));
+        let reader = {
+            let finished = Arc::clone(&finished);
+            let events = events.cloned();
+            thread::spawn(move || forward_output(reader_master, &finished, events))
+        };
+
+        let status = loop {
+            match signals::wait_child(&mut child) {
+                // No hay control de trabajos dentro de la pseudoterminal: se reanuda
+                Ok(ChildStatus::Stopped) => {
+                    let _ = signals::send_to_group(pid, ProcessSignal::Continue);
+                }
+                Ok(ChildStatus::Exited(code)) => break Ok(code),
+                Err(e) => break Err(e),
+            }
+        };
+
+        finished.store(true, Ordering::SeqCst);
+        let _ = reader.join();
+        signals::set_foreground_group(None);
+        *lock(&FOREGROUND_PTY) = None;
+        if let Some(events) = events {
+            let _ = events.send(ExecutionEvent::PtyClosed);
+        }
+
+        Ok(command.clone().with_exit_code(status.map_err(pty_error)?))
+    }
+
+    /// Abre un par maestro/esclavo con el tamaño indicado
+    fn open(cols: u16, rows: u16) -> io::Result<(File, File)> {
+        let mut master: libc::c_int = -1;
+        let mut slave: libc::c_int = -1;
+        let size = libc::winsize {
+            ws_row: rows,
+            ws_col: cols,
+            ws_xpixel: 0,
+            ws_ypixel: 0,
+        };
+        // SAFETY: todos los punteros apuntan a variables locales válidas o son nulos
+        let result = unsafe {
+            libc::openpty(&mut master, &mut slave, std::ptr::null_mut(), std::ptr::null_mut(), &size)
+        };
+        if result == -1 {
+            return Err(io::Error::last_os_error());
+        }
+
+        // SAFETY: openpty acaba de crear ambos descriptores y nadie más los posee
+        let (master, slave) = unsafe { (File::from_raw_fd(master), File::from_raw_fd(slave)) };
+        for file in [&master, &slave] {
+            // Que los demás procesos que se lancen no hereden la pseudoterminal
+            // SAFETY: fcntl solo recibe un descriptor abierto y enteros
+            unsafe { libc::fcntl(file.as_raw_fd(), libc::F_SETFD, libc::FD_CLOEXEC) };
+        }
+        Ok((master, slave))
+    }
+
+    /// Envía lo que escribe el programa hasta que se cierra la pseudoterminal. Si algún
+    /// proceso heredado la mantiene abierta, se deja de leer cuando termina el programa.
+    fn forward_output(mut master: File, finished: &AtomicBool, events: Option<EventSender>) {
+        let mut buffer = [0u8; 4096];
+        loop {
+            let mut poll = libc::pollfd {
+                fd: master.as_raw_fd(),
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            // SAFETY: `poll` es un pollfd válido durante toda la llamada
+            let ready = unsafe { libc::poll(&mut poll, 1, READ_POLL_MS) };
+            if ready == 0 {
+                if finished.load(Ordering::SeqCst) {
+                    break;
+                }
+                continue;
+            }
+            if ready < 0 {
+                if io::Error::last_os_error().kind() == io::ErrorKind::Interrupted {
+                    continue;
+                }
+                break;
+            }
+
+            match master.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(read) => {
+                    if let Some(events) = &events {
+                        let _ = events.send(ExecutionEvent::PtyOutput(buffer[..read].to_vec()));
+                    }
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                // EIO: el programa y todos sus hijos cerraron la pseudoterminal
+                Err(_) => break,
+            }
+        }
+    }
+}
@@ -1,156 +1,93 @@
-use crate::domain::entities::Command;
-use crate::domain::repositories::{CommandRepository, FileSystemRepository};
-use crate::application::ls_commands::{LsCommandsUseCase, LsCommandParser};
-use crate::presentation::commands::ls_display::LsDisplayComponent;
-use crate::application::commands::exit_commands::ExitCommandsUseCase;
-use crate::presentation::commands::exit_display::ExitDisplayComponent;
-use std::process::Command as ProcessCommand;
+use crate::domain::aliases::Aliases;
+use crate::domain::builtins::{BuiltinCommand, CommandCatalog, CommandRegistry, Session};
+use crate::domain::ast::Statement;
+use crate::domain::entities::{Command, DirectoryEntry, EventSender, PipelineOptions, PipelineStage};
+use crate::domain::errors::{AliasError, ArgumentError, ErrorFormatter, SourceLocation, TermirustError};
+use crate::domain::jobs::SharedJobTable;
+use crate::domain::parser::Parser;
+use crate::domain::repositories::{
+    AliasRepository, CommandRepository, DirectoryRepository, FileSystemRepository, UserRepository,
+};
+use crate::infrastructure::builtins;
+use crate::infrastructure::pipeline::PipelineExecutor;
 use std::env;
-use std::path::Path;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
 /// Implementación concreta del repositorio de comandos
-pub struct SystemCommandRepository;
+pub struct SystemCommandRepository {
+    /// Trabajos en segundo plano o detenidos que lanzó este repositorio
+    jobs: SharedJobTable,
+    /// Comandos internos que se ejecutan sin lanzar un proceso
+    registry: CommandRegistry,
+    /// Texto de los errores de un comando interno con la salida de errores redirigida
+    describe_error: ErrorFormatter,
+}
+
+impl SystemCommandRepository {
+    /// Sin `with_error_formatter`, los errores redirigidos se escriben con su
+    /// representación de depuración
+    pub fn new() -> Self {
+        let jobs = SharedJobTable::default();
+        Self {
+            registry: builtins::default_registry(jobs.clone()),
+            jobs,
+            describe_error: |error| format!("{:?}", error),
+        }
+    }
+
+    /// Escribe con `describe_error` los errores de los comandos internos cuya salida
+    /// de errores está redirigida
+    pub fn with_error_formatter(mut self, describe_error: ErrorFormatter) -> Self {
+        self.describe_error = describe_error;
+        self
+    }
+}
 
 impl CommandRepository for SystemCommandRepository {
-    fn execute_command(&self, command: &Command) -> Result<Command, String> {
+    fn execute_command(
+        &self,
+        command: &Command,
+        environment: &[(String, String)],
+        session: Session,
+    ) -> Result<Command, TermirustError> {
         if command.name.is_empty() {
             return Ok(command.clone());
         }
 
-        // Comandos internos
-        match command.name.as_str() {
-            "ls" | "dir" => {
-                // Usar nuestro comando ls avanzado
-                let ls_use_case = LsCommandsUseCase::new();
-                
-                // Reconstruir el comando completo para el parser
-                let mut full_command = String::from("ls");
-                for arg in &command.args {
-                    full_command.push(' ');
-                    full_command.push_str(arg);
-                }
-                
-                let parsed = LsCommandParser::parse_command(&full_command);
-                match parsed.execute(&ls_use_case) {
-                    Ok(result) => {
-                        // Detectar opciones del comando
-                        let mut options = crate::application::ls_commands::LsOptions::default();
-                        if full_command.contains("-l") {
-                            options.long_format = true;
-                        }
-                        if full_command.contains("-a") {
-                            options.show_hidden = true;
-                        }
-                        if full_command.contains("-h") {
-                            options.human_readable = true;
-                        }
-                        if full_command.contains("-S") {
-                            options.sort_by = crate::application::ls_commands::SortBy::Size;
-                        }
-                        if full_command.contains("-t") {
-                            options.sort_by = crate::application::ls_commands::SortBy::Modified;
-                        }
-                        
-                        let output_lines = LsDisplayComponent::render(&result, &options);
-                        let output = output_lines.join("\n");
-                        Ok(command.clone().with_output(output).with_exit_code(0))
-                    }
-                    Err(e) => {
-                        let error_lines = LsDisplayComponent::render_error(&e);
-                        let error_output = error_lines.join("\n");
-                        Ok(command.clone().with_output(error_output).with_exit_code(1))
-                    }
-                }
-            }
-            "cd" => {
-                let path = command.args.first().map(|s| s.to_string()).unwrap_or_else(|| ".".to_string());
-                let fs_repo = SystemFileSystemRepository;
-                match fs_repo.change_directory(&path) {
-                    Ok(_) => Ok(command.clone().with_output("".to_string()).with_exit_code(0)),
-                    Err(e) => Ok(command.clone().with_output(e).with_exit_code(1))
-                }
-            }
-            "pwd" => {
-                let fs_repo = SystemFileSystemRepository;
-                match fs_repo.get_current_directory() {
-                    Ok(path) => Ok(command.clone().with_output(path).with_exit_code(0)),
-                    Err(e) => Ok(command.clone().with_output(e).with_exit_code(1))
-                }
-            }
-            "clear" => {
-                Ok(command.clone().with_output("CLEAR".to_string()).with_exit_code(0))
-            }
-            "help" => {
-                let help_text = r#"
-Comandos disponibles:
-- ls, dir: Listar archivos y directorios (con opciones: -a, -l, -h, -S, -t)
-- cd <directorio>: Cambiar directorio
-- pwd: Mostrar directorio actual
-- clear: Limpiar pantalla
-- help: Mostrar esta ayuda
-- exit, quit: Cerrar la aplicación Termirust
-
-Opciones del comando ls:
-- ls -a: Mostrar archivos ocultos
-- ls -l: Formato largo con detalles
-- ls -h: Tamaños en formato legible
-- ls -S: Ordenar por tamaño
-- ls -t: Ordenar por fecha de modificación
-- ls -la: Combinar opciones
-"#;
-                Ok(command.clone().with_output(help_text.to_string()).with_exit_code(0))
-            }
-            "exit" | "quit" => {
-                // Usar nuestro comando exit avanzado
-                let exit_use_case = ExitCommandsUseCase::new();
-                
-                match exit_use_case.execute_exit(command) {
-                    Ok(result) => {
-                        let output_lines = ExitDisplayComponent::render(&result);
-                        let output = output_lines.join("\n");
-                        Ok(command.clone().with_output(output).with_exit_code(result.exit_code))
-                    }
-                    Err(e) => {
-                        let error_lines = ExitDisplayComponent::render_error(&e);
-                        let error_output = error_lines.join("\n");
-                        Ok(command.clone().with_output(error_output).with_exit_code(1))
-                    }
-                }
-            }
-            _ => {
-                // Intentar ejecutar comando del sistema
-                let mut process = ProcessCommand::new(&command.name);
-                process.args(&command.args);
-                
-                match process.output() {
-                    Ok(output) => {
-                        let stdout = String::from_utf8_lossy(&output.stdout);
-                        let stderr = String::from_utf8_lossy(&output.stderr);
-                        let combined_output = if !stderr.is_empty() {
-                            format!("{}\n{}", stdout, stderr)
-                        } else {
-                            stdout.to_string()
-                        };
-                        
-                        Ok(command.clone()
-                            .with_output(combined_output)
-                            .with_exit_code(output.status.code().unwrap_or(1)))
-                    }
-                    Err(e) => {
-                        // Detectar específicamente si el comando no se encuentra
-                        let error_msg = if e.kind() == std::io::ErrorKind::NotFound {
-                            format!("El comando '{}' no se pudo encontrar", command.name)
-                        } else {
-                            format!("Error: {}", e)
-                        };
-                        
-                        Ok(command.clone()
-                            .with_output(error_msg)
-                            .with_exit_code(1))
-                    }
-                }
-            }
+        // Comandos internos registrados; los demás se buscan en el PATH
+        if let Some(result) = self.registry.execute(command, environment, session) {
+            return Ok(result);
         }
+        // Intentar ejecutar comando del sistema como tubería de una sola etapa
+        let options = PipelineOptions::default();
+        self.execute_pipeline(vec![PipelineStage::External(command.clone())], &options, None)
+    }
+
+    fn is_builtin(&self, command: &Command) -> bool {
+        self.registry.find_for(command).is_some()
+    }
+
+    fn register_builtin(&mut self, command: Box<dyn BuiltinCommand>) {
+        self.registry.register(command);
+    }
+
+    fn command_catalog(&self) -> CommandCatalog {
+        self.registry.catalog()
+    }
+
+    fn execute_pipeline(
+        &self,
+        stages: Vec<PipelineStage>,
+        options: &PipelineOptions,
+        events: Option<&EventSender>,
+    ) -> Result<Command, TermirustError> {
+        PipelineExecutor::run(stages, options, &self.jobs, events, self.describe_error)
+    }
+
+    fn job_table(&self) -> SharedJobTable {
+        SharedJobTable::clone(&self.jobs)
     }
 }
 
@@ -158,14 +95,15 @@ Opciones del comando ls:
 pub struct SystemFileSystemRepository;
 
 impl FileSystemRepository for SystemFileSystemRepository {
-    fn list_directory(&self, path: &str) -> Result<Vec<String>, String> {
+    fn list_directory(&self, path: &str) -> Result<Vec<String>, TermirustError> {
+        let display = path.to_string();
         let path = Path::new(path);
         if !path.exists() {
-            return Err(format!("El directorio '{}' no existe", path.display()));
+            return Err(TermirustError::NotFound { command: String::new(), path: Some(display) });
         }
 
         if !path.is_dir() {
-            return Err(format!("'{}' no es un directorio", path.display()));
+            return Err(TermirustError::invalid_argument("", ArgumentError::NotADirectory(display)));
         }
 
         match std::fs::read_dir(path) {
@@ -195,17 +133,17 @@ impl FileSystemRepository for SystemFileSystemRepository {
                 files.sort();
                 Ok(files)
             }
-            Err(e) => Err(format!("Error leyendo directorio: {}", e))
+            Err(e) => Err(TermirustError::from_io("", Some(&display), &e))
         }
     }
 
-    fn get_current_directory(&self) -> Result<String, String> {
+    fn get_current_directory(&self) -> Result<String, TermirustError> {
         env::current_dir()
             .map(|path| path.to_string_lossy().to_string())
-            .map_err(|e| format!("Error obteniendo directorio actual: {}", e))
+            .map_err(|e| TermirustError::io("", &e))
     }
 
-    fn change_directory(&self, path: &str) -> Result<(), String> {
+    fn change_directory(&self, path: &str) -> Result<(), TermirustError> {
         let new_path = if path == "." {
             env::current_dir().unwrap()
         } else if path == ".." {
@@ -218,15 +156,254 @@ impl FileSystemRepository for SystemFileSystemRepository {
             Path::new(path).to_path_buf()
         };
 
+        let display = new_path.to_string_lossy().to_string();
         if !new_path.exists() {
-            return Err(format!("El directorio '{}' no existe", new_path.display()));
+            return Err(TermirustError::NotFound { command: String::new(), path: Some(display) });
         }
 
         if !new_path.is_dir() {
-            return Err(format!("'{}' no es un directorio", new_path.display()));
+            return Err(TermirustError::invalid_argument("", ArgumentError::NotADirectory(display)));
         }
 
-        env::set_current_dir(new_path)
-            .map_err(|e| format!("Error cambiando directorio: {}", e))
+        env::set_current_dir(new_path).map_err(|e| TermirustError::from_io("", Some(&display), &e))
+    }
+}
+
+/// Lee los directorios del sistema de archivos
+pub struct SystemDirectoryRepository;
+
+impl DirectoryRepository for SystemDirectoryRepository {
+    fn list(&self, directory: &Path) -> Vec<DirectoryEntry> {
+        let Ok(entries) = fs::read_dir(directory) else {
+            return Vec::new();
+        };
+        entries
+            .flatten()
+            .filter_map(|entry| {
+                let name = entry.file_name().into_string().ok()?;
+                let is_symlink = entry.file_type().is_ok_and(|kind| kind.is_symlink());
+                let metadata = fs::metadata(entry.path()).ok();
+                let is_dir = metadata.as_ref().is_some_and(fs::Metadata::is_dir);
+                let is_executable = metadata.as_ref().is_some_and(is_executable_file);
+                Some(DirectoryEntry { name, is_dir, is_symlink, is_executable })
+            })
+            .collect()
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        path.symlink_metadata().is_ok()
+    }
+
+    fn is_directory(&self, path: &Path) -> bool {
+        path.is_dir()
+    }
+
+    fn modified(&self, directory: &Path) -> Option<SystemTime> {
+        fs::metadata(directory).and_then(|metadata| metadata.modified()).ok()
+    }
+}
+
+/// Indica si `path` es un archivo que se puede ejecutar, siguiendo los enlaces
+pub fn is_executable(path: &Path) -> bool {
+    fs::metadata(path).is_ok_and(|metadata| is_executable_file(&metadata))
+}
+
+#[cfg(unix)]
+fn is_executable_file(metadata: &fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.is_file() && metadata.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(not(unix))]
+fn is_executable_file(metadata: &fs::Metadata) -> bool {
+    metadata.is_file()
+}
+
+/// Consulta las cuentas del sistema con `getpwnam_r`
+pub struct SystemUserRepository;
+
+impl UserRepository for SystemUserRepository {
+    #[cfg(unix)]
+    fn home_directory(&self, user: &str) -> Option<String> {
+        use std::ffi::{CStr, CString};
+
+        let name = CString::new(user).ok()?;
+        let mut buffer: Vec<libc::c_char> = vec![0; 1024];
+        // SAFETY: `passwd` es una estructura de C que se puede iniciar en ceros
+        let mut passwd: libc::passwd = unsafe { std::mem::zeroed() };
+        let mut found: *mut libc::passwd = std::ptr::null_mut();
+        loop {
+            // SAFETY: los punteros apuntan a `passwd` y a `buffer`, que viven durante
+            // la llamada, y se indica el tamaño real del búfer
+            let code = unsafe {
+                libc::getpwnam_r(name.as_ptr(), &mut passwd, buffer.as_mut_ptr(), buffer.len(), &mut found)
+            };
+            // El búfer no alcanzó para los datos de la cuenta: se intenta con uno mayor
+            if code == libc::ERANGE && buffer.len() < 1 << 20 {
+                buffer.resize(buffer.len() * 2, 0);
+                continue;
+            }
+            break;
+        }
+        if found.is_null() || passwd.pw_dir.is_null() {
+            return None;
+        }
+        // SAFETY: `pw_dir` apunta a una cadena terminada en cero dentro de `buffer`
+        let home = unsafe { CStr::from_ptr(passwd.pw_dir) };
+        Some(home.to_string_lossy().into_owned())
+    }
+
+    #[cfg(not(unix))]
+    fn home_directory(&self, _user: &str) -> Option<String> {
+        None
+    }
+}
+
+/// Directorio de configuración de Termirust: `$XDG_CONFIG_HOME/termirust` o
+/// `~/.config/termirust`
+pub fn config_directory() -> Option<PathBuf> {
+    let base = env::var_os("XDG_CONFIG_HOME")
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("termirust"))
+}
+
+/// Guarda los alias en un archivo de texto con una definición `alias nombre=valor`
+/// por línea, la misma sintaxis que se escribe en la terminal
+pub struct FileAliasRepository {
+    path: Option<PathBuf>,
+}
+
+impl FileAliasRepository {
+    /// Usa el archivo `aliases` del directorio de configuración
+    pub fn new() -> Self {
+        Self {
+            path: config_directory().map(|directory| directory.join("aliases")),
+        }
+    }
+
+    /// Usa otro archivo en lugar del predeterminado
+    #[cfg(test)]
+    pub fn with_path(mut self, path: PathBuf) -> Self {
+        self.path = Some(path);
+        self
+    }
+
+    fn path(&self) -> Result<&Path, TermirustError> {
+        self.path.as_deref().ok_or(TermirustError::NoConfigDirectory)
+    }
+}
+
+impl AliasRepository for FileAliasRepository {
+    fn load(&self) -> Result<Aliases, TermirustError> {
+        let path = self.path()?;
+        let display = path.to_string_lossy().to_string();
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Aliases::default()),
+            Err(e) => return Err(TermirustError::from_io("", Some(&display), &e)),
+        };
+
+        let mut aliases = Aliases::default();
+        for (number, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = |error: TermirustError| error.at(SourceLocation::new(&display, number + 1));
+            let alias_error = |reason: AliasError| error(TermirustError::Alias { command: String::new(), reason });
+            let command_line = Parser::parse(line).map_err(|e| error(e.into()))?;
+            let words: Vec<String> = command_line
+                .items
+                .iter()
+                .filter_map(|list| match &list.first {
+                    Statement::Pipeline(pipeline) => Some(&pipeline.commands),
+                    Statement::Compound { .. } => None,
+                })
+                .flatten()
+                .flat_map(|command| &command.words)
+                .map(|word| word.to_literal())
+                .collect();
+            match words.split_first() {
+                Some((command, definitions)) if command == "alias" => {
+                    for definition in definitions {
+                        let (name, value) = definition
+                            .split_once('=')
+                            .ok_or_else(|| alias_error(AliasError::NotADefinition(definition.clone())))?;
+                        aliases.set(name, value.to_string()).map_err(error)?;
+                    }
+                }
+                _ => return Err(alias_error(AliasError::ExpectedDefinition)),
+            }
+        }
+        Ok(aliases)
+    }
+
+    fn save(&self, aliases: &Aliases) -> Result<(), TermirustError> {
+        let path = self.path()?;
+        let io_error = |path: &Path, e: std::io::Error| TermirustError::from_io("", Some(&path.to_string_lossy()), &e);
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory).map_err(|e| io_error(directory, e))?;
+        }
+        let mut content = String::from("# Alias de Termirust\n");
+        for (name, value) in aliases.iter() {
+            content.push_str(&Aliases::definition(name, value));
+            content.push('\n');
+        }
+        fs::write(path, content).map_err(|e| io_error(path, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Archivo de alias en la carpeta temporal que se borra al terminar la prueba
+    struct AliasFile(PathBuf);
+
+    impl AliasFile {
+        fn new(name: &str) -> Self {
+            Self(env::temp_dir().join(format!("termirust-{}-{}", name, std::process::id())).join("aliases"))
+        }
+
+        fn repository(&self) -> FileAliasRepository {
+            FileAliasRepository::new().with_path(self.0.clone())
+        }
+    }
+
+    impl Drop for AliasFile {
+        fn drop(&mut self) {
+            if let Some(directory) = self.0.parent() {
+                let _ = fs::remove_dir_all(directory);
+            }
+        }
+    }
+
+    #[test]
+    fn aliases_survive_a_save_and_load() {
+        let file = AliasFile::new("alias-ida-vuelta");
+        let mut aliases = Aliases::default();
+        for (name, value) in [("ll", "ls -la"), ("sudo", "sudo "), ("saludo", "echo 'hola mundo' | tr a-z A-Z")] {
+            aliases.set(name, value.to_string()).expect("el alias es válido");
+        }
+        file.repository().save(&aliases).expect("se puede guardar");
+        assert_eq!(file.repository().load(), Ok(aliases));
+    }
+
+    #[test]
+    fn a_missing_file_has_no_aliases() {
+        let file = AliasFile::new("alias-sin-archivo");
+        assert_eq!(file.repository().load(), Ok(Aliases::default()));
+    }
+
+    #[test]
+    fn bad_lines_report_their_location() {
+        let file = AliasFile::new("alias-linea-mala");
+        fs::create_dir_all(file.0.parent().unwrap()).unwrap();
+        fs::write(&file.0, "# comentario\nalias ll='ls -la'\necho hola\n").unwrap();
+        let location = SourceLocation::new(&file.0.to_string_lossy(), 3);
+        let error = TermirustError::Alias { command: String::new(), reason: AliasError::ExpectedDefinition };
+        assert_eq!(file.repository().load(), Err(error.at(location)));
     }
 }
@@ -0,0 +1,210 @@
+use std::process::{Child, Command as ProcessCommand};
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use crate::domain::entities::{ProcessSignal, SUSPENDED_EXIT_CODE};
+use crate::domain::errors::JobError;
+
+/// Grupo de procesos que está en primer plano (0 si no hay ninguno)
+static FOREGROUND_GROUP: AtomicU32 = AtomicU32::new(0);
+
+/// Estado de un proceso hijo después de esperarlo
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChildStatus {
+    /// Terminó con el código indicado (128 + señal si lo mató una señal)
+    Exited(i32),
+    /// Fue detenido con Ctrl+Z y sigue vivo
+    Stopped,
+}
+
+impl ChildStatus {
+    /// Código de salida que se reporta para este estado
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            ChildStatus::Exited(code) => *code,
+            ChildStatus::Stopped => SUSPENDED_EXIT_CODE,
+        }
+    }
+}
+
+/// Registra el grupo de procesos que recibe Ctrl+C y Ctrl+Z, o lo limpia con `None`
+pub fn set_foreground_group(pgid: Option<u32>) {
+    FOREGROUND_GROUP.store(pgid.unwrap_or(0), Ordering::SeqCst);
+}
+
+/// Envía la señal al grupo en primer plano. Devuelve `false` si no hay ninguno.
+pub fn signal_foreground(signal: ProcessSignal) -> bool {
+    match FOREGROUND_GROUP.load(Ordering::SeqCst) {
+        0 => false,
+        pgid => send_to_group(pgid, signal).is_ok(),
+    }
+}
+
+/// Hace que el proceso se lance en el grupo indicado, o en uno nuevo propio con `None`,
+/// para que las señales lleguen a toda la tubería y no a la terminal
+pub fn join_process_group(process: &mut ProcessCommand, pgid: Option<u32>) {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        process.process_group(pgid.unwrap_or(0) as i32);
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = (process, pgid);
+    }
+}
+
+/// Hace que el proceso ignore Ctrl+Z (SIGTSTP), para los que no son trabajos que se
+/// puedan retomar con `fg`: la terminal los espera hasta que terminan
+pub fn ignore_suspend(process: &mut ProcessCommand) {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        // SAFETY: entre fork y exec solo se llama a `signal`, que es seguro en ese punto
+        unsafe {
+            process.pre_exec(|| {
+                libc::signal(libc::SIGTSTP, libc::SIG_IGN);
+                Ok(())
+            });
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = process;
+    }
+}
+
+#[cfg(unix)]
+fn signal_number(signal: ProcessSignal) -> libc::c_int {
+    match signal {
+        ProcessSignal::Interrupt => libc::SIGINT,
+        ProcessSignal::Suspend => libc::SIGTSTP,
+        ProcessSignal::Stop => libc::SIGSTOP,
+        ProcessSignal::Continue => libc::SIGCONT,
+        ProcessSignal::Terminate => libc::SIGTERM,
+        ProcessSignal::Kill => libc::SIGKILL,
+        ProcessSignal::Hangup => libc::SIGHUP,
+    }
+}
+
+/// Interpreta la señal de `kill`: por nombre (`-KILL`, `-SIGTERM`) o por el número
+/// que le da este sistema (`-9`)
+pub fn parse_signal(spec: &str) -> Option<ProcessSignal> {
+    let name = spec.trim_start_matches('-');
+    match name.parse::<i32>() {
+        Ok(number) => ProcessSignal::ALL.into_iter().find(|signal| number_of(*signal) == Some(number)),
+        Err(_) => ProcessSignal::from_name(name),
+    }
+}
+
+#[cfg(unix)]
+fn number_of(signal: ProcessSignal) -> Option<i32> {
+    Some(signal_number(signal))
+}
+
+#[cfg(not(unix))]
+fn number_of(_signal: ProcessSignal) -> Option<i32> {
+    None
+}
+
+/// Envía una señal a todos los procesos de un grupo
+#[cfg(unix)]
+pub fn send_to_group(pgid: u32, signal: ProcessSignal) -> Result<(), JobError> {
+    // SAFETY: killpg solo recibe enteros; un grupo inexistente devuelve un error
+    if unsafe { libc::killpg(pgid as libc::pid_t, signal_number(signal)) } == 0 {
+        Ok(())
+    } else {
+        Err(signal_error(format!("-{}", pgid)))
+    }
+}
+
+/// Envía una señal a un único proceso. El PID debe ser positivo: 0 y los negativos
+/// enviarían la señal a grupos enteros.
+#[cfg(unix)]
+pub fn send_to_process(pid: libc::pid_t, signal: ProcessSignal) -> Result<(), JobError> {
+    // SAFETY: kill solo recibe enteros; un proceso inexistente devuelve un error
+    if unsafe { libc::kill(pid, signal_number(signal)) } == 0 {
+        Ok(())
+    } else {
+        Err(signal_error(pid.to_string()))
+    }
+}
+
+/// Error con el último mensaje del sistema operativo
+#[cfg(unix)]
+fn signal_error(target: String) -> JobError {
+    JobError::Signal { target, detail: std::io::Error::last_os_error().to_string() }
+}
+
+#[cfg(not(unix))]
+pub fn send_to_group(_pgid: u32, _signal: ProcessSignal) -> Result<(), JobError> {
+    Err(JobError::SignalsUnsupported)
+}
+
+#[cfg(not(unix))]
+pub fn send_to_process(_pid: i32, _signal: ProcessSignal) -> Result<(), JobError> {
+    Err(JobError::SignalsUnsupported)
+}
+
+/// Espera a que el proceso termine o sea detenido
+#[cfg(unix)]
+pub fn wait_child(child: &mut Child) -> std::io::Result<ChildStatus> {
+    let pid = child.id() as libc::pid_t;
+    let mut status: libc::c_int = 0;
+    loop {
+        // SAFETY: `status` es un entero válido durante toda la llamada
+        if unsafe { libc::waitpid(pid, &mut status, libc::WUNTRACED) } != -1 {
+            break;
+        }
+        let error = std::io::Error::last_os_error();
+        if error.kind() != std::io::ErrorKind::Interrupted {
+            return Err(error);
+        }
+    }
+
+    if libc::WIFSTOPPED(status) {
+        Ok(ChildStatus::Stopped)
+    } else if libc::WIFSIGNALED(status) {
+        Ok(ChildStatus::Exited(128 + libc::WTERMSIG(status)))
+    } else {
+        Ok(ChildStatus::Exited(libc::WEXITSTATUS(status)))
+    }
+}
+
+#[cfg(not(unix))]
+pub fn wait_child(child: &mut Child) -> std::io::Result<ChildStatus> {
+    let status = child.wait()?;
+    Ok(ChildStatus::Exited(status.code().unwrap_or(1)))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::domain::entities::INTERRUPTED_EXIT_CODE;
+
+    /// Lanza `sleep` en un grupo de procesos propio, como una tubería en primer plano
+    fn sleeping() -> Child {
+        let mut process = ProcessCommand::new("sleep");
+        process.arg("5");
+        join_process_group(&mut process, None);
+        process.spawn().expect("se puede lanzar sleep")
+    }
+
+    #[test]
+    fn ctrl_c_ends_the_group_with_status_130() {
+        let mut child = sleeping();
+        send_to_group(child.id(), ProcessSignal::Interrupt).expect("el grupo existe");
+        assert_eq!(wait_child(&mut child).unwrap(), ChildStatus::Exited(INTERRUPTED_EXIT_CODE));
+    }
+
+    #[test]
+    fn ctrl_z_stops_the_group_without_ending_it() {
+        let mut child = sleeping();
+        send_to_group(child.id(), ProcessSignal::Suspend).expect("el grupo existe");
+        let status = wait_child(&mut child).unwrap();
+        assert_eq!(status, ChildStatus::Stopped);
+        assert_eq!(status.exit_code(), SUSPENDED_EXIT_CODE);
+
+        send_to_group(child.id(), ProcessSignal::Kill).expect("el grupo sigue vivo");
+        assert_eq!(wait_child(&mut child).unwrap(), ChildStatus::Exited(128 + libc::SIGKILL));
+    }
+}
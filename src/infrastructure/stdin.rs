@@ -0,0 +1,50 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+use crate::domain::jobs::JobInput;
+
+/// Extremo de escritura de la entrada estándar de la tubería en primer plano
+static FOREGROUND_INPUT: Mutex<Option<JobInput>> = Mutex::new(None);
+
+/// Los procesos leen directamente la entrada estándar de Termirust (sin ventana)
+static INHERIT_INPUT: AtomicBool = AtomicBool::new(false);
+
+fn lock() -> MutexGuard<'static, Option<JobInput>> {
+    FOREGROUND_INPUT.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Registra la entrada estándar de la tubería en primer plano, o la cierra con `None`
+pub fn set_foreground_input(writer: Option<JobInput>) {
+    *lock() = writer;
+}
+
+/// Quita la entrada de la tubería en primer plano sin cerrarla, para conservarla
+/// mientras el trabajo está detenido
+pub fn take_foreground_input() -> Option<JobInput> {
+    lock().take()
+}
+
+/// Escribe lo que tecleó el usuario en la entrada del proceso en primer plano.
+/// Devuelve `false` si no hay ninguno o ya no lee su entrada.
+pub fn write_foreground(bytes: &[u8]) -> bool {
+    match lock().as_mut() {
+        Some(writer) => writer.write_all(bytes).and_then(|_| writer.flush()).is_ok(),
+        None => false,
+    }
+}
+
+/// Cierra la entrada del proceso en primer plano para que lea el final del archivo
+/// (Ctrl+D). Devuelve `false` si no había ninguna abierta.
+pub fn close_foreground() -> bool {
+    lock().take().is_some()
+}
+
+/// Hace que los procesos en primer plano lean la entrada estándar de Termirust en
+/// lugar de lo que se escribe en el prompt, como al ejecutar un script sin ventana
+pub fn inherit_input(enabled: bool) {
+    INHERIT_INPUT.store(enabled, Ordering::SeqCst);
+}
+
+pub fn inherits_input() -> bool {
+    INHERIT_INPUT.load(Ordering::SeqCst)
+}
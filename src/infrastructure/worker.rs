@@ -0,0 +1,29 @@
+use tokio::runtime::{Builder, Runtime};
+
+use crate::domain::errors::TermirustError;
+
+/// Hilo de trabajo donde se ejecutan los comandos para no bloquear la interfaz
+pub struct CommandWorker {
+    runtime: Runtime,
+}
+
+impl CommandWorker {
+    /// Crea el runtime de tokio que ejecutará los comandos
+    pub fn new() -> Result<Self, TermirustError> {
+        let runtime = Builder::new_multi_thread()
+            .worker_threads(1)
+            .thread_name("termirust-worker")
+            .enable_all()
+            .build()
+            .map_err(|e| TermirustError::io("", &e))?;
+        Ok(Self { runtime })
+    }
+
+    /// Ejecuta un trabajo bloqueante (procesos, E/S de archivos) fuera del hilo de la interfaz
+    pub fn spawn<F>(&self, job: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.runtime.spawn_blocking(job);
+    }
+}
@@ -4,10 +4,18 @@ mod application;
 mod presentation;
 
 use domain::services::{CommandService, TerminalService};
-use infrastructure::repositories::SystemCommandRepository;
+use infrastructure::completion_specs::FileCompletionSpecRepository;
+use infrastructure::repositories::{
+    config_directory, FileAliasRepository, SystemCommandRepository, SystemDirectoryRepository, SystemUserRepository,
+};
 use infrastructure::logging::{Logger, log_application_start};
+use infrastructure::plugins;
+use domain::builtins::BuiltinCommand;
+use domain::entities::Command;
 use application::use_cases::{ExecuteCommandUseCase, HandleInputUseCase};
 use presentation::gui_terminal::MatrixTerminalApp;
+use presentation::script_runner::ScriptRunner;
+use presentation::texts::ErrorMessages;
 
 fn main() -> Result<(), eframe::Error> {
     // Inicializar sistema de logs
@@ -18,6 +26,17 @@ fn main() -> Result<(), eframe::Error> {
     // Registrar inicio de la aplicación
     log_application_start();
     
+    // `termirust --script archivo.trs` ejecuta el script sin abrir la ventana
+    let args: Vec<String> = std::env::args().collect();
+    let script = match args.as_slice() {
+        [_, flag, path, ..] if flag == "--script" => Some(path.clone()),
+        [_, flag] if flag == "--script" => {
+            eprintln!("Uso: termirust --script archivo.trs");
+            std::process::exit(2);
+        }
+        _ => None,
+    };
+
     // Configurar opciones de la ventana
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
@@ -29,21 +48,43 @@ fn main() -> Result<(), eframe::Error> {
     };
 
     // Configurar dependencias (Dependency Injection)
-    let command_repository = SystemCommandRepository;
-    let command_service = CommandService::new(command_repository);
+    let command_repository = SystemCommandRepository::new().with_error_formatter(ErrorMessages::format);
+    let mut command_service = CommandService::new(command_repository);
+    // Los plugins `termirust-<nombre>` no reemplazan a los comandos internos
+    for plugin in plugins::discover() {
+        if !command_service.is_builtin(&Command::new(plugin.name().to_string(), Vec::new())) {
+            command_service.register_builtin(Box::new(plugin));
+        }
+    }
     let terminal_service = TerminalService::new();
+    let (event_sender, event_receiver) = std::sync::mpsc::channel();
     
     // Crear casos de uso
     let input_handler = HandleInputUseCase::new(terminal_service);
-    let command_executor = ExecuteCommandUseCase::new(
+    let mut command_executor = ExecuteCommandUseCase::new(
         command_service, 
-        TerminalService::new()
-    );
+        TerminalService::new(),
+        event_sender
+    )
+    .with_alias_repository(Box::new(FileAliasRepository::new()))
+    .with_directory_repository(Box::new(SystemDirectoryRepository))
+    .with_user_repository(Box::new(SystemUserRepository))
+    .with_completion_spec_repository(Box::new(FileCompletionSpecRepository::new()));
+
+    if let Some(script) = script {
+        std::process::exit(ScriptRunner::run(command_executor, event_receiver, std::path::Path::new(&script)));
+    }
+
+    // Script de inicio del usuario: alias, variables o un `cd` para cada sesión
+    if let Some(directory) = config_directory() {
+        command_executor.run_startup_script(&directory.join("rc.trs"));
+    }
     
     // Crear la aplicación GUI
     let app = MatrixTerminalApp::new(
         input_handler, 
-        command_executor
+        command_executor,
+        event_receiver
     );
     
     // Ejecutar la aplicación
@@ -1,5 +1,7 @@
 use crate::domain::entities::{FileInfo, LsResult};
-use crate::application::ls_commands::LsOptions;
+use crate::application::ls_commands::{LsCommandParser, LsOptions};
+use crate::domain::errors::TermirustError;
+use crate::presentation::texts::ErrorMessages;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 /// Formateador para la salida del comando ls
@@ -164,29 +166,20 @@ impl LsDisplayFormatter {
         ]
     }
 
-    /// Formatea la ayuda del comando ls
-    #[allow(dead_code)]
+    /// Formatea la ayuda del comando ls a partir de sus opciones declaradas
     pub fn format_help() -> Vec<String> {
-        vec![
-            "📖 Ayuda del comando ls:".to_string(),
-            String::new(),
-            "Uso: ls [opciones] [directorio]".to_string(),
-            String::new(),
-            "Opciones:".to_string(),
-            "  -a    Mostrar archivos ocultos".to_string(),
-            "  -d    Mostrar solo directorios".to_string(),
-            "  -f    Mostrar solo archivos".to_string(),
-            "  -l    Formato largo (detallado)".to_string(),
-            "  -h    Tamaños en formato legible".to_string(),
-            "  -S    Ordenar por tamaño".to_string(),
-            "  -t    Ordenar por fecha de modificación".to_string(),
+        let mut lines = vec!["📖 Ayuda del comando ls:".to_string(), String::new()];
+        lines.extend(LsCommandParser::options().help().lines().map(str::to_string));
+        lines.extend([
             String::new(),
             "Ejemplos:".to_string(),
             "  ls                    # Listar archivos del directorio actual".to_string(),
             "  ls -la               # Listar todos los archivos con detalles".to_string(),
             "  ls -lh /ruta         # Listar con tamaños legibles".to_string(),
             "  ls -t                # Ordenar por fecha de modificación".to_string(),
-        ]
+            "  ls src/*.rs docs     # Listar varios archivos y directorios".to_string(),
+        ]);
+        lines
     }
 }
 
@@ -200,8 +193,8 @@ impl LsDisplayComponent {
     }
 
     /// Renderiza un error
-    pub fn render_error(error: &str) -> Vec<String> {
-        LsDisplayFormatter::format_error(error)
+    pub fn render_error(error: &TermirustError) -> Vec<String> {
+        LsDisplayFormatter::format_error(&ErrorMessages::describe(error))
     }
 
     /// Renderiza la ayuda
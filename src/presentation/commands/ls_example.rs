@@ -19,13 +19,15 @@ impl LsExample {
         output.push("1️⃣ Comando básico 'ls':".to_string());
         let command1 = "ls";
         let parsed1 = LsCommandParser::parse_command(command1);
-        match parsed1.execute(&use_case) {
-            Ok(result) => {
-                let display_result = LsDisplayComponent::render(&result, &crate::application::ls_commands::LsOptions::default());
-                output.extend(display_result);
-            }
-            Err(e) => {
-                output.extend(LsDisplayComponent::render_error(&e));
+        for listing in parsed1.execute(&use_case) {
+            match listing {
+                Ok(result) => {
+                    let display_result = LsDisplayComponent::render(&result, &crate::application::ls_commands::LsOptions::default());
+                    output.extend(display_result);
+                }
+                Err(e) => {
+                    output.extend(LsDisplayComponent::render_error(&e));
+                }
             }
         }
         output.push(String::new());
@@ -34,15 +36,19 @@ impl LsExample {
         output.push("2️⃣ Comando 'ls -l' (formato largo):".to_string());
         let command2 = "ls -l";
         let parsed2 = LsCommandParser::parse_command(command2);
-        match parsed2.execute(&use_case) {
-            Ok(result) => {
-                let mut options = crate::application::ls_commands::LsOptions::default();
-                options.long_format = true;
-                let display_result = LsDisplayComponent::render(&result, &options);
-                output.extend(display_result);
-            }
-            Err(e) => {
-                output.extend(LsDisplayComponent::render_error(&e));
+        for listing in parsed2.execute(&use_case) {
+            match listing {
+                Ok(result) => {
+                    let options = crate::application::ls_commands::LsOptions {
+                        long_format: true,
+                        ..Default::default()
+                    };
+                    let display_result = LsDisplayComponent::render(&result, &options);
+                    output.extend(display_result);
+                }
+                Err(e) => {
+                    output.extend(LsDisplayComponent::render_error(&e));
+                }
             }
         }
         output.push(String::new());
@@ -51,15 +57,19 @@ impl LsExample {
         output.push("3️⃣ Comando 'ls -a' (archivos ocultos):".to_string());
         let command3 = "ls -a";
         let parsed3 = LsCommandParser::parse_command(command3);
-        match parsed3.execute(&use_case) {
-            Ok(result) => {
-                let mut options = crate::application::ls_commands::LsOptions::default();
-                options.show_hidden = true;
-                let display_result = LsDisplayComponent::render(&result, &options);
-                output.extend(display_result);
-            }
-            Err(e) => {
-                output.extend(LsDisplayComponent::render_error(&e));
+        for listing in parsed3.execute(&use_case) {
+            match listing {
+                Ok(result) => {
+                    let options = crate::application::ls_commands::LsOptions {
+                        show_hidden: true,
+                        ..Default::default()
+                    };
+                    let display_result = LsDisplayComponent::render(&result, &options);
+                    output.extend(display_result);
+                }
+                Err(e) => {
+                    output.extend(LsDisplayComponent::render_error(&e));
+                }
             }
         }
         output.push(String::new());
@@ -68,16 +78,20 @@ impl LsExample {
         output.push("4️⃣ Comando 'ls -lh' (tamaños legibles):".to_string());
         let command4 = "ls -lh";
         let parsed4 = LsCommandParser::parse_command(command4);
-        match parsed4.execute(&use_case) {
-            Ok(result) => {
-                let mut options = crate::application::ls_commands::LsOptions::default();
-                options.long_format = true;
-                options.human_readable = true;
-                let display_result = LsDisplayComponent::render(&result, &options);
-                output.extend(display_result);
-            }
-            Err(e) => {
-                output.extend(LsDisplayComponent::render_error(&e));
+        for listing in parsed4.execute(&use_case) {
+            match listing {
+                Ok(result) => {
+                    let options = crate::application::ls_commands::LsOptions {
+                        long_format: true,
+                        human_readable: true,
+                        ..Default::default()
+                    };
+                    let display_result = LsDisplayComponent::render(&result, &options);
+                    output.extend(display_result);
+                }
+                Err(e) => {
+                    output.extend(LsDisplayComponent::render_error(&e));
+                }
             }
         }
         output.push(String::new());
@@ -86,15 +100,19 @@ impl LsExample {
         output.push("5️⃣ Comando 'ls -S' (ordenado por tamaño):".to_string());
         let command5 = "ls -S";
         let parsed5 = LsCommandParser::parse_command(command5);
-        match parsed5.execute(&use_case) {
-            Ok(result) => {
-                let mut options = crate::application::ls_commands::LsOptions::default();
-                options.sort_by = crate::application::ls_commands::SortBy::Size;
-                let display_result = LsDisplayComponent::render(&result, &options);
-                output.extend(display_result);
-            }
-            Err(e) => {
-                output.extend(LsDisplayComponent::render_error(&e));
+        for listing in parsed5.execute(&use_case) {
+            match listing {
+                Ok(result) => {
+                    let options = crate::application::ls_commands::LsOptions {
+                        sort_by: crate::application::ls_commands::SortBy::Size,
+                        ..Default::default()
+                    };
+                    let display_result = LsDisplayComponent::render(&result, &options);
+                    output.extend(display_result);
+                }
+                Err(e) => {
+                    output.extend(LsDisplayComponent::render_error(&e));
+                }
             }
         }
         output.push(String::new());
@@ -111,32 +129,16 @@ impl LsExample {
     pub fn execute_ls_command(command: &str) -> Vec<String> {
         let use_case = LsCommandsUseCase::new();
         let parsed = LsCommandParser::parse_command(command);
+        let options = parsed.options();
 
-        match parsed.execute(&use_case) {
-            Ok(result) => {
-                let mut options = crate::application::ls_commands::LsOptions::default();
-                
-                // Detectar opciones del comando
-                if command.contains("-l") {
-                    options.long_format = true;
-                }
-                if command.contains("-a") {
-                    options.show_hidden = true;
-                }
-                if command.contains("-h") {
-                    options.human_readable = true;
-                }
-                if command.contains("-S") {
-                    options.sort_by = crate::application::ls_commands::SortBy::Size;
-                }
-                if command.contains("-t") {
-                    options.sort_by = crate::application::ls_commands::SortBy::Modified;
-                }
-
-                LsDisplayComponent::render(&result, &options)
-            }
-            Err(e) => LsDisplayComponent::render_error(&e),
-        }
+        parsed
+            .execute(&use_case)
+            .iter()
+            .flat_map(|listing| match listing {
+                Ok(result) => LsDisplayComponent::render(result, &options),
+                Err(e) => LsDisplayComponent::render_error(e),
+            })
+            .collect()
     }
 
     /// Muestra una vista previa del directorio actual
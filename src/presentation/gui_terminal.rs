@@ -1,6 +1,8 @@
 use eframe::egui;
+use std::sync::mpsc::Receiver;
 
 use crate::application::use_cases::{ExecuteCommandUseCase, HandleInputUseCase};
+use crate::domain::entities::ExecutionEvent;
 use crate::domain::repositories::CommandRepository;
 use crate::presentation::texts::WelcomeMessages;
 use crate::presentation::input_handler::InputHandler;
@@ -29,12 +31,13 @@ where
     pub fn new(
         input_handler: HandleInputUseCase,
         command_executor: ExecuteCommandUseCase<R>,
+        events: Receiver<ExecutionEvent>,
     ) -> Self {
         let terminal_width = 1200.0;
         let terminal_height = 800.0;
         
         let mut app = Self {
-            input_handler: InputHandler::new(input_handler, command_executor),
+            input_handler: InputHandler::new(input_handler, command_executor, events),
             ui_renderer: UIRenderer::new(),
             system_monitor: SystemMonitor::new(),
             matrix_manager: MatrixManager::new(terminal_width, terminal_height),
@@ -59,6 +62,10 @@ where
         self.terminal_height = height;
         self.ui_renderer.update_dimensions(width, height);
         self.matrix_manager.update_dimensions(width, height);
+
+        // La pseudoterminal recibe el nuevo tamaño de la rejilla (TIOCSWINSZ)
+        let (cols, rows) = self.ui_renderer.screen_grid_size();
+        self.input_handler.resize_terminal(cols, rows);
     }
 
     fn draw_matrix_mode(&mut self, ui: &mut egui::Ui, _input_processed: bool) {
@@ -71,14 +78,14 @@ where
         painter.rect_filled(rect, 0.0, egui::Color32::BLACK);
         
         let last_input_time = self.input_handler.get_last_input_time();
-        self.matrix_manager.draw_matrix_effects(&painter, last_input_time);
+        self.matrix_manager.draw_matrix_effects(painter, last_input_time);
 
         if response.clicked() || !self.input_focused {
             response.request_focus();
             self.input_focused = true;
         }
         
-        self.draw_ui_layer(&painter);
+        self.draw_ui_layer(painter);
     }
 
     fn draw_ui_layer(&self, painter: &egui::Painter) {
@@ -87,7 +94,7 @@ where
         let last_input_time = self.input_handler.get_last_input_time();
         let system_stats = self.system_monitor.get_system_stats();
         
-        self.ui_renderer.draw_command_history(painter, input_handler, history_commands);
+        self.ui_renderer.draw_command_history(painter, input_handler, &history_commands);
         self.ui_renderer.draw_prompt(painter, input_handler, last_input_time);
         self.ui_renderer.draw_system_indicators(painter, system_stats);
     }
@@ -108,8 +115,12 @@ where
 {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         let available_size = ctx.available_rect().size();
+        self.ui_renderer.update_cell_width(ctx);
         self.update_dimensions(available_size.x, available_size.y);
 
+        // Mostrar la salida que llegó del hilo de trabajo sin bloquear la interfaz
+        self.input_handler.poll_execution_events();
+
         let input = ctx.input(|i| i.clone());
         let input_processed = self.input_handler.handle_keyboard_input(&input);
 
@@ -1,22 +1,44 @@
 use eframe::egui;
+use std::sync::mpsc::Receiver;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
 use std::time::Instant;
 
 use crate::application::use_cases::{ExecuteCommandUseCase, HandleInputUseCase};
-use crate::application::history_commands::{HistoryCommandsUseCase, HistoryCommandParser, HistoryCommand};
+use crate::application::history_commands::HistoryCommandsUseCase;
+use crate::domain::entities::{
+    CommandStep, EventSender, ExecutionEvent, InterruptFlag, ProcessSignal, INTERRUPTED_EXIT_CODE,
+    SUSPENDED_EXIT_CODE,
+};
+use crate::domain::completion::Completion;
+use crate::domain::errors::TermirustError;
 use crate::domain::repositories::CommandRepository;
-use crate::presentation::texts::{ErrorMessages, CommandHistoryText};
+use crate::presentation::texts::{ErrorMessages, CommandHistoryText, PromptText};
 use crate::infrastructure::logging::log_command_execution;
+use crate::infrastructure::pty;
+use crate::infrastructure::signals;
+use crate::infrastructure::stdin;
+use crate::presentation::pty_input::PtyKeyEncoder;
+use crate::infrastructure::worker::CommandWorker;
 
 pub struct InputHandler<R>
 where
     R: CommandRepository,
 {
     input_handler: HandleInputUseCase,
-    command_executor: ExecuteCommandUseCase<R>,
-    history_commands: HistoryCommandsUseCase,
+    command_executor: Arc<Mutex<ExecuteCommandUseCase<R>>>,
+    history_commands: Arc<Mutex<HistoryCommandsUseCase>>,
+    worker: CommandWorker,
+    events: Receiver<ExecutionEvent>,
+    event_sender: EventSender,
+    /// Ctrl+C lo levanta para cortar los bucles de la línea en ejecución
+    interrupt: InterruptFlag,
     last_input_time: Instant,
     input_buffer: String,
     input_buffer_dirty: bool,
+    /// Columnas y filas de la rejilla que se informaron a la pseudoterminal
+    window_size: (u16, u16),
+    /// Línea corregida que se ofreció tras un comando desconocido; F2 la ejecuta
+    suggestion: Option<String>,
 }
 
 impl<R> InputHandler<R>
@@ -26,25 +48,46 @@ where
     pub fn new(
         input_handler: HandleInputUseCase,
         command_executor: ExecuteCommandUseCase<R>,
+        events: Receiver<ExecutionEvent>,
     ) -> Self {
+        let worker = CommandWorker::new().unwrap_or_else(|e| {
+            panic!("No se pudo iniciar el hilo de trabajo de comandos: {}", ErrorMessages::describe(&e))
+        });
         Self {
             input_handler,
-            command_executor,
-            history_commands: HistoryCommandsUseCase::new(100),
+            history_commands: command_executor.history_handle(),
+            event_sender: command_executor.event_sender(),
+            interrupt: command_executor.interrupt_handle(),
+            command_executor: Arc::new(Mutex::new(command_executor)),
+            worker,
+            events,
             last_input_time: Instant::now(),
             input_buffer: String::new(),
             input_buffer_dirty: true,
+            window_size: (0, 0),
+            suggestion: None,
         }
     }
 
     pub fn handle_keyboard_input(&mut self, input: &egui::InputState) -> bool {
+        // Mientras un programa ocupa la pseudoterminal, las teclas son suyas
+        if self.input_handler.get_screen().is_some() {
+            return self.forward_to_pty(input);
+        }
+        if self.input_handler.get_document().is_some() {
+            return self.handle_document_input(input);
+        }
+
         let mut input_processed = false;
         
         for event in &input.events {
             match event {
-                egui::Event::Key { key, pressed, .. } if *pressed => {
+                egui::Event::Key { key, pressed, modifiers, .. } if *pressed => {
                     input_processed = true;
                     self.update_input_time_for_key(key);
+                    if self.handle_completion_key(*key, *modifiers) {
+                        continue;
+                    }
                     
                     match key {
                         egui::Key::Enter => {
@@ -60,8 +103,20 @@ where
                         egui::Key::F4 if input.modifiers.alt => {
                             std::process::exit(0);
                         }
+                        egui::Key::C if input.modifiers.ctrl => {
+                            self.handle_interrupt();
+                        }
+                        egui::Key::Z if input.modifiers.ctrl => {
+                            self.handle_suspend();
+                        }
+                        egui::Key::D if input.modifiers.ctrl => {
+                            self.handle_end_of_input();
+                        }
                         egui::Key::Tab => {
-                            input_processed = true;
+                            self.complete_word();
+                        }
+                        egui::Key::F2 => {
+                            self.run_suggestion();
                         }
                         egui::Key::ArrowLeft => {
                             self.input_handler.handle_arrow_left();
@@ -88,8 +143,14 @@ where
                         _ => {}
                     }
                 }
+                // egui entrega Ctrl+C como el evento de copiar
+                egui::Event::Copy => {
+                    input_processed = true;
+                    self.handle_interrupt();
+                }
                 egui::Event::Text(text) => {
                     input_processed = true;
+                    self.input_handler.close_completion_menu();
                     self.handle_text_input(text);
                 }
                 _ => {}
@@ -99,6 +160,118 @@ where
         input_processed
     }
 
+    /// Teclas del visor de documentos: desplazarse, buscar con `/` y cerrar con Esc o `q`
+    fn handle_document_input(&mut self, input: &egui::InputState) -> bool {
+        let page = (self.window_size.1 as isize - 2).max(1);
+        let mut input_processed = false;
+        let mut close = false;
+        let Some(view) = self.input_handler.get_document_mut() else {
+            return false;
+        };
+
+        if input.raw_scroll_delta.y != 0.0 {
+            input_processed = true;
+            view.scroll_by(-(input.raw_scroll_delta.y / 20.0).round() as isize);
+        }
+        for event in &input.events {
+            match event {
+                egui::Event::Key { key: egui::Key::F4, pressed: true, modifiers, .. } if modifiers.alt => {
+                    std::process::exit(0);
+                }
+                egui::Event::Key { key, pressed: true, .. } => {
+                    input_processed = true;
+                    if let Some(query) = view.search_input_mut() {
+                        match key {
+                            egui::Key::Enter => view.confirm_search(),
+                            egui::Key::Escape => view.cancel_search(),
+                            egui::Key::Backspace => {
+                                query.pop();
+                            }
+                            _ => {}
+                        }
+                        continue;
+                    }
+                    match key {
+                        egui::Key::Escape => close = true,
+                        egui::Key::ArrowDown | egui::Key::Enter => view.scroll_by(1),
+                        egui::Key::ArrowUp => view.scroll_by(-1),
+                        egui::Key::PageDown | egui::Key::Space => view.scroll_by(page),
+                        egui::Key::PageUp => view.scroll_by(-page),
+                        egui::Key::Home => view.scroll_to(0),
+                        egui::Key::End => view.scroll_to(usize::MAX),
+                        _ => {}
+                    }
+                }
+                egui::Event::Text(text) => {
+                    input_processed = true;
+                    if let Some(query) = view.search_input_mut() {
+                        query.push_str(text);
+                        continue;
+                    }
+                    match text.as_str() {
+                        "/" => view.start_search(),
+                        "n" => view.next_match(),
+                        "N" => view.previous_match(),
+                        "j" => view.scroll_by(1),
+                        "k" => view.scroll_by(-1),
+                        "g" => view.scroll_to(0),
+                        "G" => view.scroll_to(usize::MAX),
+                        "q" => close = true,
+                        _ => {}
+                    }
+                }
+                _ => {}
+            }
+        }
+        if close {
+            self.input_handler.close_document();
+        }
+        input_processed
+    }
+
+    /// Envía a la pseudoterminal las teclas, el texto y lo que se pega
+    fn forward_to_pty(&mut self, input: &egui::InputState) -> bool {
+        let (application_cursor_keys, bracketed_paste) = match self.input_handler.get_screen() {
+            Some(screen) => (screen.application_cursor_keys(), screen.bracketed_paste()),
+            None => return false,
+        };
+
+        let mut input_processed = false;
+        for event in &input.events {
+            let bytes = match event {
+                egui::Event::Key { key: egui::Key::F4, pressed: true, modifiers, .. } if modifiers.alt => {
+                    std::process::exit(0);
+                }
+                egui::Event::Key { key, pressed: true, modifiers, .. } => {
+                    PtyKeyEncoder::encode_key(*key, *modifiers, application_cursor_keys)
+                }
+                egui::Event::Text(text) => Some(text.as_bytes().to_vec()),
+                // egui entrega Ctrl+C y Ctrl+X como copiar y cortar
+                egui::Event::Copy => Some(vec![0x03]),
+                egui::Event::Cut => Some(vec![0x18]),
+                egui::Event::Paste(text) => Some(PtyKeyEncoder::encode_paste(text, bracketed_paste)),
+                _ => None,
+            };
+            if let Some(bytes) = bytes {
+                input_processed = true;
+                self.last_input_time = Instant::now();
+                pty::write_foreground(&bytes);
+            }
+        }
+        input_processed
+    }
+
+    /// Informa el tamaño de la rejilla visible; si cambió, se ajustan la pantalla
+    /// emulada y la pseudoterminal (TIOCSWINSZ)
+    pub fn resize_terminal(&mut self, cols: u16, rows: u16) {
+        if self.window_size == (cols, rows) {
+            return;
+        }
+        self.window_size = (cols, rows);
+        self.input_handler.resize_screen(cols as usize, rows as usize);
+        pty::update_window_size(cols, rows);
+    }
+
     fn update_input_time_for_key(&mut self, key: &egui::Key) {
         match key {
             egui::Key::Enter | egui::Key::Backspace | egui::Key::Escape => {
@@ -109,19 +282,80 @@ where
     }
 
     fn handle_enter_key(&mut self) {
+        // Solo se ejecuta una línea a la vez: mientras tanto, lo escrito es para el proceso
+        if self.input_handler.get_running_command().is_some() {
+            self.send_process_input(true);
+            return;
+        }
         let command = self.input_handler.get_command_buffer();
         if !command.trim().is_empty() {
-            if self.handle_history_commands(&command) {
-                self.clear_input();
-            } else {
-                self.execute_command(&command);
-            }
+            self.execute_command(&command);
         }
     }
 
+    /// F2: ejecuta la corrección sugerida para el último comando desconocido
+    fn run_suggestion(&mut self) {
+        if self.input_handler.get_running_command().is_some() {
+            return;
+        }
+        if let Some(line) = self.suggestion.take() {
+            self.execute_command(&line);
+        }
+    }
+
+    /// Ctrl+C: interrumpe el proceso en primer plano o descarta la línea que se escribe
+    fn handle_interrupt(&mut self) {
+        self.last_input_time = Instant::now();
+        if self.input_handler.get_running_command().is_some() {
+            self.interrupt.raise();
+            signals::signal_foreground(ProcessSignal::Interrupt);
+        } else {
+            let command = self.input_handler.get_command_buffer();
+            self.input_handler.add_output_line(format!("{}{}^C", PromptText::get_prompt_only(), command));
+            self.clear_input();
+        }
+    }
+
+    /// Ctrl+Z: detiene el proceso en primer plano
+    fn handle_suspend(&mut self) {
+        if self.input_handler.get_running_command().is_some() {
+            signals::signal_foreground(ProcessSignal::Suspend);
+        }
+    }
+
+    /// Ctrl+D: con la línea vacía cierra la entrada del proceso (fin de archivo); si no,
+    /// le envía lo escrito sin salto de línea, como hace una terminal
+    fn handle_end_of_input(&mut self) {
+        self.last_input_time = Instant::now();
+        if self.input_handler.get_running_command().is_none() {
+            return;
+        }
+        if self.input_handler.get_command_buffer().is_empty() {
+            stdin::close_foreground();
+        } else {
+            self.send_process_input(false);
+        }
+    }
+
+    /// Escribe la línea del prompt en la entrada estándar del proceso en primer plano
+    fn send_process_input(&mut self, newline: bool) {
+        let mut line = self.input_handler.get_command_buffer();
+        // La entrada es una tubería y no una terminal: nadie más la muestra
+        if newline {
+            self.input_handler.add_output_line(line.clone());
+            line.push('\n');
+        } else {
+            self.input_handler.add_partial_output(line.clone());
+        }
+        if !stdin::write_foreground(line.as_bytes()) {
+            self.add_error_line(&ErrorMessages::format_error(PromptText::process_input_closed()));
+        }
+        self.clear_input();
+    }
+
     fn handle_text_input(&mut self, text: &str) {
         for ch in text.chars() {
-            if ch.is_ascii() && !ch.is_control() {
+            if !ch.is_control() {
                 self.input_handler.handle_key_press(ch);
                 self.input_buffer.push(ch);
                 self.input_buffer_dirty = true;
@@ -130,63 +364,237 @@ where
         }
     }
 
-    fn handle_history_commands(&mut self, command: &str) -> bool {
-        let history_command = HistoryCommandParser::parse_command(command);
-        
-        match history_command {
-            HistoryCommand::NotHistoryCommand => false,
+    /// Tab: completa la palabra que se está escribiendo. Con un solo candidato lo
+    /// escribe entero; con varios escribe la parte común y los muestra en una lista.
+    fn complete_word(&mut self) {
+        let buffer = self.input_handler.get_command_buffer();
+        let at_end = self.input_handler.get_cursor_position() == buffer.len();
+        if self.input_handler.get_running_command().is_some() || !at_end {
+            return;
+        }
+        // Sin comandos en ejecución el ejecutor está libre; si no, no se espera
+        let completion = match self.command_executor.try_lock() {
+            Ok(mut executor) => executor.complete(&buffer),
+            Err(_) => return,
+        };
+        if completion.pending.is_some() {
+            self.complete_in_background(buffer);
+        } else {
+            self.apply_completion(completion);
+        }
+    }
+
+    /// Completa en el hilo de trabajo cuando hace falta la salida de un comando (las
+    /// ramas de `git checkout`), para no detener la interfaz mientras se ejecuta. Los
+    /// candidatos llegan como un evento.
+    fn complete_in_background(&mut self, line: String) {
+        let executor = Arc::clone(&self.command_executor);
+        let events = self.event_sender.clone();
+        self.worker.spawn(move || {
+            if let Ok(mut executor) = executor.lock() {
+                let completion = executor.complete_with_commands(&line);
+                let _ = events.send(ExecutionEvent::Completion { line, completion });
+            }
+        });
+    }
+
+    /// Candidatos que llegaron del hilo de trabajo; se descartan si la línea cambió
+    fn receive_completion(&mut self, line: String, completion: Completion) {
+        let unchanged = self.input_handler.get_command_buffer() == line
+            && self.input_handler.get_cursor_position() == line.len()
+            && self.input_handler.get_running_command().is_none();
+        if unchanged {
+            self.apply_completion(completion);
+        }
+    }
+
+    fn apply_completion(&mut self, completion: Completion) {
+        let request = &completion.request;
+        match completion.candidates.as_slice() {
+            [] => {}
+            [candidate] => {
+                let text = request.replacement(&candidate.value, !candidate.open);
+                self.replace_input(request.start, &text);
+            }
             _ => {
-                log::info!("Ejecutando comando del historial: '{}'", command);
-                
-                let output_lines = history_command.execute(&mut self.history_commands);
-                for line in output_lines {
-                    self.input_handler.add_output_line(line);
+                let common = completion.common_prefix();
+                if common.chars().count() > request.prefix.chars().count() {
+                    let text = request.replacement(&common, false);
+                    self.replace_input(request.start, &text);
                 }
-                true
+                self.input_handler.open_completion_menu(completion);
             }
         }
     }
 
+    /// Teclas mientras se muestra la lista de candidatos: las flechas y Tab eligen,
+    /// Enter escribe el elegido y Esc la cierra. Devuelve `false` si la tecla no es
+    /// para la lista, que entonces se cierra.
+    fn handle_completion_key(&mut self, key: egui::Key, modifiers: egui::Modifiers) -> bool {
+        let Some(menu) = self.input_handler.get_completion_menu_mut() else {
+            return false;
+        };
+        match key {
+            egui::Key::Tab if modifiers.shift => menu.select_previous(),
+            egui::Key::ArrowDown | egui::Key::Tab => menu.select_next(),
+            egui::Key::ArrowUp => menu.select_previous(),
+            egui::Key::Enter => {
+                let choice = menu
+                    .selected_candidate()
+                    .map(|candidate| (menu.request().start, menu.request().replacement(&candidate.value, !candidate.open)));
+                self.input_handler.close_completion_menu();
+                if let Some((start, text)) = choice {
+                    self.replace_input(start, &text);
+                }
+            }
+            egui::Key::Escape => self.input_handler.close_completion_menu(),
+            _ => {
+                self.input_handler.close_completion_menu();
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Reemplaza el final de la línea con lo que se completó
+    fn replace_input(&mut self, start: usize, text: &str) {
+        self.input_handler.replace_input_from(start, text);
+        self.input_buffer = self.input_handler.get_command_buffer();
+        self.input_buffer_dirty = true;
+        self.last_input_time = Instant::now();
+    }
+
+    /// Lanza la línea en el hilo de trabajo; su salida llega como eventos
     fn execute_command(&mut self, command: &str) {
-        let result = self.command_executor.execute(command);
+        let executor = Arc::clone(&self.command_executor);
+        let events = self.event_sender.clone();
+        let line = command.to_string();
         
-        if self.command_executor.should_exit() {
-            std::process::exit(0);
+        self.suggestion = None;
+        self.input_handler.set_running_command(Some(line.clone()));
+        self.worker.spawn(move || {
+            let (result, should_exit) = match executor.lock() {
+                Ok(mut executor) => {
+                    let result = executor.execute(&line);
+                    (result, executor.should_exit())
+                }
+                Err(_) => {
+                    let error = std::io::Error::other("El ejecutor de comandos no está disponible");
+                    (Err(TermirustError::io("", &error)), false)
+                }
+            };
+            let _ = events.send(ExecutionEvent::Finished { result, should_exit });
+        });
+        
+        self.clear_input();
+    }
+
+    /// Procesa los eventos que el hilo de trabajo envió desde el último cuadro
+    pub fn poll_execution_events(&mut self) {
+        while let Ok(event) = self.events.try_recv() {
+            match event {
+                ExecutionEvent::Output(line) => self.input_handler.add_output_line(line),
+                ExecutionEvent::Error(error) => self.add_error_line(&ErrorMessages::format(&error)),
+                ExecutionEvent::ErrorOutput { line, location } => {
+                    self.add_error_line(&ErrorMessages::format_output(&line, location.as_ref()));
+                }
+                ExecutionEvent::Suggestions { suggestions, corrected } => {
+                    for suggestion in &suggestions {
+                        self.input_handler.add_output_line(ErrorMessages::format_suggestion(suggestion));
+                    }
+                    if let Some(line) = &corrected {
+                        self.input_handler.add_output_line(PromptText::format_suggestion_hint(line));
+                    }
+                    self.suggestion = corrected;
+                }
+                ExecutionEvent::PartialOutput(text) => self.input_handler.add_partial_output(text),
+                ExecutionEvent::PartialErrorOutput { text, location } => {
+                    let line = ErrorMessages::styled_line(&ErrorMessages::format_output(&text, location.as_ref()));
+                    self.input_handler.add_partial_styled_line(line);
+                }
+                ExecutionEvent::Clear => self.input_handler.clear_output(),
+                ExecutionEvent::Document(document) => self.input_handler.open_document(document),
+                ExecutionEvent::PtyStarted { cols, rows } => {
+                    self.input_handler.open_screen(cols as usize, rows as usize);
+                }
+                ExecutionEvent::PtyOutput(bytes) => {
+                    let responses = self.input_handler.feed_screen(&bytes);
+                    if !responses.is_empty() {
+                        pty::write_foreground(&responses);
+                    }
+                }
+                ExecutionEvent::PtyClosed => self.input_handler.close_screen(),
+                ExecutionEvent::Completion { line, completion } => self.receive_completion(line, completion),
+                ExecutionEvent::Finished { result, should_exit } => {
+                    if should_exit {
+                        std::process::exit(0);
+                    }
+                    self.finish_command(result);
+                }
+            }
         }
+    }
+
+    /// Registra el resultado de una línea terminada y vuelve a mostrar el prompt
+    fn finish_command(&mut self, result: Result<Vec<CommandStep>, TermirustError>) {
+        let command = self.input_handler.get_running_command().unwrap_or_default().to_string();
+        self.input_handler.set_running_command(None);
         
-        match &result {
-            Ok(command_result) => {
-                let output_lines: Vec<String> = if !command_result.output.is_empty() {
-                    command_result.output.lines().map(|s| s.to_string()).collect()
-                } else {
-                    vec![CommandHistoryText::format_command_success(command)]
-                };
-                
-                log_command_execution(command, true, &command_result.output);
-                
-                self.history_commands.add_command_entry(
-                    command.to_string(),
-                    output_lines,
-                    true,
-                    None,
-                );
+        match result {
+            Ok(steps) => {
+                // Cada paso de una línea con `;`, `&&` o `||` tiene su propia entrada
+                for step in &steps {
+                    self.record_step(step);
+                }
             }
             Err(e) => {
-                let error_msg = e.to_string();
+                let message = ErrorMessages::describe(&e);
+                log_command_execution(&command, false, &message);
                 
-                log_command_execution(command, false, &error_msg);
-                
-                self.history_commands.add_command_entry(
-                    command.to_string(),
+                self.get_history_commands_mut().add_command_entry(
+                    command,
                     vec![],
                     false,
-                    Some(error_msg.clone()),
+                    Some(message.clone()),
                 );
-                self.input_handler.add_output_line(ErrorMessages::format_error(&error_msg));
+                self.add_error_line(&ErrorMessages::format_error(&message));
             }
         }
+    }
+
+    /// Muestra una línea de error resaltada
+    fn add_error_line(&mut self, line: &str) {
+        self.input_handler.add_styled_line(ErrorMessages::styled_line(line));
+    }
+
+    /// Registra en el historial y en el log un paso ejecutado con su estado
+    fn record_step(&mut self, step: &CommandStep) {
+        // Los errores de Termirust se muestran después de lo que escribió el programa
+        let mut error_lines: Vec<String> = step.command.error_output.lines().map(str::to_string).collect();
+        error_lines.extend(step.command.errors.iter().map(ErrorMessages::format));
+        let error_output = error_lines.join("\n");
+        let output = step.command.clone().with_error_output(error_output.clone()).combined_output();
+        let success = step.succeeded();
+        let output_lines: Vec<String> = if !output.is_empty() {
+            output.lines().map(|s| s.to_string()).collect()
+        } else {
+            vec![CommandHistoryText::format_command_success(&step.text)]
+        };
+        let error_message = match step.command.exit_code {
+            0 => None,
+            INTERRUPTED_EXIT_CODE => Some(CommandHistoryText::format_command_interrupted(INTERRUPTED_EXIT_CODE)),
+            SUSPENDED_EXIT_CODE => Some(CommandHistoryText::format_command_suspended(SUSPENDED_EXIT_CODE)),
+            _ => Some(error_output),
+        };
         
-        self.clear_input();
+        log_command_execution(&step.text, success, &output);
+        
+        self.get_history_commands_mut().add_command_entry(
+            step.text.clone(),
+            output_lines,
+            success,
+            error_message,
+        );
     }
 
     fn clear_input(&mut self) {
@@ -207,7 +615,74 @@ where
         &mut self.input_handler
     }
 
-    pub fn get_history_commands(&self) -> &HistoryCommandsUseCase {
-        &self.history_commands
+    pub fn get_history_commands(&self) -> MutexGuard<'_, HistoryCommandsUseCase> {
+        self.history_commands.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn get_history_commands_mut(&mut self) -> MutexGuard<'_, HistoryCommandsUseCase> {
+        self.history_commands.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entities::Command;
+    use crate::domain::services::{CommandService, TerminalService};
+    use crate::infrastructure::repositories::SystemCommandRepository;
+
+    fn handler() -> InputHandler<SystemCommandRepository> {
+        let (events, receiver) = std::sync::mpsc::channel();
+        let repository = SystemCommandRepository::new().with_error_formatter(ErrorMessages::format);
+        let executor = ExecuteCommandUseCase::new(CommandService::new(repository), TerminalService::new(), events);
+        InputHandler::new(HandleInputUseCase::new(TerminalService::new()), executor, receiver)
+    }
+
+    /// Comando, éxito y mensaje de error de cada entrada del historial
+    fn entries(handler: &InputHandler<SystemCommandRepository>) -> Vec<(String, bool, Option<String>)> {
+        let history = handler.get_history_commands();
+        history
+            .get_history()
+            .get_entries()
+            .iter()
+            .map(|entry| (entry.command.clone(), entry.success, entry.error_message.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn each_step_gets_its_own_history_entry_and_status() {
+        let mut handler = handler();
+        let steps = handler.command_executor.lock().unwrap().execute("true && cd /nada/termirust; echo a || echo b");
+        handler.finish_command(steps);
+
+        let error = TermirustError::NotFound { command: "cd".to_string(), path: Some("/nada/termirust".to_string()) };
+        assert_eq!(
+            entries(&handler),
+            [
+                ("true".to_string(), true, None),
+                ("cd /nada/termirust".to_string(), false, Some(ErrorMessages::format(&error))),
+                ("echo a".to_string(), true, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn interrupted_and_suspended_steps_say_so_in_the_history() {
+        let mut handler = handler();
+        let step = |text: &str, exit_code: i32| {
+            CommandStep::new(text.to_string(), Command::new(text.to_string(), Vec::new()).with_exit_code(exit_code))
+        };
+        handler.finish_command(Ok(vec![step("sleep", INTERRUPTED_EXIT_CODE), step("vim", SUSPENDED_EXIT_CODE)]));
+        assert_eq!(
+            entries(&handler),
+            [
+                (
+                    "sleep".to_string(),
+                    false,
+                    Some(CommandHistoryText::format_command_interrupted(INTERRUPTED_EXIT_CODE))
+                ),
+                ("vim".to_string(), false, Some(CommandHistoryText::format_command_suspended(SUSPENDED_EXIT_CODE))),
+            ]
+        );
     }
 }
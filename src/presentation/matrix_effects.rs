@@ -66,7 +66,7 @@ impl MatrixDrop {
     pub fn draw_with_alpha(&self, painter: &egui::Painter, alpha_factor: f32) {
         for (i, &ch) in self.chars.iter().enumerate() {
             let y_pos = self.y - i as f32 * 20.0;
-            if y_pos >= 0.0 && y_pos < 800.0 {
+            if (0.0..800.0).contains(&y_pos) {
                 let color_index = (self.brightness + i).min(MATRIX_COLORS.len() - 1);
                 let base_color = MATRIX_COLORS[color_index];
                 
@@ -89,38 +89,3 @@ impl MatrixDrop {
         }
     }
 }
-
-pub struct MatrixEffectManager {
-    pub drops: Vec<MatrixDrop>,
-    pub terminal_width: u16,
-    pub terminal_height: u16,
-}
-
-impl MatrixEffectManager {
-    pub fn new(terminal_width: u16, terminal_height: u16) -> Self {
-        Self {
-            drops: Vec::new(),
-            terminal_width,
-            terminal_height,
-        }
-    }
-
-    pub fn add_random_drops(&mut self) {
-        let mut rng = rand::thread_rng();
-        if rng.gen_bool(0.3) && self.drops.len() < 20 {
-            let x = rng.gen_range(0..self.terminal_width);
-            self.drops.push(MatrixDrop::new(x as f32, self.terminal_height as f32));
-        }
-    }
-
-    pub fn update_drops(&mut self) {
-        self.drops.retain_mut(|drop| !drop.update(1.0)); // Assuming delta_time is 1.0 for now
-    }
-
-    pub fn draw(&self, painter: &egui::Painter) -> std::io::Result<()> {
-        for drop in &self.drops {
-            drop.draw(painter);
-        }
-        Ok(())
-    }
-}
@@ -60,11 +60,11 @@ impl MatrixManager {
         effects.retain_mut(|drop| !drop.update(delta_time));
     }
 
-    fn log_matrix_updates(effects: &Vec<MatrixDrop>) {
+    fn log_matrix_updates(effects: &[MatrixDrop]) {
         static mut FRAME_COUNT: u32 = 0;
         unsafe {
             FRAME_COUNT += 1;
-            if FRAME_COUNT % 100 == 0 {
+            if FRAME_COUNT.is_multiple_of(100) {
                 log_matrix_effect_update(effects.len());
             }
         }
@@ -3,6 +3,8 @@ pub mod texts;
 pub mod commands;
 pub mod matrix_effects;
 pub mod input_handler;
+pub mod pty_input;
+pub mod script_runner;
 pub mod ui_renderer;
 pub mod system_monitor;
 pub mod matrix_manager;
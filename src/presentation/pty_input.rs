@@ -0,0 +1,99 @@
+use eframe::egui;
+
+/// Traduce las teclas de egui a las secuencias que espera un programa en una
+/// terminal xterm
+pub struct PtyKeyEncoder;
+
+impl PtyKeyEncoder {
+    /// Bytes que produce una tecla pulsada, o `None` si se escribe como texto
+    /// (las letras sin Ctrl llegan en `egui::Event::Text`)
+    pub fn encode_key(key: egui::Key, modifiers: egui::Modifiers, application_cursor_keys: bool) -> Option<Vec<u8>> {
+        if modifiers.ctrl {
+            if let Some(byte) = Self::control_byte(key) {
+                return Some(vec![byte]);
+            }
+        }
+
+        let sequence = match key {
+            egui::Key::Enter => "\r".to_string(),
+            egui::Key::Tab if modifiers.shift => "\x1b[Z".to_string(),
+            egui::Key::Tab => "\t".to_string(),
+            egui::Key::Backspace => "\x7f".to_string(),
+            egui::Key::Escape => "\x1b".to_string(),
+            egui::Key::ArrowUp => Self::cursor_key('A', modifiers, application_cursor_keys),
+            egui::Key::ArrowDown => Self::cursor_key('B', modifiers, application_cursor_keys),
+            egui::Key::ArrowRight => Self::cursor_key('C', modifiers, application_cursor_keys),
+            egui::Key::ArrowLeft => Self::cursor_key('D', modifiers, application_cursor_keys),
+            egui::Key::Home => Self::cursor_key('H', modifiers, application_cursor_keys),
+            egui::Key::End => Self::cursor_key('F', modifiers, application_cursor_keys),
+            egui::Key::Insert => Self::tilde_key(2, modifiers),
+            egui::Key::Delete => Self::tilde_key(3, modifiers),
+            egui::Key::PageUp => Self::tilde_key(5, modifiers),
+            egui::Key::PageDown => Self::tilde_key(6, modifiers),
+            egui::Key::F1 => "\x1bOP".to_string(),
+            egui::Key::F2 => "\x1bOQ".to_string(),
+            egui::Key::F3 => "\x1bOR".to_string(),
+            egui::Key::F4 => "\x1bOS".to_string(),
+            egui::Key::F5 => Self::tilde_key(15, modifiers),
+            egui::Key::F6 => Self::tilde_key(17, modifiers),
+            egui::Key::F7 => Self::tilde_key(18, modifiers),
+            egui::Key::F8 => Self::tilde_key(19, modifiers),
+            egui::Key::F9 => Self::tilde_key(20, modifiers),
+            egui::Key::F10 => Self::tilde_key(21, modifiers),
+            egui::Key::F11 => Self::tilde_key(23, modifiers),
+            egui::Key::F12 => Self::tilde_key(24, modifiers),
+            _ => return None,
+        };
+        Some(sequence.into_bytes())
+    }
+
+    /// Texto pegado; si el programa lo pidió (modo 2004) se enmarca para que no lo
+    /// confunda con lo que se teclea
+    pub fn encode_paste(text: &str, bracketed_paste: bool) -> Vec<u8> {
+        let text = text.replace('\n', "\r");
+        if bracketed_paste {
+            format!("\x1b[200~{}\x1b[201~", text).into_bytes()
+        } else {
+            text.into_bytes()
+        }
+    }
+
+    /// Ctrl+letra y los demás controles C0 (Ctrl+Espacio, Ctrl+[, Ctrl+\, Ctrl+])
+    fn control_byte(key: egui::Key) -> Option<u8> {
+        match key {
+            egui::Key::Space => Some(0x00),
+            egui::Key::OpenBracket => Some(0x1b),
+            egui::Key::Backslash => Some(0x1c),
+            egui::Key::CloseBracket => Some(0x1d),
+            _ => {
+                let name = key.name().as_bytes();
+                match name {
+                    [letter] if letter.is_ascii_alphabetic() => Some(letter.to_ascii_uppercase() - b'A' + 1),
+                    _ => None,
+                }
+            }
+        }
+    }
+
+    /// Parámetro de modificadores de xterm: 1 + Mayús + 2·Alt + 4·Ctrl
+    fn modifier_param(modifiers: egui::Modifiers) -> u8 {
+        1 + modifiers.shift as u8 + 2 * modifiers.alt as u8 + 4 * modifiers.ctrl as u8
+    }
+
+    /// Flechas, Inicio y Fin: `ESC [ A`, `ESC O A` en modo aplicación, o `ESC [1;5A` con modificadores
+    fn cursor_key(code: char, modifiers: egui::Modifiers, application_cursor_keys: bool) -> String {
+        match Self::modifier_param(modifiers) {
+            1 if application_cursor_keys => format!("\x1bO{}", code),
+            1 => format!("\x1b[{}", code),
+            param => format!("\x1b[1;{}{}", param, code),
+        }
+    }
+
+    /// Teclas de edición y de función: `ESC [ n ~` o `ESC [ n ; m ~` con modificadores
+    fn tilde_key(number: u8, modifiers: egui::Modifiers) -> String {
+        match Self::modifier_param(modifiers) {
+            1 => format!("\x1b[{}~", number),
+            param => format!("\x1b[{};{}~", number, param),
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use std::io::Write;
+use std::path::Path;
+use std::sync::mpsc::Receiver;
+use std::thread;
+
+use crate::application::use_cases::ExecuteCommandUseCase;
+use crate::domain::entities::ExecutionEvent;
+use crate::domain::repositories::CommandRepository;
+use crate::infrastructure::stdin;
+use crate::presentation::texts::ErrorMessages;
+
+/// Ejecuta un script sin abrir la ventana (`termirust --script archivo.trs`). La
+/// salida de los comandos va a la salida estándar; los errores y las sugerencias, a
+/// la de errores.
+pub struct ScriptRunner;
+
+impl ScriptRunner {
+    /// Ejecuta el script y devuelve el código de salida para el proceso
+    pub fn run<R: CommandRepository>(
+        mut command_executor: ExecuteCommandUseCase<R>,
+        events: Receiver<ExecutionEvent>,
+        path: &Path,
+    ) -> i32 {
+        // Sin prompt, los programas leen la entrada del proceso (`termirust --script x < datos`)
+        stdin::inherit_input(true);
+        let printer = thread::spawn(move || {
+            let mut stdout = std::io::stdout();
+            let mut stderr = std::io::stderr();
+            // El canal se cierra cuando el ejecutor y sus procesos terminan
+            for event in events {
+                let _ = match event {
+                    ExecutionEvent::Output(line) => writeln!(stdout, "{}", line),
+                    ExecutionEvent::ErrorOutput { line, location } => {
+                        writeln!(stderr, "{}", ErrorMessages::format_output(&line, location.as_ref()))
+                    }
+                    ExecutionEvent::Error(error) => writeln!(stderr, "{}", ErrorMessages::format(&error)),
+                    ExecutionEvent::Suggestions { suggestions, .. } => suggestions
+                        .iter()
+                        .try_for_each(|suggestion| writeln!(stderr, "{}", ErrorMessages::format_suggestion(suggestion))),
+                    ExecutionEvent::Document(document) => writeln!(stdout, "{}", document.plain_text()),
+                    ExecutionEvent::PartialOutput(text) => write!(stdout, "{}", text),
+                    ExecutionEvent::PartialErrorOutput { text, location } => {
+                        write!(stderr, "{}", ErrorMessages::format_output(&text, location.as_ref()))
+                    }
+                    ExecutionEvent::PtyOutput(bytes) => stdout.write_all(&bytes),
+                    _ => Ok(()),
+                };
+                let _ = stdout.flush();
+            }
+        });
+
+        let exit_code = match command_executor.run_script(path) {
+            Ok(exit_code) => exit_code,
+            Err(e) => {
+                eprintln!("termirust: {}", ErrorMessages::describe(&e));
+                1
+            }
+        };
+        drop(command_executor);
+        let _ = printer.join();
+        exit_code
+    }
+}
@@ -195,6 +195,16 @@ impl CommandHistoryText {
         format!("❌ {} - Error: {}", command, error)
     }
     
+    /// Formatea el estado de un comando interrumpido con Ctrl+C
+    pub fn format_command_interrupted(exit_code: i32) -> String {
+        format!("Interrumpido (código de salida {})", exit_code)
+    }
+    
+    /// Formatea el estado de un comando detenido con Ctrl+Z
+    pub fn format_command_suspended(exit_code: i32) -> String {
+        format!("Detenido (código de salida {})", exit_code)
+    }
+    
     /// Formatea el encabezado del historial
     pub fn format_history_header() -> String {
         "📜 Historial de Comandos:".to_string()
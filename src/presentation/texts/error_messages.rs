@@ -1,3 +1,11 @@
+use crate::domain::errors::{
+    AliasError, ArgumentError, Construct, ExpansionError, FileError, JobError, Nesting, PluginError,
+    SourceLocation, TermirustError,
+};
+use crate::domain::parser::ParseError;
+use crate::domain::style::{Color, StyledLine};
+use crate::domain::suggestions::CommandSuggestion;
+
 pub struct ErrorMessages;
 
 impl ErrorMessages {
@@ -5,19 +13,233 @@ impl ErrorMessages {
     pub fn format_error(error: &str) -> String {
         format!("Error: {}", error)
     }
-    
+
     /// Formatea un mensaje específico para comandos no encontrados
     pub fn format_command_not_found(command_name: &str) -> String {
-        format!("Error: El comando '{}' no se pudo encontrar", command_name)
+        format!("El comando '{}' no se pudo encontrar", command_name)
+    }
+
+    /// Propone los nombres parecidos a un comando que no se encontró
+    pub fn format_suggestion(suggestion: &CommandSuggestion) -> String {
+        let quoted: Vec<String> = suggestion.candidates.iter().map(|name| format!("'{}'", name)).collect();
+        let names = match quoted.split_last() {
+            Some((last, [])) => last.clone(),
+            Some((last, rest)) => format!("{} o {}", rest.join(", "), last),
+            None => String::new(),
+        };
+        format!("{}: ¿quisiste decir {}?", suggestion.command, names)
+    }
+
+    /// Describe un error de Termirust para el usuario, precedido por el comando en el
+    /// que ocurrió
+    pub fn describe(error: &TermirustError) -> String {
+        let with_command = |command: &str, message: String| {
+            if command.is_empty() {
+                message
+            } else {
+                format!("{}: {}", command, message)
+            }
+        };
+        match error {
+            TermirustError::NotFound { command, path: None } => Self::format_command_not_found(command),
+            TermirustError::NotFound { command, path: Some(path) } => {
+                with_command(command, format!("'{}' no existe", path))
+            }
+            TermirustError::PermissionDenied { command, path: None } => {
+                format!("No hay permiso para ejecutar '{}'", command)
+            }
+            TermirustError::PermissionDenied { command, path: Some(path) } => {
+                with_command(command, format!("'{}': permiso denegado", path))
+            }
+            TermirustError::InvalidArgument { command, reason } => {
+                with_command(command, Self::describe_argument(reason))
+            }
+            TermirustError::Io { command, path: Some(path), detail, .. } => {
+                with_command(command, format!("'{}': {}", path, detail))
+            }
+            TermirustError::Io { command, path: None, detail, .. } => with_command(command, detail.clone()),
+            TermirustError::Parse(error) => Self::describe_parse(error),
+            TermirustError::Expansion(reason) => Self::describe_expansion(reason),
+            TermirustError::Alias { command, reason } => with_command(command, Self::describe_alias(reason)),
+            TermirustError::Job { command, reason } => with_command(command, Self::describe_job(reason)),
+            TermirustError::Unsupported { command, construct } => {
+                with_command(command, Self::describe_construct(construct).to_string())
+            }
+            TermirustError::TooDeep { command, nesting: Nesting::Scripts } => {
+                with_command(command, "demasiados scripts anidados".to_string())
+            }
+            TermirustError::TooDeep { command, nesting: Nesting::Calls } => {
+                with_command(command, "demasiadas llamadas anidadas".to_string())
+            }
+            TermirustError::NoConfigDirectory => {
+                "No se encontró el directorio de configuración (falta HOME)".to_string()
+            }
+            TermirustError::InvalidFile { path, reason } => format!("'{}': {}", path, Self::describe_file(reason)),
+            TermirustError::Plugin { name, path, reason } => {
+                format!("{}: plugin '{}': {}", name, path, Self::describe_plugin(reason))
+            }
+            TermirustError::ScriptStopped { command, exit_code } => {
+                format!("'{}' terminó con código {}; se detiene el script (set -e)", command, exit_code)
+            }
+            TermirustError::Located { location, error } => {
+                format!("{}: {}", Self::describe_location(location), Self::describe(error))
+            }
+        }
+    }
+
+    /// Describe un error con el prefijo "Error:", como se muestra en la terminal. Un
+    /// error de un script empieza por su archivo y su línea.
+    pub fn format(error: &TermirustError) -> String {
+        match error {
+            TermirustError::Located { location, error } => {
+                format!("{}: {}", Self::describe_location(location), Self::format(error))
+            }
+            _ => Self::format_error(&Self::describe(error)),
+        }
+    }
+
+    /// Línea que un programa escribió en su salida de errores, precedida por el
+    /// archivo y la línea del script que lo lanzó
+    pub fn format_output(line: &str, location: Option<&SourceLocation>) -> String {
+        match location {
+            Some(location) => format!("{}: {}", Self::describe_location(location), line),
+            None => line.to_string(),
+        }
+    }
+
+    fn describe_location(location: &SourceLocation) -> String {
+        format!("{}:{}", location.path, location.line)
+    }
+
+    /// Describe un error de sintaxis
+    pub fn describe_parse(error: &ParseError) -> String {
+        match error {
+            ParseError::UnterminatedQuote { quote, position } => {
+                let kind = match quote {
+                    '\'' => "simple",
+                    '`' => "invertida",
+                    _ => "doble",
+                };
+                format!("Comilla {} sin cerrar (abierta en la columna {})", kind, position + 1)
+            }
+            ParseError::DanglingEscape { position } => {
+                format!("Barra invertida sin carácter que escapar (columna {})", position + 1)
+            }
+            ParseError::UnterminatedExpansion { position, closing } => {
+                format!("Falta '{}' para cerrar la expansión abierta en la columna {}", closing, position + 1)
+            }
+            ParseError::UnexpectedOperator(operator) => format!("Error de sintaxis cerca de '{}'", operator),
+            ParseError::MissingCommand(operator) => format!("Falta un comando después de '{}'", operator),
+            ParseError::MissingRedirectTarget(operator) => {
+                format!("Falta el destino de la redirección '{}'", operator)
+            }
+            ParseError::InvalidRedirectTarget { operator, target } => {
+                format!("Destino inválido '{}' para la redirección '{}'", target, operator)
+            }
+            ParseError::UnexpectedWord(word) => format!("Error de sintaxis cerca de '{}'", word),
+            ParseError::MissingKeyword { expected, opened, position } => {
+                format!("Falta '{}' para cerrar el '{}' de la columna {}", expected, opened, position + 1)
+            }
+        }
     }
-    
-    /// Retorna el prefijo de error estándar
-    pub fn error_prefix() -> &'static str {
-        "Error:"
+
+    fn describe_expansion(reason: &ExpansionError) -> String {
+        match reason {
+            ExpansionError::AmbiguousRedirect(target) => format!("{}: redirección ambigua", target),
+            ExpansionError::UnterminatedSubstitution(closing) => {
+                format!("Falta '{}' para cerrar la sustitución de comandos", closing)
+            }
+            ExpansionError::UnterminatedBrace(body) => format!("Falta '}}' en la expansión ${{{}", body),
+            ExpansionError::BadSubstitution(body) => format!("${{{}}}: sustitución incorrecta", body),
+            ExpansionError::SubstitutionUnavailable(command) => {
+                format!("$({}): la sustitución de comandos no está disponible aquí", command)
+            }
+        }
+    }
+
+    fn describe_alias(reason: &AliasError) -> String {
+        match reason {
+            AliasError::InvalidName(name) => format!("'{}': nombre de alias no válido", name),
+            AliasError::NotFound(name) => format!("{}: no encontrado", name),
+            AliasError::Loop(aliases) => format!("bucle de alias: {}", aliases.join(" -> ")),
+            AliasError::Syntax { name, error } => format!("'{}': {}", name, Self::describe_parse(error)),
+            AliasError::NotSimple(name) => format!("'{}': solo puede contener un comando o una tubería", name),
+            AliasError::NotADefinition(text) => format!("'{}' no es una definición de alias", text),
+            AliasError::ExpectedDefinition => "se esperaba una línea 'alias nombre=valor'".to_string(),
+        }
+    }
+
+    fn describe_job(reason: &JobError) -> String {
+        match reason {
+            JobError::InvalidSpec(spec) => format!("especificación de trabajo inválida: {}", spec),
+            JobError::NoSuchJob(spec) => format!("no existe el trabajo {}", spec),
+            JobError::NoJobs => "no hay trabajos activos".to_string(),
+            JobError::Signal { target, detail } => format!("no se pudo enviar la señal a {}: {}", target, detail),
+            #[cfg(not(unix))]
+            JobError::SignalsUnsupported => "las señales no están soportadas en este sistema".to_string(),
+        }
+    }
+
+    fn describe_file(reason: &FileError) -> String {
+        match reason {
+            FileError::Format(detail) => detail.clone(),
+            FileError::UnknownArgumentKind(kind) => {
+                format!("tipo de argumento desconocido '{}' (file, directory o text)", kind)
+            }
+        }
     }
-    
-    /// Verifica si una línea contiene un mensaje de error
-    pub fn is_error_message(line: &str) -> bool {
-        line.starts_with(Self::error_prefix())
+
+    fn describe_plugin(reason: &PluginError) -> String {
+        match reason {
+            PluginError::Io(detail) => detail.clone(),
+            PluginError::Timeout(seconds) => format!("no respondió en {} segundos", seconds),
+            PluginError::NoResponse { code, stderr } if stderr.is_empty() => {
+                format!("terminó con código {} sin responder", code)
+            }
+            PluginError::NoResponse { code, stderr } => format!("terminó con código {} sin responder: {}", code, stderr),
+            PluginError::Signal(_) => "terminó por una señal sin responder".to_string(),
+            PluginError::InvalidResponse(detail) => format!("respuesta no válida: {}", detail),
+        }
+    }
+
+    fn describe_construct(construct: &Construct) -> &'static str {
+        match construct {
+            Construct::FunctionInPipeline => {
+                "una función no se puede usar en una tubería, con redirecciones ni en segundo plano"
+            }
+            Construct::SourceInPipeline => "no se puede usar en una tubería ni en segundo plano",
+            Construct::BackgroundList => "'&' solo se admite después de una tubería; separa los comandos con ';'",
+        }
+    }
+
+    fn describe_argument(reason: &ArgumentError) -> String {
+        match reason {
+            ArgumentError::UnknownOption(option) => format!("opción desconocida: '{}'", option),
+            ArgumentError::UnexpectedValue(option) => format!("la opción '{}' no admite un valor", option),
+            ArgumentError::MissingValue { option, value_name } => {
+                format!("la opción '{}' necesita un valor ({})", option, value_name)
+            }
+            ArgumentError::InvalidNumber { option, value } => {
+                format!("número no válido para {}: '{}'", option, value)
+            }
+            ArgumentError::UnexpectedOperand(operand) => format!("argumento inesperado: '{}'", operand),
+            ArgumentError::NotADirectory(path) => format!("'{}' no es un directorio", path),
+            ArgumentError::WrongCommand(name) => format!("'{}' no corresponde a este comando", name),
+            ArgumentError::InvalidIdentifier(name) => format!("'{}': no es un identificador válido", name),
+            ArgumentError::UnknownSignal(name) => format!("señal desconocida: {}", name),
+            ArgumentError::Usage(usage) => format!("uso: {}", usage),
+        }
+    }
+
+    /// Línea de error lista para la salida, en rojo salvo donde el texto traiga su
+    /// propio color
+    pub fn styled_line(line: &str) -> StyledLine {
+        let mut styled = StyledLine::parse_ansi(line);
+        for span in &mut styled.spans {
+            if span.style.fg == Color::Default {
+                span.style.fg = Color::Rgb(255, 100, 100);
+            }
+        }
+        styled
     }
 }
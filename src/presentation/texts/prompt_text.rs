@@ -23,20 +23,64 @@ impl PromptText {
         format!("{}{}", prompt, command_buffer)
     }
     
+    /// Formatea el prompt mientras una línea se ejecuta en segundo plano
+    pub fn format_running_prompt(command: &str, elapsed: std::time::Duration) -> String {
+        const SPINNER: [char; 4] = ['|', '/', '-', '\\'];
+        let frame = SPINNER[(elapsed.as_millis() / 250) as usize % SPINNER.len()];
+        format!("[{}] Ejecutando: {} ({}s)", frame, command, elapsed.as_secs())
+    }
+    
+    /// Prompt de la línea que se escribe en la entrada del proceso en ejecución
+    pub fn get_process_input_prompt() -> String {
+        "entrada del proceso> ".to_string()
+    }
+    
+    /// Formatea la línea que se enviará al proceso en ejecución
+    pub fn format_process_input_prompt(input_buffer: &str) -> String {
+        format!("{}{}", Self::get_process_input_prompt(), input_buffer)
+    }
+    
+    /// Aviso cuando el proceso ya no acepta lo que se escribe
+    pub fn process_input_closed() -> &'static str {
+        "el proceso no está leyendo su entrada"
+    }
+    
+    /// Indica la tecla que ejecuta la línea corregida tras un comando desconocido
+    pub fn format_suggestion_hint(line: &str) -> String {
+        format!("Pulsa F2 para ejecutar: {}", line)
+    }
+    
+    /// Estado del visor de documentos: posición, búsqueda y teclas
+    pub fn format_document_status(line: usize, total: usize, query: &str, current: Option<usize>, count: usize) -> String {
+        let mut status = format!("línea {} de {}", line, total);
+        if !query.is_empty() {
+            match current {
+                Some(current) if count > 0 => {
+                    status.push_str(&format!(" · '{}': {} de {}", query, current + 1, count));
+                }
+                _ => status.push_str(&format!(" · '{}': sin coincidencias", query)),
+            }
+        }
+        format!("{} · /: buscar · n/N: siguiente/anterior · Esc: cerrar", status)
+    }
+    
+    /// Posición del candidato elegido cuando la lista de Tab no muestra todos
+    pub fn format_completion_count(selected: usize, total: usize) -> String {
+        format!("{} de {} · ↑↓ elegir · Enter escribir · Esc cerrar", selected + 1, total)
+    }
+    
+    /// Búsqueda que se escribe en el visor de documentos
+    pub fn format_document_search(query: &str) -> String {
+        format!("/{}", query)
+    }
+    
     /// Obtiene solo el prompt sin el comando
     pub fn get_prompt_only() -> String {
         Self::generate_prompt()
     }
     
-    /// Calcula la posición X del cursor basado en el prompt y la posición del cursor
-    pub fn calculate_cursor_x(prompt: &str, cursor_position: usize) -> f32 {
-        let prompt_width = prompt.len() as f32 * 9.5;
-        let buffer_width = cursor_position as f32 * 9.5;
-        20.0 + prompt_width + buffer_width
-    }
-    
     /// Calcula la posición X del cursor considerando el prompt completo y el comando
-    pub fn calculate_cursor_x_with_buffer(prompt: &str, command_buffer: &String, cursor_position: usize) -> f32 {
+    pub fn calculate_cursor_x_with_buffer(prompt: &str, command_buffer: &str, cursor_position: usize) -> f32 {
         let prompt_width = prompt.len() as f32 * 9.5;
         // Calcula el ancho del texto del buffer hasta la posición del cursor
         let buffer_text = &command_buffer[..cursor_position.min(command_buffer.len())];
@@ -3,11 +3,49 @@ use std::time::Instant;
 
 use crate::application::use_cases::HandleInputUseCase;
 use crate::application::history_commands::HistoryCommandsUseCase;
-use crate::presentation::texts::{ErrorMessages, SystemIndicators, PromptText, DebugMessages};
+use crate::domain::completion::CompletionMenu;
+use crate::domain::document::{BlockKind, DocumentLine, DocumentView, Inline};
+use crate::domain::screen::Screen;
+use crate::domain::style::{CellStyle, Color, StyledLine};
+use crate::presentation::texts::{SystemIndicators, PromptText, DebugMessages};
+
+/// Tamaño de letra de la pantalla emulada de la pseudoterminal
+const SCREEN_FONT_SIZE: f32 = 14.0;
+
+/// Alto de cada fila de la pantalla emulada
+const SCREEN_ROW_HEIGHT: f32 = 17.0;
+
+/// Esquina superior izquierda de la pantalla emulada, alineada con la lista de líneas
+const SCREEN_ORIGIN: egui::Pos2 = egui::pos2(20.0, 20.0);
+
+/// Candidatos de Tab que se muestran a la vez; con más, la lista se desplaza
+const COMPLETION_MENU_ROWS: usize = 10;
+
+/// Los 16 colores básicos de xterm
+const ANSI_COLORS: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
 
 pub struct UIRenderer {
     terminal_width: f32,
     terminal_height: f32,
+    /// Ancho de un carácter de la fuente monoespaciada de la pantalla emulada
+    cell_width: f32,
 }
 
 impl UIRenderer {
@@ -15,6 +53,7 @@ impl UIRenderer {
         Self {
             terminal_width: 1200.0,
             terminal_height: 800.0,
+            cell_width: 8.4,
         }
     }
 
@@ -23,16 +62,44 @@ impl UIRenderer {
         self.terminal_height = height;
     }
 
+    /// Mide el ancho de carácter de la fuente con la que se dibuja la pantalla emulada
+    pub fn update_cell_width(&mut self, ctx: &egui::Context) {
+        let font = egui::FontId::monospace(SCREEN_FONT_SIZE);
+        self.cell_width = ctx.fonts(|fonts| fonts.glyph_width(&font, 'M')).max(1.0);
+    }
+
+    /// Columnas y filas que caben en el área de salida
+    pub fn screen_grid_size(&self) -> (u16, u16) {
+        let width = (self.terminal_width - 2.0 * SCREEN_ORIGIN.x).max(0.0);
+        let height = (self.terminal_height - 120.0 - SCREEN_ORIGIN.y).max(0.0);
+        let cols = (width / self.cell_width) as u16;
+        let rows = (height / SCREEN_ROW_HEIGHT) as u16;
+        (cols.max(1), rows.max(1))
+    }
+
     pub fn draw_command_history(
         &self,
         painter: &egui::Painter,
         input_handler: &HandleInputUseCase,
         history_commands: &HistoryCommandsUseCase,
     ) {
+        // Un programa en la pseudoterminal ocupa el área de salida con su propia pantalla
+        if let Some(screen) = input_handler.get_screen() {
+            self.draw_screen(painter, screen);
+            return;
+        }
+        if let Some(view) = input_handler.get_document() {
+            self.draw_document(painter, view);
+            return;
+        }
+
         let output_lines = input_handler.get_output_lines();
         let mut y_offset = 20.0;
         
-        for line in output_lines.iter().take(30) {
+        // Mostrar las últimas líneas para seguir la salida a medida que llega
+        let visible_lines = (((self.terminal_height - 140.0) / 20.0).max(0.0) as usize).min(30);
+        let first_line = output_lines.len().saturating_sub(visible_lines);
+        for line in &output_lines[first_line..] {
             if y_offset < self.terminal_height - 120.0 {
                 self.draw_output_line(painter, line, y_offset);
                 y_offset += 20.0;
@@ -42,10 +109,231 @@ impl UIRenderer {
         self.draw_history_stats(painter, history_commands);
     }
 
-    fn draw_output_line(&self, painter: &egui::Painter, line: &str, y_offset: f32) {
+    /// Dibuja la rejilla de la pantalla emulada, una fila por trabajo de texto de egui
+    fn draw_screen(&self, painter: &egui::Painter, screen: &Screen) {
+        let (cols, rows) = screen.size();
+        let background = egui::Rect::from_min_size(
+            SCREEN_ORIGIN - egui::vec2(5.0, 5.0),
+            egui::vec2(cols as f32 * self.cell_width + 10.0, rows as f32 * SCREEN_ROW_HEIGHT + 10.0),
+        );
+        painter.rect_filled(
+            background,
+            3.0,
+            egui::Color32::from_rgba_premultiplied(0, 0, 0, 230),
+        );
+
+        let default_color = egui::Color32::from_rgb(0, 255, 0);
+        for (row, cells) in screen.lines().iter().enumerate() {
+            let mut line = StyledLine::default();
+            for cell in cells {
+                line.push(cell.ch.encode_utf8(&mut [0; 4]), cell.style);
+            }
+            let mut job = self.build_layout_job(&line, SCREEN_FONT_SIZE, default_color);
+            for section in &mut job.sections {
+                section.format.line_height = Some(SCREEN_ROW_HEIGHT);
+            }
+            let y = SCREEN_ORIGIN.y + row as f32 * SCREEN_ROW_HEIGHT;
+            painter.galley(egui::pos2(SCREEN_ORIGIN.x, y), painter.layout_job(job), default_color);
+        }
+
+        if screen.cursor_visible() {
+            let (row, col) = screen.cursor();
+            let cursor_rect = egui::Rect::from_min_size(
+                egui::pos2(
+                    SCREEN_ORIGIN.x + col as f32 * self.cell_width,
+                    SCREEN_ORIGIN.y + row as f32 * SCREEN_ROW_HEIGHT,
+                ),
+                egui::vec2(self.cell_width, SCREEN_ROW_HEIGHT),
+            );
+            painter.rect_filled(
+                cursor_rect,
+                0.0,
+                egui::Color32::from_rgba_unmultiplied(0, 255, 0, 120),
+            );
+        }
+    }
+
+    /// Dibuja el documento abierto en el visor desde la línea visible, partiendo las
+    /// líneas largas según el ancho del área de salida
+    fn draw_document(&self, painter: &egui::Painter, view: &DocumentView) {
+        let bottom = self.terminal_height - 120.0;
+        let width = (self.terminal_width - 2.0 * SCREEN_ORIGIN.x).max(self.cell_width);
+        let area = egui::Rect::from_min_max(
+            SCREEN_ORIGIN - egui::vec2(5.0, 5.0),
+            egui::pos2(SCREEN_ORIGIN.x + width + 5.0, bottom + 5.0),
+        );
+        painter.rect_filled(area, 3.0, egui::Color32::from_rgba_premultiplied(0, 0, 0, 235));
+
+        let document = view.document();
+        painter.text(
+            SCREEN_ORIGIN,
+            egui::Align2::LEFT_TOP,
+            &document.title,
+            egui::FontId::monospace(16.0),
+            egui::Color32::from_rgb(255, 255, 0),
+        );
+        let current = view.current_match().map(|index| view.matches()[index]);
+        let mut y = SCREEN_ORIGIN.y + 28.0;
+        for (index, line) in document.lines.iter().enumerate().skip(view.scroll()) {
+            let indent = line.indent as f32 * 2.0 * self.cell_width;
+            let galley = painter.layout_job(self.build_document_job(line, width - indent));
+            let height = galley.size().y.max(SCREEN_ROW_HEIGHT);
+            if y + height > bottom && y > SCREEN_ORIGIN.y + 28.0 {
+                break;
+            }
+            let row = egui::Rect::from_min_size(egui::pos2(SCREEN_ORIGIN.x + indent - 2.0, y), egui::vec2(width - indent + 4.0, height));
+            if Some(index) == current {
+                painter.rect_filled(row, 2.0, egui::Color32::from_rgba_unmultiplied(255, 255, 0, 70));
+            } else if view.matches().contains(&index) {
+                painter.rect_filled(row, 2.0, egui::Color32::from_rgba_unmultiplied(255, 255, 0, 30));
+            } else if line.kind == BlockKind::Code {
+                painter.rect_filled(row, 0.0, egui::Color32::from_rgb(25, 25, 25));
+            }
+            if line.kind == BlockKind::Rule {
+                let middle = y + height / 2.0;
+                painter.line_segment(
+                    [egui::pos2(SCREEN_ORIGIN.x, middle), egui::pos2(SCREEN_ORIGIN.x + width, middle)],
+                    egui::Stroke::new(1.0, egui::Color32::from_rgb(0, 120, 0)),
+                );
+            }
+            painter.galley(egui::pos2(SCREEN_ORIGIN.x + indent, y), galley, egui::Color32::from_rgb(0, 255, 0));
+            y += height + if matches!(line.kind, BlockKind::Heading(_)) { 4.0 } else { 1.0 };
+        }
+    }
+
+    /// Trabajo de texto de una línea del documento, con el estilo de su bloque y de
+    /// cada fragmento
+    fn build_document_job(&self, line: &DocumentLine, width: f32) -> egui::text::LayoutJob {
+        let (font_size, color) = match line.kind {
+            BlockKind::Heading(1) => (18.0, egui::Color32::from_rgb(255, 255, 0)),
+            BlockKind::Heading(2) => (16.0, egui::Color32::from_rgb(0, 255, 255)),
+            BlockKind::Heading(_) => (15.0, egui::Color32::from_rgb(0, 200, 255)),
+            BlockKind::Code | BlockKind::Table => (SCREEN_FONT_SIZE, egui::Color32::from_rgb(210, 210, 210)),
+            BlockKind::Quote => (SCREEN_FONT_SIZE, egui::Color32::from_rgb(150, 190, 150)),
+            _ => (SCREEN_FONT_SIZE, egui::Color32::from_rgb(0, 255, 0)),
+        };
+        let format = |color: egui::Color32| egui::TextFormat {
+            font_id: egui::FontId::monospace(font_size),
+            color,
+            line_height: Some(font_size + 3.0),
+            ..Default::default()
+        };
+
+        let mut job = egui::text::LayoutJob::default();
+        job.wrap.max_width = width.max(self.cell_width);
+        if let Some(marker) = &line.marker {
+            job.append(&format!("{} ", marker), 0.0, format(egui::Color32::from_rgb(255, 200, 0)));
+        }
+        if line.kind == BlockKind::Quote {
+            job.append("│ ", 0.0, format(color));
+        }
+        for inline in &line.inlines {
+            match inline {
+                Inline::Text(text) => job.append(text, 0.0, format(color)),
+                Inline::Bold(text) => job.append(text, 0.0, format(egui::Color32::WHITE)),
+                Inline::Italic(text) => job.append(text, 0.0, egui::TextFormat { italics: true, ..format(color) }),
+                Inline::Code(text) => job.append(
+                    text,
+                    0.0,
+                    egui::TextFormat {
+                        background: egui::Color32::from_rgb(45, 45, 45),
+                        ..format(egui::Color32::from_rgb(255, 180, 100))
+                    },
+                ),
+                Inline::Link { text, target } => {
+                    let link = egui::Color32::from_rgb(100, 180, 255);
+                    job.append(text, 0.0, egui::TextFormat { underline: egui::Stroke::new(1.0, link), ..format(link) });
+                    if text != target {
+                        job.append(&format!(" ({})", target), 0.0, format(egui::Color32::from_rgb(110, 110, 110)));
+                    }
+                }
+            }
+        }
+        // Una línea vacía también ocupa su alto
+        if job.text.is_empty() {
+            job.append(" ", 0.0, format(color));
+        }
+        job
+    }
+
+    /// Arma el trabajo de texto de egui de una línea con estilos. Los fragmentos sin
+    /// color propio usan `default_color`.
+    fn build_layout_job(&self, line: &StyledLine, font_size: f32, default_color: egui::Color32) -> egui::text::LayoutJob {
+        let mut job = egui::text::LayoutJob::default();
+        for span in &line.spans {
+            let (text_color, background_color) = self.get_span_colors(&span.style, default_color);
+            let text_color = if span.style.hidden { egui::Color32::TRANSPARENT } else { text_color };
+            let decoration = |enabled: bool| if enabled { egui::Stroke::new(1.0, text_color) } else { egui::Stroke::NONE };
+            job.append(
+                &span.text,
+                0.0,
+                egui::TextFormat {
+                    font_id: egui::FontId::monospace(font_size),
+                    color: text_color,
+                    background: background_color.unwrap_or(egui::Color32::TRANSPARENT),
+                    italics: span.style.italic,
+                    underline: decoration(span.style.underline),
+                    strikethrough: decoration(span.style.strikethrough),
+                    ..Default::default()
+                },
+            );
+        }
+        job
+    }
+
+    /// Color del texto y, si no es el de la terminal, del fondo de un fragmento
+    fn get_span_colors(&self, style: &CellStyle, default_color: egui::Color32) -> (egui::Color32, Option<egui::Color32>) {
+        let mut text_color = self.get_ansi_color(style.fg, style.bold).unwrap_or(default_color);
+        let mut background_color = self.get_ansi_color(style.bg, false);
+        if style.inverse {
+            let inverted = text_color;
+            text_color = background_color.unwrap_or(egui::Color32::BLACK);
+            background_color = Some(inverted);
+        }
+        if style.dim {
+            text_color = text_color.gamma_multiply(0.6);
+        }
+        (text_color, background_color)
+    }
+
+    /// Convierte un color ANSI a egui; `None` para el color por defecto. En negrita,
+    /// los 8 colores básicos se muestran en su variante brillante como en xterm.
+    fn get_ansi_color(&self, color: Color, bold: bool) -> Option<egui::Color32> {
+        match color {
+            Color::Default => None,
+            Color::Indexed(index) if bold && index < 8 => Some(Self::palette_color(index + 8)),
+            Color::Indexed(index) => Some(Self::palette_color(index)),
+            Color::Rgb(r, g, b) => Some(egui::Color32::from_rgb(r, g, b)),
+        }
+    }
+
+    /// Paleta de 256 colores de xterm
+    fn palette_color(index: u8) -> egui::Color32 {
+        match index {
+            0..=15 => {
+                let (r, g, b) = ANSI_COLORS[index as usize];
+                egui::Color32::from_rgb(r, g, b)
+            }
+            16..=231 => {
+                let level = |value: u8| if value == 0 { 0 } else { 55 + value * 40 };
+                let cube = index - 16;
+                egui::Color32::from_rgb(level(cube / 36), level((cube / 6) % 6), level(cube % 6))
+            }
+            _ => {
+                let gray = 8 + (index - 232) * 10;
+                egui::Color32::from_rgb(gray, gray, gray)
+            }
+        }
+    }
+
+    fn draw_output_line(&self, painter: &egui::Painter, line: &StyledLine, y_offset: f32) {
+        // Las heurísticas dan el color de los fragmentos que no traen uno propio
+        let text_color = self.get_line_color(&line.text());
+        let galley = painter.layout_job(self.build_layout_job(line, 14.0, text_color));
+
         let text_rect = egui::Rect::from_min_size(
             egui::pos2(15.0, y_offset - 2.0),
-            egui::vec2(line.len() as f32 * 8.5 + 10.0, 18.0),
+            egui::vec2(galley.size().x + 10.0, 18.0),
         );
         painter.rect_filled(
             text_rect,
@@ -53,22 +341,12 @@ impl UIRenderer {
             egui::Color32::from_rgba_premultiplied(0, 0, 0, 180),
         );
         
-        let text_color = self.get_line_color(line);
-        
-        painter.text(
-            egui::pos2(20.0, y_offset),
-            egui::Align2::LEFT_TOP,
-            line,
-            egui::FontId::monospace(14.0),
-            text_color,
-        );
+        painter.galley(egui::pos2(20.0, y_offset), galley, text_color);
     }
 
     fn get_line_color(&self, line: &str) -> egui::Color32 {
         if line.contains(":~$ ") {
             egui::Color32::from_rgb(0, 255, 255)  // Cyan para comandos
-        } else if ErrorMessages::is_error_message(line) {
-            egui::Color32::from_rgb(255, 100, 100)  // Rojo para errores
         } else if line.starts_with("Bienvenido") {
             egui::Color32::from_rgb(255, 255, 0)  // Amarillo para mensajes de bienvenida
         } else {
@@ -126,11 +404,25 @@ impl UIRenderer {
         input_handler: &HandleInputUseCase,
         last_input_time: Instant,
     ) {
+        let prompt_y = self.terminal_height - 60.0;
+        
+        if let Some(view) = input_handler.get_document() {
+            self.draw_document_status(painter, view, prompt_y);
+            return;
+        }
+        
+        if let Some(running_command) = input_handler.get_running_command() {
+            self.draw_running_prompt(painter, running_command, prompt_y - 30.0, last_input_time);
+            // Con pseudoterminal las teclas van directamente a su pantalla
+            if input_handler.get_screen().is_none() {
+                self.draw_process_input(painter, input_handler, prompt_y, last_input_time);
+            }
+            return;
+        }
+        
         let command_buffer = input_handler.get_command_buffer();
         let prompt_text = PromptText::format_full_prompt(&command_buffer);
         
-        let prompt_y = self.terminal_height - 60.0;
-        
         let prompt_rect = egui::Rect::from_min_size(
             egui::pos2(15.0, prompt_y - 5.0),
             egui::vec2(prompt_text.len() as f32 * 9.5 + 10.0, 30.0),
@@ -150,7 +442,165 @@ impl UIRenderer {
         );
         
         self.draw_debug_info(painter, input_handler, prompt_y);
-        self.draw_cursor(painter, input_handler, prompt_y, last_input_time);
+        let prompt = PromptText::get_prompt_only();
+        self.draw_cursor(painter, input_handler, &prompt, prompt_y, last_input_time);
+        if let Some(menu) = input_handler.get_completion_menu() {
+            self.draw_completion_menu(painter, menu, &prompt, &command_buffer, prompt_y);
+        }
+    }
+
+    /// Dibuja sobre el prompt la lista de candidatos de Tab, alineada con la palabra
+    /// que completan. Si no caben todos, se muestran los que rodean al elegido.
+    fn draw_completion_menu(
+        &self,
+        painter: &egui::Painter,
+        menu: &CompletionMenu,
+        prompt: &str,
+        command_buffer: &str,
+        prompt_y: f32,
+    ) {
+        let candidates = menu.candidates();
+        if candidates.is_empty() {
+            return;
+        }
+        let visible = candidates.len().min(COMPLETION_MENU_ROWS);
+        let first = menu.selected().saturating_sub(visible - 1).min(candidates.len() - visible);
+        let shown = &candidates[first..first + visible];
+
+        let label_width = shown.iter().map(|candidate| candidate.label.chars().count()).max().unwrap_or(0);
+        let rows: Vec<(String, String)> = shown
+            .iter()
+            .map(|candidate| {
+                let description = candidate.description.clone().unwrap_or_default();
+                (format!("{:<width$}", candidate.label, width = label_width), description)
+            })
+            .collect();
+        let footer = (candidates.len() > visible).then(|| PromptText::format_completion_count(menu.selected(), candidates.len()));
+        let columns = rows
+            .iter()
+            .map(|(label, description)| label.chars().count() + 2 + description.chars().count())
+            .chain(footer.iter().map(|footer| footer.chars().count()))
+            .max()
+            .unwrap_or(0);
+
+        let row_height = 18.0;
+        let lines = rows.len() + usize::from(footer.is_some());
+        let x = PromptText::calculate_cursor_x_with_buffer(prompt, command_buffer, menu.request().start.min(command_buffer.len()));
+        let x = x.min((self.terminal_width - columns as f32 * self.cell_width - 20.0).max(20.0));
+        let top = prompt_y - 10.0 - lines as f32 * row_height;
+        let area = egui::Rect::from_min_size(
+            egui::pos2(x - 5.0, top - 4.0),
+            egui::vec2(columns as f32 * self.cell_width + 10.0, lines as f32 * row_height + 8.0),
+        );
+        painter.rect_filled(area, 3.0, egui::Color32::from_rgb(10, 25, 10));
+        painter.rect_stroke(area, 3.0, egui::Stroke::new(1.0, egui::Color32::from_rgb(0, 120, 0)));
+
+        let font = egui::FontId::monospace(SCREEN_FONT_SIZE);
+        for (index, (label, description)) in rows.iter().enumerate() {
+            let y = top + index as f32 * row_height;
+            let selected = first + index == menu.selected();
+            if selected {
+                let row = egui::Rect::from_min_size(egui::pos2(x - 3.0, y - 1.0), egui::vec2(area.width() - 4.0, row_height));
+                painter.rect_filled(row, 2.0, egui::Color32::from_rgb(0, 90, 0));
+            }
+            let color = if selected { egui::Color32::WHITE } else { egui::Color32::from_rgb(0, 255, 0) };
+            painter.text(egui::pos2(x, y), egui::Align2::LEFT_TOP, label, font.clone(), color);
+            painter.text(
+                egui::pos2(x + (label.chars().count() + 2) as f32 * self.cell_width, y),
+                egui::Align2::LEFT_TOP,
+                description,
+                font.clone(),
+                egui::Color32::from_rgb(120, 160, 120),
+            );
+        }
+        if let Some(footer) = footer {
+            let y = top + rows.len() as f32 * row_height;
+            painter.text(egui::pos2(x, y), egui::Align2::LEFT_TOP, footer, font, egui::Color32::from_rgb(120, 160, 120));
+        }
+    }
+
+    /// Muestra la posición en el documento y las teclas del visor, o la búsqueda que se
+    /// está escribiendo, en lugar del prompt
+    fn draw_document_status(&self, painter: &egui::Painter, view: &DocumentView, prompt_y: f32) {
+        let status = match view.search_input() {
+            Some(query) => PromptText::format_document_search(query),
+            None => PromptText::format_document_status(
+                view.scroll() + 1,
+                view.document().lines.len(),
+                view.query(),
+                view.current_match(),
+                view.matches().len(),
+            ),
+        };
+        let status_rect = egui::Rect::from_min_size(
+            egui::pos2(15.0, prompt_y - 5.0),
+            egui::vec2(status.chars().count() as f32 * 9.5 + 10.0, 30.0),
+        );
+        painter.rect_filled(status_rect, 3.0, egui::Color32::from_rgba_premultiplied(0, 0, 0, 200));
+        painter.text(
+            egui::pos2(20.0, prompt_y),
+            egui::Align2::LEFT_TOP,
+            &status,
+            egui::FontId::monospace(16.0),
+            egui::Color32::from_rgb(255, 255, 0),
+        );
+    }
+
+    /// Dibuja el estado "ejecutando" en lugar del prompt hasta que llega el código de salida
+    fn draw_running_prompt(&self, painter: &egui::Painter, running_command: &str, prompt_y: f32, started_at: Instant) {
+        let prompt_text = PromptText::format_running_prompt(running_command, started_at.elapsed());
+        
+        let prompt_rect = egui::Rect::from_min_size(
+            egui::pos2(15.0, prompt_y - 5.0),
+            egui::vec2(prompt_text.len() as f32 * 9.5 + 10.0, 30.0),
+        );
+        painter.rect_filled(
+            prompt_rect,
+            3.0,
+            egui::Color32::from_rgba_premultiplied(0, 0, 0, 200),
+        );
+        
+        painter.text(
+            egui::pos2(20.0, prompt_y),
+            egui::Align2::LEFT_TOP,
+            &prompt_text,
+            egui::FontId::monospace(16.0),
+            egui::Color32::from_rgb(255, 255, 0),
+        );
+    }
+
+    /// Dibuja la línea que se enviará a la entrada estándar del proceso en ejecución,
+    /// en otro color que el prompt para que no se confunda con un comando
+    fn draw_process_input(
+        &self,
+        painter: &egui::Painter,
+        input_handler: &HandleInputUseCase,
+        prompt_y: f32,
+        last_input_time: Instant,
+    ) {
+        let command_buffer = input_handler.get_command_buffer();
+        let prompt_text = PromptText::format_process_input_prompt(&command_buffer);
+        
+        let prompt_rect = egui::Rect::from_min_size(
+            egui::pos2(15.0, prompt_y - 5.0),
+            egui::vec2(prompt_text.len() as f32 * 9.5 + 10.0, 30.0),
+        );
+        painter.rect_filled(
+            prompt_rect,
+            3.0,
+            egui::Color32::from_rgba_premultiplied(0, 0, 0, 200),
+        );
+        
+        painter.text(
+            egui::pos2(20.0, prompt_y),
+            egui::Align2::LEFT_TOP,
+            &prompt_text,
+            egui::FontId::monospace(16.0),
+            egui::Color32::from_rgb(255, 165, 0),
+        );
+        
+        let prompt = PromptText::get_process_input_prompt();
+        self.draw_cursor(painter, input_handler, &prompt, prompt_y, last_input_time);
     }
 
     fn draw_debug_info(&self, painter: &egui::Painter, input_handler: &HandleInputUseCase, prompt_y: f32) {
@@ -171,13 +621,13 @@ impl UIRenderer {
         &self,
         painter: &egui::Painter,
         input_handler: &HandleInputUseCase,
+        prompt: &str,
         prompt_y: f32,
         last_input_time: Instant,
     ) {
         let cursor_position = input_handler.get_cursor_position();
         let command_buffer = input_handler.get_command_buffer();
-        let prompt = PromptText::get_prompt_only();
-        let cursor_x = PromptText::calculate_cursor_x_with_buffer(&prompt, &command_buffer, cursor_position);
+        let cursor_x = PromptText::calculate_cursor_x_with_buffer(prompt, &command_buffer, cursor_position);
         
         let cursor_time = last_input_time.elapsed().as_secs_f32();
         if (cursor_time * 2.0) as i32 % 2 == 0 {